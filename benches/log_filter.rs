@@ -0,0 +1,64 @@
+#![feature(test)]
+extern crate test;
+
+use fast_log::appender::{FastLogRecord, LogAppender};
+use fast_log::filter::RegexFilter;
+use fast_log::Config;
+use std::sync::Once;
+
+use test::{black_box, Bencher};
+
+struct BenchRecvLog {}
+
+impl LogAppender for BenchRecvLog {
+    fn do_logs(&self, _records: &[FastLogRecord]) {
+        //nothing
+    }
+}
+
+/// trace records, records of target `noisy` and messages starting with `health` are dropped
+/// before a FastLogRecord is built
+fn init() {
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        fast_log::init(
+            Config::new()
+                .custom(BenchRecvLog {})
+                .level(log::LevelFilter::Info)
+                .deny_target("noisy")
+                .add_filter(RegexFilter::new().args("^health").unwrap())
+                .chan_len(Some(1000000)),
+        )
+        .unwrap();
+    });
+}
+
+#[bench]
+fn bench_log_denied_target(b: &mut Bencher) {
+    init();
+    b.iter(|| {
+        black_box({
+            log::info!(target: "noisy", "Commencing yak shaving {}", 1);
+        });
+    });
+}
+
+#[bench]
+fn bench_log_filtered_args(b: &mut Bencher) {
+    init();
+    b.iter(|| {
+        black_box({
+            log::info!("health check {}", 1);
+        });
+    });
+}
+
+#[bench]
+fn bench_log_filtered_level(b: &mut Bencher) {
+    init();
+    b.iter(|| {
+        black_box({
+            log::trace!("Commencing yak shaving {}", 1);
+        });
+    });
+}
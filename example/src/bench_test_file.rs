@@ -1,14 +1,20 @@
-use fast_log::bencher::TPS;
+use fast_log::bencher::{Histogram, Latency, TPS};
 use fast_log::config::Config;
+use fast_log::plugin::file::FileAppender;
+use std::sync::Arc;
 use std::time::Instant;
 
 /// cargo run --release --package example --bin bench_test_file
 fn main() {
     //clear data
     let _ = std::fs::remove_file("target/test.log");
+    let histogram = Arc::new(Histogram::new());
     fast_log::init(
         Config::new()
-            .file("target/test.log")
+            .add_appender(Latency::new(
+                FileAppender::new("target/test.log").unwrap(),
+                histogram.clone(),
+            ))
             .chan_len(Some(1000000)),
     )
     .unwrap();
@@ -21,4 +27,5 @@ fn main() {
     log::logger().flush();
     now.time(total);
     now.tps(total);
+    histogram.report();
 }
@@ -1,8 +1,7 @@
 use fast_log::config::Config;
-use fast_log::FastLogFormatJson;
 
 fn main() {
-    fast_log::init(Config::new().format(FastLogFormatJson::new()).console()).unwrap();
+    fast_log::init(Config::new().json().console()).unwrap();
     log::info!("Commencing \"yak\" shaving{}", 0);
     log::logger().flush();
 }
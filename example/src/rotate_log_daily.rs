@@ -233,25 +233,41 @@ mod tests {
         let today_record = FastLogRecord {
             command: fast_log::appender::Command::CommandRecord,
             level: log::Level::Info,
-            target: String::default(),
+            target: "".into(),
             args: String::default(),
-            module_path: String::default(),
-            file: String::default(),
+            module_path: "".into(),
+            file: "".into(),
             line: None,
             now: today.clone().into(),
             formated: String::default(),
+            key_values: vec![],
+            pid: 0,
+            thread_id: 0,
+            thread_name: "".into(),
+            hostname: "".into(),
+            trace_id: None,
+            span_id: None,
+            seq: 0,
         };
         let tomorrow = today.clone().add(Duration::from_day(1));
         let tomorrow_record = FastLogRecord {
             command: fast_log::appender::Command::CommandRecord,
             level: log::Level::Info,
-            target: String::default(),
+            target: "".into(),
             args: String::default(),
-            module_path: String::default(),
-            file: String::default(),
+            module_path: "".into(),
+            file: "".into(),
             line: None,
             now: tomorrow.clone().into(),
             formated: String::default(),
+            key_values: vec![],
+            pid: 0,
+            thread_id: 0,
+            thread_name: "".into(),
+            hostname: "".into(),
+            trace_id: None,
+            span_id: None,
+            seq: 0,
         };
         let tomorrow_date_str = date_str(&tomorrow);
 
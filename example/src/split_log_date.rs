@@ -29,10 +29,12 @@ impl Packer for DateLogPacker {
             }
         }
         //do nothing,and not remove file
-        let now = fastdate::DateTime::now()
-            .set_hour(0)
-            .set_min(0)
-            .set_sec(0)
+        let now = fastdate::DateTime::now();
+        let now = now
+            .clone()
+            .sub(Duration::from_secs(
+                now.hour() as u64 * 3600 + now.minute() as u64 * 60 + now.sec() as u64,
+            ))
             .set_nano(0);
         let name = self.new_log_name(log_file_path, now);
         let mut f = OpenOptions::new()
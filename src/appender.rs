@@ -1,19 +1,41 @@
 use crate::WaitGroup;
+use parking_lot::Mutex;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
 use std::time::SystemTime;
 
 /// LogAppender append logs
 /// Appender will be running on single main thread,please do_log for new thread or new an Future
 pub trait LogAppender: Send {
     /// Batch write log, or do nothing
+    ///
+    /// `records` is everything drained from the channel since the last call,
+    /// so an appender can write once per batch. it may contain `CommandFlush`
+    /// (finish pending writes before returning) and `CommandExit`.
     fn do_logs(&self, records: &[FastLogRecord]);
 }
 
+/// an appender with async io, running as a tokio task instead of a thread,
+/// see Config::add_async_appender
+#[cfg(feature = "tokio")]
+#[async_trait::async_trait]
+pub trait AsyncLogAppender: Send + Sync {
+    /// Batch write log, same records as LogAppender::do_logs
+    async fn do_logs(&self, records: &[FastLogRecord]);
+}
+
 #[derive(Clone, Debug)]
 pub enum Command {
     CommandRecord,
     CommandExit,
     /// Ensure that the log splitter forces splitting and saves the log
     CommandFlush(WaitGroup),
+    /// start an appender on the running logger, see Logger::add_appender
+    CommandAddAppender(NamedAppender),
+    /// exit and remove appenders by name, see Logger::remove_appender
+    CommandRemoveAppender(String),
+    /// roll the log file now, see fast_log::rotate
+    CommandRotate,
 }
 
 impl Command {
@@ -22,6 +44,9 @@ impl Command {
             Command::CommandRecord => 1,
             Command::CommandExit => 2,
             Command::CommandFlush(_) => 3,
+            Command::CommandAddAppender(_) => 4,
+            Command::CommandRemoveAppender(_) => 5,
+            Command::CommandRotate => 6,
         }
     }
 }
@@ -34,17 +59,85 @@ impl PartialEq for Command {
 
 impl Eq for Command {}
 
+/// an appender added at runtime, the dispatcher takes it out and runs it on a new thread
+#[derive(Clone)]
+pub struct NamedAppender {
+    pub name: String,
+    pub appender: Arc<Mutex<Option<Box<dyn LogAppender>>>>,
+}
+
+impl NamedAppender {
+    pub fn new(name: &str, appender: Box<dyn LogAppender>) -> Self {
+        Self {
+            name: name.to_string(),
+            appender: Arc::new(Mutex::new(Some(appender))),
+        }
+    }
+}
+
+impl Debug for NamedAppender {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NamedAppender")
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct FastLogRecord {
     pub command: Command,
     pub level: log::Level,
-    pub target: String,
+    /// interned, see fast_log::intern
+    pub target: Arc<str>,
     pub args: String,
-    pub module_path: String,
-    pub file: String,
+    pub module_path: Arc<str>,
+    pub file: Arc<str>,
     pub line: Option<u32>,
     pub now: SystemTime,
     pub formated: String,
+    /// structured key-values, for example `log::info!(user = "bob"; "login")`
+    pub key_values: Vec<(String, String)>,
+    /// the process id
+    pub pid: u32,
+    /// the logging thread, numbered from 1 in the order threads first log
+    pub thread_id: u64,
+    /// the name of the logging thread, empty if unnamed
+    pub thread_name: Arc<str>,
+    /// the hostname, read once
+    pub hostname: Arc<str>,
+    /// the trace id of the current span, see Config::trace_context
+    pub trace_id: Option<String>,
+    /// the span id of the current span, see Config::trace_context
+    pub span_id: Option<String>,
+    /// the sequence number of the record in its logger, from 1. a gap downstream means records were lost,
+    /// for example dropped by OverflowPolicy or a network appender. 0 for commands
+    pub seq: u64,
+}
+
+impl FastLogRecord {
+    /// a record made now, the other fields are empty. set them with struct update syntax, for example
+    /// `FastLogRecord { line: Some(7), ..FastLogRecord::new(Command::CommandRecord, Level::Info, "hello") }`
+    pub fn new(command: Command, level: log::Level, args: &str) -> Self {
+        Self {
+            command,
+            level,
+            target: "".into(),
+            args: args.to_string(),
+            module_path: "".into(),
+            file: "".into(),
+            line: None,
+            now: SystemTime::now(),
+            formated: String::new(),
+            key_values: vec![],
+            pid: 0,
+            thread_id: 0,
+            thread_name: "".into(),
+            hostname: "".into(),
+            trace_id: None,
+            span_id: None,
+            seq: 0,
+        }
+    }
 }
 
 /// format record data
@@ -1,3 +1,8 @@
+use crate::appender::{Command, FastLogRecord, LogAppender};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
 pub trait TPS {
     fn tps(&self, total: u64);
     fn time(&self, total: u64);
@@ -27,3 +32,134 @@ impl TPS for std::time::Instant {
         println!("cost:{:?}", time);
     }
 }
+
+/// sub buckets of a power of two, the bucket of a value is within 1/32 (about 3%) of it
+const SUB_BITS: u32 = 5;
+const SUB: u64 = 1 << SUB_BITS;
+
+/// an HDR style histogram of nanoseconds, log linear buckets counted with atomics so threads can share it
+pub struct Histogram {
+    buckets: Vec<AtomicU64>,
+    count: AtomicU64,
+    max: AtomicU64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Histogram {
+    pub fn new() -> Self {
+        let len = ((64 - SUB_BITS as u64 + 1) * SUB) as usize;
+        Self {
+            buckets: (0..len).map(|_| AtomicU64::new(0)).collect(),
+            count: AtomicU64::new(0),
+            max: AtomicU64::new(0),
+        }
+    }
+
+    pub fn record(&self, nanos: u64) {
+        self.buckets[index(nanos)].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.max.fetch_max(nanos, Ordering::Relaxed);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    pub fn max(&self) -> Duration {
+        Duration::from_nanos(self.max.load(Ordering::Relaxed))
+    }
+
+    /// the latency `q`(0.0..=1.0) of records are at most, for example 0.99 for p99
+    pub fn percentile(&self, q: f64) -> Duration {
+        let count = self.count();
+        if count == 0 {
+            return Duration::ZERO;
+        }
+        let rank = ((count as f64 * q).ceil() as u64).clamp(1, count);
+        let mut seen = 0;
+        for (i, bucket) in self.buckets.iter().enumerate() {
+            seen += bucket.load(Ordering::Relaxed);
+            if seen >= rank {
+                return Duration::from_nanos(upper(i).min(self.max.load(Ordering::Relaxed)));
+            }
+        }
+        self.max()
+    }
+
+    /// print `p50 p95 p99 p999 max` of the recorded latencies
+    pub fn report(&self) {
+        println!(
+            "Latency: count:{} p50:{:?} p95:{:?} p99:{:?} p999:{:?} max:{:?}",
+            self.count(),
+            self.percentile(0.50),
+            self.percentile(0.95),
+            self.percentile(0.99),
+            self.percentile(0.999),
+            self.max()
+        );
+    }
+}
+
+fn index(v: u64) -> usize {
+    if v < SUB {
+        return v as usize;
+    }
+    let e = 63 - v.leading_zeros();
+    let sub = (v >> (e - SUB_BITS)) & (SUB - 1);
+    ((e - SUB_BITS + 1) as u64 * SUB + sub) as usize
+}
+
+/// the largest value of bucket `i`
+fn upper(i: usize) -> u64 {
+    let i = i as u64;
+    if i < SUB {
+        return i;
+    }
+    let shift = i / SUB - 1;
+    ((((SUB + i % SUB + 1) as u128) << shift) - 1).min(u64::MAX as u128) as u64
+}
+
+/// wrap an appender to record the latency of each record, from `log!`(the record time) to the appender
+/// returning from `do_logs`. for example compare the tail latency of appenders:
+/// ```rust,no_run
+/// use std::sync::Arc;
+/// use fast_log::bencher::{Histogram, Latency};
+/// use fast_log::plugin::file::FileAppender;
+/// use fast_log::Config;
+/// let histogram = Arc::new(Histogram::new());
+/// let appender = FileAppender::new("target/bench.log").unwrap();
+/// fast_log::init(Config::new().add_appender(Latency::new(appender, histogram.clone()))).unwrap();
+/// for i in 0..100000 {
+///     log::info!("Commencing yak shaving {}", i);
+/// }
+/// log::logger().flush();
+/// histogram.report();
+/// ```
+pub struct Latency<A: LogAppender> {
+    inner: A,
+    histogram: Arc<Histogram>,
+}
+
+impl<A: LogAppender> Latency<A> {
+    pub fn new(inner: A, histogram: Arc<Histogram>) -> Self {
+        Self { inner, histogram }
+    }
+}
+
+impl<A: LogAppender> LogAppender for Latency<A> {
+    fn do_logs(&self, records: &[FastLogRecord]) {
+        self.inner.do_logs(records);
+        let now = SystemTime::now();
+        for record in records {
+            if record.command == Command::CommandRecord {
+                let latency = now.duration_since(record.now).unwrap_or_default();
+                self.histogram.record(latency.as_nanos() as u64);
+            }
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use fastdate::DateTime;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// the time source of records, rotation periods and pack names, see Config::clock
+pub trait Clock: Send + Sync {
+    fn now(&self) -> SystemTime;
+}
+
+/// SystemTime::now(), the default
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// a clock set by hand, so time dependent behavior(rotation at midnight, timestamps of formats) can be tested.
+/// clones share the time
+/// ```rust
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use fast_log::clock::MockClock;
+/// use fast_log::Config;
+/// let clock = MockClock::new(UNIX_EPOCH + Duration::from_secs(1_700_000_000));
+/// fast_log::init(Config::new().console().clock(clock.clone())).unwrap();
+/// log::info!("at 2023-11-14");
+/// clock.advance(Duration::from_secs(86400));
+/// log::info!("one day later");
+/// ```
+#[derive(Clone, Debug)]
+pub struct MockClock {
+    /// nanoseconds after UNIX_EPOCH
+    nanos: Arc<AtomicI64>,
+}
+
+impl MockClock {
+    pub fn new(time: SystemTime) -> Self {
+        Self {
+            nanos: Arc::new(AtomicI64::new(nanos(time))),
+        }
+    }
+
+    pub fn set(&self, time: SystemTime) {
+        self.nanos.store(nanos(time), Ordering::SeqCst);
+    }
+
+    pub fn advance(&self, d: Duration) {
+        self.nanos.fetch_add(d.as_nanos() as i64, Ordering::SeqCst);
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> SystemTime {
+        let nanos = self.nanos.load(Ordering::SeqCst);
+        if nanos >= 0 {
+            UNIX_EPOCH + Duration::from_nanos(nanos as u64)
+        } else {
+            UNIX_EPOCH - Duration::from_nanos(nanos.unsigned_abs())
+        }
+    }
+}
+
+fn nanos(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_nanos() as i64,
+        Err(e) => -(e.duration().as_nanos() as i64),
+    }
+}
+
+/// the local time of `fast_log::now()`
+pub(crate) fn local_now() -> DateTime {
+    DateTime::from_system_time(crate::now(), 0).set_offset(fastdate::offset_sec())
+}
@@ -1,16 +1,25 @@
-use crate::appender::{LogAppender, RecordFormat};
+use crate::appender::{FastLogRecord, LogAppender, RecordFormat};
+use crate::clock::{Clock, SystemClock};
 use crate::consts::LogSize;
-use crate::filter::{Filter};
-use crate::plugin::console::ConsoleAppender;
+use crate::error::LogError;
+use crate::filter::{module_match, Filter, ModuleLevelFilter};
+use crate::plugin::console::{ConsoleAppender, ConsoleStream};
 use crate::plugin::file::FileAppender;
+use crate::plugin::file_audit::AuditFileAppender;
+use crate::plugin::file_date::DateFileAppender;
+use crate::plugin::file_level::LevelFileAppender;
 use crate::plugin::file_loop::FileLoopAppender;
 use crate::plugin::file_rotate::{FileRotateAppender, Rotate};
-use crate::plugin::file_split::{FileSplitAppender, Keep, Packer, RawFile, SplitFile};
-use crate::FastLogFormat;
+use crate::plugin::file_split::{FileSplitAppender, Keep, Packer, Period, RawFile, SplitFile};
+use crate::plugin::format::FormatAppender;
+use crate::plugin::msgpack::MsgPackFileAppender;
+use crate::{CsvFormat, FastLogFormat, FastLogFormatJson, LogFmtFormat, PatternFormat, TimeFormat};
 use dark_std::sync::SyncVec;
 use log::LevelFilter;
 use std::fmt::{Debug, Formatter};
+use std::str::FromStr;
 use parking_lot::Mutex;
+use std::sync::Arc;
 
 /// the fast_log Config
 /// for example:
@@ -20,6 +29,20 @@ use parking_lot::Mutex;
 ///    fast_log::init(Config::new().console().chan_len(Some(1000000))).unwrap();
 /// }
 /// ```
+/// appenders can be chained, `init()` consumes the config:
+/// ```rust,no_run
+/// use fast_log::Config;
+/// use fast_log::consts::LogSize;
+/// use fast_log::plugin::file_split::RollingType;
+/// use fast_log::plugin::packer::LogPacker;
+/// fast_log::init(
+///     Config::new()
+///         .console()
+///         .file("target/logs/app.log")
+///         .file_split("target/logs/", LogSize::MB(10), RollingType::All, LogPacker {}),
+/// )
+/// .unwrap();
+/// ```
 pub struct Config {
     /// Each appender is responsible for printing its own business
     /// every LogAppender have one thread(need Mutex) access this.
@@ -30,8 +53,110 @@ pub struct Config {
     pub filters: SyncVec<Box<dyn Filter>>,
     /// format record into field fast_log_record's formatted:String
     pub format: Box<dyn RecordFormat>,
+    /// the time rendering of all built-in formats, default None(each format's own)
+    pub time_format: Option<TimeFormat>,
     /// the channel length,default None(Unbounded channel)
     pub chan_len: Option<usize>,
+    /// what to do when the bounded channel is full, default OverflowPolicy::Block
+    pub overflow: OverflowPolicy,
+    /// called on packer, file and channel errors, default None(print to stderr)
+    pub on_error: Option<ErrorHandler>,
+    /// run on every record before formatting, see Config::enricher
+    pub enrichers: Vec<Enricher>,
+    /// return (trace_id, span_id) of the current span, see Config::trace_context
+    pub trace_context: Option<TraceContext>,
+    /// records of these targets(and their sub modules) are dropped
+    pub deny_targets: Vec<String>,
+    /// if not empty, only records of these targets(and their sub modules) are logged
+    pub allow_targets: Vec<String>,
+    /// appenders which only receive records of a target, see Config::route
+    pub routes: SyncVec<(String, Mutex<Box<dyn LogAppender>>)>,
+    /// appenders run by a pool of threads, see Config::add_appender_workers
+    pub worker_appends: Vec<WorkerAppend>,
+    /// appenders running as tokio tasks, see Config::add_async_appender
+    #[cfg(feature = "tokio")]
+    pub async_appends: Vec<AsyncAppend>,
+    /// call appenders inline from Logger::log instead of the channel and threads, default false
+    pub sync: bool,
+    /// create file appenders in the temp dir when their path fails, see Config::temp_dir_fallback
+    pub temp_dir_fallback: bool,
+    /// errors of appenders and formats which could not be made, returned by `init()` unless lenient
+    pub errors: Vec<LogError>,
+    /// see Config::lenient
+    pub lenient: bool,
+    /// the max bytes of a formatted record, see Config::max_record_len
+    pub max_record_len: Option<usize>,
+    /// `init()` applies the `FAST_LOG_*` env, see Config::apply_env
+    pub env_overrides: bool,
+    /// the unix socket path of text commands, see Config::control_socket
+    pub control_socket: Option<String>,
+    /// the time of records, see Config::clock
+    pub clock: Arc<dyn Clock>,
+}
+
+/// handle an error of the logger or an appender, see Config::on_error
+pub type ErrorHandler = Box<dyn Fn(&LogError) + Send + Sync>;
+
+/// how records are spread over the workers of an appender, see Config::add_appender_workers
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Delivery {
+    /// records of a target always go to the same worker, so they are written in order
+    #[default]
+    Ordered,
+    /// each batch goes to the next worker, records may be written out of order
+    Unordered,
+}
+
+/// an appender run by a pool of threads, see Config::add_appender_workers
+pub struct WorkerAppend {
+    pub appender: Arc<dyn LogAppender + Sync>,
+    pub workers: usize,
+    pub delivery: Delivery,
+}
+
+/// an AsyncLogAppender and the runtime it runs on, see Config::add_async_appender
+#[cfg(feature = "tokio")]
+pub type AsyncAppend = (
+    tokio::runtime::Handle,
+    std::sync::Arc<dyn crate::appender::AsyncLogAppender>,
+);
+
+/// add fields to a record, see Config::enricher
+pub type Enricher = Box<dyn Fn(&mut FastLogRecord) + Send + Sync>;
+
+/// return (trace_id, span_id) of the current span, see Config::trace_context
+pub type TraceContext = Box<dyn Fn() -> Option<(String, String)> + Send + Sync>;
+
+/// what to do when a bounded channel(`chan_len(Some(len))`) is full
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum OverflowPolicy {
+    /// wait until the channel has space
+    #[default]
+    Block,
+    /// drop the record being logged
+    DropNewest,
+    /// drop the oldest record in the channel to make space
+    DropOldest,
+}
+
+/// `path` under `{temp dir}/fast_log/`, for example `/var/log/app/` is `/tmp/fast_log/var/log/app/`
+fn temp_dir_path(path: &str) -> String {
+    let path = path.replace("\\", "/");
+    let mut temp = std::env::temp_dir()
+        .join("fast_log")
+        .to_str()
+        .unwrap_or_default()
+        .replace("\\", "/");
+    for part in path.split('/') {
+        if !part.is_empty() && part != "." && part != ".." && !part.ends_with(':') {
+            temp.push('/');
+            temp.push_str(part);
+        }
+    }
+    if path.ends_with('/') {
+        temp.push('/');
+    }
+    temp
 }
 
 impl Debug for Config {
@@ -39,7 +164,23 @@ impl Debug for Config {
         f.debug_struct("Config")
             .field("appends", &self.appends.len())
             .field("level", &self.level)
+            .field("time_format", &self.time_format)
             .field("chan_len", &self.chan_len)
+            .field("overflow", &self.overflow)
+            .field("on_error", &self.on_error.is_some())
+            .field("enrichers", &self.enrichers.len())
+            .field("trace_context", &self.trace_context.is_some())
+            .field("routes", &self.routes.len())
+            .field("worker_appends", &self.worker_appends.len())
+            .field("deny_targets", &self.deny_targets)
+            .field("allow_targets", &self.allow_targets)
+            .field("sync", &self.sync)
+            .field("temp_dir_fallback", &self.temp_dir_fallback)
+            .field("errors", &self.errors)
+            .field("lenient", &self.lenient)
+            .field("max_record_len", &self.max_record_len)
+            .field("env_overrides", &self.env_overrides)
+            .field("control_socket", &self.control_socket)
             .finish()
     }
 }
@@ -51,7 +192,26 @@ impl Default for Config {
             level: LevelFilter::Trace,
             filters: SyncVec::new(),
             format: Box::new(FastLogFormat::new()),
+            time_format: None,
             chan_len: None,
+            overflow: OverflowPolicy::default(),
+            on_error: None,
+            enrichers: vec![],
+            trace_context: None,
+            routes: SyncVec::new(),
+            worker_appends: vec![],
+            #[cfg(feature = "tokio")]
+            async_appends: vec![],
+            deny_targets: vec![],
+            allow_targets: vec![],
+            sync: false,
+            temp_dir_fallback: false,
+            errors: vec![],
+            lenient: false,
+            max_record_len: None,
+            env_overrides: true,
+            control_socket: None,
+            clock: Arc::new(SystemClock),
         }
     }
 }
@@ -79,29 +239,186 @@ impl Config {
         }
         self
     }
+    /// drop records of the target and its sub modules,
+    /// for example `deny_target("cogo::io::sys::select")` to silence a runtime's internal logs
+    pub fn deny_target(mut self, target: &str) -> Self {
+        self.deny_targets.push(target.to_string());
+        self
+    }
+
+    /// only log records of the allowed targets and their sub modules, can be called many times
+    pub fn allow_target(mut self, target: &str) -> Self {
+        self.allow_targets.push(target.to_string());
+        self
+    }
+
+    /// true if the target is not denied and is allowed
+    pub fn target_enabled(&self, target: &str) -> bool {
+        if self.deny_targets.iter().any(|t| module_match(t, target)) {
+            return false;
+        }
+        self.allow_targets.is_empty() || self.allow_targets.iter().any(|t| module_match(t, target))
+    }
+
+    /// add per module level directives, for example `"hyper=warn,my_app::db=trace,info"`.
+    /// the log level is raised to the max level of directives, call `level()` after this to override it
+    pub fn module_levels(self, spec: &str) -> Self {
+        self.module_level_filter(ModuleLevelFilter::parse(spec))
+    }
+
+    /// use directives of env `RUST_LOG` if it is set, see `module_levels()`
+    pub fn rust_log(self) -> Self {
+        match ModuleLevelFilter::from_env("RUST_LOG") {
+            None => self,
+            Some(filter) => self.module_level_filter(filter),
+        }
+    }
+
+    /// apply the env on top of this config, so a deployment can change it without code changes:
+    /// * `FAST_LOG_LEVEL` a level, or module directives like `"hyper=warn,info"`
+    /// * `FAST_LOG_FORMAT` `default` `json` `logfmt` or `csv`
+    /// * `FAST_LOG_FILE` add a FileAppender of the path
+    /// * `FAST_LOG_CONSOLE` `true` adds a ConsoleAppender
+    ///
+    /// `init()` calls this unless `env_overrides(false)`. invalid values are errors of `init()`, see Config::lenient
+    pub fn apply_env(mut self) -> Self {
+        if let Ok(level) = std::env::var("FAST_LOG_LEVEL") {
+            if level.contains('=') || level.contains(',') {
+                self = self.module_levels(&level);
+            } else {
+                match LevelFilter::from_str(level.trim()) {
+                    Ok(level) => self.level = level,
+                    Err(_) => self.errors.push(LogError::from(format!(
+                        "[fast_log] FAST_LOG_LEVEL: unknown level {}",
+                        level
+                    ))),
+                }
+            }
+        }
+        if let Ok(format) = std::env::var("FAST_LOG_FORMAT") {
+            match format.trim() {
+                "default" => self = self.format(FastLogFormat::new()),
+                "json" => self = self.format(FastLogFormatJson::new()),
+                "logfmt" => self = self.format(LogFmtFormat::new()),
+                "csv" => self = self.format(CsvFormat::new()),
+                other => self.errors.push(LogError::from(format!(
+                    "[fast_log] FAST_LOG_FORMAT: unknown format {}",
+                    other
+                ))),
+            }
+        }
+        if let Ok(file) = std::env::var("FAST_LOG_FILE") {
+            if !file.is_empty() {
+                self = self.file(&file);
+            }
+        }
+        if let Ok(console) = std::env::var("FAST_LOG_CONSOLE") {
+            match console.trim() {
+                "true" | "1" => self = self.console(),
+                "false" | "0" | "" => {}
+                other => self.errors.push(LogError::from(format!(
+                    "[fast_log] FAST_LOG_CONSOLE: must be true or false, not {}",
+                    other
+                ))),
+            }
+        }
+        self
+    }
+
+    /// if false, `init()` does not apply the `FAST_LOG_*` env, see Config::apply_env. default true
+    pub fn env_overrides(mut self, env_overrides: bool) -> Self {
+        self.env_overrides = env_overrides;
+        self
+    }
+
+    fn module_level_filter(mut self, filter: ModuleLevelFilter) -> Self {
+        self.level = filter.max_level();
+        self.add_filter(filter)
+    }
+
     /// set log format
     pub fn format<F: RecordFormat + 'static>(mut self, format: F) -> Self {
         self.format = Box::new(format);
         self
     }
+    /// set the time rendering of all built-in formats, for example:
+    /// ```rust,no_run
+    /// use fast_log::{Config, TimeFormat};
+    /// fast_log::init(Config::new().console().time_format(TimeFormat::rfc3339().utc())).unwrap();
+    /// ```
+    pub fn time_format(mut self, time_format: TimeFormat) -> Self {
+        self.time_format = Some(time_format);
+        self
+    }
+
+    /// set log format to json, one json object per line
+    pub fn json(self) -> Self {
+        self.format(FastLogFormatJson::new())
+    }
+    /// set log format to logfmt, `ts=... level=info target=app msg="..."`
+    pub fn logfmt(self) -> Self {
+        self.format(LogFmtFormat::new())
+    }
+    /// set log format to csv, a quoted row `timestamp,level,target,location,message` per record, see CsvFormat
+    pub fn csv(self) -> Self {
+        self.format(CsvFormat::new())
+    }
+    /// set log format by a pattern, for example `"%d [%l] %t %f:%L - %m%n"`, see PatternFormat.
+    /// an invalid pattern is an error of `init()`, see Config::lenient
+    pub fn pattern(mut self, pattern: &str) -> Self {
+        match PatternFormat::new(pattern) {
+            Ok(format) => self.format(format),
+            Err(e) => {
+                self.errors.push(e);
+                self
+            }
+        }
+    }
     /// add a ConsoleAppender
     pub fn console(self) -> Self {
-        self.appends.push(Mutex::new(Box::new(ConsoleAppender {})));
+        self.appends.push(Mutex::new(Box::new(ConsoleAppender::new())));
         self
     }
-    /// add a FileAppender
-    pub fn file(self, file: &str) -> Self {
+    /// add a ConsoleAppender which colors records by level when stdout is a terminal.
+    /// set env `NO_COLOR` to disable colors
+    pub fn console_color(self) -> Self {
         self.appends
-            .push(Mutex::new(Box::new(FileAppender::new(file).unwrap())));
+            .push(Mutex::new(Box::new(ConsoleAppender::new().color(true))));
         self
     }
-    /// add a FileLoopAppender
-    pub fn file_loop(self, file: &str, max_temp_size: LogSize) -> Self {
+    /// add a ConsoleAppender which writes ERROR and WARN to stderr, other levels to stdout
+    pub fn console_by_level(self) -> Self {
         self.appends.push(Mutex::new(Box::new(
-            FileLoopAppender::<RawFile>::new(file, max_temp_size).expect("make file_loop fail"),
+            ConsoleAppender::new().stream(ConsoleStream::ByLevel),
         )));
         self
     }
+    /// add a FileAppender
+    pub fn file(self, file: &str) -> Self {
+        let appender = FileAppender::new(&self.file_path(file));
+        self.try_appender(appender)
+    }
+    /// add an AuditFileAppender, every record is synced to the disk before the next one
+    pub fn file_audit(self, file: &str) -> Self {
+        let appender = AuditFileAppender::new(&self.file_path(file));
+        self.try_appender(appender)
+    }
+    /// add a MsgPackFileAppender, records are written as length-prefixed MessagePack frames, see MsgPackReader
+    pub fn file_msgpack(self, file: &str) -> Self {
+        let appender = MsgPackFileAppender::new(&self.file_path(file));
+        self.try_appender(appender)
+    }
+    /// add a DateFileAppender, records are written into a file named by the date,
+    /// for example `file_date("logs/app.log")` writes `logs/app-2024-05-01.log`
+    pub fn file_date(self, file: &str) -> Self {
+        let appender = DateFileAppender::new(&self.file_path(file));
+        self.try_appender(appender)
+    }
+    /// add a FileLoopAppender
+    pub fn file_loop(self, file: &str, max_temp_size: LogSize) -> Self {
+        let appender = FileLoopAppender::<RawFile>::new(&self.file_path(file), max_temp_size);
+        self.try_appender(appender)
+    }
     /// add a FileSplitAppender
     pub fn file_split<P: Packer + Sync + 'static, R: Keep + 'static>(
         self,
@@ -110,11 +427,60 @@ impl Config {
         rolling_type: R,
         packer: P,
     ) -> Self {
-        self.appends.push(Mutex::new(Box::new(
-            FileSplitAppender::<RawFile>::new(file_path, temp_size, rolling_type, Box::new(packer))
-                .unwrap(),
-        )));
-        self
+        let appender = FileSplitAppender::<RawFile>::new(
+            &self.file_path(file_path),
+            temp_size,
+            rolling_type,
+            Box::new(packer),
+        );
+        self.try_appender(appender)
+    }
+
+    /// add a FileSplitAppender which also split at time boundaries(hourly/daily/weekly)
+    /// for example:
+    /// ```rust,no_run
+    /// use fast_log::Config;
+    /// use fast_log::consts::LogSize;
+    /// use fast_log::plugin::file_split::{KeepType, Period};
+    /// use fast_log::plugin::packer::LogPacker;
+    /// fast_log::init(Config::new().file_split_period(
+    ///     "target/logs/",
+    ///     LogSize::MB(100),
+    ///     Period::Day,
+    ///     KeepType::KeepNum(7),
+    ///     LogPacker {},
+    /// ))
+    /// .unwrap();
+    /// ```
+    pub fn file_split_period<P: Packer + Sync + 'static, R: Keep + 'static>(
+        self,
+        file_path: &str,
+        temp_size: LogSize,
+        period: Period,
+        rolling_type: R,
+        packer: P,
+    ) -> Self {
+        let appender = FileSplitAppender::<RawFile>::new(
+            &self.file_path(file_path),
+            temp_size,
+            rolling_type,
+            Box::new(packer),
+        )
+        .map(|v| v.period(period));
+        self.try_appender(appender)
+    }
+
+    /// add a LevelFileAppender, split `error.log` `warn.log` `info.log`... in `dir` by level
+    pub fn file_level<P: Packer + Clone + Sync + 'static, R: Keep + Clone + 'static>(
+        self,
+        dir: &str,
+        temp_size: LogSize,
+        rolling_type: R,
+        packer: P,
+    ) -> Self {
+        let appender =
+            LevelFileAppender::split(&self.file_path(dir), temp_size, rolling_type, packer);
+        self.try_appender(appender)
     }
 
     /// add a FileRotateAppender
@@ -125,16 +491,13 @@ impl Config {
         rolling_type: R,
         packer: P,
     ) -> Self {
-        self.appends.push(Mutex::new(Box::new(
-            FileRotateAppender::<RawFile, R>::new(
-                file_path,
-                temp_size,
-                rolling_type,
-                Box::new(packer),
-            )
-            .unwrap(),
-        )));
-        self
+        let appender = FileRotateAppender::<RawFile, R>::new(
+            &self.file_path(file_path),
+            temp_size,
+            rolling_type,
+            Box::new(packer),
+        );
+        self.try_appender(appender)
     }
 
     /// add a SplitAppender
@@ -158,11 +521,35 @@ impl Config {
         keeper: R,
         packer: P,
     ) -> Self {
-        self.appends.push(Mutex::new(Box::new(
-            FileSplitAppender::<F>::new(file_path, temp_size, keeper, Box::new(packer)).unwrap(),
-        )));
+        let appender = FileSplitAppender::<F>::new(
+            &self.file_path(file_path),
+            temp_size,
+            keeper,
+            Box::new(packer),
+        );
+        self.try_appender(appender)
+    }
+    /// add the appender, or keep the error for `init()`(see Config::lenient)
+    pub fn try_appender<Appender: LogAppender + 'static>(
+        mut self,
+        appender: Result<Appender, LogError>,
+    ) -> Self {
+        match appender {
+            Ok(appender) => self.add_appender(appender),
+            Err(e) => {
+                self.errors.push(e);
+                self
+            }
+        }
+    }
+
+    /// if true, `init()` reports the errors of appenders which could not be made and logs to
+    /// the console in their place, instead of returning the first error. default false
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
         self
     }
+
     /// add a custom LogAppender
     pub fn custom<Appender: LogAppender + 'static>(self, arg: Appender) -> Self {
         self.add_appender(arg)
@@ -174,9 +561,196 @@ impl Config {
         self
     }
 
+    /// add an AsyncLogAppender, it runs as a task on the tokio runtime of `handle`.
+    /// records are still received by the logger thread, the channel to the task is unbounded.
+    /// do not block a runtime thread on `fast_log::flush().wait()`, use `spawn_blocking`
+    /// ```rust,ignore
+    /// fast_log::init(Config::new().add_async_appender(tokio::runtime::Handle::current(), MyHttpAppender::new()))
+    ///     .unwrap();
+    /// ```
+    #[cfg(feature = "tokio")]
+    pub fn add_async_appender<Appender: crate::appender::AsyncLogAppender + 'static>(
+        mut self,
+        handle: tokio::runtime::Handle,
+        arg: Appender,
+    ) -> Self {
+        self.async_appends.push((handle, std::sync::Arc::new(arg)));
+        self
+    }
+
+    /// add a LogAppender run by `workers` threads instead of one, for slow sinks(compression, network).
+    /// do_logs is called concurrently, so the appender must be Sync.
+    /// flush and exit are sent to every worker
+    /// ```rust,ignore
+    /// use fast_log::Config;
+    /// use fast_log::config::Delivery;
+    /// fast_log::init(Config::new().add_appender_workers(MyHttpAppender::new(), 4, Delivery::Unordered))
+    ///     .unwrap();
+    /// ```
+    pub fn add_appender_workers<Appender: LogAppender + Sync + 'static>(
+        mut self,
+        arg: Appender,
+        workers: usize,
+        delivery: Delivery,
+    ) -> Self {
+        self.worker_appends.push(WorkerAppend {
+            appender: Arc::new(arg),
+            workers: workers.max(1),
+            delivery,
+        });
+        self
+    }
+
+    /// add a LogAppender which formats records with its own format, see FormatAppender
+    pub fn add_appender_format<Appender: LogAppender + 'static, F: RecordFormat + 'static>(
+        self,
+        arg: Appender,
+        format: F,
+    ) -> Self {
+        self.add_appender(FormatAppender::new(arg, format))
+    }
+
+    /// send records whose target is `target` or a child of it(`target::*`) to `appender`,
+    /// the other appenders no longer receive them. for example split audit logs:
+    /// ```rust,no_run
+    /// use fast_log::Config;
+    /// use fast_log::plugin::file::FileAppender;
+    /// fast_log::init(
+    ///     Config::new()
+    ///         .file("target/logs/app.log")
+    ///         .route("audit", FileAppender::new("target/logs/audit.log").unwrap()),
+    /// )
+    /// .unwrap();
+    /// log::info!(target: "audit::login", "bob login"); // only in audit.log
+    /// ```
+    pub fn route<Appender: LogAppender + 'static>(self, target: &str, arg: Appender) -> Self {
+        let target = target.trim_end_matches("::*");
+        self.routes
+            .push((target.to_string(), Mutex::new(Box::new(arg))));
+        self
+    }
+
+    /// call `f` on errors the logger can not return, for example packer failures,
+    /// file create failures and channel send failures. default they are printed to stderr
+    /// ```rust,no_run
+    /// use fast_log::Config;
+    /// fast_log::init(Config::new().file("target/logs/app.log").on_error(|e| {
+    ///     eprintln!("log error: {}", e);
+    /// }))
+    /// .unwrap();
+    /// ```
+    pub fn on_error<F: Fn(&LogError) + Send + Sync + 'static>(mut self, f: F) -> Self {
+        self.on_error = Some(Box::new(f));
+        self
+    }
+
+    /// add an enricher, enrichers run in order on every record before it is formatted.
+    /// for example stamp the app version on all records:
+    /// ```rust,no_run
+    /// use fast_log::Config;
+    /// fast_log::init(Config::new().json().console().enricher(|r| {
+    ///     r.key_values.push(("version".to_string(), env!("CARGO_PKG_VERSION").to_string()));
+    /// }))
+    /// .unwrap();
+    /// ```
+    pub fn enricher<F: Fn(&mut FastLogRecord) + Send + Sync + 'static>(mut self, f: F) -> Self {
+        self.enrichers.push(Box::new(f));
+        self
+    }
+
+    /// capture the trace_id and span_id of the current span on the logging thread,
+    /// the json format writes them as `"trace_id"` and `"span_id"`. for example with opentelemetry:
+    /// ```rust,ignore
+    /// use opentelemetry::trace::TraceContextExt;
+    /// fast_log::init(Config::new().json().console().trace_context(|| {
+    ///     let cx = opentelemetry::Context::current();
+    ///     let span = cx.span().span_context().clone();
+    ///     span.is_valid()
+    ///         .then(|| (span.trace_id().to_string(), span.span_id().to_string()))
+    /// }))
+    /// .unwrap();
+    /// ```
+    pub fn trace_context<F: Fn() -> Option<(String, String)> + Send + Sync + 'static>(
+        mut self,
+        f: F,
+    ) -> Self {
+        self.trace_context = Some(Box::new(f));
+        self
+    }
+
+    /// truncate records longer than `len` formatted bytes. the message is cut and ends with
+    /// `...(truncated, N bytes)`(N the original length), so json stays valid. default None
+    pub fn max_record_len(mut self, len: usize) -> Self {
+        self.max_record_len = Some(len);
+        self
+    }
+
+    /// `init()` listens on the unix socket `path` for commands like `level debug` `flush` `rotate` `stats`,
+    /// see `fast_log::plugin::control`. an error of `init()` on other systems
+    pub fn control_socket(mut self, path: &str) -> Self {
+        if cfg!(unix) {
+            self.control_socket = Some(path.to_string());
+        } else {
+            self.errors
+                .push(LogError::from("[fast_log] control_socket needs a unix system"));
+        }
+        self
+    }
+
+    /// the time source of records(so of formats and rotation periods) and of pack names, default SystemClock.
+    /// for example a MockClock in tests
+    pub fn clock<C: Clock + 'static>(mut self, clock: C) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
     /// if none=> unbounded() channel,if Some =>  bounded(len) channel
     pub fn chan_len(mut self, len: Option<usize>) -> Self {
         self.chan_len = len;
         self
     }
+
+    /// if true, records are formatted and written by the logging thread under a mutex,
+    /// no channel and appender threads are used. slower, but a record is written when `log!` returns.
+    /// appenders must not log, or they deadlock
+    pub fn sync(mut self, sync: bool) -> Self {
+        self.sync = sync;
+        self
+    }
+
+    /// if the dir of a file appender added after this can not be created(for example no permission),
+    /// report the error and create it under `{temp dir}/fast_log/` instead, default false
+    pub fn temp_dir_fallback(mut self, fallback: bool) -> Self {
+        self.temp_dir_fallback = fallback;
+        self
+    }
+
+    /// `path`, or the same path in the temp dir if its dir can not be created and temp_dir_fallback is set
+    pub(crate) fn file_path(&self, path: &str) -> String {
+        if !self.temp_dir_fallback {
+            return path.to_string();
+        }
+        let normalized = path.replace("\\", "/");
+        let dir = match normalized.rfind('/') {
+            Some(right) => &normalized[..right],
+            None => return path.to_string(),
+        };
+        match std::fs::create_dir_all(dir) {
+            Ok(_) => path.to_string(),
+            Err(e) => {
+                let temp = temp_dir_path(path);
+                crate::report_error(&LogError::from(format!(
+                    "[fast_log] create {} fail: {}, use {}",
+                    dir, e, temp
+                )));
+                temp
+            }
+        }
+    }
+
+    /// set the OverflowPolicy of bounded channel, dropped records can be read by `LOGGER.dropped()`
+    pub fn overflow(mut self, policy: OverflowPolicy) -> Self {
+        self.overflow = policy;
+        self
+    }
 }
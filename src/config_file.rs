@@ -0,0 +1,577 @@
+//! build a Config from a toml file, see `fast_log::init_from_file`.
+//!
+//! needs the `toml` feature. keys are strings, integers, booleans or arrays of them,
+//! appenders are `[[appender]]` tables. unknown keys are errors, so typos are found at init.
+//! ```toml
+//! level = "info"                       # off error warn info debug trace
+//! module_levels = "hyper=warn,info"    # see Config::module_levels
+//! format = "json"                      # default json logfmt csv, or `pattern = "%d [%l] %t - %m%n"`
+//! chan_len = 100000
+//! deny_targets = ["noisy_crate"]
+//! allow_targets = []
+//! deny_args = ["^health check"]        # regex of messages to drop, see RegexFilter(regex feature)
+//! max_record_len = 65536
+//! lenient = true                       # see Config::lenient
+//!
+//! [[appender]]
+//! type = "console"                     # stream = "stdout" "stderr" "by_level", color = true
+//!
+//! [[appender]]
+//! type = "file_split"                  # also "file" "file_audit" "file_date" "file_loop" "file_level" "file_msgpack"
+//! path = "logs/"
+//! size = "100MB"
+//! keep = 7                             # "all", a number of packs, a duration "7d" "12h" "30m" or a size "2GB"
+//! packer = "gzip"                      # log zip gzip lz4 zstd, with their features
+//! period = "day"                       # hour day week
+//! ```
+use crate::config::Config;
+use crate::consts::LogSize;
+use crate::error::LogError;
+use crate::fast_log::Logger;
+use crate::filter::{Filter, FilterHandle};
+use crate::plugin::console::{ColorMode, ConsoleAppender, ConsoleStream};
+use crate::plugin::file::FileAppender;
+use crate::plugin::file_audit::AuditFileAppender;
+use crate::plugin::file_date::DateFileAppender;
+use crate::plugin::file_level::LevelFileAppender;
+use crate::plugin::file_loop::FileLoopAppender;
+use crate::plugin::file_split::{FileSplitAppender, KeepType, Packer, Period, RawFile};
+use crate::plugin::msgpack::MsgPackFileAppender;
+use crate::plugin::packer::LogPacker;
+use crate::{CsvFormat, FastLogFormatJson, LogFmtFormat, PatternFormat};
+use dark_std::sync::SyncVec;
+use log::{Level, LevelFilter};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+impl Config {
+    /// read a toml config file, see `fast_log::config_file`
+    pub fn from_file(path: &str) -> Result<Config, LogError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| LogError::from(format!("[fast_log] read {} fail: {}", path, e)))?;
+        Self::from_toml(&text).map_err(|e| LogError::from(format!("{}: {}", path, e)))
+    }
+
+    /// parse a toml config, see `fast_log::config_file`
+    pub fn from_toml(text: &str) -> Result<Config, LogError> {
+        let doc = parse(text)?;
+        let mut config = root_config(&doc)?;
+        for (_, table) in &doc.tables {
+            config = add_appender(config, table)?;
+        }
+        Ok(config)
+    }
+}
+
+/// the config of the root keys, without appenders
+fn root_config(doc: &Document) -> Result<Config, LogError> {
+    let root = &doc.root;
+    root.check(&[
+        "level",
+        "module_levels",
+        "format",
+        "pattern",
+        "chan_len",
+        "sync",
+        "deny_targets",
+        "allow_targets",
+        "deny_args",
+        "max_record_len",
+        "lenient",
+        "temp_dir_fallback",
+    ])?;
+    let mut config = Config::new();
+    if let Some(v) = root.get("lenient") {
+        config = config.lenient(v.as_bool("lenient")?);
+    }
+    if let Some(v) = root.get("temp_dir_fallback") {
+        config = config.temp_dir_fallback(v.as_bool("temp_dir_fallback")?);
+    }
+    if let Some(v) = root.get("module_levels") {
+        config = config.module_levels(v.as_str("module_levels")?);
+    }
+    if let Some(v) = root.get("level") {
+        let level = v.as_str("level")?;
+        config = config.level(
+            LevelFilter::from_str(level)
+                .map_err(|_| LogError::from(format!("unknown level {}", level)))?,
+        );
+    }
+    match root.get("format").map(|v| v.as_str("format")).transpose()? {
+        None | Some("default") => {}
+        Some("json") => config = config.format(FastLogFormatJson::new()),
+        Some("logfmt") => config = config.format(LogFmtFormat::new()),
+        Some("csv") => config = config.format(CsvFormat::new()),
+        Some(other) => return Err(LogError::from(format!("unknown format {}", other))),
+    }
+    if let Some(v) = root.get("pattern") {
+        config = config.format(PatternFormat::new(v.as_str("pattern")?)?);
+    }
+    if let Some(v) = root.get("chan_len") {
+        config = config.chan_len(Some(v.as_usize("chan_len")?));
+    }
+    if let Some(v) = root.get("sync") {
+        config = config.sync(v.as_bool("sync")?);
+    }
+    if let Some(v) = root.get("max_record_len") {
+        config = config.max_record_len(v.as_usize("max_record_len")?);
+    }
+    for v in root.strings("deny_targets")? {
+        config = config.deny_target(v);
+    }
+    for v in root.strings("allow_targets")? {
+        config = config.allow_target(v);
+    }
+    let deny_args = root.strings("deny_args")?;
+    if !deny_args.is_empty() {
+        config = deny_args_filter(config, deny_args)?;
+    }
+    if let Some((name, _)) = doc.tables.iter().find(|(name, _)| name != "appender") {
+        return Err(LogError::from(format!("unknown table [{}]", name)));
+    }
+    Ok(config)
+}
+
+#[cfg(feature = "regex")]
+fn deny_args_filter(config: Config, deny_args: Vec<&str>) -> Result<Config, LogError> {
+    let mut filter = crate::filter::RegexFilter::new();
+    for v in deny_args {
+        filter = filter.args(v)?;
+    }
+    Ok(config.add_filter(filter))
+}
+
+#[cfg(not(feature = "regex"))]
+fn deny_args_filter(_config: Config, _deny_args: Vec<&str>) -> Result<Config, LogError> {
+    Err(LogError::from(
+        "deny_args needs the regex feature of fast_log",
+    ))
+}
+
+/// add the appender of a `[[appender]]` table
+fn add_appender(config: Config, t: &Table) -> Result<Config, LogError> {
+    let kind = t
+        .get("type")
+        .ok_or_else(|| LogError::from("[[appender]] needs a type"))?
+        .as_str("type")?;
+    match kind {
+        "console" => {
+            t.check(&["type", "stream", "color"])?;
+            let stream = match t.get("stream").map(|v| v.as_str("stream")).transpose()? {
+                None | Some("stdout") => ConsoleStream::Stdout,
+                Some("stderr") => ConsoleStream::Stderr,
+                Some("by_level") => ConsoleStream::ByLevel,
+                Some(other) => return Err(LogError::from(format!("unknown stream {}", other))),
+            };
+            let color = match t.get("color") {
+                Some(v) if v.as_bool("color")? => ColorMode::Auto,
+                _ => ColorMode::Never,
+            };
+            Ok(config.add_appender(ConsoleAppender::new().stream(stream).color_mode(color)))
+        }
+        "file" => {
+            t.check(&["type", "path", "lock"])?;
+            let lock = match t.get("lock") {
+                Some(v) => v.as_bool("lock")?,
+                None => false,
+            };
+            let appender = FileAppender::new(&t.path(&config)?).map(|v| v.lock(lock));
+            Ok(config.try_appender(appender))
+        }
+        "file_audit" => {
+            t.check(&["type", "path"])?;
+            let appender = AuditFileAppender::new(&t.path(&config)?);
+            Ok(config.try_appender(appender))
+        }
+        "file_msgpack" => {
+            t.check(&["type", "path"])?;
+            let appender = MsgPackFileAppender::new(&t.path(&config)?);
+            Ok(config.try_appender(appender))
+        }
+        "file_date" => {
+            t.check(&["type", "path"])?;
+            let appender = DateFileAppender::new(&t.path(&config)?);
+            Ok(config.try_appender(appender))
+        }
+        "file_loop" => {
+            t.check(&["type", "path", "size"])?;
+            let appender = FileLoopAppender::<RawFile>::new(&t.path(&config)?, t.size()?);
+            Ok(config.try_appender(appender))
+        }
+        "file_split" => {
+            t.check(&["type", "path", "size", "keep", "packer", "period"])?;
+            let period = match t.get("period").map(|v| v.as_str("period")).transpose()? {
+                None => None,
+                Some("hour") => Some(Period::Hour),
+                Some("day") => Some(Period::Day),
+                Some("week") => Some(Period::Week),
+                Some(other) => return Err(LogError::from(format!("unknown period {}", other))),
+            };
+            let path = t.path(&config)?;
+            let appender =
+                FileSplitAppender::<RawFile>::new(&path, t.size()?, t.keep()?, t.packer()?).map(
+                    |v| match period {
+                        Some(period) => v.period(period),
+                        None => v,
+                    },
+                );
+            Ok(config.try_appender(appender))
+        }
+        "file_level" => {
+            t.check(&["type", "path", "size", "keep", "packer"])?;
+            let mut dir = t.path(&config)?.replace("\\", "/");
+            if !dir.is_empty() && !dir.ends_with('/') {
+                dir.push('/');
+            }
+            let mut appender = Ok(LevelFileAppender::new());
+            for level in Level::iter() {
+                let file_path = format!("{}{}.log", dir, level.as_str().to_lowercase());
+                let split = FileSplitAppender::<RawFile>::new(
+                    &file_path,
+                    t.size()?,
+                    t.keep()?,
+                    t.packer()?,
+                );
+                appender = appender.and_then(|a| Ok(a.level(level, split?)));
+            }
+            Ok(config.try_appender(appender))
+        }
+        other => Err(LogError::from(format!("unknown appender type {}", other))),
+    }
+}
+
+/// reload a watched config file, see `fast_log::init_from_file_watch`.
+/// the level, targets, filters and appenders follow the file, other root keys are read at init only
+pub(crate) struct Reloader {
+    path: String,
+    modified: Option<SystemTime>,
+    filter: FilterHandle,
+    /// (table, appender name) of the appenders added from the file
+    appenders: Vec<(String, String)>,
+    next_id: usize,
+}
+
+impl Reloader {
+    /// the config to init the logger with, its targets and filters are moved to the reloaded filter
+    pub(crate) fn new(path: &str) -> Result<(Config, Self), LogError> {
+        let (mut config, _) = read(path)?;
+        config.filters = SyncVec::new();
+        config.deny_targets.clear();
+        config.allow_targets.clear();
+        let filter = FilterHandle::default();
+        let reloader = Self {
+            path: path.to_string(),
+            modified: None,
+            filter: filter.clone(),
+            appenders: vec![],
+            next_id: 0,
+        };
+        Ok((config.add_filter(filter), reloader))
+    }
+
+    /// true if the file was modified after the last reload
+    pub(crate) fn changed(&self) -> bool {
+        modified(&self.path) != self.modified
+    }
+
+    /// read the file, set the level and filters, remove appenders whose table is gone or changed
+    /// and add the new ones. nothing is changed on a parse error. an appender that fails to create
+    /// is reported in lenient mode(or returned) and created again at the next change
+    pub(crate) fn reload(&mut self, logger: &Logger) -> Result<(), LogError> {
+        let reload = self.prepare()?;
+        self.apply(logger, reload)
+    }
+
+    /// read the file and create the new appenders, the logger is not changed yet
+    pub(crate) fn prepare(&mut self) -> Result<Reload, LogError> {
+        self.modified = modified(&self.path);
+        let (config, doc) = read(&self.path)?;
+        let mut removed = self.appenders.clone();
+        let mut kept = vec![];
+        let mut created = vec![];
+        for (_, table) in &doc.tables {
+            let key = format!("{:?}", table.entries);
+            match removed.iter().position(|(k, _)| *k == key) {
+                Some(i) => kept.push(removed.remove(i)),
+                None => {
+                    let scratch = Config::new().temp_dir_fallback(config.temp_dir_fallback);
+                    created.push((key, add_appender(scratch, table)?));
+                }
+            }
+        }
+        Ok(Reload {
+            config,
+            removed,
+            kept,
+            created,
+        })
+    }
+
+    /// set the level and filters, remove and add the appenders by commands, the dispatcher must be running
+    pub(crate) fn apply(&mut self, logger: &Logger, reload: Reload) -> Result<(), LogError> {
+        let Reload {
+            config,
+            removed,
+            mut kept,
+            created,
+        } = reload;
+        let level = config.level;
+        let lenient = config.lenient;
+        self.filter.set(TargetFilter(config));
+        logger.set_level(level);
+        for (_, name) in removed {
+            logger.remove_appender(&name)?;
+        }
+        let mut result = Ok(());
+        for (key, mut scratch) in created {
+            if let Some(e) = scratch.errors.pop() {
+                if lenient {
+                    crate::report_error(&e);
+                } else if result.is_ok() {
+                    result = Err(e);
+                }
+                continue;
+            }
+            for appender in scratch.appends.into_inner() {
+                self.next_id += 1;
+                let name = format!("config_file:{}", self.next_id);
+                logger.add_appender(&name, appender.into_inner())?;
+                kept.push((key.clone(), name));
+            }
+        }
+        self.appenders = kept;
+        result
+    }
+}
+
+/// the changes of a reload, see Reloader::prepare
+pub(crate) struct Reload {
+    config: Config,
+    /// (table, appender name) of the appenders to remove and to keep
+    removed: Vec<(String, String)>,
+    kept: Vec<(String, String)>,
+    /// (table, config with the new appender or its error)
+    created: Vec<(String, Config)>,
+}
+
+impl Reload {
+    /// the first error of the new appenders
+    pub(crate) fn error(&self) -> Option<&LogError> {
+        self.created.iter().find_map(|(_, c)| c.errors.last())
+    }
+
+    /// true if the logger has appenders of the file after apply
+    pub(crate) fn has_appenders(&self) -> bool {
+        !self.kept.is_empty() || self.created.iter().any(|(_, c)| c.errors.is_empty())
+    }
+}
+
+/// the targets and filters of a reloaded file
+struct TargetFilter(Config);
+
+impl Filter for TargetFilter {
+    fn accept(&self, record: &log::Record) -> bool {
+        self.0.target_enabled(record.target()) && self.0.filters.iter().all(|f| f.accept(record))
+    }
+}
+
+/// read the root config and the tables of a file
+fn read(path: &str) -> Result<(Config, Document), LogError> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| LogError::from(format!("[fast_log] read {} fail: {}", path, e)))?;
+    let with_path = |e: LogError| LogError::from(format!("{}: {}", path, e));
+    let doc = parse(&text).map_err(with_path)?;
+    let config = root_config(&doc).map_err(with_path)?;
+    Ok((config, doc))
+}
+
+fn modified(path: &str) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Value {
+    Str(String),
+    Int(i64),
+    Bool(bool),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    fn as_str(&self, key: &str) -> Result<&str, LogError> {
+        match self {
+            Value::Str(v) => Ok(v),
+            _ => Err(LogError::from(format!("{} must be a string", key))),
+        }
+    }
+
+    fn as_bool(&self, key: &str) -> Result<bool, LogError> {
+        match self {
+            Value::Bool(v) => Ok(*v),
+            _ => Err(LogError::from(format!("{} must be true or false", key))),
+        }
+    }
+
+    fn as_usize(&self, key: &str) -> Result<usize, LogError> {
+        match self {
+            Value::Int(v) if *v >= 0 => Ok(*v as usize),
+            _ => Err(LogError::from(format!("{} must be a positive integer", key))),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Table {
+    entries: Vec<(String, Value)>,
+}
+
+impl Table {
+    fn get(&self, key: &str) -> Option<&Value> {
+        self.entries.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    /// error on keys not in `keys`
+    fn check(&self, keys: &[&str]) -> Result<(), LogError> {
+        match self.entries.iter().find(|(k, _)| !keys.contains(&k.as_str())) {
+            Some((k, _)) => Err(LogError::from(format!("unknown key {}", k))),
+            None => Ok(()),
+        }
+    }
+
+    /// the strings of an array, empty if the key is missing
+    fn strings(&self, key: &str) -> Result<Vec<&str>, LogError> {
+        match self.get(key) {
+            None => Ok(vec![]),
+            Some(Value::Array(items)) => items.iter().map(|v| v.as_str(key)).collect(),
+            Some(_) => Err(LogError::from(format!("{} must be an array", key))),
+        }
+    }
+
+    /// the path, moved to the temp dir by Config::temp_dir_fallback
+    fn path(&self, config: &Config) -> Result<String, LogError> {
+        let path = self
+            .get("path")
+            .ok_or_else(|| LogError::from("[[appender]] needs a path"))?
+            .as_str("path")?;
+        Ok(config.file_path(path))
+    }
+
+    fn size(&self) -> Result<LogSize, LogError> {
+        match self.get("size") {
+            None => Ok(LogSize::MB(100)),
+            Some(v) => LogSize::parse(v.as_str("size")?),
+        }
+    }
+
+    fn keep(&self) -> Result<KeepType, LogError> {
+        let v = match self.get("keep") {
+            None => return Ok(KeepType::All),
+            Some(Value::Int(n)) => return Ok(KeepType::KeepNum(*n)),
+            Some(v) => v.as_str("keep")?,
+        };
+        if v == "all" {
+            return Ok(KeepType::All);
+        }
+        if v.ends_with('B') {
+            return Ok(KeepType::KeepSize(LogSize::parse(v)?));
+        }
+        let unit = match v.chars().last() {
+            Some('d') => 24 * 3600,
+            Some('h') => 3600,
+            Some('m') => 60,
+            Some('s') => 1,
+            _ => return Err(LogError::from(format!("unknown keep {}", v))),
+        };
+        let n: u64 = v[..v.len() - 1]
+            .parse()
+            .map_err(|_| LogError::from(format!("unknown keep {}", v)))?;
+        Ok(KeepType::KeepTime(Duration::from_secs(n * unit)))
+    }
+
+    fn packer(&self) -> Result<Box<dyn Packer>, LogError> {
+        let name = match self.get("packer") {
+            None => return Ok(Box::new(LogPacker {})),
+            Some(v) => v.as_str("packer")?,
+        };
+        match name {
+            "log" => Ok(Box::new(LogPacker {})),
+            #[cfg(feature = "zip")]
+            "zip" => Ok(Box::new(crate::plugin::packer::ZipPacker {})),
+            #[cfg(feature = "gzip")]
+            "gzip" => Ok(Box::new(crate::plugin::packer::GZipPacker {})),
+            #[cfg(feature = "lz4")]
+            "lz4" => Ok(Box::new(crate::plugin::packer::LZ4Packer {})),
+            #[cfg(feature = "zstd")]
+            "zstd" => Ok(Box::new(crate::plugin::packer::ZstdPacker::default())),
+            #[allow(unreachable_patterns)]
+            "zip" | "gzip" | "lz4" | "zstd" => Err(LogError::from(format!(
+                "packer {} needs the {} feature of fast_log",
+                name, name
+            ))),
+            other => Err(LogError::from(format!("unknown packer {}", other))),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Document {
+    root: Table,
+    /// `[name]` and `[[name]]` tables, in file order for each name
+    tables: Vec<(String, Table)>,
+}
+
+/// the root keys and `[[appender]]` tables of a toml text
+fn parse(text: &str) -> Result<Document, LogError> {
+    let root = text
+        .parse::<toml::Table>()
+        .map_err(|e| LogError::from(format!("[fast_log] {}", e)))?;
+    let mut doc = Document::default();
+    for (key, value) in root {
+        match value {
+            toml::Value::Table(t) => doc.tables.push((key, table(t)?)),
+            toml::Value::Array(items) if is_tables(&items) => {
+                for item in items {
+                    if let toml::Value::Table(t) = item {
+                        doc.tables.push((key.clone(), table(t)?));
+                    }
+                }
+            }
+            value => {
+                let value = to_value(&key, value)?;
+                doc.root.entries.push((key, value));
+            }
+        }
+    }
+    Ok(doc)
+}
+
+/// true for a `[[name]]` array of tables
+fn is_tables(items: &[toml::Value]) -> bool {
+    !items.is_empty() && items.iter().all(|v| v.is_table())
+}
+
+fn table(t: toml::Table) -> Result<Table, LogError> {
+    let mut table = Table::default();
+    for (key, value) in t {
+        let value = to_value(&key, value)?;
+        table.entries.push((key, value));
+    }
+    Ok(table)
+}
+
+fn to_value(key: &str, value: toml::Value) -> Result<Value, LogError> {
+    match value {
+        toml::Value::String(v) => Ok(Value::Str(v)),
+        toml::Value::Integer(v) => Ok(Value::Int(v)),
+        toml::Value::Boolean(v) => Ok(Value::Bool(v)),
+        toml::Value::Array(items) => items
+            .into_iter()
+            .map(|v| to_value(key, v))
+            .collect::<Result<_, _>>()
+            .map(Value::Array),
+        _ => Err(LogError::from(format!(
+            "{} must be a string, integer, boolean or array",
+            key
+        ))),
+    }
+}
@@ -0,0 +1,61 @@
+//! thread-local context(MDC), merged into the key_values of every record logged on this thread.
+//! for example:
+//! ```rust,no_run
+//! use fast_log::Config;
+//! fast_log::init(Config::new().console().json()).unwrap();
+//! fast_log::context::insert("request_id", "4bf92f35");
+//! log::info!("handle request"); // {"args":"handle request",...,"request_id":"4bf92f35"}
+//! fast_log::context::remove("request_id");
+//! ```
+use std::cell::RefCell;
+
+thread_local! {
+    static CONTEXT: RefCell<Vec<(String, String)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// insert or replace a value of this thread's context
+pub fn insert<V: ToString>(key: &str, value: V) {
+    let value = value.to_string();
+    CONTEXT.with(|c| {
+        let mut c = c.borrow_mut();
+        match c.iter_mut().find(|(k, _)| k == key) {
+            Some((_, v)) => *v = value,
+            None => c.push((key.to_string(), value)),
+        }
+    });
+}
+
+/// remove a value of this thread's context, return the old value
+pub fn remove(key: &str) -> Option<String> {
+    CONTEXT.with(|c| {
+        let mut c = c.borrow_mut();
+        let index = c.iter().position(|(k, _)| k == key)?;
+        Some(c.remove(index).1)
+    })
+}
+
+/// get a value of this thread's context
+pub fn get(key: &str) -> Option<String> {
+    CONTEXT.with(|c| {
+        c.borrow()
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.clone())
+    })
+}
+
+/// remove all values of this thread's context
+pub fn clear() {
+    CONTEXT.with(|c| c.borrow_mut().clear());
+}
+
+/// append the context to key_values, keys already in key_values are skipped
+pub(crate) fn merge(key_values: &mut Vec<(String, String)>) {
+    CONTEXT.with(|c| {
+        for (k, v) in c.borrow().iter() {
+            if !key_values.iter().any(|(key, _)| key == k) {
+                key_values.push((k.clone(), v.clone()));
+            }
+        }
+    });
+}
@@ -33,6 +33,13 @@ impl From<std::io::Error> for LogError {
     }
 }
 
+#[cfg(feature = "regex")]
+impl From<regex::Error> for LogError {
+    fn from(value: regex::Error) -> Self {
+        LogError::E(value.to_string())
+    }
+}
+
 impl Display for LogError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         return match self {
@@ -1,88 +1,533 @@
-use crate::appender::{Command, FastLogRecord};
-use crate::config::Config;
+use crate::appender::{Command, FastLogRecord, LogAppender, NamedAppender};
+use crate::config::{Config, Delivery, OverflowPolicy, WorkerAppend};
+#[cfg(feature = "toml")]
+use crate::config_file::Reloader;
 use crate::error::LogError;
-use crate::{chan, spawn, Receiver, SendError, Sender, WaitGroup};
+use crate::filter::module_match;
+use crate::{chan, spawn, Receiver, SendError, Sender, TrySendError, WaitGroup};
+use log::kv::{self, Key, Value, VisitSource};
 use log::{LevelFilter, Log, Metadata, Record};
 use once_cell::sync::{Lazy, OnceCell};
+use parking_lot::Mutex;
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
 use std::ops::Deref;
-use std::sync::Arc;
-use std::time::SystemTime;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, SystemTime};
 
-pub static LOGGER: Lazy<Logger> = Lazy::new(|| Logger {
-    cfg: OnceCell::new(),
-    send: OnceCell::new(),
-    recv: OnceCell::new(),
-});
+pub static LOGGER: Lazy<Logger> = Lazy::new(Logger::new);
+
+/// how many FastLoggers and with_level scopes hold each level, indexed by LevelFilter as usize.
+/// `log::max_level()` is the most verbose held level or the level of the global logger,
+/// the log macros skip records above it
+static LEVEL_HOLDS: Mutex<[usize; 6]> = Mutex::new([0; 6]);
+
+/// add and remove a hold of a level, then update `log::max_level()`
+fn change_level_holds(add: Option<LevelFilter>, remove: Option<LevelFilter>) {
+    let mut holds = LEVEL_HOLDS.lock();
+    if let Some(level) = add {
+        holds[level as usize] += 1;
+    }
+    if let Some(level) = remove {
+        holds[level as usize] = holds[level as usize].saturating_sub(1);
+    }
+    let held = (0..holds.len()).rev().find(|i| holds[*i] > 0).unwrap_or(0);
+    let held = LevelFilter::iter().nth(held).unwrap_or(LevelFilter::Off);
+    log::set_max_level(LOGGER.get_level().max(held));
+}
+
+thread_local! {
+    /// the level of with_level on this thread
+    static THREAD_LEVEL: Cell<Option<LevelFilter>> = const { Cell::new(None) };
+    /// the FastLogger whose dispatcher, appender or inline call runs on this thread, None for the global logger
+    static CURRENT: RefCell<Option<Weak<Logger>>> = const { RefCell::new(None) };
+    /// (logger address, records logged again) of each Logger::send_inline running on this thread
+    static INLINE_QUEUES: RefCell<Vec<(usize, Vec<FastLogRecord>)>> = const { RefCell::new(vec![]) };
+}
+
+/// call `f` with the logger running on this thread, see Logger::enter. the global logger if there is none.
+/// time_format, on_error and the counters of formats, filters and appenders are read from it
+pub(crate) fn with_logger<R>(f: impl FnOnce(&Logger) -> R) -> R {
+    let current = CURRENT
+        .try_with(|c| c.borrow().as_ref().and_then(Weak::upgrade))
+        .ok()
+        .flatten();
+    match current {
+        Some(logger) => f(&logger),
+        None => f(&LOGGER),
+    }
+}
+
+/// spawn a thread running in the logger of the current thread, see Logger::enter
+pub(crate) fn spawn_in_logger<F: FnOnce() + Send + 'static>(f: F) {
+    let current = CURRENT.with(|c| c.borrow().clone());
+    spawn(move || {
+        CURRENT.with(|c| *c.borrow_mut() = current);
+        f()
+    });
+}
+
+/// restore the logger of the thread on Drop, see Logger::enter
+pub(crate) struct LoggerScope(Option<Weak<Logger>>);
+
+impl Drop for LoggerScope {
+    fn drop(&mut self) {
+        let _ = CURRENT.try_with(|c| *c.borrow_mut() = self.0.take());
+    }
+}
 
 pub struct Logger {
     pub cfg: OnceCell<Config>,
     pub send: OnceCell<Sender<FastLogRecord>>,
     pub recv: OnceCell<Receiver<FastLogRecord>>,
+    /// records dropped by OverflowPolicy
+    pub dropped: AtomicU64,
+    /// records accepted by filters and sent to the channel
+    pub received: AtomicU64,
+    /// files rotated by split and rotate appenders
+    pub rotations: AtomicU64,
+    /// records lost by failed writes of file appenders, see WritePolicy
+    pub write_errors: AtomicU64,
+    /// the last sequence number of records, see FastLogRecord::seq
+    pub seq: AtomicU64,
+    /// the LevelFilter as usize
+    level: AtomicUsize,
+    /// (appender name, formatted bytes sent to it)
+    appender_bytes: Mutex<Vec<(String, Arc<AtomicU64>)>>,
+    /// appenders called inline by Logger::log, see Config::sync
+    inline: Mutex<Vec<AppenderSender>>,
+    /// set by FastLogger::new, None for the global logger
+    this: OnceCell<Weak<Logger>>,
+}
+
+impl Drop for Logger {
+    fn drop(&mut self) {
+        //the global logger is never dropped, other loggers release their level hold
+        change_level_holds(None, Some(self.get_level()));
+    }
+}
+
+/// counters of the logger, see `fast_log::metrics()`
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Metrics {
+    /// records accepted by filters and sent to the channel
+    pub received: u64,
+    /// records dropped by OverflowPolicy
+    pub dropped: u64,
+    /// records waiting in the main channel
+    pub queue_len: usize,
+    /// formatted bytes sent to each appender. appenders of Config are named by index(`"0"`, `"1"`),
+    /// route appenders by `"route:<target>"`, and appenders of Logger::add_appender by their name
+    pub appender_bytes: Vec<(String, u64)>,
+    /// files rotated by split and rotate appenders
+    pub rotations: u64,
+    /// records lost by failed writes of file appenders, see WritePolicy
+    pub write_errors: u64,
+    /// the last sequence number of records, see FastLogRecord::seq
+    pub seq: u64,
 }
 
 impl Logger {
+    /// a logger not started yet, see init and FastLogger
+    pub fn new() -> Self {
+        Self {
+            cfg: OnceCell::new(),
+            send: OnceCell::new(),
+            recv: OnceCell::new(),
+            dropped: AtomicU64::new(0),
+            received: AtomicU64::new(0),
+            rotations: AtomicU64::new(0),
+            write_errors: AtomicU64::new(0),
+            seq: AtomicU64::new(0),
+            level: AtomicUsize::new(LevelFilter::Off as usize),
+            appender_bytes: Mutex::new(vec![]),
+            inline: Mutex::new(vec![]),
+            this: OnceCell::new(),
+        }
+    }
+
+    /// make this the logger of the current thread until the scope is dropped, see `with_logger`
+    pub(crate) fn enter(&self) -> LoggerScope {
+        let this = self.this.get().cloned();
+        LoggerScope(CURRENT.with(|c| c.replace(this)))
+    }
+
     pub fn set_level(&self, level: LevelFilter) {
-        log::set_max_level(level);
+        let old = self.level.swap(level as usize, Ordering::Relaxed);
+        if std::ptr::eq(self, LOGGER.deref()) {
+            change_level_holds(None, None);
+        } else {
+            //a FastLogger holds its level until it is dropped
+            let old = LevelFilter::iter().nth(old).unwrap_or(LevelFilter::Off);
+            change_level_holds(Some(level), Some(old));
+        }
     }
 
     pub fn get_level(&self) -> LevelFilter {
-        log::max_level()
+        LevelFilter::iter()
+            .nth(self.level.load(Ordering::Relaxed))
+            .unwrap_or(LevelFilter::Off)
     }
 
     /// print no other info
     pub fn print(&self, log: String) -> Result<(), SendError<FastLogRecord>> {
+        let (thread_id, thread_name) = current_thread();
         let fast_log_record = FastLogRecord {
             command: Command::CommandRecord,
             level: log::Level::Info,
-            target: "".to_string(),
+            target: "".into(),
             args: "".to_string(),
-            module_path: "".to_string(),
-            file: "".to_string(),
+            module_path: "".into(),
+            file: "".into(),
             line: None,
-            now: SystemTime::now(),
+            now: now(),
             formated: log,
+            key_values: vec![],
+            pid: std::process::id(),
+            thread_id,
+            thread_name,
+            hostname: cached_hostname(),
+            trace_id: None,
+            span_id: None,
+            seq: self.next_seq(),
         };
-        if let Some(send) = LOGGER.send.get() {
-            send.send(fast_log_record)
-        } else {
-            // Ok(())
-            Err(crossbeam_channel::SendError(fast_log_record))
+        let result = self.send(fast_log_record);
+        if result.is_ok() {
+            self.received.fetch_add(1, Ordering::Relaxed);
         }
+        result.map_err(|e| *e)
     }
 
     pub fn wait(&self) {
         self.flush();
     }
+
+    /// start an appender on the running logger, records logged after this are sent to it.
+    /// for example attach a network appender only while debugging
+    /// ```rust,no_run
+    /// use fast_log::Config;
+    /// use fast_log::plugin::tcp::TcpAppender;
+    /// let logger = fast_log::init(Config::new().console()).unwrap();
+    /// logger.add_appender("debug", Box::new(TcpAppender::new("127.0.0.1:5000"))).unwrap();
+    /// //...
+    /// logger.remove_appender("debug").unwrap();
+    /// ```
+    pub fn add_appender(&self, name: &str, appender: Box<dyn LogAppender>) -> Result<(), LogError> {
+        let record = command_record(Command::CommandAddAppender(NamedAppender::new(
+            name, appender,
+        )));
+        self.send_command(record, "[fast_log] add appender fail!")
+    }
+
+    /// exit and remove all appenders added by `add_appender` with this name,
+    /// records logged before this are still written by them
+    pub fn remove_appender(&self, name: &str) -> Result<(), LogError> {
+        let record = command_record(Command::CommandRemoveAppender(name.to_string()));
+        self.send_command(record, "[fast_log] remove appender fail!")
+    }
+
+    /// send CommandRotate, split and rotate appenders roll the log file now
+    pub fn rotate(&self) -> Result<(), LogError> {
+        let record = command_record(Command::CommandRotate);
+        self.send_command(record, "[fast_log] rotate fail!")
+    }
+
+    /// send CommandFlush, the WaitGroup is done when appenders have flushed
+    fn send_flush(&self) -> Result<WaitGroup, LogError> {
+        let wg = WaitGroup::new();
+        let fast_log_record = command_record(Command::CommandFlush(wg.clone()));
+        self.send_command(fast_log_record, "[fast_log] flush fail!")?;
+        Ok(wg)
+    }
+
+    /// check and keep the config, create the main channel
+    fn setup(&self, config: Config) -> Result<(), LogError> {
+        self.setup_with(config, false)
+    }
+
+    /// like setup, `added_later` is true if appenders are added by commands after start
+    fn setup_with(&self, mut config: Config, added_later: bool) -> Result<(), LogError> {
+        if !config.errors.is_empty() {
+            if !config.lenient {
+                return Err(config.errors.remove(0));
+            }
+            for e in config.errors.drain(..) {
+                match &config.on_error {
+                    Some(f) => f(&e),
+                    None => eprintln!("{}", e),
+                }
+            }
+            config = config.console();
+        }
+        #[cfg(feature = "tokio")]
+        let has_async = !config.async_appends.is_empty();
+        #[cfg(not(feature = "tokio"))]
+        let has_async = false;
+        if !added_later
+            && config.appends.is_empty()
+            && config.routes.is_empty()
+            && config.worker_appends.is_empty()
+            && !has_async
+        {
+            return Err(LogError::from("[fast_log] appends can not be empty!"));
+        }
+        self.install(config)
+    }
+
+    /// keep the config and create the main channel
+    fn install(&self, config: Config) -> Result<(), LogError> {
+        let (s, r) = chan(config.chan_len);
+        self.send.set(s).map_err(|_| LogError::from("set fail"))?;
+        self.recv.set(r).map_err(|_| LogError::from("set fail"))?;
+        self.cfg
+            .set(config)
+            .map_err(|_| LogError::from("set fail="))?;
+        Ok(())
+    }
+
+    /// send a command, never dropped by OverflowPolicy
+    fn send_command(&self, record: FastLogRecord, err: &str) -> Result<(), LogError> {
+        let send = self.send.get().ok_or_else(|| LogError::from("not init"))?;
+        if self.is_sync() {
+            self.send_inline(record);
+            return Ok(());
+        }
+        send.send(record).map_err(|_| LogError::from(err))
+    }
+
+    fn is_sync(&self) -> bool {
+        self.cfg.get().map(|cfg| cfg.sync).unwrap_or_default()
+    }
+
+    /// format the record and call appenders on the current thread.
+    /// records logged again by the appenders, on_error or a panic hook on this thread would lock `inline` twice,
+    /// they are queued and dispatched after the current ones
+    fn send_inline(&self, record: FastLogRecord) {
+        struct Leave(usize);
+        impl Drop for Leave {
+            fn drop(&mut self) {
+                let _ = INLINE_QUEUES.try_with(|q| q.borrow_mut().retain(|(id, _)| *id != self.0));
+            }
+        }
+        let cfg = match self.cfg.get() {
+            None => return,
+            Some(cfg) => cfg,
+        };
+        let id = self as *const Logger as usize;
+        let record = INLINE_QUEUES.with(|q| {
+            let mut q = q.borrow_mut();
+            match q.iter_mut().find(|(queue_id, _)| *queue_id == id) {
+                Some((_, queue)) => {
+                    //a flush can not wait for the appenders running it, its WaitGroup is done now
+                    if !matches!(record.command, Command::CommandFlush(_)) {
+                        queue.push(record);
+                    }
+                    None
+                }
+                None => {
+                    q.push((id, vec![]));
+                    Some(record)
+                }
+            }
+        });
+        let mut records = match record {
+            None => return,
+            Some(record) => vec![record],
+        };
+        let _leave = Leave(id);
+        let _scope = self.enter();
+        let mut senders = self.inline.lock();
+        while !records.is_empty() {
+            if dispatch(self, cfg, &mut senders, records) {
+                senders.clear();
+            }
+            records = INLINE_QUEUES.with(|q| {
+                q.borrow_mut()
+                    .iter_mut()
+                    .find(|(queue_id, _)| *queue_id == id)
+                    .map(|(_, queue)| std::mem::take(queue))
+                    .unwrap_or_default()
+            });
+        }
+    }
+
+    /// true if a record would be dropped by OverflowPolicy::DropNewest now
+    fn full(&self, cfg: &Config) -> bool {
+        matches!(cfg.overflow, OverflowPolicy::DropNewest)
+            && !cfg.sync
+            && self.send.get().map(|s| s.is_full()).unwrap_or_default()
+    }
+
+    /// the next sequence number of records
+    pub(crate) fn next_seq(&self) -> u64 {
+        self.seq.fetch_add(1, Ordering::Relaxed) + 1
+    }
+
+    /// the number of records dropped because the channel was full
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// a snapshot of the counters
+    pub fn metrics(&self) -> Metrics {
+        Metrics {
+            received: self.received.load(Ordering::Relaxed),
+            dropped: self.dropped(),
+            queue_len: self.recv.get().map(|r| r.len()).unwrap_or_default(),
+            appender_bytes: self
+                .appender_bytes
+                .lock()
+                .iter()
+                .map(|(name, bytes)| (name.clone(), bytes.load(Ordering::Relaxed)))
+                .collect(),
+            rotations: self.rotations.load(Ordering::Relaxed),
+            write_errors: self.write_errors.load(Ordering::Relaxed),
+            seq: self.seq.load(Ordering::Relaxed),
+        }
+    }
+
+    /// track the bytes of an appender, see Metrics::appender_bytes
+    fn track_bytes(&self, name: String) -> Arc<AtomicU64> {
+        let bytes = Arc::new(AtomicU64::new(0));
+        self.appender_bytes.lock().push((name, bytes.clone()));
+        bytes
+    }
+
+    fn untrack_bytes(&self, bytes: &Arc<AtomicU64>) {
+        self.appender_bytes
+            .lock()
+            .retain(|(_, b)| !Arc::ptr_eq(b, bytes));
+    }
+
+    /// send record to the main channel, apply the OverflowPolicy when the channel is full
+    pub(crate) fn send(&self, record: FastLogRecord) -> Result<(), Box<SendError<FastLogRecord>>> {
+        let send = match self.send.get() {
+            None => return Err(Box::new(crossbeam_channel::SendError(record))),
+            Some(send) => send,
+        };
+        if self.is_sync() {
+            self.send_inline(record);
+            return Ok(());
+        }
+        let policy = match self.cfg.get() {
+            None => OverflowPolicy::Block,
+            Some(cfg) => cfg.overflow,
+        };
+        match policy {
+            OverflowPolicy::Block => send.send(record).map_err(Box::new),
+            OverflowPolicy::DropNewest => match send.try_send(record) {
+                Ok(()) => Ok(()),
+                Err(TrySendError::Full(_)) => {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    Ok(())
+                }
+                Err(TrySendError::Disconnected(r)) => {
+                    Err(Box::new(crossbeam_channel::SendError(r)))
+                }
+            },
+            OverflowPolicy::DropOldest => {
+                let mut record = record;
+                loop {
+                    match send.try_send(record) {
+                        Ok(()) => return Ok(()),
+                        Err(TrySendError::Full(r)) => {
+                            record = r;
+                            if let Some(Ok(old)) = self.recv.get().map(|v| v.try_recv()) {
+                                if old.command == Command::CommandRecord {
+                                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                                } else {
+                                    //never drop exit/flush
+                                    send.send(old)?;
+                                }
+                            }
+                        }
+                        Err(TrySendError::Disconnected(r)) => {
+                            return Err(Box::new(crossbeam_channel::SendError(r)))
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Default for Logger {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Log for Logger {
     fn enabled(&self, metadata: &Metadata) -> bool {
-        metadata.level() <= self.get_level()
+        //STATIC_MAX_LEVEL is a constant, records above it are removed at compile time
+        metadata.level() <= log::STATIC_MAX_LEVEL
+            && (metadata.level() <= self.get_level()
+                || THREAD_LEVEL.with(|l| l.get()).is_some_and(|l| metadata.level() <= l))
     }
+    /// level, target deny-list and filters only see the borrowed Record,
+    /// a FastLogRecord(args, key values, thread name) is built for accepted records alone
     fn log(&self, record: &Record) {
-        if let Some(filter) = LOGGER.cfg.get() {
-            if let Some(send) = LOGGER.send.get() {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        if let Some(filter) = self.cfg.get() {
+            if self.send.get().is_some() {
+                if !filter.target_enabled(record.target()) {
+                    return;
+                }
+                //filters report to this logger, for example RateLimitFilter summaries
+                let _scope = self.enter();
                 for filter in filter.filters.iter() {
-                    if !filter.do_log(record) {
+                    if !filter.accept(record) {
                         return;
                     }
                 }
-                let _ = send.send(FastLogRecord {
+                if self.full(filter) {
+                    //dropped by DropNewest anyway, do not build the record
+                    self.next_seq();
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                    self.received.fetch_add(1, Ordering::Relaxed);
+                    return;
+                }
+                let (thread_id, thread_name) = current_thread();
+                let (trace_id, span_id) = match &filter.trace_context {
+                    Some(f) => f().map(|(t, s)| (Some(t), Some(s))).unwrap_or_default(),
+                    None => (None, None),
+                };
+                let result = self.send(FastLogRecord {
                     command: Command::CommandRecord,
                     level: record.level(),
-                    target: record.metadata().target().to_string(),
-                    args: record.args().to_string(),
-                    module_path: record.module_path().unwrap_or_default().to_string(),
-                    file: record.file().unwrap_or_default().to_string(),
+                    target: intern(record.metadata().target()),
+                    args: format_args(record.args()),
+                    module_path: intern(record.module_path().unwrap_or_default()),
+                    file: intern(record.file().unwrap_or_default()),
                     line: record.line().clone(),
-                    now: SystemTime::now(),
+                    now: filter.clock.now(),
                     formated: String::new(),
+                    key_values: key_values(record),
+                    pid: std::process::id(),
+                    thread_id,
+                    thread_name,
+                    hostname: cached_hostname(),
+                    trace_id,
+                    span_id,
+                    seq: self.next_seq(),
                 });
+                match result {
+                    Ok(()) => {
+                        self.received.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Err(_) => {
+                        report_error(&LogError::from("[fast_log] send record fail: channel closed"));
+                    }
+                }
             }
         }
     }
     fn flush(&self) {
-        match flush() {
+        match self.send_flush() {
             Ok(v) => {
                 v.wait();
             }
@@ -91,174 +536,945 @@ impl Log for Logger {
     }
 }
 
-pub fn init(config: Config) -> Result<&'static Logger, LogError> {
-    if config.appends.is_empty() {
-        return Err(LogError::from("[fast_log] appends can not be empty!"));
+pub fn init(mut config: Config) -> Result<&'static Logger, LogError> {
+    if config.env_overrides {
+        config = config.apply_env();
     }
-    let (s, r) = chan(config.chan_len);
-    LOGGER.send.set(s).map_err(|_| LogError::from("set fail"))?;
-    LOGGER.recv.set(r).map_err(|_| LogError::from("set fail"))?;
-    LOGGER.set_level(config.level);
-    LOGGER
-        .cfg
-        .set(config)
-        .map_err(|_| LogError::from("set fail="))?;
+    LOGGER.setup(config)?;
     //main recv data
-    log::set_logger(LOGGER.deref())
-        .map(|()| log::set_max_level(LOGGER.cfg.get().unwrap().level))
-        .map_err(|e| LogError::from(e))?;
-
-    let mut receiver_vec = vec![];
-    let mut sender_vec: Vec<Sender<Arc<Vec<FastLogRecord>>>> = vec![];
+    log::set_logger(LOGGER.deref()).map_err(|e| LogError::from(e))?;
+    //a failed setup or set_logger leaves no socket behind and the level unchanged
     let cfg = LOGGER.cfg.get().unwrap();
-    for a in cfg.appends.iter() {
-        let (s, r) = chan(cfg.chan_len);
-        sender_vec.push(s);
-        receiver_vec.push((r, a));
-    }
-    for (receiver, appender) in receiver_vec {
-        spawn(move || {
-            let mut exit = false;
+    #[cfg(unix)]
+    if let Some(path) = &cfg.control_socket {
+        crate::plugin::control::listen(path)?;
+    }
+    LOGGER.set_level(cfg.level);
+    start(LOGGER.deref());
+    return Ok(LOGGER.deref());
+}
+
+/// start the appenders and the dispatcher thread of a logger after setup
+fn start<L>(logger: L)
+where
+    L: Deref<Target = Logger> + Clone + Send + Sync + 'static,
+{
+    //the appender threads spawned here belong to this logger
+    let _scope = logger.enter();
+    let mut senders: Vec<AppenderSender> = vec![];
+    let cfg = logger.cfg.get().unwrap();
+    for i in 0..cfg.appends.len() {
+        let bytes = logger.track_bytes(i.to_string());
+        let a = ConfigAppender::new(logger.clone(), i, false);
+        senders.push(AppenderSender::start(cfg, None, None, a, bytes));
+    }
+    for (i, (target, _)) in cfg.routes.iter().enumerate() {
+        let bytes = logger.track_bytes(format!("route:{}", target));
+        let a = ConfigAppender::new(logger.clone(), i, true);
+        senders.push(AppenderSender::start(cfg, None, Some(target.clone()), a, bytes));
+    }
+    for (i, w) in cfg.worker_appends.iter().enumerate() {
+        let bytes = logger.track_bytes(format!("workers:{}", i));
+        senders.push(AppenderSender::start_workers(cfg, w, bytes));
+    }
+    #[cfg(feature = "tokio")]
+    for (i, (handle, a)) in cfg.async_appends.iter().enumerate() {
+        let bytes = logger.track_bytes(format!("async:{}", i));
+        senders.push(AppenderSender::start_async(handle, a.clone(), bytes));
+    }
+    if cfg.sync {
+        //appenders added before start are kept
+        logger.inline.lock().extend(senders);
+        return;
+    }
+    spawn(move || {
+        let _scope = logger.enter();
+        let cfg = logger.cfg.get().unwrap();
+        loop {
+            let recv = logger.recv.get().unwrap();
+            let mut remain = Vec::with_capacity(recv.len());
+            //recv
+            if recv.len() == 0 {
+                if let Ok(item) = recv.recv() {
+                    remain.push(item);
+                }
+            }
+            //recv all
             loop {
-                let mut remain = vec![];
-                if receiver.len() == 0 {
-                    if let Ok(msg) = receiver.recv() {
-                        remain.push(msg);
+                match recv.try_recv() {
+                    Ok(v) => {
+                        remain.push(v);
+                    }
+                    Err(_) => {
+                        break;
                     }
                 }
-                //recv all
-                loop {
-                    match receiver.try_recv() {
-                        Ok(v) => {
-                            remain.push(v);
-                        }
-                        Err(_) => {
-                            break;
-                        }
+            }
+            if dispatch(&logger, cfg, &mut senders, remain) {
+                break;
+            }
+        }
+    });
+}
+
+/// an appender of Config::appends or Config::routes, keeps the logger alive for the appender thread
+struct ConfigAppender<L> {
+    logger: L,
+    index: usize,
+    route: bool,
+}
+
+impl<L: Deref<Target = Logger>> ConfigAppender<L> {
+    fn new(logger: L, index: usize, route: bool) -> Self {
+        Self {
+            logger,
+            index,
+            route,
+        }
+    }
+}
+
+impl<L: Deref<Target = Logger>> Deref for ConfigAppender<L> {
+    type Target = Mutex<Box<dyn LogAppender>>;
+
+    fn deref(&self) -> &Self::Target {
+        let cfg = self.logger.cfg.get().unwrap();
+        if self.route {
+            &cfg.routes[self.index].1
+        } else {
+            &cfg.appends[self.index]
+        }
+    }
+}
+
+/// format records and send them to appenders, return true on CommandExit
+fn dispatch(
+    logger: &Logger,
+    cfg: &Config,
+    senders: &mut Vec<AppenderSender>,
+    records: Vec<FastLogRecord>,
+) -> bool {
+    let mut exit = false;
+    let mut batch = Vec::with_capacity(records.len());
+    for mut x in records {
+        match &x.command {
+            Command::CommandAddAppender(a) => {
+                //records before it are not sent to the new appender
+                send_batch(senders, &mut batch);
+                if let Some(appender) = a.appender.lock().take() {
+                    let bytes = logger.track_bytes(a.name.clone());
+                    let appender = Box::new(Mutex::new(appender));
+                    senders.push(AppenderSender::start(
+                        cfg,
+                        Some(a.name.clone()),
+                        None,
+                        appender,
+                        bytes,
+                    ));
+                }
+            }
+            Command::CommandRemoveAppender(name) => {
+                send_batch(senders, &mut batch);
+                senders.retain(|s| {
+                    if s.name.as_ref() != Some(name) {
+                        return true;
+                    }
+                    s.send(Arc::new(vec![command_record(Command::CommandExit)]));
+                    logger.untrack_bytes(&s.bytes);
+                    false
+                });
+            }
+            _ => {
+                if x.command == Command::CommandRecord {
+                    for enricher in &cfg.enrichers {
+                        enricher(&mut x);
                     }
                 }
-                //lock get appender
-                let shared_appender= appender.lock();
-                for msg in remain {
-                    shared_appender.do_logs(msg.as_ref());
-                    for x in msg.iter() {
-                        match x.command {
-                            Command::CommandRecord => {}
-                            Command::CommandExit => {
-                                exit = true;
-                                continue;
-                            }
-                            Command::CommandFlush(_) => {
-                                continue;
-                            }
-                        }
+                if x.formated.is_empty() {
+                    cfg.format.do_format(&mut x);
+                    if let Some(max) = cfg.max_record_len {
+                        limit_len(cfg, &mut x, max);
                     }
                 }
-                if exit {
-                    break;
+                if x.command.eq(&Command::CommandExit) {
+                    exit = true;
                 }
+                batch.push(x);
             }
-        });
+        }
     }
-    let sender_vec = Arc::new(sender_vec);
-    for _ in 0..1 {
-        let senders = sender_vec.clone();
-        spawn(move || {
-            loop {
-                let recv = LOGGER.recv.get().unwrap();
-                let mut remain = Vec::with_capacity(recv.len());
-                //recv
-                if recv.len() == 0 {
-                    if let Ok(item) = recv.recv() {
-                        remain.push(item);
+    send_batch(senders, &mut batch);
+    exit
+}
+
+/// cut the message of a record longer than `max` formatted bytes and format it again,
+/// or cut the formatted record if the rest of it is still too long
+fn limit_len(cfg: &Config, x: &mut FastLogRecord, max: usize) {
+    let len = x.formated.len();
+    if len <= max || x.command != Command::CommandRecord {
+        return;
+    }
+    let marker = format!("...(truncated, {} bytes)", len);
+    let keep = x.args.len().saturating_sub(len - max + marker.len());
+    x.args.truncate(char_boundary(&x.args, keep));
+    x.args.push_str(&marker);
+    x.formated.clear();
+    cfg.format.do_format(x);
+    if x.formated.len() > max {
+        let newline = x.formated.ends_with('\n');
+        let keep = max.saturating_sub(marker.len() + newline as usize);
+        x.formated.truncate(char_boundary(&x.formated, keep));
+        x.formated.push_str(&marker);
+        if newline {
+            x.formated.push('\n');
+        }
+    }
+}
+
+/// the largest char boundary of `s` not after `index`
+fn char_boundary(s: &str, mut index: usize) -> usize {
+    while !s.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// an appender shared with its thread, or called inline
+type SharedAppender = Box<dyn Deref<Target = Mutex<Box<dyn LogAppender>>> + Send>;
+
+/// where an AppenderSender delivers records
+enum Output {
+    /// the channel to the appender thread
+    Channel(Sender<Arc<Vec<FastLogRecord>>>),
+    /// call the appender on the logging thread, see Config::sync
+    Inline(SharedAppender),
+    /// the channels to the worker threads of the appender, see Config::add_appender_workers
+    Workers {
+        senders: Vec<Sender<Arc<Vec<FastLogRecord>>>>,
+        delivery: Delivery,
+        next: AtomicUsize,
+    },
+    /// call the Sync appender on the logging thread, see Config::sync
+    InlineSync(Arc<dyn LogAppender + Sync>),
+    /// the channel to the appender task, see Config::add_async_appender
+    #[cfg(feature = "tokio")]
+    Task(tokio::sync::mpsc::UnboundedSender<Arc<Vec<FastLogRecord>>>),
+}
+
+/// the channel to an appender thread
+struct AppenderSender {
+    /// set by Logger::add_appender
+    name: Option<String>,
+    /// set by Config::route, only records of this target are sent
+    route: Option<String>,
+    output: Output,
+    /// formatted bytes sent
+    bytes: Arc<AtomicU64>,
+}
+
+impl AppenderSender {
+    /// spawn the appender thread, or keep the appender inline if Config::sync
+    fn start<A>(
+        cfg: &Config,
+        name: Option<String>,
+        route: Option<String>,
+        appender: A,
+        bytes: Arc<AtomicU64>,
+    ) -> Self
+    where
+        A: Deref<Target = Mutex<Box<dyn LogAppender>>> + Send + 'static,
+    {
+        let output = if cfg.sync {
+            Output::Inline(Box::new(appender))
+        } else {
+            let (s, r) = chan(cfg.chan_len);
+            spawn_appender(r, appender);
+            Output::Channel(s)
+        };
+        Self {
+            name,
+            route,
+            output,
+            bytes,
+        }
+    }
+
+    /// spawn the worker threads, or keep the appender inline if Config::sync
+    fn start_workers(cfg: &Config, w: &WorkerAppend, bytes: Arc<AtomicU64>) -> Self {
+        let output = if cfg.sync {
+            Output::InlineSync(w.appender.clone())
+        } else {
+            let mut senders = Vec::with_capacity(w.workers);
+            for _ in 0..w.workers {
+                let (s, r) = chan::<Arc<Vec<FastLogRecord>>>(cfg.chan_len);
+                let appender = w.appender.clone();
+                spawn_in_logger(move || {
+                    while let Ok(data) = r.recv() {
+                        appender.do_logs(data.as_ref());
+                        if data.iter().any(|x| x.command == Command::CommandExit) {
+                            break;
+                        }
                     }
+                });
+                senders.push(s);
+            }
+            Output::Workers {
+                senders,
+                delivery: w.delivery,
+                next: AtomicUsize::new(0),
+            }
+        };
+        Self {
+            name: None,
+            route: None,
+            output,
+            bytes,
+        }
+    }
+
+    /// spawn the appender task on the runtime, it runs until CommandExit
+    #[cfg(feature = "tokio")]
+    fn start_async(
+        handle: &tokio::runtime::Handle,
+        appender: Arc<dyn crate::appender::AsyncLogAppender>,
+        bytes: Arc<AtomicU64>,
+    ) -> Self {
+        let (s, mut r) = tokio::sync::mpsc::unbounded_channel::<Arc<Vec<FastLogRecord>>>();
+        handle.spawn(async move {
+            while let Some(data) = r.recv().await {
+                appender.do_logs(data.as_ref()).await;
+                if data.iter().any(|x| x.command == Command::CommandExit) {
+                    break;
                 }
-                //recv all
-                loop {
-                    match recv.try_recv() {
-                        Ok(v) => {
-                            remain.push(v);
+            }
+        });
+        Self {
+            name: None,
+            route: None,
+            output: Output::Task(s),
+            bytes,
+        }
+    }
+
+    fn send(&self, data: Arc<Vec<FastLogRecord>>) {
+        let bytes: usize = data
+            .iter()
+            .filter(|x| x.command == Command::CommandRecord)
+            .map(|x| x.formated.len())
+            .sum();
+        self.bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+        match &self.output {
+            Output::Channel(sender) => {
+                let _ = sender.send(data);
+            }
+            Output::Inline(appender) => appender.lock().do_logs(data.as_ref()),
+            Output::InlineSync(appender) => appender.do_logs(data.as_ref()),
+            Output::Workers {
+                senders,
+                delivery,
+                next,
+            } => {
+                let n = senders.len();
+                let mut batches: Vec<Vec<FastLogRecord>> = vec![vec![]; n];
+                let round_robin = next.fetch_add(1, Ordering::Relaxed) % n;
+                for x in data.iter() {
+                    if x.command != Command::CommandRecord {
+                        //flush and exit reach every worker
+                        for batch in batches.iter_mut() {
+                            batch.push(x.clone());
                         }
-                        Err(_) => {
-                            break;
+                        continue;
+                    }
+                    let i = match delivery {
+                        Delivery::Ordered => {
+                            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+                            x.target.hash(&mut hasher);
+                            hasher.finish() as usize % n
                         }
+                        Delivery::Unordered => round_robin,
+                    };
+                    batches[i].push(x.clone());
+                }
+                for (sender, batch) in senders.iter().zip(batches) {
+                    if !batch.is_empty() {
+                        let _ = sender.send(Arc::new(batch));
                     }
                 }
-                let mut exit = false;
-                for x in &mut remain {
-                    if x.formated.is_empty() {
-                        LOGGER.cfg.get().unwrap().format.do_format(x);
+            }
+            #[cfg(feature = "tokio")]
+            Output::Task(sender) => {
+                let _ = sender.send(data);
+            }
+        }
+    }
+
+    /// commands are sent to all appenders, records by route
+    fn accept(&self, record: &FastLogRecord) -> bool {
+        match &self.route {
+            None => true,
+            Some(route) => {
+                record.command != Command::CommandRecord || module_match(route, &record.target)
+            }
+        }
+    }
+}
+
+/// send the batch to all appenders, routed records only go to their route appenders
+fn send_batch(senders: &[AppenderSender], batch: &mut Vec<FastLogRecord>) {
+    if batch.is_empty() {
+        return;
+    }
+    let mut data = std::mem::take(batch);
+    if senders.iter().any(|s| s.route.is_some()) {
+        for s in senders.iter().filter(|s| s.route.is_some()) {
+            let routed: Vec<FastLogRecord> = data.iter().filter(|x| s.accept(x)).cloned().collect();
+            if !routed.is_empty() {
+                s.send(Arc::new(routed));
+            }
+        }
+        data.retain(|x| {
+            x.command != Command::CommandRecord
+                || !senders.iter().any(|s| s.route.is_some() && s.accept(x))
+        });
+    }
+    let data = Arc::new(data);
+    for s in senders.iter().filter(|s| s.route.is_none()) {
+        s.send(data.clone());
+    }
+}
+
+/// run the appender on a new thread until CommandExit or the channel is closed
+fn spawn_appender<A>(receiver: Receiver<Arc<Vec<FastLogRecord>>>, appender: A)
+where
+    A: Deref<Target = Mutex<Box<dyn LogAppender>>> + Send + 'static,
+{
+    spawn_in_logger(move || {
+        let mut exit = false;
+        loop {
+            let mut remain = vec![];
+            if receiver.len() == 0 {
+                match receiver.recv() {
+                    Ok(msg) => remain.push(msg),
+                    Err(_) => break,
+                }
+            }
+            //recv all
+            loop {
+                match receiver.try_recv() {
+                    Ok(v) => {
+                        remain.push(v);
                     }
-                    if x.command.eq(&Command::CommandExit) {
-                        exit = true;
+                    Err(_) => {
+                        break;
                     }
                 }
-                let data = Arc::new(remain);
-                for x in senders.iter() {
-                    let _ = x.send(data.clone());
-                }
-                if exit {
-                    break;
+            }
+            //lock get appender
+            let shared_appender = appender.lock();
+            for msg in remain {
+                shared_appender.do_logs(msg.as_ref());
+                if msg.iter().any(|x| x.command == Command::CommandExit) {
+                    exit = true;
                 }
             }
-        });
+            if exit {
+                break;
+            }
+        }
+    });
+}
+
+/// the message of a record. a message without arguments is copied, others are formatted into a reused
+/// buffer of the thread and copied out at their exact size, instead of growing a new String
+fn format_args(args: &std::fmt::Arguments) -> String {
+    use std::fmt::Write;
+    thread_local! {
+        static BUF: RefCell<String> = RefCell::new(String::with_capacity(256));
     }
-    return Ok(LOGGER.deref());
+    if let Some(s) = args.as_str() {
+        return s.to_string();
+    }
+    BUF.with(|buf| match buf.try_borrow_mut() {
+        Ok(mut buf) => {
+            buf.clear();
+            let _ = buf.write_fmt(*args);
+            let s = buf.as_str().to_string();
+            if buf.capacity() > 64 * 1024 {
+                *buf = String::with_capacity(256);
+            }
+            s
+        }
+        //a Display of the arguments is logging
+        Err(_) => args.to_string(),
+    })
 }
 
-pub fn exit() -> Result<(), LogError> {
-    let fast_log_record = FastLogRecord {
-        command: Command::CommandExit,
-        level: log::Level::Info,
-        target: String::new(),
-        args: String::new(),
-        module_path: String::new(),
-        file: String::new(),
-        line: None,
-        now: SystemTime::now(),
-        formated: String::new(),
-    };
-    let result = LOGGER
-        .send
-        .get()
-        .ok_or_else(|| LogError::from("not init"))?
-        .send(fast_log_record);
-    match result {
-        Ok(()) => {
-            return Ok(());
+/// collect the structured key-values of the record and the thread-local context
+fn key_values(record: &Record) -> Vec<(String, String)> {
+    struct Collect(Vec<(String, String)>);
+    impl<'kvs> VisitSource<'kvs> for Collect {
+        fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error> {
+            self.0.push((key.to_string(), value.to_string()));
+            Ok(())
+        }
+    }
+    let source = record.key_values();
+    let mut collect = Collect(Vec::with_capacity(source.count()));
+    let _ = source.visit(&mut collect);
+    crate::context::merge(&mut collect.0);
+    collect.0
+}
+
+/// the id and name of the current thread, cached per thread
+pub(crate) fn current_thread() -> (u64, Arc<str>) {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+    thread_local! {
+        static THREAD: (u64, Arc<str>) = (
+            NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            Arc::from(std::thread::current().name().unwrap_or_default()),
+        );
+    }
+    THREAD.with(|(id, name)| (*id, name.clone()))
+}
+
+/// share one Arc<str> for equal target, module_path and file strings,
+/// only the first record of a call site allocates them
+pub fn intern(value: &str) -> Arc<str> {
+    thread_local! {
+        static CACHE: RefCell<HashSet<Arc<str>>> = RefCell::new(HashSet::new());
+    }
+    CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        if let Some(v) = cache.get(value) {
+            return v.clone();
+        }
+        //targets built at runtime must not grow it forever
+        if cache.len() >= 4096 {
+            cache.clear();
+        }
+        let v: Arc<str> = Arc::from(value);
+        cache.insert(v.clone());
+        v
+    })
+}
+
+/// the hostname of the os, gethostname on unix and env COMPUTERNAME on windows
+pub(crate) fn hostname() -> String {
+    #[cfg(unix)]
+    {
+        let mut buf = [0u8; 256];
+        if unsafe { libc::gethostname(buf.as_mut_ptr() as *mut libc::c_char, buf.len()) } != 0 {
+            return String::new();
+        }
+        let len = buf.iter().position(|v| *v == 0).unwrap_or(buf.len());
+        String::from_utf8_lossy(&buf[..len]).into_owned()
+    }
+    #[cfg(windows)]
+    {
+        std::env::var("COMPUTERNAME").unwrap_or_default()
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        String::new()
+    }
+}
+
+/// the hostname, read once
+pub(crate) fn cached_hostname() -> Arc<str> {
+    static HOSTNAME: Lazy<Arc<str>> = Lazy::new(|| Arc::from(hostname()));
+    HOSTNAME.clone()
+}
+
+/// a record only carry the command
+fn command_record(command: Command) -> FastLogRecord {
+    let (thread_id, thread_name) = current_thread();
+    FastLogRecord {
+        pid: std::process::id(),
+        thread_id,
+        thread_name,
+        hostname: cached_hostname(),
+        ..FastLogRecord::new(command, log::Level::Info, "")
+    }
+}
+
+/// init with a toml config file, see `fast_log::config_file` for the keys
+/// ```rust,no_run
+/// fast_log::init_from_file("log.toml").unwrap();
+/// ```
+#[cfg(feature = "toml")]
+pub fn init_from_file(path: &str) -> Result<&'static Logger, LogError> {
+    init(Config::from_file(path)?)
+}
+
+/// init with a toml config file like `init_from_file`, then check its modified time every `interval`.
+/// a changed file sets the level and filters, `[[appender]]` tables that are gone or changed are removed
+/// and new ones are added by dispatcher commands. other root keys(format, chan_len, sync...) are read at init only,
+/// a reload error is reported to `Config::on_error` and the last good config is kept
+/// ```rust,no_run
+/// use std::time::Duration;
+/// fast_log::init_from_file_watch("log.toml", Duration::from_secs(5)).unwrap();
+/// ```
+#[cfg(feature = "toml")]
+pub fn init_from_file_watch(path: &str, interval: Duration) -> Result<&'static Logger, LogError> {
+    let (mut config, mut reloader) = Reloader::new(path)?;
+    if config.env_overrides {
+        config = config.apply_env();
+    }
+    //the appenders of the file are created now, and added by commands once the dispatcher runs
+    let first = reloader.prepare()?;
+    if let Some(e) = first.error() {
+        if !config.lenient {
+            return Err(e.clone());
+        }
+    }
+    let has_appenders = first.has_appenders();
+    if !has_appenders && config.appends.is_empty() && config.lenient {
+        config = config.console();
+    }
+    let level = config.level;
+    LOGGER.setup_with(config, has_appenders)?;
+    LOGGER.set_level(level);
+    log::set_logger(LOGGER.deref()).map_err(LogError::from)?;
+    start(LOGGER.deref());
+    reloader.apply(LOGGER.deref(), first)?;
+    //the file level until the next change, or FAST_LOG_LEVEL
+    LOGGER.set_level(level);
+    spawn(move || loop {
+        std::thread::sleep(interval);
+        if reloader.changed() {
+            if let Err(e) = reloader.reload(LOGGER.deref()) {
+                crate::report_error(&e);
+            }
         }
-        _ => {}
+    });
+    Ok(LOGGER.deref())
+}
+
+/// init the logger and return a LoggerGuard,
+/// records are flushed and the logger exits when the guard is dropped.
+/// for example:
+/// ```rust,no_run
+/// use fast_log::Config;
+/// fn main() {
+///     let _guard = fast_log::init_guard(Config::new().file("target/logs/app.log")).unwrap();
+///     log::info!("flushed before main returns");
+/// }
+/// ```
+pub fn init_guard(config: Config) -> Result<LoggerGuard, LogError> {
+    init(config)?;
+    Ok(LoggerGuard {
+        timeout: Duration::from_secs(10),
+    })
+}
+
+/// a logger with its own channel, appenders and threads, independent of the global logger,
+/// for libraries that need a private pipeline. log to it with the `logger:` argument of the log macros.
+/// its threads use its own Config::time_format, on_error and counters. threads started by appenders
+/// before the logger(for example the packer thread of FileSplitAppender) report errors to the global logger.
+/// it exits when dropped, after the records sent before
+/// ```rust
+/// use fast_log::{Config, FastLogger};
+/// let logger = FastLogger::new(Config::new().console()).unwrap();
+/// log::info!(logger: logger, "private pipeline");
+/// logger.flush().unwrap().wait();
+/// ```
+pub struct FastLogger {
+    logger: Arc<Logger>,
+}
+
+impl FastLogger {
+    pub fn new(config: Config) -> Result<Self, LogError> {
+        let logger = Arc::new(Logger::new());
+        let _ = logger.this.set(Arc::downgrade(&logger));
+        logger.set_level(config.level);
+        logger.setup(config)?;
+        start(logger.clone());
+        Ok(Self { logger })
+    }
+
+    /// the WaitGroup is done when appenders have flushed the records sent before
+    pub fn flush(&self) -> Result<WaitGroup, LogError> {
+        self.logger.send_flush()
+    }
+
+    pub fn exit(&self) -> Result<(), LogError> {
+        let fast_log_record = command_record(Command::CommandExit);
+        self.logger
+            .send_command(fast_log_record, "[fast_log] exit fail!")
     }
-    return Err(LogError::E("[fast_log] exit fail!".to_string()));
+}
+
+impl Deref for FastLogger {
+    type Target = Logger;
+
+    fn deref(&self) -> &Logger {
+        &self.logger
+    }
+}
+
+impl Log for FastLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.logger.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        self.logger.log(record)
+    }
+
+    fn flush(&self) {
+        Log::flush(self.logger.as_ref())
+    }
+}
+
+impl Drop for FastLogger {
+    fn drop(&mut self) {
+        //release the level hold now, the dispatcher thread keeps the Logger until it exits
+        self.logger.set_level(LevelFilter::Off);
+        let _ = self.exit();
+    }
+}
+
+/// flush all records and exit the logger on Drop
+#[must_use = "the logger exits when LoggerGuard is dropped"]
+pub struct LoggerGuard {
+    timeout: Duration,
+}
+
+impl LoggerGuard {
+    /// max time Drop waits for appenders to flush, default 10s
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+impl Drop for LoggerGuard {
+    fn drop(&mut self) {
+        let _ = shutdown(self.timeout);
+    }
+}
+
+/// flush all records and exit the logger,
+/// block until appenders have written and flushed every record sent before, or the timeout elapses.
+/// records logged after shutdown are not written
+/// for example:
+/// ```rust,no_run
+/// use fast_log::Config;
+/// use std::time::Duration;
+/// fast_log::init(Config::new().file("target/logs/app.log")).unwrap();
+/// log::info!("bye");
+/// fast_log::shutdown(Duration::from_secs(5)).unwrap();
+/// ```
+pub fn shutdown(timeout: Duration) -> Result<(), LogError> {
+    let wg = flush()?;
+    exit()?;
+    if wait_timeout(wg, timeout) {
+        Ok(())
+    } else {
+        Err(LogError::from("[fast_log] shutdown timeout"))
+    }
+}
+
+/// wait the WaitGroup, return false if timeout
+pub(crate) fn wait_timeout(wg: WaitGroup, timeout: Duration) -> bool {
+    let (s, r) = chan(Some(1));
+    spawn(move || {
+        wg.wait();
+        let _ = s.send(());
+    });
+    r.recv_timeout(timeout).is_ok()
+}
+
+pub fn exit() -> Result<(), LogError> {
+    let fast_log_record = command_record(Command::CommandExit);
+    LOGGER.send_command(fast_log_record, "[fast_log] exit fail!")
 }
 
 pub fn flush() -> Result<WaitGroup, LogError> {
-    let wg = WaitGroup::new();
-    let fast_log_record = FastLogRecord {
-        command: Command::CommandFlush(wg.clone()),
-        level: log::Level::Info,
-        target: String::new(),
-        args: String::new(),
-        module_path: String::new(),
-        file: String::new(),
-        line: None,
-        now: SystemTime::now(),
-        formated: String::new(),
-    };
-    let result = LOGGER
-        .send
-        .get()
-        .ok_or_else(|| LogError::from("not init"))?
-        .send(fast_log_record);
-    match result {
-        Ok(()) => {
-            return Ok(wg);
-        }
-        _ => {}
-    }
-    return Err(LogError::E("[fast_log] flush fail!".to_string()));
+    LOGGER.send_flush()
+}
+
+/// flush and block until every appender has written and flushed the records sent before,
+/// or return an error when the timeout elapses. for example checkpoint before a risky operation:
+/// ```rust,no_run
+/// use std::time::Duration;
+/// fast_log::flush_sync(Duration::from_secs(5)).unwrap();
+/// ```
+pub fn flush_sync(timeout: Duration) -> Result<(), LogError> {
+    if wait_timeout(flush()?, timeout) {
+        Ok(())
+    } else {
+        Err(LogError::from("[fast_log] flush timeout"))
+    }
+}
+
+/// roll the log files now(without waiting for the size limit or the period), see Command::CommandRotate
+pub fn rotate() -> Result<(), LogError> {
+    LOGGER.rotate()
 }
 
 pub fn print(log: String) -> Result<(), SendError<FastLogRecord>> {
     LOGGER.print(log)
 }
+
+/// report an error to `Config::on_error`, or print it to stderr if not set.
+/// appenders use it for errors they can not return
+pub fn report_error(e: &LogError) {
+    with_logger(|logger| {
+        match logger.cfg.get().and_then(|cfg| cfg.on_error.as_ref()) {
+            Some(f) => f(e),
+            None => eprintln!("{}", e),
+        }
+    })
+}
+
+/// log panics at Error level(target `panic`) with message, location and backtrace(if `RUST_BACKTRACE` is set),
+/// then wait up to 5s for appenders to flush, so the last words reach the disk before abort.
+/// the previous panic hook still runs after it
+/// ```rust,no_run
+/// use fast_log::Config;
+/// fast_log::init(Config::new().file("target/logs/app.log")).unwrap();
+/// fast_log::install_panic_hook();
+/// ```
+pub fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let payload = info.payload();
+        let message = match payload.downcast_ref::<&str>() {
+            Some(v) => v.to_string(),
+            None => match payload.downcast_ref::<String>() {
+                Some(v) => v.clone(),
+                None => "Box<dyn Any>".to_string(),
+            },
+        };
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()))
+            .unwrap_or_default();
+        let thread = std::thread::current();
+        let name = thread.name().unwrap_or("<unnamed>");
+        let backtrace = std::backtrace::Backtrace::capture();
+        if backtrace.status() == std::backtrace::BacktraceStatus::Captured {
+            log::error!(target: "panic", "thread '{}' panicked at {}: {}\n{}", name, location, message, backtrace);
+        } else {
+            log::error!(target: "panic", "thread '{}' panicked at {}: {}", name, location, message);
+        }
+        if let Ok(wg) = flush() {
+            wait_timeout(wg, Duration::from_secs(5));
+        }
+        previous(info);
+    }));
+}
+
+/// the write end of the pipe of rotate_on_sighup, -1 if not installed
+#[cfg(unix)]
+static SIGHUP_PIPE: std::sync::atomic::AtomicI32 = std::sync::atomic::AtomicI32::new(-1);
+
+/// call `rotate()` on SIGHUP, so logrotate-style tools can roll the files with `kill -HUP <pid>`.
+/// the signal handler only writes to a pipe, a thread reads it and sends the command
+/// ```rust,no_run
+/// use fast_log::Config;
+/// fast_log::init(Config::new().file_split("target/logs/", fast_log::consts::LogSize::MB(100),
+///     fast_log::plugin::file_split::RollingType::All, fast_log::plugin::packer::LogPacker {})).unwrap();
+/// fast_log::rotate_on_sighup().unwrap();
+/// ```
+#[cfg(unix)]
+pub fn rotate_on_sighup() -> Result<(), LogError> {
+    use std::io::Read;
+    use std::os::unix::io::FromRawFd;
+    extern "C" fn on_sighup(_: libc::c_int) {
+        let fd = SIGHUP_PIPE.load(Ordering::Relaxed);
+        if fd >= 0 {
+            unsafe {
+                libc::write(fd, [1u8].as_ptr() as *const libc::c_void, 1);
+            }
+        }
+    }
+    if SIGHUP_PIPE.load(Ordering::SeqCst) >= 0 {
+        return Ok(());
+    }
+    let mut fds = [0 as libc::c_int; 2];
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+        return Err(LogError::from(std::io::Error::last_os_error()));
+    }
+    if SIGHUP_PIPE
+        .compare_exchange(-1, fds[1], Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        unsafe {
+            libc::close(fds[0]);
+            libc::close(fds[1]);
+        }
+        return Ok(());
+    }
+    let mut reader = unsafe { std::fs::File::from_raw_fd(fds[0]) };
+    spawn(move || {
+        let mut buf = [0u8; 64];
+        loop {
+            match reader.read(&mut buf) {
+                Ok(0) => break,
+                Ok(_) => {
+                    let _ = rotate();
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(_) => break,
+            }
+        }
+    });
+    let handler = on_sighup as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    if unsafe { libc::signal(libc::SIGHUP, handler) } == libc::SIG_ERR {
+        return Err(LogError::from(std::io::Error::last_os_error()));
+    }
+    Ok(())
+}
+
+/// a snapshot of the logger counters, for example to alert when the queue is backed up
+pub fn metrics() -> Metrics {
+    LOGGER.metrics()
+}
+
+/// the time of the clock of the global logger, SystemTime::now() before init. see Config::clock
+pub fn now() -> SystemTime {
+    match LOGGER.cfg.get() {
+        Some(cfg) => cfg.clock.now(),
+        None => SystemTime::now(),
+    }
+}
+
+/// change the log level while the logger is running
+pub fn set_level(level: LevelFilter) {
+    LOGGER.set_level(level);
+}
+
+/// the current log level
+pub fn get_level() -> LevelFilter {
+    LOGGER.get_level()
+}
+
+/// run `f` with records up to `level` enabled on the current thread only, for example to debug one request.
+/// it never lowers the level of the logger, filters still apply, and records logged by other threads
+/// spawned in `f` are not affected
+/// ```rust
+/// use fast_log::Config;
+/// use log::Level;
+/// fast_log::init(Config::new().console().level(log::LevelFilter::Info)).unwrap();
+/// fast_log::with_level(Level::Trace, || {
+///     log::debug!("logged");
+/// });
+/// log::debug!("not logged");
+/// ```
+pub fn with_level<R>(level: log::Level, f: impl FnOnce() -> R) -> R {
+    struct Restore(Option<LevelFilter>, LevelFilter);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            THREAD_LEVEL.with(|l| l.set(self.0));
+            change_level_holds(None, Some(self.1));
+        }
+    }
+    let level = level.to_level_filter();
+    //let the macros pass records of this level to the logger while f runs, other threads are checked by enabled
+    change_level_holds(Some(level), None);
+    let prev = THREAD_LEVEL.with(|l| l.replace(Some(l.get().map_or(level, |p| p.max(level)))));
+    let _restore = Restore(prev, level);
+    f()
+}
@@ -1,9 +1,94 @@
+use crate::appender::{Command, FastLogRecord};
+#[cfg(feature = "regex")]
+use crate::error::LogError;
 use dark_std::sync::SyncVec;
+use log::{Level, LevelFilter};
+use parking_lot::{Mutex, RwLock};
+#[cfg(feature = "regex")]
+use regex::Regex;
+use std::collections::hash_map::{DefaultHasher, RandomState};
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-///log filter
+///log filter, records are checked before they are built(see Config::add_filter).
+/// a record is logged only if every filter accepts it
+/// ```rust
+/// use fast_log::Config;
+/// use fast_log::filter::Filter;
+/// struct NoHealthCheck;
+/// impl Filter for NoHealthCheck {
+///     fn accept(&self, record: &log::Record) -> bool {
+///         record.target() != "health"
+///     }
+/// }
+/// fast_log::init(Config::new().console().add_filter(NoHealthCheck)).unwrap();
+/// ```
 pub trait Filter: Send + Sync {
+    /// true=log the record, false=drop it.
+    /// the default calls do_log, so filters written for older versions keep working
+    fn accept(&self, record: &log::Record) -> bool {
+        #[allow(deprecated)]
+        self.do_log(record)
+    }
+
     /// if return true=do_log/false=not_log
-    fn do_log(&self, record: &log::Record) -> bool;
+    #[deprecated(since = "1.7.0", note = "implement and call Filter::accept")]
+    fn do_log(&self, _record: &log::Record) -> bool {
+        true
+    }
+}
+
+/// accept all records, the filter of an empty `FilterHandle`
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoFilter;
+
+impl Filter for NoFilter {
+    fn accept(&self, _record: &log::Record) -> bool {
+        true
+    }
+}
+
+/// accept records at or below `level` whose target is one of `targets` or a sub module of it,
+/// no targets accepts every target
+/// ```rust
+/// use fast_log::Config;
+/// use fast_log::filter::LevelAndTargetFilter;
+/// use log::LevelFilter;
+/// let filter = LevelAndTargetFilter::new(LevelFilter::Debug)
+///     .target("my_app")
+///     .target("my_lib");
+/// fast_log::init(Config::new().console().add_filter(filter)).unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct LevelAndTargetFilter {
+    pub level: LevelFilter,
+    pub targets: Vec<String>,
+}
+
+impl LevelAndTargetFilter {
+    pub fn new(level: LevelFilter) -> Self {
+        Self {
+            level,
+            targets: vec![],
+        }
+    }
+
+    pub fn target(mut self, target: &str) -> Self {
+        self.targets.push(target.to_string());
+        self
+    }
+}
+
+impl Filter for LevelAndTargetFilter {
+    fn accept(&self, record: &log::Record) -> bool {
+        record.level() <= self.level
+            && (self.targets.is_empty()
+                || self.targets.iter().any(|t| module_match(t, record.target())))
+    }
 }
 
 /// an Module Filter
@@ -27,7 +112,7 @@ impl ModuleFilter {
 }
 
 impl Filter for ModuleFilter {
-    fn do_log(&self, record: &log::Record) -> bool {
+    fn accept(&self, record: &log::Record) -> bool {
         let module = record.module_path().unwrap_or("");
         if !self.modules.is_empty() {
             for x in &self.modules {
@@ -39,3 +124,532 @@ impl Filter for ModuleFilter {
         return true;
     }
 }
+
+/// per module level directives like env_logger, for example `"hyper=warn,my_app::db=trace,info"`.
+/// a directive matches the target itself and its sub modules, the longest match wins.
+/// a bare level sets the default, records of targets without any directive are dropped if no default is set.
+/// ```rust
+/// use fast_log::Config;
+/// fast_log::init(Config::new().console().module_levels("hyper=warn,my_app::db=trace,info")).unwrap();
+/// ```
+pub struct ModuleLevelFilter {
+    pub default: LevelFilter,
+    /// (module,level), sorted by module len desc
+    pub directives: Vec<(String, LevelFilter)>,
+}
+
+impl ModuleLevelFilter {
+    pub fn new() -> Self {
+        Self {
+            default: LevelFilter::Off,
+            directives: vec![],
+        }
+    }
+
+    /// parse directives, invalid levels are ignored. a module without level means `trace`
+    pub fn parse(spec: &str) -> Self {
+        let mut filter = Self::new();
+        for item in spec.split(',') {
+            let item = item.trim();
+            if item.is_empty() {
+                continue;
+            }
+            match item.split_once('=') {
+                None => {
+                    if let Ok(level) = LevelFilter::from_str(item) {
+                        filter.default = level;
+                    } else {
+                        filter.add(item, LevelFilter::Trace);
+                    }
+                }
+                Some((module, level)) => {
+                    if let Ok(level) = LevelFilter::from_str(level.trim()) {
+                        filter.add(module.trim(), level);
+                    }
+                }
+            }
+        }
+        filter
+    }
+
+    /// parse directives from env, for example `ModuleLevelFilter::from_env("RUST_LOG")`
+    pub fn from_env(name: &str) -> Option<Self> {
+        match std::env::var(name) {
+            Ok(spec) if !spec.trim().is_empty() => Some(Self::parse(&spec)),
+            _ => None,
+        }
+    }
+
+    /// add a directive
+    pub fn add(&mut self, module: &str, level: LevelFilter) {
+        self.directives.retain(|(m, _)| m != module);
+        self.directives.push((module.to_string(), level));
+        self.directives.sort_by_key(|v| std::cmp::Reverse(v.0.len()));
+    }
+
+    /// the max level of all directives
+    pub fn max_level(&self) -> LevelFilter {
+        let mut max = self.default;
+        for (_, level) in &self.directives {
+            if *level > max {
+                max = *level;
+            }
+        }
+        max
+    }
+
+    /// the level of target
+    pub fn level(&self, target: &str) -> LevelFilter {
+        for (module, level) in &self.directives {
+            if module_match(module, target) {
+                return *level;
+            }
+        }
+        self.default
+    }
+}
+
+/// true if target is the module or a child of it, `app` matches `app` and `app::db` but not `application`
+pub(crate) fn module_match(module: &str, target: &str) -> bool {
+    target.starts_with(module)
+        && (target.len() == module.len() || target[module.len()..].starts_with("::"))
+}
+
+impl Default for ModuleLevelFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Filter for ModuleLevelFilter {
+    fn accept(&self, record: &log::Record) -> bool {
+        record.level() <= self.level(record.target())
+    }
+}
+
+/// what RateLimitFilter counts records by
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum RateLimitKey {
+    Target,
+    /// the hash of target and message
+    Message,
+}
+
+/// allow at most `max` records per key in each `window`,
+/// when a window with dropped records ends a `"suppressed N similar messages"` record is logged.
+/// the summary is logged by the next record passing the filter(of any key), there is no timer:
+/// if the storm stops and nothing else is logged, the summary only appears with the next record.
+/// ```rust
+/// use fast_log::Config;
+/// use fast_log::filter::{RateLimitFilter, RateLimitKey};
+/// use std::time::Duration;
+/// let filter = RateLimitFilter::new(100, Duration::from_secs(1), RateLimitKey::Message);
+/// fast_log::init(Config::new().console().add_filter(filter)).unwrap();
+/// ```
+pub struct RateLimitFilter {
+    pub max: u64,
+    pub window: Duration,
+    pub key: RateLimitKey,
+    windows: Mutex<HashMap<u64, RateWindow>>,
+    next_sweep: Mutex<Instant>,
+}
+
+struct RateWindow {
+    start: Instant,
+    count: u64,
+    suppressed: u64,
+    level: Level,
+    target: String,
+}
+
+impl RateLimitFilter {
+    pub fn new(max: u64, window: Duration, key: RateLimitKey) -> Self {
+        Self {
+            max,
+            window,
+            key,
+            windows: Mutex::new(HashMap::new()),
+            next_sweep: Mutex::new(Instant::now() + window),
+        }
+    }
+
+    fn hash(&self, record: &log::Record) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        record.target().hash(&mut hasher);
+        if self.key == RateLimitKey::Message {
+            match record.args().as_str() {
+                Some(args) => args.hash(&mut hasher),
+                None => record.args().to_string().hash(&mut hasher),
+            }
+        }
+        hasher.finish()
+    }
+
+    /// remove ended windows, return the (level, target, suppressed) of windows with suppressed records
+    fn sweep(&self, now: Instant) -> Vec<(Level, String, u64)> {
+        {
+            let mut next_sweep = self.next_sweep.lock();
+            if now < *next_sweep {
+                return vec![];
+            }
+            *next_sweep = now + self.window;
+        }
+        let mut summaries = vec![];
+        self.windows.lock().retain(|_, w| {
+            if now.duration_since(w.start) < self.window {
+                return true;
+            }
+            if w.suppressed > 0 {
+                summaries.push((w.level, std::mem::take(&mut w.target), w.suppressed));
+            }
+            false
+        });
+        summaries
+    }
+}
+
+impl Filter for RateLimitFilter {
+    fn accept(&self, record: &log::Record) -> bool {
+        let now = Instant::now();
+        let mut summaries = self.sweep(now);
+        let key = self.hash(record);
+        let allow = {
+            let mut windows = self.windows.lock();
+            let w = windows.entry(key).or_insert_with(|| RateWindow {
+                start: now,
+                count: 0,
+                suppressed: 0,
+                level: record.level(),
+                target: record.target().into(),
+            });
+            if now.duration_since(w.start) >= self.window {
+                if w.suppressed > 0 {
+                    summaries.push((w.level, w.target.clone(), w.suppressed));
+                }
+                w.start = now;
+                w.count = 0;
+                w.suppressed = 0;
+                w.level = record.level();
+            }
+            if w.count < self.max {
+                w.count += 1;
+                true
+            } else {
+                w.suppressed += 1;
+                w.level = w.level.min(record.level());
+                false
+            }
+        };
+        let (thread_id, thread_name) = crate::fast_log::current_thread();
+        crate::fast_log::with_logger(|logger| {
+            let now = logger.cfg.get().map(|cfg| cfg.clock.now());
+            for (level, target, suppressed) in summaries {
+                let target: std::sync::Arc<str> = target.into();
+                let _ = logger.send(FastLogRecord {
+                    command: Command::CommandRecord,
+                    level,
+                    target: target.clone(),
+                    args: format!("suppressed {} similar messages", suppressed),
+                    module_path: target,
+                    file: "".into(),
+                    line: None,
+                    now: now.unwrap_or_else(std::time::SystemTime::now),
+                    formated: String::new(),
+                    key_values: vec![],
+                    pid: std::process::id(),
+                    thread_id,
+                    thread_name: thread_name.clone(),
+                    hostname: crate::fast_log::cached_hostname(),
+                    trace_id: None,
+                    span_id: None,
+                    seq: logger.next_seq(),
+                });
+            }
+        });
+        allow
+    }
+}
+
+/// how SamplingFilter keeps records
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Sample {
+    /// keep 1 of every n records
+    Every(u64),
+    /// keep each record with this probability(0.0..=1.0)
+    Probability(f64),
+}
+
+/// keep a sample of records of the configured targets and levels, ERROR records always pass.
+/// ```rust
+/// use fast_log::Config;
+/// use fast_log::filter::{Sample, SamplingFilter};
+/// use log::LevelFilter;
+/// let filter = SamplingFilter::new(Sample::Every(100))
+///     .target("my_app::hot_loop")
+///     .level(LevelFilter::Debug);
+/// fast_log::init(Config::new().console().add_filter(filter)).unwrap();
+/// ```
+pub struct SamplingFilter {
+    pub sample: Sample,
+    /// sampled targets(and their sub modules), empty samples all targets
+    pub targets: Vec<String>,
+    /// records of this level and lower(more verbose) are sampled, default Warn(all except ERROR)
+    pub level: LevelFilter,
+    counter: AtomicU64,
+}
+
+impl SamplingFilter {
+    pub fn new(sample: Sample) -> Self {
+        Self {
+            sample,
+            targets: vec![],
+            level: LevelFilter::Warn,
+            counter: AtomicU64::new(RandomState::new().build_hasher().finish() | 1),
+        }
+    }
+
+    /// sample records of this target, can be called many times
+    pub fn target(mut self, target: &str) -> Self {
+        self.targets.push(target.to_string());
+        self
+    }
+
+    /// sample records of this level and lower(more verbose) levels
+    pub fn level(mut self, level: LevelFilter) -> Self {
+        self.level = level;
+        self
+    }
+
+    fn sampled(&self, record: &log::Record) -> bool {
+        record.level() != Level::Error
+            && record.level() >= self.level
+            && (self.targets.is_empty()
+                || self.targets.iter().any(|t| module_match(t, record.target())))
+    }
+}
+
+impl Filter for SamplingFilter {
+    fn accept(&self, record: &log::Record) -> bool {
+        if !self.sampled(record) {
+            return true;
+        }
+        match self.sample {
+            Sample::Every(n) => n <= 1 || self.counter.fetch_add(1, Ordering::Relaxed).is_multiple_of(n),
+            Sample::Probability(p) => {
+                //xorshift64
+                let mut x = self.counter.load(Ordering::Relaxed);
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                self.counter.store(x, Ordering::Relaxed);
+                ((x >> 11) as f64 / (1u64 << 53) as f64) < p
+            }
+        }
+    }
+}
+
+/// pass if all filters pass, filters run in order and stop at the first fail
+/// ```rust
+/// use fast_log::Config;
+/// use fast_log::filter::{FilterExt, ModuleLevelFilter};
+/// //info and above, but nothing of noisy_crate
+/// let noisy = ModuleLevelFilter::parse("noisy_crate=trace");
+/// let filter = ModuleLevelFilter::parse("info").and(noisy.not());
+/// fast_log::init(Config::new().console().add_filter(filter)).unwrap();
+/// ```
+#[derive(Default)]
+pub struct AndFilter {
+    pub filters: Vec<Box<dyn Filter>>,
+}
+
+impl AndFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_filter<F: Filter + 'static>(mut self, filter: F) -> Self {
+        self.filters.push(Box::new(filter));
+        self
+    }
+}
+
+impl Filter for AndFilter {
+    fn accept(&self, record: &log::Record) -> bool {
+        self.filters.iter().all(|f| f.accept(record))
+    }
+}
+
+/// pass if any filter passes, filters run in order and stop at the first pass
+#[derive(Default)]
+pub struct OrFilter {
+    pub filters: Vec<Box<dyn Filter>>,
+}
+
+impl OrFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_filter<F: Filter + 'static>(mut self, filter: F) -> Self {
+        self.filters.push(Box::new(filter));
+        self
+    }
+}
+
+impl Filter for OrFilter {
+    fn accept(&self, record: &log::Record) -> bool {
+        self.filters.iter().any(|f| f.accept(record))
+    }
+}
+
+/// pass if the filter does not pass
+pub struct NotFilter {
+    pub filter: Box<dyn Filter>,
+}
+
+impl NotFilter {
+    pub fn new<F: Filter + 'static>(filter: F) -> Self {
+        Self {
+            filter: Box::new(filter),
+        }
+    }
+}
+
+impl Filter for NotFilter {
+    fn accept(&self, record: &log::Record) -> bool {
+        !self.filter.accept(record)
+    }
+}
+
+/// combine filters, `a.and(b)`, `a.or(b)`, `a.not()`
+pub trait FilterExt: Filter + Sized + 'static {
+    fn and<F: Filter + 'static>(self, other: F) -> AndFilter {
+        AndFilter::new().add_filter(self).add_filter(other)
+    }
+
+    fn or<F: Filter + 'static>(self, other: F) -> OrFilter {
+        OrFilter::new().add_filter(self).add_filter(other)
+    }
+
+    fn not(self) -> NotFilter {
+        NotFilter::new(self)
+    }
+}
+
+impl<T: Filter + 'static> FilterExt for T {}
+
+/// drop(default) or only keep records whose args or target match any of the patterns(regex crate syntax),
+/// needs the `regex` feature
+/// ```rust
+/// use fast_log::Config;
+/// use fast_log::filter::RegexFilter;
+/// let filter = RegexFilter::new()
+///     .args(r"^connection \d+ reset")
+///     .unwrap()
+///     .target("^noisy_crate::")
+///     .unwrap();
+/// fast_log::init(Config::new().console().add_filter(filter)).unwrap();
+/// ```
+#[cfg(feature = "regex")]
+#[derive(Default)]
+pub struct RegexFilter {
+    /// true keeps only matching records, false drops matching records
+    pub keep: bool,
+    pub args: Vec<Regex>,
+    pub targets: Vec<Regex>,
+}
+
+#[cfg(feature = "regex")]
+impl RegexFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// only keep matching records, drop the others
+    pub fn keep(mut self) -> Self {
+        self.keep = true;
+        self
+    }
+
+    /// match the args(message) of records
+    pub fn args(mut self, pattern: &str) -> Result<Self, LogError> {
+        self.args.push(Regex::new(pattern)?);
+        Ok(self)
+    }
+
+    /// match the target of records
+    pub fn target(mut self, pattern: &str) -> Result<Self, LogError> {
+        self.targets.push(Regex::new(pattern)?);
+        Ok(self)
+    }
+
+    fn matched(&self, record: &log::Record) -> bool {
+        if self.targets.iter().any(|r| r.is_match(record.target())) {
+            return true;
+        }
+        if self.args.is_empty() {
+            return false;
+        }
+        match record.args().as_str() {
+            Some(args) => self.args.iter().any(|r| r.is_match(args)),
+            None => {
+                let args = record.args().to_string();
+                self.args.iter().any(|r| r.is_match(&args))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "regex")]
+impl Filter for RegexFilter {
+    fn accept(&self, record: &log::Record) -> bool {
+        self.matched(record) == self.keep
+    }
+}
+
+/// a filter which can be replaced while the logger is running, for example by an admin endpoint.
+/// clones share the same filter, keep one as the handle
+/// ```rust
+/// use fast_log::Config;
+/// use fast_log::filter::{FilterHandle, ModuleLevelFilter};
+/// use log::LevelFilter;
+/// let handle = FilterHandle::new(ModuleLevelFilter::parse("info"));
+/// fast_log::init(Config::new().console().add_filter(handle.clone())).unwrap();
+/// //later
+/// handle.set(ModuleLevelFilter::parse("my_app::db=trace,info"));
+/// handle.set_level(LevelFilter::Trace);
+/// ```
+#[derive(Clone)]
+pub struct FilterHandle {
+    filter: Arc<RwLock<Box<dyn Filter>>>,
+}
+
+impl Default for FilterHandle {
+    fn default() -> Self {
+        Self::new(NoFilter)
+    }
+}
+
+impl FilterHandle {
+    pub fn new<F: Filter + 'static>(filter: F) -> Self {
+        Self {
+            filter: Arc::new(RwLock::new(Box::new(filter))),
+        }
+    }
+
+    /// replace the filter
+    pub fn set<F: Filter + 'static>(&self, filter: F) {
+        *self.filter.write() = Box::new(filter);
+    }
+
+    /// change the log level, records above it never reach filters
+    pub fn set_level(&self, level: LevelFilter) {
+        crate::set_level(level);
+    }
+}
+
+impl Filter for FilterHandle {
+    fn accept(&self, record: &log::Record) -> bool {
+        self.filter.read().accept(record)
+    }
+}
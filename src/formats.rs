@@ -1,6 +1,11 @@
 use crate::appender::{Command, FastLogRecord, RecordFormat};
+use crate::error::LogError;
 use log::LevelFilter;
+#[cfg(feature = "regex")]
+use regex::{NoExpand, Regex};
+use std::time::SystemTime;
 
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum TimeType {
     Local,
     //default
@@ -13,6 +18,87 @@ impl Default for TimeType {
     }
 }
 
+/// how built-in formats render the record time, see Config::time_format
+#[derive(Clone, Debug)]
+pub struct TimeFormat {
+    style: TimeStyle,
+    time_type: Option<TimeType>,
+}
+
+#[derive(Clone, Debug)]
+enum TimeStyle {
+    Pattern(Vec<DateItem>),
+    Rfc3339,
+    EpochMillis,
+}
+
+impl TimeFormat {
+    /// date pattern with `%Y %m %d %H %M %S %f(micros) %3f(millis) %z(+08:00)`, for example `"%Y-%m-%d %H:%M:%S"`
+    pub fn pattern(pattern: &str) -> Result<Self, LogError> {
+        Ok(Self {
+            style: TimeStyle::Pattern(parse_date_pattern(pattern)?),
+            time_type: None,
+        })
+    }
+
+    /// for example `2022-08-19T09:53:47.798674000+08:00`, or `...Z` in utc
+    pub fn rfc3339() -> Self {
+        Self {
+            style: TimeStyle::Rfc3339,
+            time_type: None,
+        }
+    }
+
+    /// milliseconds since the unix epoch
+    pub fn epoch_millis() -> Self {
+        Self {
+            style: TimeStyle::EpochMillis,
+            time_type: None,
+        }
+    }
+
+    /// use utc, instead of the time_type of the format
+    pub fn utc(mut self) -> Self {
+        self.time_type = Some(TimeType::Utc);
+        self
+    }
+
+    /// use local time, instead of the time_type of the format
+    pub fn local(mut self) -> Self {
+        self.time_type = Some(TimeType::Local);
+        self
+    }
+
+    /// render the time, `time_type` is used unless utc() or local() was set
+    pub fn format(&self, now: SystemTime, time_type: TimeType) -> String {
+        let t = match self.time_type.unwrap_or(time_type) {
+            TimeType::Local => fastdate::DateTime::from(now).set_offset(fastdate::offset_sec()),
+            TimeType::Utc => fastdate::DateTime::from(now),
+        };
+        match &self.style {
+            TimeStyle::Pattern(items) => {
+                let mut buf = String::new();
+                push_date(&mut buf, items, &t);
+                buf
+            }
+            TimeStyle::Rfc3339 => t.display(true),
+            TimeStyle::EpochMillis => now
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis()
+                .to_string(),
+        }
+    }
+}
+
+/// the time rendered by Config::time_format of the logger running on this thread, if set
+fn config_time(now: SystemTime, time_type: TimeType) -> Option<String> {
+    crate::fast_log::with_logger(|logger| {
+        let cfg = logger.cfg.get()?;
+        cfg.time_format.as_ref().map(|f| f.format(now, time_type))
+    })
+}
+
 pub struct FastLogFormat {
     // show line level
     pub display_line_level: LevelFilter,
@@ -23,11 +109,14 @@ impl RecordFormat for FastLogFormat {
     fn do_format(&self, arg: &mut FastLogRecord) {
         match &arg.command {
             Command::CommandRecord => {
-                let now = match self.time_type {
-                    TimeType::Local => fastdate::DateTime::from(arg.now)
-                        .set_offset(fastdate::offset_sec())
-                        .display_stand(),
-                    TimeType::Utc => fastdate::DateTime::from(arg.now).display_stand(),
+                let now = match config_time(arg.now, self.time_type) {
+                    Some(now) => now,
+                    None => match self.time_type {
+                        TimeType::Local => fastdate::DateTime::from(arg.now)
+                            .set_offset(fastdate::offset_sec())
+                            .display_stand(),
+                        TimeType::Utc => fastdate::DateTime::from(arg.now).display_stand(),
+                    },
                 };
                 if arg.level.to_level_filter() <= self.display_line_level {
                     arg.formated = format!(
@@ -43,6 +132,8 @@ impl RecordFormat for FastLogFormat {
                 }
             }
             Command::CommandExit => {}
+            Command::CommandAddAppender(_) | Command::CommandRemoveAppender(_) => {}
+            Command::CommandRotate => {}
             Command::CommandFlush(_) => {}
         }
     }
@@ -69,14 +160,23 @@ impl FastLogFormat {
     }
 }
 
+/// format record into one json object per line,
+/// for example: `{"args":"...","date":"...","file":"...","level":"INFO","line":1,"module_path":"...","target":"..."}`.
+/// key-values of the record are appended as string fields, a key equal to a field of the object is prefixed with `kv_`
 pub struct FastLogFormatJson {
     pub time_type: TimeType,
+    /// append `"hostname"`, `"pid"`, `"thread_id"` and `"thread_name"`, default false
+    pub process: bool,
+    /// append `"seq"`, see FastLogRecord::seq. default false
+    pub seq: bool,
 }
 
 impl Default for FastLogFormatJson {
     fn default() -> Self {
         Self {
             time_type: TimeType::default(),
+            process: false,
+            seq: false,
         }
     }
 }
@@ -85,34 +185,728 @@ impl RecordFormat for FastLogFormatJson {
     fn do_format(&self, arg: &mut FastLogRecord) {
         match &arg.command {
             Command::CommandRecord => {
-                let now = match self.time_type {
-                    TimeType::Local => fastdate::DateTime::from(arg.now)
-                        .add_sub_sec(fastdate::offset_sec() as i64)
-                        .display_stand(),
-                    TimeType::Utc => fastdate::DateTime::from(arg.now).display_stand(),
+                let now = match config_time(arg.now, self.time_type) {
+                    Some(now) => now,
+                    None => match self.time_type {
+                        TimeType::Local => fastdate::DateTime::from(arg.now)
+                            .add_sub_sec(fastdate::offset_sec() as i64)
+                            .display_stand(),
+                        TimeType::Utc => fastdate::DateTime::from(arg.now).display_stand(),
+                    },
                 };
-                //{"args":"Commencing yak shaving","date":"2022-08-19 09:53:47.798674","file":"example/src/split_log.rs","level":"INFO","line":21}
-                let args = arg.args.replace("\"", "\\\"");
+                //{"args":"Commencing yak shaving","date":"2022-08-19 09:53:47.798674","file":"example/src/split_log.rs","level":"INFO","line":21,"module_path":"split_log","target":"split_log"}
                 let file = arg.file.replace("\\", "/");
+                let mut key_values = String::new();
+                if self.process {
+                    key_values.push_str(&format!(
+                        ",\"hostname\":\"{}\",\"pid\":{},\"thread_id\":{},\"thread_name\":\"{}\"",
+                        escape_json(&arg.hostname),
+                        arg.pid,
+                        arg.thread_id,
+                        escape_json(&arg.thread_name)
+                    ));
+                }
+                if self.seq {
+                    key_values.push_str(&format!(",\"seq\":{}", arg.seq));
+                }
+                if let (Some(trace_id), Some(span_id)) = (&arg.trace_id, &arg.span_id) {
+                    key_values.push_str(&format!(
+                        ",\"trace_id\":\"{}\",\"span_id\":\"{}\"",
+                        escape_json(trace_id),
+                        escape_json(span_id)
+                    ));
+                }
+                let has_trace = arg.trace_id.is_some() && arg.span_id.is_some();
+                for (k, v) in &arg.key_values {
+                    let builtin = JSON_FIELDS.contains(&k.as_str())
+                        || (self.process && PROCESS_FIELDS.contains(&k.as_str()))
+                        || (self.seq && k == "seq")
+                        || (has_trace && (k == "trace_id" || k == "span_id"));
+                    key_values.push_str(&format!(
+                        ",\"{}{}\":\"{}\"",
+                        if builtin { "kv_" } else { "" },
+                        escape_json(k),
+                        escape_json(v)
+                    ));
+                }
                 arg.formated = format!(
-                    "{}\"args\":\"{}\",\"date\":\"{}\",\"file\":\"{}\",\"level\":\"{}\",\"line\":{}{}",
+                    "{}\"args\":\"{}\",\"date\":\"{}\",\"file\":\"{}\",\"level\":\"{}\",\"line\":{},\"module_path\":\"{}\",\"target\":\"{}\"{}{}",
                     "{",
-                    args,
+                    escape_json(&arg.args),
                     now,
-                    file,
+                    escape_json(&file),
                     arg.level,
                     arg.line.unwrap_or_default(),
+                    escape_json(&arg.module_path),
+                    escape_json(&arg.target),
+                    key_values,
                     "}\n"
                 );
             }
             Command::CommandExit => {}
+            Command::CommandAddAppender(_) | Command::CommandRemoveAppender(_) => {}
+            Command::CommandRotate => {}
             Command::CommandFlush(_) => {}
         }
     }
 }
 
+/// the fields of every FastLogFormatJson object
+const JSON_FIELDS: [&str; 7] = [
+    "args",
+    "date",
+    "file",
+    "level",
+    "line",
+    "module_path",
+    "target",
+];
+
+/// the fields of FastLogFormatJson::process
+const PROCESS_FIELDS: [&str; 4] = ["hostname", "pid", "thread_id", "thread_name"];
+
 impl FastLogFormatJson {
     pub fn new() -> FastLogFormatJson {
         Self::default()
     }
+
+    /// append hostname, pid and thread fields, to tell replicas apart in a shared pipeline
+    pub fn set_process(mut self, process: bool) -> Self {
+        self.process = process;
+        self
+    }
+
+    /// append the sequence number, so consumers can find lost records by gaps
+    pub fn set_seq(mut self, seq: bool) -> Self {
+        self.seq = seq;
+        self
+    }
+}
+
+/// format record into logfmt,
+/// for example: `ts=2022-08-19T09:53:47.798674000+08:00 level=info target=app msg="Commencing yak shaving" user=bob`
+#[derive(Default)]
+pub struct LogFmtFormat {
+    pub time_type: TimeType,
+}
+
+impl RecordFormat for LogFmtFormat {
+    fn do_format(&self, arg: &mut FastLogRecord) {
+        match &arg.command {
+            Command::CommandRecord => {
+                let now = match config_time(arg.now, self.time_type) {
+                    Some(now) => now,
+                    None => match self.time_type {
+                        TimeType::Local => fastdate::DateTime::from(arg.now)
+                            .set_offset(fastdate::offset_sec())
+                            .display(true),
+                        TimeType::Utc => fastdate::DateTime::from(arg.now).display(true),
+                    },
+                };
+                let mut formated = format!(
+                    "ts={} level={} target={} msg={}",
+                    now,
+                    arg.level.as_str().to_lowercase(),
+                    logfmt_value(&arg.target),
+                    logfmt_value(&arg.args)
+                );
+                for (k, v) in &arg.key_values {
+                    formated.push_str(&format!(" {}={}", logfmt_key(k), logfmt_value(v)));
+                }
+                formated.push('\n');
+                arg.formated = formated;
+            }
+            Command::CommandExit => {}
+            Command::CommandAddAppender(_) | Command::CommandRemoveAppender(_) => {}
+            Command::CommandRotate => {}
+            Command::CommandFlush(_) => {}
+        }
+    }
+}
+
+impl LogFmtFormat {
+    pub fn new() -> LogFmtFormat {
+        Self::default()
+    }
+}
+
+/// replace space, `=`, `"` and control chars of a key with `_`, keys can not be quoted in logfmt
+fn logfmt_key(key: &str) -> String {
+    if key.is_empty() {
+        return "_".to_string();
+    }
+    key.chars()
+        .map(|c| {
+            if c == ' ' || c == '=' || c == '"' || c.is_control() {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// quote the value if it is empty or contains space, `=`, `"` or control chars
+fn logfmt_value(arg: &str) -> String {
+    if !arg.is_empty()
+        && !arg
+            .chars()
+            .any(|c| c == ' ' || c == '=' || c == '"' || c.is_control())
+    {
+        return arg.to_string();
+    }
+    format!("\"{}\"", escape_json(arg))
+}
+
+/// a column of CsvFormat
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CsvColumn {
+    Time,
+    Level,
+    Target,
+    /// `file:line`
+    Location,
+    Message,
+    ModulePath,
+    /// thread name, or id if unnamed
+    Thread,
+    Seq,
+    /// a key-value of the record, empty if missing
+    Field(String),
+}
+
+impl CsvColumn {
+    fn name(&self) -> &str {
+        match self {
+            CsvColumn::Time => "timestamp",
+            CsvColumn::Level => "level",
+            CsvColumn::Target => "target",
+            CsvColumn::Location => "location",
+            CsvColumn::Message => "message",
+            CsvColumn::ModulePath => "module_path",
+            CsvColumn::Thread => "thread",
+            CsvColumn::Seq => "seq",
+            CsvColumn::Field(key) => key,
+        }
+    }
+}
+
+/// format record into one CSV row, every value is quoted(`"` doubled, newlines kept inside the quotes),
+/// default columns `timestamp,level,target,location,message`. for example:
+/// `"2022-08-19T09:53:47.798674000+08:00","INFO","app","src/main.rs:12","Commencing yak shaving"`
+/// ```rust
+/// use fast_log::{Config, CsvColumn, CsvFormat};
+/// let format = CsvFormat::new()
+///     .columns(vec![
+///         CsvColumn::Time,
+///         CsvColumn::Level,
+///         CsvColumn::Field("user".to_string()),
+///         CsvColumn::Message,
+///     ])
+///     .separator(';');
+/// println!("{}", format.header()); // "timestamp";"level";"user";"message"
+/// fast_log::init(Config::new().file("target/logs/app.csv").format(format)).unwrap();
+/// ```
+pub struct CsvFormat {
+    pub columns: Vec<CsvColumn>,
+    pub separator: char,
+    pub time_type: TimeType,
+}
+
+impl Default for CsvFormat {
+    fn default() -> Self {
+        Self {
+            columns: vec![
+                CsvColumn::Time,
+                CsvColumn::Level,
+                CsvColumn::Target,
+                CsvColumn::Location,
+                CsvColumn::Message,
+            ],
+            separator: ',',
+            time_type: TimeType::default(),
+        }
+    }
+}
+
+impl CsvFormat {
+    pub fn new() -> CsvFormat {
+        Self::default()
+    }
+
+    /// set the columns and their order
+    pub fn columns(mut self, columns: Vec<CsvColumn>) -> Self {
+        self.columns = columns;
+        self
+    }
+
+    /// default `,`
+    pub fn separator(mut self, separator: char) -> Self {
+        self.separator = separator;
+        self
+    }
+
+    pub fn set_time_type(mut self, time_type: TimeType) -> Self {
+        self.time_type = time_type;
+        self
+    }
+
+    fn time(&self, now: SystemTime) -> String {
+        match config_time(now, self.time_type) {
+            Some(now) => now,
+            None => match self.time_type {
+                TimeType::Local => fastdate::DateTime::from(now)
+                    .set_offset(fastdate::offset_sec())
+                    .display(true),
+                TimeType::Utc => fastdate::DateTime::from(now).display(true),
+            },
+        }
+    }
+
+    /// the header row of the columns, with a newline
+    pub fn header(&self) -> String {
+        let mut row = String::new();
+        for (i, c) in self.columns.iter().enumerate() {
+            if i != 0 {
+                row.push(self.separator);
+            }
+            csv_value(&mut row, c.name());
+        }
+        row.push('\n');
+        row
+    }
+}
+
+impl RecordFormat for CsvFormat {
+    fn do_format(&self, arg: &mut FastLogRecord) {
+        match &arg.command {
+            Command::CommandRecord => {
+                let mut row = String::with_capacity(arg.args.len() + 96);
+                for (i, c) in self.columns.iter().enumerate() {
+                    if i != 0 {
+                        row.push(self.separator);
+                    }
+                    match c {
+                        CsvColumn::Time => csv_value(&mut row, &self.time(arg.now)),
+                        CsvColumn::Level => csv_value(&mut row, arg.level.as_str()),
+                        CsvColumn::Target => csv_value(&mut row, &arg.target),
+                        CsvColumn::Location => {
+                            let file = arg.file.replace("\\", "/");
+                            let location = format!("{}:{}", file, arg.line.unwrap_or_default());
+                            csv_value(&mut row, &location)
+                        }
+                        CsvColumn::Message => csv_value(&mut row, &arg.args),
+                        CsvColumn::ModulePath => csv_value(&mut row, &arg.module_path),
+                        CsvColumn::Thread => {
+                            if arg.thread_name.is_empty() {
+                                csv_value(&mut row, &arg.thread_id.to_string())
+                            } else {
+                                csv_value(&mut row, &arg.thread_name)
+                            }
+                        }
+                        CsvColumn::Seq => csv_value(&mut row, &arg.seq.to_string()),
+                        CsvColumn::Field(key) => {
+                            let v = arg.key_values.iter().find(|(k, _)| k == key);
+                            csv_value(&mut row, v.map(|(_, v)| v.as_str()).unwrap_or_default())
+                        }
+                    }
+                }
+                row.push('\n');
+                arg.formated = row;
+            }
+            Command::CommandExit => {}
+            Command::CommandAddAppender(_) | Command::CommandRemoveAppender(_) => {}
+            Command::CommandRotate => {}
+            Command::CommandFlush(_) => {}
+        }
+    }
+}
+
+/// push the quoted value, `"` is doubled
+fn csv_value(row: &mut String, value: &str) {
+    row.push('"');
+    for c in value.chars() {
+        if c == '"' {
+            row.push('"');
+        }
+        row.push(c);
+    }
+    row.push('"');
+}
+
+/// format record by a pattern parsed once at init, for example
+/// `"%d{%Y-%m-%d %H:%M:%S} [%l] %t %f:%L - %m%n"`
+///
+/// * `%d` date, default `%Y-%m-%d %H:%M:%S.%f`(or Config::time_format). `%d{...}` with `%Y %m %d %H %M %S %f(micros) %3f(millis) %z(+08:00)`
+/// * `%l` level, `%t` target, `%M` module_path, `%f` file, `%L` line, `%m` message, `%n` newline, `%%` `%`
+/// * `%P` pid, `%T` thread name(or id if unnamed), `%H` hostname, `%s` sequence number(see FastLogRecord::seq)
+/// * a width pads the value: `%5l` right aligned, `%-5l` left aligned
+pub struct PatternFormat {
+    items: Vec<PatternItem>,
+    pub time_type: TimeType,
+}
+
+enum PatternItem {
+    Literal(String),
+    /// date items, and true if `%d` has no `{...}`(Config::time_format replaces it)
+    Date(Vec<DateItem>, bool),
+    Value(PatternValue, Option<(bool, usize)>),
+}
+
+#[derive(Copy, Clone)]
+enum PatternValue {
+    Level,
+    Target,
+    Module,
+    File,
+    Line,
+    Message,
+    Pid,
+    Thread,
+    Hostname,
+    Seq,
+}
+
+#[derive(Clone, Debug)]
+enum DateItem {
+    Literal(String),
+    Year,
+    Month,
+    Day,
+    Hour,
+    Minute,
+    Second,
+    Millis,
+    Micros,
+    Offset,
+}
+
+impl PatternFormat {
+    pub fn new(pattern: &str) -> Result<PatternFormat, LogError> {
+        let mut items = vec![];
+        let mut literal = String::new();
+        let mut chars = pattern.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                literal.push(c);
+                continue;
+            }
+            let mut left = false;
+            if chars.peek() == Some(&'-') {
+                left = true;
+                chars.next();
+            }
+            let mut width = String::new();
+            while let Some(c) = chars.peek().filter(|c| c.is_ascii_digit()) {
+                width.push(*c);
+                chars.next();
+            }
+            let pad = width.parse().ok().map(|w| (left, w));
+            let value = match chars.next() {
+                Some('%') => {
+                    literal.push('%');
+                    continue;
+                }
+                Some('n') => {
+                    literal.push('\n');
+                    continue;
+                }
+                Some('d') => {
+                    let default = chars.peek() != Some(&'{');
+                    let date = if !default {
+                        chars.next();
+                        let mut date = String::new();
+                        loop {
+                            match chars.next() {
+                                Some('}') => break,
+                                Some(c) => date.push(c),
+                                None => {
+                                    return Err(LogError::from(format!(
+                                        "[fast_log] unclosed '{{' in pattern: {}",
+                                        pattern
+                                    )))
+                                }
+                            }
+                        }
+                        parse_date_pattern(&date)?
+                    } else {
+                        parse_date_pattern("%Y-%m-%d %H:%M:%S.%f")?
+                    };
+                    if !literal.is_empty() {
+                        items.push(PatternItem::Literal(std::mem::take(&mut literal)));
+                    }
+                    items.push(PatternItem::Date(date, default));
+                    continue;
+                }
+                Some('l') => PatternValue::Level,
+                Some('t') => PatternValue::Target,
+                Some('M') => PatternValue::Module,
+                Some('f') => PatternValue::File,
+                Some('L') => PatternValue::Line,
+                Some('m') => PatternValue::Message,
+                Some('P') => PatternValue::Pid,
+                Some('T') => PatternValue::Thread,
+                Some('H') => PatternValue::Hostname,
+                Some('s') => PatternValue::Seq,
+                c => {
+                    return Err(LogError::from(format!(
+                        "[fast_log] unknown '%{}' in pattern: {}",
+                        c.map(String::from).unwrap_or_default(),
+                        pattern
+                    )))
+                }
+            };
+            if !literal.is_empty() {
+                items.push(PatternItem::Literal(std::mem::take(&mut literal)));
+            }
+            items.push(PatternItem::Value(value, pad));
+        }
+        if !literal.is_empty() {
+            items.push(PatternItem::Literal(literal));
+        }
+        Ok(Self {
+            items,
+            time_type: TimeType::default(),
+        })
+    }
+
+    /// set time_type
+    pub fn set_time_type(mut self, time_type: TimeType) -> Self {
+        self.time_type = time_type;
+        self
+    }
+}
+
+impl RecordFormat for PatternFormat {
+    fn do_format(&self, arg: &mut FastLogRecord) {
+        match &arg.command {
+            Command::CommandRecord => {
+                let mut buf = String::with_capacity(arg.args.len() + 64);
+                for item in &self.items {
+                    match item {
+                        PatternItem::Literal(v) => buf.push_str(v),
+                        PatternItem::Date(date, default) => {
+                            if *default {
+                                if let Some(now) = config_time(arg.now, self.time_type) {
+                                    buf.push_str(&now);
+                                    continue;
+                                }
+                            }
+                            let now = match self.time_type {
+                                TimeType::Local => fastdate::DateTime::from(arg.now)
+                                    .set_offset(fastdate::offset_sec()),
+                                TimeType::Utc => fastdate::DateTime::from(arg.now),
+                            };
+                            push_date(&mut buf, date, &now);
+                        }
+                        PatternItem::Value(value, pad) => {
+                            let number;
+                            let v = match value {
+                                PatternValue::Level => arg.level.as_str(),
+                                PatternValue::Target => &arg.target,
+                                PatternValue::Module => &arg.module_path,
+                                PatternValue::File => &arg.file,
+                                PatternValue::Line => {
+                                    number = arg.line.unwrap_or_default().to_string();
+                                    number.as_str()
+                                }
+                                PatternValue::Message => arg.args.as_str(),
+                                PatternValue::Pid => {
+                                    number = arg.pid.to_string();
+                                    number.as_str()
+                                }
+                                PatternValue::Thread if arg.thread_name.is_empty() => {
+                                    number = arg.thread_id.to_string();
+                                    number.as_str()
+                                }
+                                PatternValue::Thread => &arg.thread_name,
+                                PatternValue::Hostname => &arg.hostname,
+                                PatternValue::Seq => {
+                                    number = arg.seq.to_string();
+                                    number.as_str()
+                                }
+                            };
+                            match pad {
+                                None => buf.push_str(v),
+                                Some((true, w)) => buf.push_str(&format!("{:<w$}", v, w = w)),
+                                Some((false, w)) => buf.push_str(&format!("{:>w$}", v, w = w)),
+                            }
+                        }
+                    }
+                }
+                arg.formated = buf;
+            }
+            Command::CommandExit => {}
+            Command::CommandAddAppender(_) | Command::CommandRemoveAppender(_) => {}
+            Command::CommandRotate => {}
+            Command::CommandFlush(_) => {}
+        }
+    }
+}
+
+fn parse_date_pattern(pattern: &str) -> Result<Vec<DateItem>, LogError> {
+    let mut items = vec![];
+    let mut literal = String::new();
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            literal.push(c);
+            continue;
+        }
+        let item = match chars.next() {
+            Some('%') => {
+                literal.push('%');
+                continue;
+            }
+            Some('Y') => DateItem::Year,
+            Some('m') => DateItem::Month,
+            Some('d') => DateItem::Day,
+            Some('H') => DateItem::Hour,
+            Some('M') => DateItem::Minute,
+            Some('S') => DateItem::Second,
+            Some('f') => DateItem::Micros,
+            Some('z') => DateItem::Offset,
+            Some('3') if chars.next() == Some('f') => DateItem::Millis,
+            c => {
+                return Err(LogError::from(format!(
+                    "[fast_log] unknown '%{}' in date pattern: {}",
+                    c.map(String::from).unwrap_or_default(),
+                    pattern
+                )))
+            }
+        };
+        if !literal.is_empty() {
+            items.push(DateItem::Literal(std::mem::take(&mut literal)));
+        }
+        items.push(item);
+    }
+    if !literal.is_empty() {
+        items.push(DateItem::Literal(literal));
+    }
+    Ok(items)
+}
+
+fn push_date(buf: &mut String, items: &[DateItem], t: &fastdate::DateTime) {
+    use std::fmt::Write;
+    for item in items {
+        let _ = match item {
+            DateItem::Literal(v) => {
+                buf.push_str(v);
+                Ok(())
+            }
+            DateItem::Year => write!(buf, "{:04}", t.year()),
+            DateItem::Month => write!(buf, "{:02}", t.mon()),
+            DateItem::Day => write!(buf, "{:02}", t.day()),
+            DateItem::Hour => write!(buf, "{:02}", t.hour()),
+            DateItem::Minute => write!(buf, "{:02}", t.minute()),
+            DateItem::Second => write!(buf, "{:02}", t.sec()),
+            DateItem::Millis => write!(buf, "{:03}", t.nano() / 1_000_000),
+            DateItem::Micros => write!(buf, "{:06}", t.nano() / 1000),
+            DateItem::Offset => {
+                let offset = t.offset();
+                let sign = if offset < 0 { '-' } else { '+' };
+                let offset = offset.abs();
+                write!(buf, "{}{:02}:{:02}", sign, offset / 3600, offset % 3600 / 60)
+            }
+        };
+    }
+}
+
+/// mask sensitive data of the message and key-values with regex rules(regex crate syntax),
+/// then format with the inner format. the rules run at the producer, before any appender sees the record.
+/// needs the `regex` feature
+/// ```rust
+/// use fast_log::{Config, FastLogFormat, RedactionFormat};
+/// let format = RedactionFormat::new(FastLogFormat::new())
+///     .credit_cards()
+///     .bearer_tokens()
+///     .emails()
+///     .rule(r"password=\S+", "password=***")
+///     .unwrap();
+/// fast_log::init(Config::new().console().format(format)).unwrap();
+/// ```
+#[cfg(feature = "regex")]
+pub struct RedactionFormat<F: RecordFormat> {
+    inner: F,
+    rules: Vec<(Regex, String)>,
+}
+
+#[cfg(feature = "regex")]
+impl<F: RecordFormat> RedactionFormat<F> {
+    pub fn new(inner: F) -> Self {
+        Self {
+            inner,
+            rules: vec![],
+        }
+    }
+
+    /// replace every match of `pattern` by `replacement`, `$` in it is not expanded
+    pub fn rule(mut self, pattern: &str, replacement: &str) -> Result<Self, LogError> {
+        self.rules
+            .push((Regex::new(pattern)?, replacement.to_string()));
+        Ok(self)
+    }
+
+    fn builtin(self, pattern: &str, replacement: &str) -> Self {
+        self.rule(pattern, replacement)
+            .expect("built-in redaction rule")
+    }
+
+    /// card numbers of 13-19 digits, spaces or dashes between them
+    pub fn credit_cards(self) -> Self {
+        self.builtin(r"\b\d(?:[ -]?\d){12,18}\b", "[CARD]")
+    }
+
+    /// `Bearer <token>` of authorization headers
+    pub fn bearer_tokens(self) -> Self {
+        self.builtin(r"(?i)bearer\s+[\w\-.~+/]+=*", "Bearer [TOKEN]")
+    }
+
+    /// email addresses
+    pub fn emails(self) -> Self {
+        self.builtin(r"[\w.+\-]+@[\w\-]+(?:\.[\w\-]+)+", "[EMAIL]")
+    }
+
+    /// apply the rules to `text`
+    pub fn redact(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for (regex, replacement) in &self.rules {
+            if regex.is_match(&text) {
+                text = regex.replace_all(&text, NoExpand(replacement)).into_owned();
+            }
+        }
+        text
+    }
+}
+
+#[cfg(feature = "regex")]
+impl<F: RecordFormat> RecordFormat for RedactionFormat<F> {
+    fn do_format(&self, arg: &mut FastLogRecord) {
+        if arg.command == Command::CommandRecord {
+            arg.args = self.redact(&arg.args);
+            for (_, v) in arg.key_values.iter_mut() {
+                *v = self.redact(v);
+            }
+        }
+        self.inner.do_format(arg);
+    }
+}
+
+/// escape a str so that it can be embedded in a json string value
+pub(crate) fn escape_json(arg: &str) -> String {
+    let mut s = String::with_capacity(arg.len());
+    for c in arg.chars() {
+        match c {
+            '"' => s.push_str("\\\""),
+            '\\' => s.push_str("\\\\"),
+            '\n' => s.push_str("\\n"),
+            '\r' => s.push_str("\\r"),
+            '\t' => s.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                s.push_str(&format!("\\u{:04x}", c as u32));
+            }
+            c => s.push(c),
+        }
+    }
+    s
 }
@@ -2,14 +2,23 @@ extern crate core;
 
 pub mod appender;
 pub mod bencher;
+pub mod clock;
 pub mod config;
+#[cfg(feature = "toml")]
+pub mod config_file;
 pub mod consts;
+pub mod context;
 pub mod error;
 pub mod fast_log;
 pub mod filter;
 pub mod formats;
 pub mod plugin;
 pub mod runtime;
+pub mod timing;
+#[cfg(feature = "tracing")]
+pub mod tracing_bridge;
+#[cfg(feature = "slog")]
+pub mod slog_drain;
 
 
 pub use crate::config::Config;
@@ -0,0 +1,219 @@
+use crate::error::LogError;
+use crate::Metrics;
+use log::LevelFilter;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// max bytes of the request line and headers
+const MAX_HEAD: u64 = 8 * 1024;
+/// max bytes of the body
+const MAX_BODY: usize = 1024;
+/// a request must be read in this time
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// you need enable fast_log = { ... ,features=["admin"]}
+/// a tiny http server controlling the global logger:
+/// * `GET /level` the level, `PUT /level` with a body like `debug` sets it
+/// * `POST /flush` flush and wait up to 5s, `POST /rotate` roll the log files
+/// * `GET /metrics` the counters of `fast_log::metrics()` in the prometheus text format
+///
+/// every connection is served on its own thread, the request must fit in 8KB of headers and arrive
+/// in 5s. anyone reaching the address can change the level,
+/// bind to a local address or set a `token`, then requests need `Authorization: Bearer <token>`
+/// ```rust,no_run
+/// use fast_log::Config;
+/// use fast_log::plugin::admin::AdminServer;
+/// fast_log::init(Config::new().console()).unwrap();
+/// AdminServer::new("127.0.0.1:9199").token("secret").start().unwrap();
+/// // curl -X PUT -H 'Authorization: Bearer secret' -d debug http://127.0.0.1:9199/level
+/// ```
+pub struct AdminServer {
+    addr: String,
+    token: Option<String>,
+}
+
+impl AdminServer {
+    pub fn new(addr: &str) -> Self {
+        Self {
+            addr: addr.to_string(),
+            token: None,
+        }
+    }
+
+    pub fn token(mut self, token: &str) -> Self {
+        self.token = Some(token.to_string());
+        self
+    }
+
+    /// bind and serve on a thread, return the bound address(for example of port 0)
+    pub fn start(self) -> Result<SocketAddr, LogError> {
+        let listener = TcpListener::bind(&self.addr).map_err(|e| {
+            LogError::from(format!("[fast_log] admin bind {} fail: {}", self.addr, e))
+        })?;
+        let addr = listener.local_addr()?;
+        let server = Arc::new(self);
+        crate::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        let server = server.clone();
+                        crate::spawn(move || {
+                            if let Err(e) = server.serve(stream) {
+                                crate::report_error(&LogError::from(format!(
+                                    "[fast_log] admin: {}",
+                                    e
+                                )));
+                            }
+                        });
+                    }
+                    Err(_) => std::thread::sleep(Duration::from_millis(100)),
+                }
+            }
+        });
+        Ok(addr)
+    }
+
+    fn serve(&self, stream: TcpStream) -> std::io::Result<()> {
+        let deadline = Deadline {
+            stream: stream.try_clone()?,
+            deadline: Instant::now() + REQUEST_TIMEOUT,
+        };
+        let mut reader = BufReader::new(deadline.take(MAX_HEAD));
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let mut parts = line.split_whitespace();
+        let method = parts.next().unwrap_or_default().to_string();
+        let path = parts.next().unwrap_or_default().to_string();
+        let mut len = 0;
+        let mut authorized = self.token.is_none();
+        loop {
+            let mut header = String::new();
+            if reader.read_line(&mut header)? == 0 {
+                return match reader.get_ref().limit() {
+                    0 => respond(stream, "431 Request Header Fields Too Large", "too large\n"),
+                    _ => Ok(()),
+                };
+            }
+            if header.trim().is_empty() {
+                break;
+            }
+            let (name, value) = header.split_once(':').unwrap_or((&header, ""));
+            let value = value.trim();
+            match name.trim().to_lowercase().as_str() {
+                "content-length" => len = value.parse().unwrap_or_default(),
+                "authorization" => {
+                    if let Some(token) = &self.token {
+                        authorized = value
+                            .strip_prefix("Bearer ")
+                            .map(|v| constant_time_eq(v.as_bytes(), token.as_bytes()))
+                            .unwrap_or_default();
+                    }
+                }
+                _ => {}
+            }
+        }
+        let len = len.min(MAX_BODY);
+        reader.get_mut().set_limit(len as u64);
+        let mut body = vec![0u8; len];
+        reader.read_exact(&mut body)?;
+        let (status, text) = if authorized {
+            handle(&method, &path, String::from_utf8_lossy(&body).trim())
+        } else {
+            ("401 Unauthorized", "unauthorized\n".to_string())
+        };
+        respond(stream, status, &text)
+    }
+}
+
+fn respond(mut stream: TcpStream, status: &str, text: &str) -> std::io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        text.len(),
+        text
+    )?;
+    stream.flush()
+}
+
+/// read the stream until the deadline, a slow client can not hold the connection
+struct Deadline {
+    stream: TcpStream,
+    deadline: Instant,
+}
+
+impl Read for Deadline {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let left = self.deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Err(std::io::ErrorKind::TimedOut.into());
+        }
+        self.stream.set_read_timeout(Some(left))?;
+        self.stream.read(buf)
+    }
+}
+
+/// compare the token without leaking the position of the first difference by timing
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// (status, body) of a request
+fn handle(method: &str, path: &str, body: &str) -> (&'static str, String) {
+    let path = path.split('?').next().unwrap_or_default();
+    match (method, path) {
+        ("GET", "/level") => ok(crate::get_level().as_str().to_lowercase()),
+        ("PUT", "/level") => match LevelFilter::from_str(body) {
+            Ok(level) => {
+                crate::set_level(level);
+                ok(level.as_str().to_lowercase())
+            }
+            Err(_) => ("400 Bad Request", format!("unknown level {}\n", body)),
+        },
+        ("POST", "/flush") => match crate::flush_sync(Duration::from_secs(5)) {
+            Ok(_) => ok("ok".to_string()),
+            Err(e) => ("500 Internal Server Error", format!("{}\n", e)),
+        },
+        ("POST", "/rotate") => match crate::rotate() {
+            Ok(_) => ok("ok".to_string()),
+            Err(e) => ("500 Internal Server Error", format!("{}\n", e)),
+        },
+        ("GET", "/metrics") => ("200 OK", prometheus(&crate::metrics())),
+        (_, "/level" | "/flush" | "/rotate" | "/metrics") => {
+            ("405 Method Not Allowed", "method not allowed\n".to_string())
+        }
+        _ => ("404 Not Found", "not found\n".to_string()),
+    }
+}
+
+fn ok(text: String) -> (&'static str, String) {
+    ("200 OK", text + "\n")
+}
+
+/// the metrics in the prometheus text format
+pub fn prometheus(m: &Metrics) -> String {
+    let mut s = String::new();
+    let counters = [
+        ("fast_log_received_total", "counter", m.received),
+        ("fast_log_dropped_total", "counter", m.dropped),
+        ("fast_log_queue_len", "gauge", m.queue_len as u64),
+        ("fast_log_rotations_total", "counter", m.rotations),
+        ("fast_log_write_errors_total", "counter", m.write_errors),
+        ("fast_log_seq", "counter", m.seq),
+    ];
+    for (name, kind, value) in counters {
+        s.push_str(&format!("# TYPE {} {}\n{} {}\n", name, kind, name, value));
+    }
+    s.push_str("# TYPE fast_log_appender_bytes_total counter\n");
+    for (name, bytes) in &m.appender_bytes {
+        s.push_str(&format!(
+            "fast_log_appender_bytes_total{{appender=\"{}\"}} {}\n",
+            name.replace('\\', "\\\\").replace('"', "\\\""),
+            bytes
+        ));
+    }
+    s
+}
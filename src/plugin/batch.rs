@@ -0,0 +1,98 @@
+use crate::{chan, spawn, Sender, WaitGroup};
+use crossbeam_channel::RecvTimeoutError;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+pub(crate) enum BatchMsg<T> {
+    Item(T),
+    /// send the current batch, then drop the WaitGroup
+    Flush(WaitGroup),
+}
+
+/// spawn a thread collect items, call `f` when `batch_size` items collected,
+/// `interval` passed, on flush or when all senders are dropped
+pub(crate) fn spawn_batch<T, F>(
+    batch_size: usize,
+    interval: Duration,
+    mut f: F,
+) -> Sender<BatchMsg<T>>
+where
+    T: Send + 'static,
+    F: FnMut(Vec<T>) + Send + 'static,
+{
+    let (sender, receiver) = chan(None);
+    spawn(move || {
+        let mut items = Vec::new();
+        let mut deadline = Instant::now() + interval;
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            let done = match receiver.recv_timeout(timeout) {
+                Ok(BatchMsg::Item(v)) => {
+                    items.push(v);
+                    items.len() >= batch_size
+                }
+                Ok(BatchMsg::Flush(wg)) => {
+                    if !items.is_empty() {
+                        f(std::mem::take(&mut items));
+                    }
+                    drop(wg);
+                    false
+                }
+                Err(RecvTimeoutError::Timeout) => true,
+                Err(RecvTimeoutError::Disconnected) => {
+                    if !items.is_empty() {
+                        f(std::mem::take(&mut items));
+                    }
+                    break;
+                }
+            };
+            if done {
+                if !items.is_empty() {
+                    f(std::mem::take(&mut items));
+                }
+                deadline = Instant::now() + interval;
+            }
+        }
+    });
+    sender
+}
+
+/// send the current batch and wait until it is sent, for CommandExit
+pub(crate) fn flush_wait<T>(sender: &Sender<BatchMsg<T>>) {
+    let wg = WaitGroup::new();
+    let _ = sender.send(BatchMsg::Flush(wg.clone()));
+    wg.wait();
+}
+
+/// allow at most `max` events every `period`
+pub(crate) struct RateLimiter {
+    max: usize,
+    period: Duration,
+    sent: VecDeque<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new(max: usize, period: Duration) -> Self {
+        Self {
+            max,
+            period,
+            sent: VecDeque::new(),
+        }
+    }
+
+    pub fn allow(&mut self) -> bool {
+        let now = Instant::now();
+        while let Some(v) = self.sent.front() {
+            if now.duration_since(*v) >= self.period {
+                self.sent.pop_front();
+            } else {
+                break;
+            }
+        }
+        if self.sent.len() >= self.max {
+            return false;
+        }
+        self.sent.push_back(now);
+        true
+    }
+}
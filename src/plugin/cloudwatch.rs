@@ -0,0 +1,423 @@
+use crate::appender::{Command, FastLogRecord, LogAppender};
+use crate::error::LogError;
+use crate::formats::escape_json;
+use crate::plugin::batch::{flush_wait, spawn_batch, BatchMsg};
+use crate::plugin::http::{Http, HttpError, HttpOptions, InFlight};
+use crate::plugin::sigv4::{self, endpoint_host, payload_hash, Credentials};
+use crate::{report_error, Sender};
+use fastdate::DateTime;
+use once_cell::sync::OnceCell;
+use std::time::{Duration, UNIX_EPOCH};
+
+/// PutLogEvents limits: events of a request, bytes of a request(message bytes + 26 per event),
+/// bytes of one event and the time span of a request
+const MAX_EVENTS: usize = 10_000;
+const MAX_BATCH_BYTES: usize = 1_048_576;
+const EVENT_OVERHEAD: usize = 26;
+const MAX_EVENT_BYTES: usize = 256 * 1024 - EVENT_OVERHEAD;
+const MAX_SPAN_MILLIS: i64 = 24 * 3600 * 1000;
+
+/// one log event of a PutLogEvents request
+pub(crate) struct CloudWatchEvent {
+    /// unix epoch in milliseconds
+    timestamp: i64,
+    message: String,
+}
+
+/// you need enable fast_log = { ... ,features=["cloudwatch"]}
+/// send records to a log stream of AWS CloudWatch Logs with PutLogEvents, signed with AWS Signature V4.
+/// the log group and stream are created if missing, records are batched under the request limits
+/// (10000 events, 1MB, 24 hours) and sent at least every `flush_interval`. throttled requests, network
+/// errors and 5xx are retried with backoff, rejected sequence tokens are replaced by the expected one.
+/// the credentials default to the env `AWS_ACCESS_KEY_ID` `AWS_SECRET_ACCESS_KEY` `AWS_SESSION_TOKEN`
+/// (set in Lambda and ECS tasks)
+/// ```rust,no_run
+/// use fast_log::Config;
+/// use fast_log::plugin::cloudwatch::CloudWatchAppender;
+/// fast_log::init(Config::new().json().add_appender(
+///     CloudWatchAppender::new("/ecs/my_app", "web-0", "us-east-1"),
+/// ))
+/// .unwrap();
+/// ```
+pub struct CloudWatchAppender {
+    group: String,
+    stream: String,
+    region: String,
+    endpoint: Option<String>,
+    credentials: Credentials,
+    create: bool,
+    flush_interval: Duration,
+    options: HttpOptions,
+    in_flight: InFlight,
+    sender: OnceCell<Sender<BatchMsg<CloudWatchEvent>>>,
+}
+
+impl CloudWatchAppender {
+    pub fn new(log_group: &str, log_stream: &str, region: &str) -> Self {
+        Self {
+            group: log_group.to_string(),
+            stream: log_stream.to_string(),
+            region: region.to_string(),
+            endpoint: None,
+            credentials: Credentials::from_env(),
+            create: true,
+            flush_interval: Duration::from_secs(1),
+            options: HttpOptions {
+                retries: 5,
+                backoff: Duration::from_millis(200),
+                ..Default::default()
+            },
+            in_flight: InFlight::default(),
+            sender: OnceCell::new(),
+        }
+    }
+
+    pub fn credentials(mut self, access_key: &str, secret_key: &str) -> Self {
+        self.credentials.access_key = access_key.to_string();
+        self.credentials.secret_key = secret_key.to_string();
+        self
+    }
+
+    pub fn session_token(mut self, session_token: &str) -> Self {
+        self.credentials.session_token = Some(session_token.to_string());
+        self
+    }
+
+    /// default `https://logs.{region}.amazonaws.com`, for example `http://127.0.0.1:4566` for localstack
+    pub fn endpoint(mut self, endpoint: &str) -> Self {
+        self.endpoint = Some(endpoint.trim_end_matches('/').to_string());
+        self
+    }
+
+    /// create the log group and stream before the first request, default true.
+    /// disable it when the role may only call PutLogEvents
+    pub fn create(mut self, create: bool) -> Self {
+        self.create = create;
+        self
+    }
+
+    /// send at least every `interval`, default 1s
+    pub fn flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = interval;
+        self
+    }
+
+    /// retries of throttled requests, network errors and 5xx, default 5
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.options.retries = retries;
+        self
+    }
+
+    /// the wait before the first retry, doubled after each retry. default 200ms
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.options.backoff = backoff;
+        self
+    }
+
+    /// max bytes of events waiting to be sent(queued or being retried), default 16MB.
+    /// records logged while it is full are dropped
+    pub fn max_in_flight(mut self, bytes: usize) -> Self {
+        self.options.max_in_flight = bytes.max(1);
+        self
+    }
+
+    fn sender(&self) -> &Sender<BatchMsg<CloudWatchEvent>> {
+        self.sender.get_or_init(|| {
+            let mut client = Client {
+                http: Http::with_options(&self.options).map(|mut v| {
+                    //retried by Client::put
+                    v.retries = 0;
+                    v
+                }),
+                url: self
+                    .endpoint
+                    .clone()
+                    .unwrap_or_else(|| format!("https://logs.{}.amazonaws.com", self.region)),
+                region: self.region.clone(),
+                credentials: self.credentials.clone(),
+                group: self.group.clone(),
+                stream: self.stream.clone(),
+                retries: self.options.retries,
+                backoff: self.options.backoff,
+                create: self.create,
+                sequence_token: None,
+            };
+            let in_flight = self.in_flight.clone();
+            spawn_batch(MAX_EVENTS, self.flush_interval, move |mut events| {
+                //events of a request must be in chronological order
+                events.sort_by_key(|v: &CloudWatchEvent| v.timestamp);
+                let len: usize = events.iter().map(|v| v.message.len()).sum();
+                for events in split(&events) {
+                    if let Err(e) = client.put(events) {
+                        report_error(&LogError::from(format!(
+                            "[fast_log] cloudwatch put {} events fail: {}",
+                            events.len(),
+                            e
+                        )));
+                    }
+                }
+                in_flight.remove(len);
+            })
+        })
+    }
+}
+
+/// split sorted events into requests under the PutLogEvents limits
+fn split(events: &[CloudWatchEvent]) -> Vec<&[CloudWatchEvent]> {
+    let mut batches = vec![];
+    let mut start = 0;
+    let mut bytes = 0;
+    for (i, x) in events.iter().enumerate() {
+        let size = x.message.len() + EVENT_OVERHEAD;
+        if i > start
+            && (i - start >= MAX_EVENTS
+                || bytes + size > MAX_BATCH_BYTES
+                || x.timestamp - events[start].timestamp > MAX_SPAN_MILLIS)
+        {
+            batches.push(&events[start..i]);
+            start = i;
+            bytes = 0;
+        }
+        bytes += size;
+    }
+    if start < events.len() {
+        batches.push(&events[start..]);
+    }
+    batches
+}
+
+struct Client {
+    http: Result<Http, LogError>,
+    url: String,
+    region: String,
+    credentials: Credentials,
+    group: String,
+    stream: String,
+    retries: u32,
+    backoff: Duration,
+    /// create the group and stream before the next request
+    create: bool,
+    sequence_token: Option<String>,
+}
+
+impl Client {
+    /// call an action of the Logs_20140328 api
+    fn call(&self, action: &str, body: &str) -> Result<Vec<u8>, HttpError> {
+        let http = self
+            .http
+            .as_ref()
+            .map_err(|e| HttpError::Fatal(format!("http client fail: {}", e)))?;
+        let amz_date = DateTime::utc().format("YYYYMMDDThhmmssZ");
+        let payload_hash = payload_hash(body.as_bytes());
+        let mut headers = vec![
+            ("content-type", "application/x-amz-json-1.1".to_string()),
+            ("host", endpoint_host(&self.url)),
+            ("x-amz-date", amz_date.clone()),
+        ];
+        if let Some(token) = &self.credentials.session_token {
+            headers.push(("x-amz-security-token", token.clone()));
+        }
+        headers.push(("x-amz-target", format!("Logs_20140328.{}", action)));
+        let authorization = sigv4::authorization(
+            &self.credentials,
+            &self.region,
+            "logs",
+            "POST",
+            "/",
+            &headers,
+            &payload_hash,
+            &amz_date,
+        );
+        let mut send_headers: Vec<(String, String)> = headers
+            .into_iter()
+            .filter(|(k, _)| *k != "host")
+            .map(|(k, v)| (k.to_string(), v))
+            .collect();
+        send_headers.push(("authorization".to_string(), authorization));
+        http.send("POST", &format!("{}/", self.url), &send_headers, body.as_bytes())
+    }
+
+    /// create the log group and stream, existing ones are kept
+    fn create(&mut self) -> Result<(), LogError> {
+        let group = format!("{{\"logGroupName\":\"{}\"}}", escape_json(&self.group));
+        let stream = format!(
+            "{{\"logGroupName\":\"{}\",\"logStreamName\":\"{}\"}}",
+            escape_json(&self.group),
+            escape_json(&self.stream)
+        );
+        for (action, body) in [("CreateLogGroup", group), ("CreateLogStream", stream)] {
+            match self.call(action, &body) {
+                Ok(_) => {}
+                Err(HttpError::Fatal(e)) if e.contains("ResourceAlreadyExistsException") => {}
+                Err(HttpError::Retry(e)) | Err(HttpError::Fatal(e)) => {
+                    return Err(LogError::from(format!("{} fail: {}", action, e)));
+                }
+            }
+        }
+        self.create = false;
+        self.sequence_token = None;
+        Ok(())
+    }
+
+    fn put(&mut self, events: &[CloudWatchEvent]) -> Result<(), LogError> {
+        let mut backoff = self.backoff;
+        let mut attempt = 0;
+        loop {
+            let step = match self.create {
+                true => self.create().map(|_| Step::Again)?,
+                false => {
+                    let body = put_body(
+                        &self.group,
+                        &self.stream,
+                        events,
+                        self.sequence_token.as_deref(),
+                    );
+                    match self.call("PutLogEvents", &body) {
+                        Ok(data) => {
+                            self.sequence_token = json_str(&data, "nextSequenceToken");
+                            Step::Done
+                        }
+                        Err(e) => self.step(e)?,
+                    }
+                }
+            };
+            match step {
+                Step::Done => return Ok(()),
+                Step::Again => {}
+                Step::Retry(e) => {
+                    if attempt >= self.retries {
+                        return Err(LogError::from(e));
+                    }
+                    std::thread::sleep(backoff);
+                    backoff *= 2;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    /// what to do after a failed PutLogEvents
+    fn step(&mut self, e: HttpError) -> Result<Step, LogError> {
+        match e {
+            HttpError::Retry(e) => Ok(Step::Retry(e)),
+            HttpError::Fatal(e) if e.contains("ThrottlingException") => Ok(Step::Retry(e)),
+            HttpError::Fatal(e) if e.contains("InvalidSequenceTokenException") => {
+                let expected = json_str(e.as_bytes(), "expectedSequenceToken");
+                if expected.is_none() || expected == self.sequence_token {
+                    return Err(LogError::from(e));
+                }
+                self.sequence_token = expected;
+                Ok(Step::Again)
+            }
+            HttpError::Fatal(e) if e.contains("DataAlreadyAcceptedException") => {
+                //the events were stored by a request that seemed to fail
+                self.sequence_token = json_str(e.as_bytes(), "expectedSequenceToken");
+                Ok(Step::Done)
+            }
+            HttpError::Fatal(e) if e.contains("ResourceNotFoundException") => {
+                //the group or stream was deleted
+                self.create = true;
+                Ok(Step::Retry(e))
+            }
+            HttpError::Fatal(e) => Err(LogError::from(e)),
+        }
+    }
+}
+
+enum Step {
+    Done,
+    /// send again at once, with the expected token or after the stream was created
+    Again,
+    /// send again after backoff
+    Retry(String),
+}
+
+/// the json body of a PutLogEvents request
+fn put_body(group: &str, stream: &str, events: &[CloudWatchEvent], token: Option<&str>) -> String {
+    let len: usize = events.iter().map(|v| v.message.len() + 40).sum();
+    let mut body = String::with_capacity(len + 128);
+    body.push_str(&format!(
+        "{{\"logGroupName\":\"{}\",\"logStreamName\":\"{}\",",
+        escape_json(group),
+        escape_json(stream)
+    ));
+    if let Some(token) = token {
+        body.push_str(&format!("\"sequenceToken\":\"{}\",", escape_json(token)));
+    }
+    body.push_str("\"logEvents\":[");
+    for (i, x) in events.iter().enumerate() {
+        if i != 0 {
+            body.push(',');
+        }
+        body.push_str(&format!(
+            "{{\"timestamp\":{},\"message\":\"{}\"}}",
+            x.timestamp,
+            escape_json(&x.message)
+        ));
+    }
+    body.push_str("]}");
+    body
+}
+
+/// the string value of `key` in a flat json object
+fn json_str(data: &[u8], key: &str) -> Option<String> {
+    let data = String::from_utf8_lossy(data);
+    let pattern = format!("\"{}\":", key);
+    let start = data.find(&pattern)? + pattern.len();
+    let rest = data[start..].trim_start().strip_prefix('"')?;
+    let mut value = String::new();
+    let mut chars = rest.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return Some(value),
+            '\\' => value.push(chars.next()?),
+            c => value.push(c),
+        }
+    }
+    None
+}
+
+/// cut a message over the event limit at a char boundary
+fn limit(mut message: String) -> String {
+    if message.len() > MAX_EVENT_BYTES {
+        let mut end = MAX_EVENT_BYTES;
+        while !message.is_char_boundary(end) {
+            end -= 1;
+        }
+        message.truncate(end);
+    }
+    message
+}
+
+impl LogAppender for CloudWatchAppender {
+    fn do_logs(&self, records: &[FastLogRecord]) {
+        let sender = self.sender();
+        for x in records {
+            match &x.command {
+                Command::CommandRecord => {
+                    let message = limit(x.formated.trim_end_matches('\n').to_string());
+                    if !self
+                        .in_flight
+                        .add(message.len(), self.options.max_in_flight, "cloudwatch")
+                    {
+                        continue;
+                    }
+                    let _ = sender.send(BatchMsg::Item(CloudWatchEvent {
+                        timestamp: x
+                            .now
+                            .duration_since(UNIX_EPOCH)
+                            .unwrap_or_default()
+                            .as_millis() as i64,
+                        message,
+                    }));
+                }
+                Command::CommandExit => flush_wait(sender),
+                Command::CommandAddAppender(_) | Command::CommandRemoveAppender(_) => {}
+                Command::CommandRotate => {}
+                Command::CommandFlush(w) => {
+                    let _ = sender.send(BatchMsg::Flush(w.clone()));
+                }
+            }
+        }
+    }
+}
@@ -1,7 +1,107 @@
-use crate::appender::{FastLogRecord, LogAppender};
+use crate::appender::{Command, FastLogRecord, LogAppender};
+use log::Level;
+use std::io::IsTerminal;
 
-/// only write append into console
-pub struct ConsoleAppender {}
+/// when ConsoleAppender colors the output by level
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum ColorMode {
+    /// plain text
+    #[default]
+    Never,
+    /// color only if the stream is a terminal and env `NO_COLOR` is not set
+    Auto,
+    /// always color, even if the output is redirected
+    Always,
+}
+
+/// where ConsoleAppender writes records
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum ConsoleStream {
+    #[default]
+    Stdout,
+    Stderr,
+    /// ERROR and WARN to stderr, other levels to stdout
+    ByLevel,
+}
+
+impl ConsoleStream {
+    /// true if records of this level are written to stderr
+    pub fn is_stderr(&self, level: Level) -> bool {
+        match self {
+            ConsoleStream::Stdout => false,
+            ConsoleStream::Stderr => true,
+            ConsoleStream::ByLevel => level <= Level::Warn,
+        }
+    }
+}
+
+/// how a stream is colored
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+enum Painter {
+    #[default]
+    Plain,
+    /// ansi escape codes
+    Ansi,
+    /// the console api of windows consoles without virtual terminal processing
+    #[cfg_attr(not(windows), allow(dead_code))]
+    Console,
+}
+
+/// only write append into console.
+/// build it with `ConsoleAppender::new()` or `Default`, the `ConsoleAppender {}` literal of 1.6 no longer compiles since 1.7
+#[derive(Default)]
+pub struct ConsoleAppender {
+    color: Painter,
+    color_stderr: Painter,
+    stream: ConsoleStream,
+}
+
+impl ConsoleAppender {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// write all records to stderr
+    pub fn stderr() -> Self {
+        Self::new().stream(ConsoleStream::Stderr)
+    }
+
+    /// set the output stream, default ConsoleStream::Stdout
+    pub fn stream(mut self, stream: ConsoleStream) -> Self {
+        self.stream = stream;
+        self
+    }
+
+    /// color(true) is ColorMode::Auto, color(false) is ColorMode::Never
+    pub fn color(self, color: bool) -> Self {
+        if color {
+            self.color_mode(ColorMode::Auto)
+        } else {
+            self.color_mode(ColorMode::Never)
+        }
+    }
+
+    /// ERROR red, WARN yellow, INFO green, DEBUG/TRACE dim.
+    /// on windows virtual terminal processing is enabled, old consoles are colored by SetConsoleTextAttribute
+    pub fn color_mode(mut self, mode: ColorMode) -> Self {
+        let auto = std::env::var_os("NO_COLOR").is_none();
+        let (color, color_stderr) = match mode {
+            ColorMode::Never => (false, false),
+            ColorMode::Always => (true, true),
+            ColorMode::Auto => (
+                auto && std::io::stdout().is_terminal(),
+                auto && std::io::stderr().is_terminal(),
+            ),
+        };
+        let painter = |color: bool, stderr: bool| match color {
+            true => sys::painter(stderr),
+            false => Painter::Plain,
+        };
+        self.color = painter(color, false);
+        self.color_stderr = painter(color_stderr, true);
+        self
+    }
+}
 
 impl LogAppender for ConsoleAppender {
     fn do_logs(&self, records: &[FastLogRecord]) {
@@ -9,9 +109,171 @@ impl LogAppender for ConsoleAppender {
             return;
         }
         let mut buffer = String::with_capacity(records.len());
+        let mut buffer_err = String::new();
         for x in records {
-            buffer.push_str(&x.formated);
+            let stderr = self.stream.is_stderr(x.level);
+            let (buffer, painter) = if stderr {
+                (&mut buffer_err, self.color_stderr)
+            } else {
+                (&mut buffer, self.color)
+            };
+            match painter {
+                Painter::Ansi if x.command == Command::CommandRecord => {
+                    push_colored(buffer, x.level, &x.formated);
+                }
+                Painter::Console if x.command == Command::CommandRecord => {
+                    print_buffer(stderr, buffer);
+                    sys::print_console(stderr, x.level, &x.formated);
+                }
+                _ => buffer.push_str(&x.formated),
+            }
         }
+        print_buffer(false, &mut buffer);
+        print_buffer(true, &mut buffer_err);
+    }
+}
+
+fn print_buffer(stderr: bool, buffer: &mut String) {
+    if buffer.is_empty() {
+        return;
+    }
+    if stderr {
+        eprint!("{}", buffer);
+    } else {
         print!("{}", buffer);
     }
+    buffer.clear();
+}
+
+fn push_colored(buffer: &mut String, level: Level, formated: &str) {
+    let color = match level {
+        Level::Error => "\x1b[31m",
+        Level::Warn => "\x1b[33m",
+        Level::Info => "\x1b[32m",
+        Level::Debug | Level::Trace => "\x1b[2m",
+    };
+    let line = formated.trim_end_matches('\n');
+    buffer.push_str(color);
+    buffer.push_str(line);
+    buffer.push_str("\x1b[0m");
+    buffer.push_str(&formated[line.len()..]);
+}
+
+#[cfg(windows)]
+mod sys {
+    use super::Painter;
+    use log::Level;
+    use std::ffi::c_void;
+    use std::io::Write;
+
+    const STD_OUTPUT_HANDLE: u32 = -11i32 as u32;
+    const STD_ERROR_HANDLE: u32 = -12i32 as u32;
+    const ENABLE_VIRTUAL_TERMINAL_PROCESSING: u32 = 0x0004;
+    const FOREGROUND_GREEN: u16 = 0x0002;
+    const FOREGROUND_RED: u16 = 0x0004;
+    const FOREGROUND_INTENSITY: u16 = 0x0008;
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct Coord {
+        x: i16,
+        y: i16,
+    }
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct SmallRect {
+        left: i16,
+        top: i16,
+        right: i16,
+        bottom: i16,
+    }
+
+    #[repr(C)]
+    #[derive(Default)]
+    struct ScreenBufferInfo {
+        size: Coord,
+        cursor: Coord,
+        attributes: u16,
+        window: SmallRect,
+        max_size: Coord,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetStdHandle(id: u32) -> *mut c_void;
+        fn GetConsoleMode(console: *mut c_void, mode: *mut u32) -> i32;
+        fn SetConsoleMode(console: *mut c_void, mode: u32) -> i32;
+        fn GetConsoleScreenBufferInfo(console: *mut c_void, info: *mut ScreenBufferInfo) -> i32;
+        fn SetConsoleTextAttribute(console: *mut c_void, attributes: u16) -> i32;
+    }
+
+    fn handle(stderr: bool) -> *mut c_void {
+        unsafe {
+            GetStdHandle(match stderr {
+                true => STD_ERROR_HANDLE,
+                false => STD_OUTPUT_HANDLE,
+            })
+        }
+    }
+
+    /// enable virtual terminal processing, or use the console api if the console is too old.
+    /// streams which are not consoles(redirected, mintty) get ansi codes
+    pub fn painter(stderr: bool) -> Painter {
+        let console = handle(stderr);
+        let mut mode = 0;
+        unsafe {
+            if GetConsoleMode(console, &mut mode) == 0 {
+                return Painter::Ansi;
+            }
+            if mode & ENABLE_VIRTUAL_TERMINAL_PROCESSING != 0
+                || SetConsoleMode(console, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING) != 0
+            {
+                return Painter::Ansi;
+            }
+        }
+        Painter::Console
+    }
+
+    /// print a record colored by SetConsoleTextAttribute, the background is kept
+    pub fn print_console(stderr: bool, level: Level, formated: &str) {
+        let console = handle(stderr);
+        let mut info = ScreenBufferInfo::default();
+        let mut out: Box<dyn Write> = match stderr {
+            true => Box::new(std::io::stderr().lock()),
+            false => Box::new(std::io::stdout().lock()),
+        };
+        if unsafe { GetConsoleScreenBufferInfo(console, &mut info) } == 0 {
+            let _ = out.write_all(formated.as_bytes());
+            return;
+        }
+        let color = match level {
+            Level::Error => FOREGROUND_RED | FOREGROUND_INTENSITY,
+            Level::Warn => FOREGROUND_RED | FOREGROUND_GREEN | FOREGROUND_INTENSITY,
+            Level::Info => FOREGROUND_GREEN | FOREGROUND_INTENSITY,
+            Level::Debug | Level::Trace => FOREGROUND_INTENSITY,
+        };
+        let line = formated.trim_end_matches('\n');
+        let _ = out.flush();
+        unsafe { SetConsoleTextAttribute(console, (info.attributes & 0xfff0) | color) };
+        let _ = out.write_all(line.as_bytes());
+        let _ = out.flush();
+        unsafe { SetConsoleTextAttribute(console, info.attributes) };
+        let _ = out.write_all(formated[line.len()..].as_bytes());
+        let _ = out.flush();
+    }
+}
+
+#[cfg(not(windows))]
+mod sys {
+    use super::Painter;
+    use log::Level;
+
+    pub fn painter(_stderr: bool) -> Painter {
+        Painter::Ansi
+    }
+
+    pub fn print_console(stderr: bool, _level: Level, formated: &str) {
+        super::print_buffer(stderr, &mut formated.to_string());
+    }
 }
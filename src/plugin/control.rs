@@ -0,0 +1,96 @@
+use crate::error::LogError;
+use log::LevelFilter;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// listen on a unix socket for text commands to the global logger, one per line,
+/// each answered by one line. see Config::control_socket:
+/// * `level` the level, `level debug`(or `level=debug`) sets it
+/// * `flush` flush and wait up to 5s
+/// * `rotate` roll the log files
+/// * `stats` the counters of `fast_log::metrics()`, like `received=10 dropped=0 queue_len=0 ...`
+///
+/// a stale socket file is replaced. the socket is made 0o600, so only the user of the process can connect
+/// ```sh
+/// echo level=trace | nc -U /run/app/log.sock
+/// ```
+pub fn listen(path: &str) -> Result<(), LogError> {
+    if let Ok(meta) = std::fs::symlink_metadata(path) {
+        if meta.file_type().is_socket() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+    let listener = UnixListener::bind(path)
+        .map_err(|e| LogError::from(format!("[fast_log] control bind {} fail: {}", path, e)))?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    crate::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    crate::spawn(move || {
+                        let _ = serve(stream);
+                    });
+                }
+                Err(_) => std::thread::sleep(Duration::from_millis(100)),
+            }
+        }
+    });
+    Ok(())
+}
+
+fn serve(stream: UnixStream) -> std::io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        writeln!(writer, "{}", command(line.trim()))?;
+    }
+    Ok(())
+}
+
+/// the answer of a command
+fn command(line: &str) -> String {
+    let (name, arg) = match line.split_once(|c: char| c == '=' || c.is_whitespace()) {
+        Some((name, arg)) => (name, arg.trim()),
+        None => (line, ""),
+    };
+    match (name, arg) {
+        ("level", "") => crate::get_level().as_str().to_lowercase(),
+        ("level", level) => match LevelFilter::from_str(level) {
+            Ok(level) => {
+                crate::set_level(level);
+                "ok".to_string()
+            }
+            Err(_) => format!("error: unknown level {}", level),
+        },
+        ("flush", "") => result(crate::flush_sync(Duration::from_secs(5))),
+        ("rotate", "") => result(crate::rotate()),
+        ("stats", "") => {
+            let m = crate::metrics();
+            let mut s = format!(
+                "received={} dropped={} queue_len={} rotations={} write_errors={} seq={}",
+                m.received, m.dropped, m.queue_len, m.rotations, m.write_errors, m.seq
+            );
+            for (name, bytes) in &m.appender_bytes {
+                s.push_str(&format!(" bytes[{}]={}", name, bytes));
+            }
+            s
+        }
+        _ => format!(
+            "error: unknown command {}, use level [LEVEL] | flush | rotate | stats",
+            line
+        ),
+    }
+}
+
+fn result(r: Result<(), LogError>) -> String {
+    match r {
+        Ok(_) => "ok".to_string(),
+        Err(e) => format!("error: {}", e),
+    }
+}
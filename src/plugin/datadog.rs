@@ -0,0 +1,266 @@
+use crate::appender::{Command, FastLogRecord, LogAppender};
+use crate::error::LogError;
+use crate::formats::escape_json;
+use crate::plugin::batch::{flush_wait, spawn_batch, BatchMsg};
+use crate::plugin::http::{Http, HttpOptions, InFlight};
+use crate::{report_error, Sender};
+use once_cell::sync::OnceCell;
+use std::time::{Duration, UNIX_EPOCH};
+
+/// intake limits: entries of a payload, bytes of a payload and bytes of one message
+const MAX_ENTRIES: usize = 1000;
+const MAX_PAYLOAD_BYTES: usize = 5 * 1024 * 1024;
+const MAX_MESSAGE_BYTES: usize = 1024 * 1024 - 1024;
+
+/// you need enable fast_log = { ... ,features=["http"]}
+/// POST records as json arrays to the Datadog logs intake(`/api/v2/logs`) over https, without a local agent.
+/// every entry has `message` `status` `service` `ddsource` `ddtags` `hostname` `timestamp` `logger.name`,
+/// `trace_id` `span_id` if set and the key_values as attributes.
+/// batches are sent at 1000 entries(or 5MB) and at least every `flush_interval`,
+/// network errors, 429 and 5xx are retried with backoff. entries waiting to be sent are limited by
+/// `max_in_flight` bytes, records logged while it is full are dropped
+/// ```rust,no_run
+/// use fast_log::Config;
+/// use fast_log::plugin::datadog::DatadogAppender;
+/// fast_log::init(Config::new().console().add_appender(
+///     DatadogAppender::new(&std::env::var("DD_API_KEY").unwrap())
+///         .site("datadoghq.eu")
+///         .service("web")
+///         .tag("env", "prod"),
+/// ))
+/// .unwrap();
+/// ```
+pub struct DatadogAppender {
+    api_key: String,
+    url: String,
+    service: String,
+    source: String,
+    hostname: Option<String>,
+    tags: Vec<String>,
+    batch_size: usize,
+    flush_interval: Duration,
+    options: HttpOptions,
+    in_flight: InFlight,
+    sender: OnceCell<Sender<BatchMsg<String>>>,
+}
+
+impl DatadogAppender {
+    pub fn new(api_key: &str) -> Self {
+        Self {
+            api_key: api_key.to_string(),
+            url: intake_url("datadoghq.com"),
+            service: String::new(),
+            source: "rust".to_string(),
+            hostname: None,
+            tags: vec![],
+            batch_size: MAX_ENTRIES,
+            flush_interval: Duration::from_secs(1),
+            options: HttpOptions::default(),
+            in_flight: InFlight::default(),
+            sender: OnceCell::new(),
+        }
+    }
+
+    /// the Datadog site, default `datadoghq.com`. for example `datadoghq.eu` `us3.datadoghq.com`
+    pub fn site(mut self, site: &str) -> Self {
+        self.url = intake_url(site);
+        self
+    }
+
+    /// the full intake url instead of the site, for example a proxy
+    pub fn endpoint(mut self, url: &str) -> Self {
+        self.url = url.to_string();
+        self
+    }
+
+    /// the `service` of the entries
+    pub fn service(mut self, service: &str) -> Self {
+        self.service = service.to_string();
+        self
+    }
+
+    /// the `ddsource` of the entries, default `rust`
+    pub fn source(mut self, source: &str) -> Self {
+        self.source = source.to_string();
+        self
+    }
+
+    /// the `hostname` of the entries, default the hostname of the record
+    pub fn hostname(mut self, hostname: &str) -> Self {
+        self.hostname = Some(hostname.to_string());
+        self
+    }
+
+    /// add `key:value` to the `ddtags` of the entries, for example `env:prod`
+    pub fn tag(mut self, key: &str, value: &str) -> Self {
+        self.tags.push(format!("{}:{}", key, value));
+        self
+    }
+
+    /// send when `size` records collected, at most 1000. default 1000
+    pub fn batch_size(mut self, size: usize) -> Self {
+        self.batch_size = size.clamp(1, MAX_ENTRIES);
+        self
+    }
+
+    /// send at least every `interval`, default 1s
+    pub fn flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = interval;
+        self
+    }
+
+    /// retry failed batches(network errors, 429 and 5xx) `retries` times, default 3
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.options.retries = retries;
+        self
+    }
+
+    /// the wait before the first retry, doubled after each retry. default 500ms
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.options.backoff = backoff;
+        self
+    }
+
+    /// timeout of one request, default 10s
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.options.timeout = timeout;
+        self
+    }
+
+    /// max bytes of entries waiting to be sent(queued or being retried), default 16MB
+    pub fn max_in_flight(mut self, bytes: usize) -> Self {
+        self.options.max_in_flight = bytes.max(1);
+        self
+    }
+
+    /// gzip the body, `Content-Encoding: gzip`
+    #[cfg(feature = "gzip")]
+    pub fn gzip(mut self, gzip: bool) -> Self {
+        self.options.gzip = gzip;
+        self
+    }
+
+    /// the json object of one record
+    fn entry(&self, x: &FastLogRecord) -> String {
+        let mut message = x.args.as_str();
+        if message.len() > MAX_MESSAGE_BYTES {
+            let mut end = MAX_MESSAGE_BYTES;
+            while !message.is_char_boundary(end) {
+                end -= 1;
+            }
+            message = &message[..end];
+        }
+        let timestamp = x.now.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let mut entry = format!(
+            "{{\"message\":\"{}\",\"status\":\"{}\",\"service\":\"{}\",\"ddsource\":\"{}\",\"ddtags\":\"{}\",\"hostname\":\"{}\",\"timestamp\":{},\"logger.name\":\"{}\"",
+            escape_json(message),
+            x.level.as_str().to_lowercase(),
+            escape_json(&self.service),
+            escape_json(&self.source),
+            escape_json(&self.tags.join(",")),
+            escape_json(self.hostname.as_deref().unwrap_or(&x.hostname)),
+            timestamp.as_millis(),
+            escape_json(&x.target)
+        );
+        if let Some(trace_id) = &x.trace_id {
+            entry.push_str(&format!(",\"trace_id\":\"{}\"", escape_json(trace_id)));
+        }
+        if let Some(span_id) = &x.span_id {
+            entry.push_str(&format!(",\"span_id\":\"{}\"", escape_json(span_id)));
+        }
+        for (k, v) in &x.key_values {
+            entry.push_str(&format!(
+                ",\"{}\":\"{}\"",
+                escape_json(k),
+                escape_json(v)
+            ));
+        }
+        entry.push('}');
+        entry
+    }
+
+    fn sender(&self) -> &Sender<BatchMsg<String>> {
+        self.sender.get_or_init(|| {
+            let url = self.url.clone();
+            let encode = Http::gzip_encoder(&self.options);
+            let mut headers = vec![("DD-API-KEY".to_string(), self.api_key.clone())];
+            headers.push(("Content-Type".to_string(), "application/json".to_string()));
+            headers.extend(self.options.content_encoding());
+            let in_flight = self.in_flight.clone();
+            let http = Http::with_options(&self.options);
+            spawn_batch(
+                self.batch_size,
+                self.flush_interval,
+                move |entries: Vec<String>| {
+                    let len: usize = entries.iter().map(|v| v.len()).sum();
+                    for body in payloads(&entries) {
+                        let result = match &http {
+                            Ok(http) => http.send_retry("POST", &url, &headers, &encode(body)),
+                            Err(e) => Err(LogError::from(format!(
+                                "[fast_log] http client fail: {}",
+                                e
+                            ))),
+                        };
+                        if let Err(e) = result {
+                            report_error(&LogError::from(format!(
+                                "[fast_log] datadog send {} records fail: {}",
+                                entries.len(),
+                                e
+                            )));
+                        }
+                    }
+                    in_flight.remove(len);
+                },
+            )
+        })
+    }
+}
+
+fn intake_url(site: &str) -> String {
+    format!("https://http-intake.logs.{}/api/v2/logs", site)
+}
+
+/// join the entries into json arrays under the payload limit
+fn payloads(entries: &[String]) -> Vec<Vec<u8>> {
+    let mut payloads = vec![];
+    let mut body = Vec::with_capacity(entries.iter().map(|v| v.len() + 1).sum::<usize>() + 2);
+    for entry in entries {
+        if body.len() > 1 && body.len() + entry.len() + 2 > MAX_PAYLOAD_BYTES {
+            body.push(b']');
+            payloads.push(std::mem::take(&mut body));
+        }
+        body.push(if body.is_empty() { b'[' } else { b',' });
+        body.extend_from_slice(entry.as_bytes());
+    }
+    if !body.is_empty() {
+        body.push(b']');
+        payloads.push(body);
+    }
+    payloads
+}
+
+impl LogAppender for DatadogAppender {
+    fn do_logs(&self, records: &[FastLogRecord]) {
+        let sender = self.sender();
+        for x in records {
+            match &x.command {
+                Command::CommandRecord => {
+                    let entry = self.entry(x);
+                    if !self
+                        .in_flight
+                        .add(entry.len(), self.options.max_in_flight, "datadog")
+                    {
+                        continue;
+                    }
+                    let _ = sender.send(BatchMsg::Item(entry));
+                }
+                Command::CommandExit => flush_wait(sender),
+                Command::CommandAddAppender(_) | Command::CommandRemoveAppender(_) => {}
+                Command::CommandRotate => {}
+                Command::CommandFlush(w) => {
+                    let _ = sender.send(BatchMsg::Flush(w.clone()));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use crate::appender::{Command, FastLogRecord, LogAppender, RecordFormat};
+use crate::FastLogFormat;
+use std::cell::{Cell, RefCell};
+
+/// how DedupAppender decides two records are the same
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum DedupCompare {
+    /// same level, target and args
+    #[default]
+    Args,
+    /// same formatted output
+    Formated,
+}
+
+/// collapse runs of identical records like syslogd: the first record is written,
+/// the repeats are counted and replaced by one `"last message repeated N times"` record
+/// when a different record, a flush or exit arrives.
+/// for example:
+/// ```rust,no_run
+/// use fast_log::Config;
+/// use fast_log::plugin::console::ConsoleAppender;
+/// use fast_log::plugin::dedup::DedupAppender;
+/// fast_log::init(Config::new().add_appender(DedupAppender::new(ConsoleAppender::new()))).unwrap();
+/// ```
+pub struct DedupAppender<A: LogAppender> {
+    appender: A,
+    compare: DedupCompare,
+    last: RefCell<Option<FastLogRecord>>,
+    repeats: Cell<u64>,
+}
+
+impl<A: LogAppender> DedupAppender<A> {
+    pub fn new(appender: A) -> Self {
+        Self {
+            appender,
+            compare: DedupCompare::default(),
+            last: RefCell::new(None),
+            repeats: Cell::new(0),
+        }
+    }
+
+    /// default DedupCompare::Args
+    pub fn compare(mut self, compare: DedupCompare) -> Self {
+        self.compare = compare;
+        self
+    }
+
+    fn same(&self, last: &FastLogRecord, record: &FastLogRecord) -> bool {
+        match self.compare {
+            DedupCompare::Args => {
+                last.level == record.level
+                    && last.target == record.target
+                    && last.args == record.args
+            }
+            DedupCompare::Formated => last.formated == record.formated,
+        }
+    }
+
+    /// push the repeat record of the last run, if any
+    fn push_repeated(&self, records: &mut Vec<FastLogRecord>) {
+        let repeats = self.repeats.replace(0);
+        if repeats == 0 {
+            return;
+        }
+        if let Some(last) = self.last.borrow().as_ref() {
+            let mut record = last.clone();
+            record.args = format!("last message repeated {} times", repeats);
+            record.formated.clear();
+            crate::fast_log::with_logger(|logger| match logger.cfg.get() {
+                Some(cfg) => {
+                    record.now = cfg.clock.now();
+                    cfg.format.do_format(&mut record)
+                }
+                None => {
+                    record.now = std::time::SystemTime::now();
+                    FastLogFormat::new().do_format(&mut record)
+                }
+            });
+            records.push(record);
+        }
+    }
+}
+
+impl<A: LogAppender> LogAppender for DedupAppender<A> {
+    fn do_logs(&self, records: &[FastLogRecord]) {
+        let mut out = Vec::with_capacity(records.len());
+        for x in records {
+            if x.command == Command::CommandRecord {
+                let same = match self.last.borrow().as_ref() {
+                    None => false,
+                    Some(last) => self.same(last, x),
+                };
+                if same {
+                    self.repeats.set(self.repeats.get() + 1);
+                    continue;
+                }
+                self.push_repeated(&mut out);
+                *self.last.borrow_mut() = Some(x.clone());
+            } else {
+                //a flush ends the run
+                self.push_repeated(&mut out);
+                *self.last.borrow_mut() = None;
+            }
+            out.push(x.clone());
+        }
+        if !out.is_empty() {
+            self.appender.do_logs(&out);
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use crate::appender::{Command, FastLogRecord, LogAppender};
+use crate::error::LogError;
+use crate::fast_log::hostname;
+use crate::plugin::base64;
+use crate::plugin::batch::{flush_wait, spawn_batch, BatchMsg, RateLimiter};
+use crate::{report_error, Sender};
+use log::Level;
+use once_cell::sync::OnceCell;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::time::{Duration, SystemTime};
+
+/// smtp server and mail addresses
+#[derive(Clone, Debug)]
+pub struct SmtpConfig {
+    /// smtp server, for example "127.0.0.1:25"
+    pub addr: String,
+    pub from: String,
+    pub to: Vec<String>,
+    /// AUTH PLAIN user and password
+    pub credentials: Option<(String, String)>,
+    pub subject: String,
+    pub timeout: Duration,
+}
+
+/// email Error records to an address, as digests and with a rate limit
+/// for example:
+/// ```rust,no_run
+/// use fast_log::Config;
+/// use fast_log::plugin::email::EmailAppender;
+/// use std::time::Duration;
+/// fast_log::init(Config::new().console().add_appender(
+///     EmailAppender::new("127.0.0.1:25", "app@example.com", &["ops@example.com"])
+///         .digest_size(50)
+///         .rate_limit(10, Duration::from_secs(3600)),
+/// ))
+/// .unwrap();
+/// ```
+pub struct EmailAppender {
+    smtp: SmtpConfig,
+    level: Level,
+    digest_size: usize,
+    digest_interval: Duration,
+    max_mails: usize,
+    period: Duration,
+    sender: OnceCell<Sender<BatchMsg<String>>>,
+}
+
+impl EmailAppender {
+    pub fn new(addr: &str, from: &str, to: &[&str]) -> Self {
+        Self {
+            smtp: SmtpConfig {
+                addr: addr.to_string(),
+                from: from.to_string(),
+                to: to.iter().map(|v| v.to_string()).collect(),
+                credentials: None,
+                subject: "[fast_log] error logs".to_string(),
+                timeout: Duration::from_secs(10),
+            },
+            level: Level::Error,
+            digest_size: 50,
+            digest_interval: Duration::from_secs(60),
+            max_mails: 10,
+            period: Duration::from_secs(3600),
+            sender: OnceCell::new(),
+        }
+    }
+
+    /// lowest level mailed, default Level::Error
+    pub fn level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// login with AUTH PLAIN. the connection is not encrypted
+    pub fn credentials(mut self, user: &str, password: &str) -> Self {
+        self.smtp.credentials = Some((user.to_string(), password.to_string()));
+        self
+    }
+
+    /// mail subject, the record count is appended
+    pub fn subject(mut self, subject: &str) -> Self {
+        self.smtp.subject = subject.to_string();
+        self
+    }
+
+    /// max records in one mail, default 50
+    pub fn digest_size(mut self, size: usize) -> Self {
+        self.digest_size = size.max(1);
+        self
+    }
+
+    /// wait at most `interval` collecting records for one mail, default 60s
+    pub fn digest_interval(mut self, interval: Duration) -> Self {
+        self.digest_interval = interval;
+        self
+    }
+
+    /// send at most `max_mails` every `period`, records over the limit are dropped
+    /// and counted in the next mail. default 10 per hour
+    pub fn rate_limit(mut self, max_mails: usize, period: Duration) -> Self {
+        self.max_mails = max_mails;
+        self.period = period;
+        self
+    }
+
+    fn sender(&self) -> &Sender<BatchMsg<String>> {
+        self.sender.get_or_init(|| {
+            let smtp = self.smtp.clone();
+            let mut limiter = RateLimiter::new(self.max_mails, self.period);
+            let mut suppressed = 0;
+            spawn_batch(
+                self.digest_size,
+                self.digest_interval,
+                move |records: Vec<String>| {
+                    if !limiter.allow() {
+                        suppressed += records.len();
+                        return;
+                    }
+                    let mut body = String::new();
+                    if suppressed != 0 {
+                        body.push_str(&format!(
+                            "{} records were not mailed because of the rate limit\n\n",
+                            suppressed
+                        ));
+                    }
+                    for x in &records {
+                        body.push_str(x);
+                    }
+                    let subject = format!("{} ({})", smtp.subject, records.len() + suppressed);
+                    match send_mail(&smtp, &subject, &body) {
+                        Ok(_) => suppressed = 0,
+                        Err(e) => report_error(&LogError::from(format!(
+                            "[fast_log] send mail fail: {}",
+                            e
+                        ))),
+                    }
+                },
+            )
+        })
+    }
+}
+
+impl LogAppender for EmailAppender {
+    fn do_logs(&self, records: &[FastLogRecord]) {
+        let sender = self.sender();
+        for x in records {
+            match x.command {
+                Command::CommandRecord => {
+                    if x.level <= self.level {
+                        let _ = sender.send(BatchMsg::Item(x.formated.clone()));
+                    }
+                }
+                Command::CommandExit => flush_wait(sender),
+                Command::CommandAddAppender(_) | Command::CommandRemoveAppender(_) => {}
+                Command::CommandRotate => {}
+                Command::CommandFlush(ref w) => {
+                    let _ = sender.send(BatchMsg::Flush(w.clone()));
+                }
+            }
+        }
+    }
+}
+
+/// send one plain text mail
+pub fn send_mail(smtp: &SmtpConfig, subject: &str, body: &str) -> Result<(), LogError> {
+    let stream = TcpStream::connect(&smtp.addr)?;
+    stream.set_read_timeout(Some(smtp.timeout))?;
+    stream.set_write_timeout(Some(smtp.timeout))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut stream = stream;
+    let mut cmd = |line: &str, code: &str| -> Result<(), LogError> {
+        if !line.is_empty() {
+            stream.write_all(line.as_bytes())?;
+            stream.write_all(b"\r\n")?;
+        }
+        let reply = read_reply(&mut reader)?;
+        if reply.starts_with(code) {
+            Ok(())
+        } else {
+            Err(LogError::from(format!(
+                "[fast_log] smtp reply: {}",
+                reply.trim_end()
+            )))
+        }
+    };
+    cmd("", "220")?;
+    cmd(&format!("EHLO {}", hostname()), "250")?;
+    if let Some((user, password)) = &smtp.credentials {
+        let token = base64::encode(format!("\0{}\0{}", user, password).as_bytes());
+        cmd(&format!("AUTH PLAIN {}", token), "235")?;
+    }
+    cmd(&format!("MAIL FROM:<{}>", smtp.from), "250")?;
+    for to in &smtp.to {
+        cmd(&format!("RCPT TO:<{}>", to), "250")?;
+    }
+    cmd("DATA", "354")?;
+    let mut data = format!(
+        "From: <{}>\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\nMIME-Version: 1.0\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n",
+        smtp.from,
+        smtp.to
+            .iter()
+            .map(|v| format!("<{}>", v))
+            .collect::<Vec<_>>()
+            .join(", "),
+        subject,
+        rfc2822_date(SystemTime::now())
+    );
+    for line in body.lines() {
+        //dot stuffing
+        if line.starts_with('.') {
+            data.push('.');
+        }
+        data.push_str(line);
+        data.push_str("\r\n");
+    }
+    data.push('.');
+    cmd(&data, "250")?;
+    let _ = cmd("QUIT", "221");
+    Ok(())
+}
+
+/// read a (multi line) smtp reply
+fn read_reply<R: BufRead>(reader: &mut R) -> Result<String, LogError> {
+    let mut reply = String::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(LogError::from("[fast_log] smtp connection closed"));
+        }
+        reply.push_str(&line);
+        if line.as_bytes().get(3) != Some(&b'-') {
+            return Ok(reply);
+        }
+    }
+}
+
+fn rfc2822_date(time: SystemTime) -> String {
+    const DAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let t = fastdate::DateTime::from(time);
+    format!(
+        "{}, {:02} {} {} {:02}:{:02}:{:02} +0000",
+        DAYS[(t.week_day() as usize + 6) % 7],
+        t.day(),
+        MONTHS[(t.mon() as usize + 11) % 12],
+        t.year(),
+        t.hour(),
+        t.minute(),
+        t.sec()
+    )
+}
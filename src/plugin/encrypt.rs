@@ -0,0 +1,166 @@
+use crate::error::LogError;
+use crate::plugin::file_split::{Packed, Packer};
+use aes::Aes256;
+use ctr::cipher::{KeyIvInit, StreamCipher};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+type HmacSha256 = Hmac<Sha256>;
+/// AES-256 in counter mode, the iv is the first counter block(big endian)
+type Aes256Ctr = ctr::Ctr128BE<Aes256>;
+
+const MAGIC: &[u8; 8] = b"FASTLOG1";
+const SALT_LEN: usize = 16;
+const IV_LEN: usize = 16;
+const TAG_LEN: usize = 32;
+const HEADER_LEN: usize = MAGIC.len() + SALT_LEN + IV_LEN;
+/// pbkdf2 rounds of a password, a key file takes one round
+const PASSWORD_ROUNDS: u32 = 100_000;
+
+/// you need enable fast_log = { ... ,features=["encrypt"]}
+/// encrypt the pack of the inner packer(for example ZipPacker) into `<pack>.enc` with AES-256-CTR and HMAC-SHA256,
+/// the keys are derived from a password(pbkdf2) or a key file. the file is
+/// `FASTLOG1 | salt(16) | iv(16) | ciphertext | hmac(32)`, read it with `decrypt`
+/// ```rust,no_run
+/// use fast_log::consts::LogSize;
+/// use fast_log::plugin::encrypt::EncryptedPacker;
+/// use fast_log::plugin::file_split::KeepType;
+/// use fast_log::plugin::packer::LogPacker;
+/// use fast_log::Config;
+/// fast_log::init(Config::new().file_split(
+///     "target/logs/",
+///     LogSize::MB(100),
+///     KeepType::KeepNum(7),
+///     EncryptedPacker::password(LogPacker {}, "secret"),
+/// ))
+/// .unwrap();
+/// ```
+pub struct EncryptedPacker<P: Packer> {
+    inner: P,
+    secret: Vec<u8>,
+    rounds: u32,
+}
+
+impl<P: Packer> EncryptedPacker<P> {
+    /// derive the keys from a password
+    pub fn password(inner: P, password: &str) -> Self {
+        Self {
+            inner,
+            secret: password.as_bytes().to_vec(),
+            rounds: PASSWORD_ROUNDS,
+        }
+    }
+
+    /// read the key from a file, for example 32 random bytes
+    pub fn key_file(inner: P, path: &str) -> Result<Self, LogError> {
+        let secret = std::fs::read(path)?;
+        if secret.is_empty() {
+            return Err(LogError::from(format!(
+                "[fast_log] key file {} is empty",
+                path
+            )));
+        }
+        Ok(Self {
+            inner,
+            secret,
+            rounds: 1,
+        })
+    }
+
+    /// return the (aes key, hmac key) of the salt
+    fn keys(&self, salt: &[u8]) -> ([u8; 32], [u8; 32]) {
+        let mut out = [0u8; 64];
+        pbkdf2::pbkdf2::<HmacSha256>(&self.secret, salt, self.rounds, &mut out);
+        let mut aes_key = [0u8; 32];
+        let mut mac_key = [0u8; 32];
+        aes_key.copy_from_slice(&out[..32]);
+        mac_key.copy_from_slice(&out[32..]);
+        (aes_key, mac_key)
+    }
+
+    /// check and decrypt a file made by this packer
+    pub fn decrypt(&self, path: &Path) -> Result<Vec<u8>, LogError> {
+        let data = std::fs::read(path)?;
+        if data.len() < HEADER_LEN + TAG_LEN || &data[..MAGIC.len()] != MAGIC {
+            return Err(LogError::from("[fast_log] not an encrypted pack"));
+        }
+        let salt = &data[MAGIC.len()..MAGIC.len() + SALT_LEN];
+        let iv = &data[MAGIC.len() + SALT_LEN..HEADER_LEN];
+        let (aes_key, mac_key) = self.keys(salt);
+        let (body, tag) = data.split_at(data.len() - TAG_LEN);
+        let mut mac =
+            <HmacSha256 as Mac>::new_from_slice(&mac_key).expect("hmac accepts any key size");
+        mac.update(body);
+        mac.verify_slice(tag)
+            .map_err(|_| LogError::from("[fast_log] wrong key or the pack is tampered"))?;
+        let mut plain = body[HEADER_LEN..].to_vec();
+        Aes256Ctr::new(&aes_key.into(), iv.into()).apply_keystream(&mut plain);
+        Ok(plain)
+    }
+
+    fn encrypt(&self, src: &Path, dst: &Path) -> Result<(), LogError> {
+        let mut salt = [0u8; SALT_LEN];
+        let mut iv = [0u8; IV_LEN];
+        getrandom::getrandom(&mut salt)
+            .and_then(|_| getrandom::getrandom(&mut iv))
+            .map_err(|e| LogError::from(format!("[fast_log] os random fail: {}", e)))?;
+        let (aes_key, mac_key) = self.keys(&salt);
+        let mut ctr = Aes256Ctr::new(&aes_key.into(), &iv.into());
+        let mut mac =
+            <HmacSha256 as Mac>::new_from_slice(&mac_key).expect("hmac accepts any key size");
+        let mut src = File::open(src)?;
+        let mut out = File::create(dst)?;
+        for part in [&MAGIC[..], &salt, &iv] {
+            mac.update(part);
+            out.write_all(part)?;
+        }
+        let mut buf = vec![0u8; 64 * 1024];
+        loop {
+            let n = src.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            ctr.apply_keystream(&mut buf[..n]);
+            mac.update(&buf[..n]);
+            out.write_all(&buf[..n])?;
+        }
+        out.write_all(&mac.finalize().into_bytes())?;
+        out.flush()?;
+        Ok(())
+    }
+}
+
+impl<P: Packer> Packer for EncryptedPacker<P> {
+    fn pack_name(&self) -> &'static str {
+        "enc"
+    }
+
+    fn do_pack(&self, _log_file: File, log_file_path: &str) -> Result<bool, LogError> {
+        Ok(self.pack(Path::new(log_file_path))? != Packed::Kept)
+    }
+
+    fn pack(&self, log_file: &Path) -> Result<Packed, LogError> {
+        let archive = self.inner.pack(log_file)?.path(log_file).ok_or_else(|| {
+            LogError::from(format!(
+                "[fast_log] the {} packer does not return its archive from Packer::pack",
+                self.inner.pack_name()
+            ))
+        })?;
+        let mut encrypted = archive.as_os_str().to_os_string();
+        encrypted.push(".enc");
+        let encrypted = PathBuf::from(encrypted);
+        self.encrypt(&archive, &encrypted)?;
+        //the log file is removed by the caller
+        if archive != log_file {
+            let _ = std::fs::remove_file(&archive);
+        }
+        Ok(Packed::Archive(encrypted))
+    }
+
+    fn retry(&self) -> i32 {
+        self.inner.retry()
+    }
+}
@@ -1,48 +1,259 @@
 use crate::appender::{Command, FastLogRecord, LogAppender};
 use crate::error::LogError;
-use std::cell::RefCell;
+use crate::plugin::file_lock::FileLock;
+use crate::plugin::file_mode::FileMode;
+use crate::plugin::write_policy::WritePolicy;
+use parking_lot::Mutex;
+use std::cell::Cell;
 use std::fs::{File, OpenOptions};
-use std::io::Write;
+use std::io::{BufWriter, Write};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
 
-/// only write append into file
+/// only write append into file.
+/// writes are buffered and flushed every `flush_interval`(default 500ms) and on log flush/exit.
+/// if the file is deleted or moved(for example by logrotate) a new one is created at the path
 pub struct FileAppender {
-    file: RefCell<File>,
+    path: String,
+    file: Arc<Mutex<Buffer>>,
+    flush_interval: Option<Duration>,
+    flusher: Cell<bool>,
+    lock: Option<FileLock>,
+    mode: FileMode,
+}
+
+/// the write buffer and the records in it not flushed yet
+struct Buffer {
+    writer: BufWriter<File>,
+    /// the number of records in the buffer
+    count: usize,
+    /// their copies, only kept for WritePolicy::Fallback
+    records: Vec<FastLogRecord>,
+    policy: WritePolicy,
+}
+
+impl Buffer {
+    fn new(file: File) -> Self {
+        Self {
+            writer: BufWriter::with_capacity(64 * 1024, file),
+            count: 0,
+            records: vec![],
+            policy: WritePolicy::default(),
+        }
+    }
+
+    /// keep track of a record written to the buffer
+    fn push(&mut self, record: &FastLogRecord) {
+        if record.command != Command::CommandRecord {
+            return;
+        }
+        self.count += 1;
+        if matches!(self.policy, WritePolicy::Fallback(_)) {
+            self.records.push(record.clone());
+        }
+    }
 }
 
 impl FileAppender {
     pub fn new(log_file_path: &str) -> Result<FileAppender, LogError> {
+        Self::with_mode(log_file_path, FileMode::default())
+    }
+
+    /// create the file and missing dirs with the permissions of `mode`, see FileMode
+    pub fn with_mode(log_file_path: &str, mode: FileMode) -> Result<FileAppender, LogError> {
         let log_file_path = log_file_path.replace("\\", "/");
-        if let Some(right) = log_file_path.rfind("/") {
-            let path = &log_file_path[0..right];
-            let _ = std::fs::create_dir_all(path);
-        }
+        create_with_mode(&mode, &log_file_path)?;
+        let file = open(&log_file_path)?;
         Ok(Self {
-            file: RefCell::new(
-                OpenOptions::new()
-                    .create(true)
-                    .append(true)
-                    .open(&log_file_path)?,
-            ),
+            path: log_file_path,
+            file: Arc::new(Mutex::new(Buffer::new(file))),
+            flush_interval: Some(Duration::from_millis(500)),
+            flusher: Cell::new(false),
+            lock: None,
+            mode,
         })
     }
+
+    /// take an OS advisory lock(see FileLock) around every batch, so processes sharing the file
+    /// do not interleave partial lines. batches are written through when locked
+    pub fn lock(mut self, lock: bool) -> Self {
+        self.lock = match lock {
+            true => Some(FileLock::of(&self.path)),
+            false => None,
+        };
+        self
+    }
+
+    /// what to do when a write fails, default WritePolicy::Drop.
+    /// with a flush_interval the buffered records go through it when the buffer is full,
+    /// flushed by the interval or by the logger
+    pub fn write_policy(self, policy: WritePolicy) -> Self {
+        self.file.lock().policy = policy;
+        self
+    }
+
+    /// flush buffered records every `interval`.
+    /// None writes through on every batch, for example for audit logs
+    pub fn flush_interval(mut self, interval: Option<Duration>) -> Self {
+        self.flush_interval = interval;
+        self
+    }
+
+    /// spawn a thread flush the buffer periodically, it exits when the appender is dropped
+    fn spawn_flusher(&self, interval: Duration) {
+        if self.flusher.replace(true) {
+            return;
+        }
+        let file: Weak<Mutex<Buffer>> = Arc::downgrade(&self.file);
+        let path = self.path.clone();
+        crate::fast_log::spawn_in_logger(move || loop {
+            std::thread::sleep(interval);
+            match file.upgrade() {
+                None => break,
+                Some(file) => flush(&path, &mut file.lock()),
+            }
+        });
+    }
+}
+
+/// flush the buffer, a failure goes through the WritePolicy and the buffer is dropped if it is not written
+fn flush(path: &str, buffer: &mut Buffer) {
+    if let Err(e) = buffer.writer.flush() {
+        let Buffer {
+            writer,
+            count,
+            records,
+            policy,
+        } = buffer;
+        if !policy.on_buffer_error(path, records, *count, e, || writer.flush()) {
+            discard(writer);
+        }
+    }
+    buffer.count = 0;
+    buffer.records.clear();
+}
+
+fn open(path: &str) -> std::io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+/// drop the buffer after a failed write, so it is not written again(or twice, after a fallback)
+fn discard(log_file: &mut BufWriter<File>) {
+    if let Ok(file) = log_file.get_ref().try_clone() {
+        let old = std::mem::replace(log_file, BufWriter::with_capacity(64 * 1024, file));
+        let _ = old.into_parts();
+    }
+}
+
+/// create the missing dirs and the file of `path` with `mode`
+pub(crate) fn create_with_mode(mode: &FileMode, path: &str) -> std::io::Result<()> {
+    if let Some(right) = path.rfind("/") {
+        if right != 0 {
+            mode.create_dir(&path[0..right])?;
+        }
+    }
+    mode.create_file(path)
+}
+
+/// true if `path` no longer points to `file`, for example it is deleted or moved by logrotate
+pub(crate) fn file_moved(file: &File, path: &str) -> bool {
+    let path_meta = match std::fs::metadata(path) {
+        Ok(v) => v,
+        Err(_) => return true,
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        if let Ok(file_meta) = file.metadata() {
+            return file_meta.dev() != path_meta.dev() || file_meta.ino() != path_meta.ino();
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = (file, path_meta);
+    false
+}
+
+/// point the link `dir/link` at `dir/target`(a relative symlink), so `tail -F` follows the active file.
+/// where symlinks are not allowed(windows without the privilege) the file is copied
+pub(crate) fn link_current(dir: &str, link: &str, target: &str) {
+    let link = std::path::Path::new(dir).join(link);
+    let _ = std::fs::remove_file(&link);
+    #[cfg(unix)]
+    let r = std::os::unix::fs::symlink(target, &link);
+    #[cfg(windows)]
+    let r = std::os::windows::fs::symlink_file(target, &link);
+    #[cfg(not(any(unix, windows)))]
+    let r: std::io::Result<()> = Err(std::io::ErrorKind::Unsupported.into());
+    if r.is_err() {
+        if let Err(e) = std::fs::copy(std::path::Path::new(dir).join(target), &link) {
+            crate::report_error(&LogError::from(format!(
+                "[fast_log] link {:?} fail: {}",
+                link, e
+            )));
+        }
+    }
 }
 
 impl LogAppender for FileAppender {
     fn do_logs(&self, records: &[FastLogRecord]) {
-        let mut log_file = self.file.borrow_mut();
-        let mut buf = String::new();
+        let mut buffer = self.file.lock();
+        let _guard = match &self.lock {
+            None => None,
+            Some(lock) => match lock.lock() {
+                Ok(guard) => Some(guard),
+                Err(e) => {
+                    crate::report_error(&LogError::from(format!(
+                        "[fast_log] lock {} fail: {}",
+                        self.path, e
+                    )));
+                    None
+                }
+            },
+        };
+        if file_moved(buffer.writer.get_ref(), &self.path) {
+            //the rest of the buffer belongs to the old file
+            flush(&self.path, &mut buffer);
+            let _ = create_with_mode(&self.mode, &self.path);
+            match open(&self.path) {
+                Ok(file) => buffer.writer = BufWriter::with_capacity(64 * 1024, file),
+                Err(e) => crate::report_error(&LogError::from(format!(
+                    "[fast_log] reopen {} fail: {}",
+                    self.path, e
+                ))),
+            }
+        }
         for x in records {
-            buf.push_str(&x.formated);
+            let capacity = buffer.writer.capacity();
+            //flush a full buffer here instead of inside write_all, so its records go through the WritePolicy
+            if buffer.writer.buffer().len() + x.formated.len() > capacity {
+                flush(&self.path, &mut buffer);
+            }
+            let Buffer { writer, policy, .. } = &mut *buffer;
+            match writer.write_all(x.formated.as_bytes()) {
+                Err(e) => {
+                    if !policy.on_error(&self.path, std::slice::from_ref(x), e, || {
+                        writer.write_all(x.formated.as_bytes())
+                    }) {
+                        discard(writer);
+                    }
+                }
+                //a record larger than the buffer is written through
+                Ok(()) if x.formated.len() < capacity => buffer.push(x),
+                Ok(()) => {}
+            }
             match &x.command {
                 Command::CommandRecord => {}
-                Command::CommandExit => {}
-                Command::CommandFlush(_) => {
-                    let _ = log_file.write_all(buf.as_bytes());
-                    let _ = log_file.flush();
-                    buf.clear();
+                Command::CommandExit | Command::CommandFlush(_) => {
+                    flush(&self.path, &mut buffer);
                 }
+                Command::CommandAddAppender(_) | Command::CommandRemoveAppender(_) => {}
+                Command::CommandRotate => {}
             }
         }
-        let _ = log_file.write_all(buf.as_bytes());
+        match self.flush_interval {
+            _ if self.lock.is_some() => flush(&self.path, &mut buffer),
+            None => flush(&self.path, &mut buffer),
+            Some(interval) => self.spawn_flusher(interval),
+        }
     }
 }
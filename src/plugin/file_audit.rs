@@ -0,0 +1,123 @@
+use crate::appender::{Command, FastLogRecord, LogAppender};
+use crate::error::LogError;
+use crate::plugin::file::{create_with_mode, file_moved};
+use crate::plugin::file_mode::FileMode;
+use crate::plugin::write_policy::WritePolicy;
+use std::cell::RefCell;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+
+/// an append-only file for audit records: every record is written unbuffered and `sync_data` is called
+/// before the next one, so a written record survives a power failure. the file is never truncated,
+/// split or rotated(CommandRotate is ignored); if it is moved away a new one is created at the path.
+/// records reach the appender through the log channel, records still queued there are lost on a crash.
+/// use `Config::sync(true)` for the guarantee, then a record is synced when `log!` returns.
+/// fsync per record costs a disk round trip, keep other records in another appender, for example:
+/// ```rust,no_run
+/// use fast_log::Config;
+/// use fast_log::plugin::file_audit::AuditFileAppender;
+/// use fast_log::plugin::write_policy::WritePolicy;
+/// fast_log::init(
+///     Config::new().sync(true).file("target/logs/app.log").route(
+///         "audit",
+///         AuditFileAppender::new("target/logs/audit.log")
+///             .unwrap()
+///             .write_policy(WritePolicy::Panic),
+///     ),
+/// )
+/// .unwrap();
+/// log::info!(target: "audit", "transfer id=42 amount=100");
+/// ```
+pub struct AuditFileAppender {
+    path: String,
+    file: RefCell<File>,
+    mode: FileMode,
+    write_policy: WritePolicy,
+}
+
+impl AuditFileAppender {
+    pub fn new(log_file_path: &str) -> Result<AuditFileAppender, LogError> {
+        Self::with_mode(log_file_path, FileMode::default())
+    }
+
+    /// create the file and missing dirs with the permissions of `mode`, see FileMode
+    pub fn with_mode(log_file_path: &str, mode: FileMode) -> Result<AuditFileAppender, LogError> {
+        let path = log_file_path.replace("\\", "/");
+        let file = create(&mode, &path)?;
+        Ok(Self {
+            path,
+            file: RefCell::new(file),
+            mode,
+            write_policy: WritePolicy::default(),
+        })
+    }
+
+    /// what to do when a write or sync fails, default WritePolicy::Drop.
+    /// `WritePolicy::Panic` stops the appender rather than going on without the record
+    pub fn write_policy(mut self, policy: WritePolicy) -> Self {
+        self.write_policy = policy;
+        self
+    }
+}
+
+/// create(or open) the file append-only, and sync its directory so a new file survives a power failure
+fn create(mode: &FileMode, path: &str) -> std::io::Result<File> {
+    let exists = std::path::Path::new(path).exists();
+    create_with_mode(mode, path)?;
+    let file = OpenOptions::new().append(true).open(path)?;
+    if !exists {
+        sync_dir(path);
+    }
+    Ok(file)
+}
+
+#[cfg(unix)]
+fn sync_dir(path: &str) {
+    let dir = match path.rfind('/') {
+        Some(0) => "/",
+        Some(i) => &path[..i],
+        None => ".",
+    };
+    if let Ok(dir) = File::open(dir) {
+        let _ = dir.sync_all();
+    }
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_path: &str) {}
+
+fn write_sync(file: &mut File, data: &[u8]) -> std::io::Result<()> {
+    file.write_all(data)?;
+    file.sync_data()
+}
+
+impl LogAppender for AuditFileAppender {
+    fn do_logs(&self, records: &[FastLogRecord]) {
+        let mut file = self.file.borrow_mut();
+        if file_moved(&file, &self.path) {
+            match create(&self.mode, &self.path) {
+                Ok(new) => *file = new,
+                Err(e) => crate::report_error(&LogError::from(format!(
+                    "[fast_log] reopen {} fail: {}",
+                    self.path, e
+                ))),
+            }
+        }
+        for x in records {
+            match &x.command {
+                Command::CommandRecord => {
+                    let data = x.formated.as_bytes();
+                    if let Err(e) = write_sync(&mut file, data) {
+                        self.write_policy
+                            .on_error(&self.path, std::slice::from_ref(x), e, || {
+                                write_sync(&mut file, data)
+                            });
+                    }
+                }
+                Command::CommandExit | Command::CommandFlush(_) => {}
+                Command::CommandAddAppender(_) | Command::CommandRemoveAppender(_) => {}
+                Command::CommandRotate => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use crate::appender::{Command, FastLogRecord, LogAppender};
+use crate::error::LogError;
+use crate::plugin::file_split::Period;
+use std::cell::RefCell;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+
+/// write directly into a file named by the date, for example `logs/app.log` writes `logs/app-2024-05-01.log`
+/// and switches to `logs/app-2024-05-02.log` at midnight(local time). no temp file and no packing
+pub struct DateFileAppender {
+    /// path without the extension, for example `logs/app`
+    stem: String,
+    /// extension with the dot, for example `.log`
+    ext: String,
+    period: Period,
+    /// (end of the period in local unix seconds, the file of the period)
+    file: RefCell<Option<(i64, BufWriter<File>)>>,
+    current_link: Option<String>,
+}
+
+impl DateFileAppender {
+    pub fn new(log_file_path: &str) -> Result<DateFileAppender, LogError> {
+        let log_file_path = log_file_path.replace("\\", "/");
+        if let Some(right) = log_file_path.rfind("/") {
+            std::fs::create_dir_all(&log_file_path[0..right])?;
+        }
+        let name_start = log_file_path.rfind("/").map(|v| v + 1).unwrap_or_default();
+        let (stem, ext) = match log_file_path[name_start..].rfind(".") {
+            Some(dot) if dot > 0 => {
+                let (stem, ext) = log_file_path.split_at(name_start + dot);
+                (stem.to_string(), ext.to_string())
+            }
+            _ => (log_file_path.clone(), String::new()),
+        };
+        Ok(Self {
+            stem,
+            ext,
+            period: Period::Day,
+            file: RefCell::new(None),
+            current_link: None,
+        })
+    }
+
+    /// switch files every period, default Period::Day
+    pub fn period(mut self, period: Period) -> Self {
+        self.period = period;
+        self
+    }
+
+    /// keep a link(for example `current.log`) in the dir pointing at the file of the period
+    pub fn current_link(mut self, link: &str) -> Self {
+        self.current_link = Some(link.to_string());
+        self
+    }
+
+    /// the file name of the period which starts at `start`(local unix seconds)
+    pub fn file_name(&self, start: i64) -> String {
+        format!("{}-{}{}", self.stem, self.period.name(start), self.ext)
+    }
+
+    fn open(&self, start: i64) -> std::io::Result<File> {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.file_name(start))
+    }
+}
+
+impl LogAppender for DateFileAppender {
+    fn do_logs(&self, records: &[FastLogRecord]) {
+        let mut current = self.file.borrow_mut();
+        for x in records {
+            match &x.command {
+                Command::CommandRecord => {
+                    let (start, end) = self.period.range(x.now);
+                    let expired = match current.as_ref() {
+                        None => true,
+                        Some((current_end, _)) => end != *current_end,
+                    };
+                    if expired {
+                        if let Some((_, mut file)) = current.take() {
+                            let _ = file.flush();
+                        }
+                        match self.open(start) {
+                            Ok(file) => {
+                                *current = Some((end, BufWriter::new(file)));
+                                if let Some(link) = &self.current_link {
+                                    let name = self.file_name(start);
+                                    let (dir, target) = match name.rfind("/") {
+                                        Some(i) => (&name[..i], &name[i + 1..]),
+                                        None => ("", name.as_str()),
+                                    };
+                                    crate::plugin::file::link_current(dir, link, target);
+                                }
+                            }
+                            Err(e) => {
+                                crate::report_error(&LogError::from(e));
+                                continue;
+                            }
+                        }
+                    }
+                    if let Some((_, file)) = current.as_mut() {
+                        let _ = file.write_all(x.formated.as_bytes());
+                    }
+                }
+                Command::CommandExit | Command::CommandFlush(_) => {}
+                Command::CommandAddAppender(_) | Command::CommandRemoveAppender(_) => {}
+                Command::CommandRotate => {}
+            }
+        }
+        if let Some((_, file)) = current.as_mut() {
+            let _ = file.flush();
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use crate::appender::{Command, FastLogRecord, LogAppender};
+use crate::consts::LogSize;
+use crate::error::LogError;
+use crate::plugin::file_split::{FileSplitAppender, Keep, Packer, RawFile};
+use log::Level;
+
+/// write records into one appender per level, for example `logs/error.log` `logs/warn.log` `logs/info.log`,
+/// every file splits and keeps on its own. levels without an appender are skipped.
+/// commands(flush,exit,rotate) are sent to every appender
+/// ```rust,no_run
+/// use fast_log::Config;
+/// use fast_log::consts::LogSize;
+/// use fast_log::plugin::file_split::KeepType;
+/// use fast_log::plugin::packer::LogPacker;
+/// fast_log::init(Config::new().file_level("target/logs/", LogSize::MB(100), KeepType::KeepNum(7), LogPacker {}))
+///     .unwrap();
+/// ```
+#[derive(Default)]
+pub struct LevelFileAppender {
+    appenders: Vec<(Level, Box<dyn LogAppender>)>,
+}
+
+impl LevelFileAppender {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// a FileSplitAppender per level in `dir`, named `error.log` `warn.log` `info.log` `debug.log` `trace.log`
+    pub fn split<P: Packer + Clone + Sync + 'static, R: Keep + Clone + 'static>(
+        dir: &str,
+        temp_size: LogSize,
+        rolling_type: R,
+        packer: P,
+    ) -> Result<Self, LogError> {
+        let mut dir = dir.replace("\\", "/");
+        if !dir.is_empty() && !dir.ends_with("/") {
+            dir.push('/');
+        }
+        let mut appender = Self::new();
+        for level in Level::iter() {
+            let file_path = format!("{}{}.log", dir, level.as_str().to_lowercase());
+            appender = appender.level(
+                level,
+                FileSplitAppender::<RawFile>::new(
+                    &file_path,
+                    temp_size,
+                    rolling_type.clone(),
+                    Box::new(packer.clone()),
+                )?,
+            );
+        }
+        Ok(appender)
+    }
+
+    /// write records of `level` into `appender`
+    pub fn level<A: LogAppender + 'static>(mut self, level: Level, appender: A) -> Self {
+        self.appenders.push((level, Box::new(appender)));
+        self
+    }
+}
+
+impl LogAppender for LevelFileAppender {
+    fn do_logs(&self, records: &[FastLogRecord]) {
+        for (level, appender) in &self.appenders {
+            let records: Vec<FastLogRecord> = records
+                .iter()
+                .filter(|x| x.command != Command::CommandRecord || x.level == *level)
+                .cloned()
+                .collect();
+            if !records.is_empty() {
+                appender.do_logs(&records);
+            }
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use once_cell::sync::OnceCell;
+use std::fs::{File, OpenOptions};
+
+/// an OS advisory lock(flock on unix, LockFileEx on windows) on a lock file,
+/// so processes sharing a log directory do not interleave writes and rotations.
+/// the lock file is opened on the first lock
+pub struct FileLock {
+    path: String,
+    file: OnceCell<File>,
+}
+
+/// holds the lock until dropped
+pub struct FileLockGuard<'a> {
+    file: &'a File,
+}
+
+impl FileLock {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            file: OnceCell::new(),
+        }
+    }
+
+    /// the lock file of a log file, for example `logs/.app.log.lock` of `logs/app.log`.
+    /// it starts with a dot so the rolling(KeepType) does not take it as a log pack
+    pub fn of(log_file_path: &str) -> Self {
+        let log_file_path = log_file_path.replace("\\", "/");
+        let (dir, name) = match log_file_path.rfind("/") {
+            Some(i) => log_file_path.split_at(i + 1),
+            None => ("", log_file_path.as_str()),
+        };
+        Self::new(&format!("{}.{}.lock", dir, name))
+    }
+
+    /// block until the lock is taken
+    pub fn lock(&self) -> std::io::Result<FileLockGuard<'_>> {
+        let file = self.file.get_or_try_init(|| {
+            OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .read(true)
+                .write(true)
+                .open(&self.path)
+        })?;
+        sys::lock(file)?;
+        Ok(FileLockGuard { file })
+    }
+}
+
+impl Drop for FileLockGuard<'_> {
+    fn drop(&mut self) {
+        let _ = sys::unlock(self.file);
+    }
+}
+
+#[cfg(unix)]
+mod sys {
+    use std::fs::File;
+    use std::os::unix::io::AsRawFd;
+
+    fn flock(file: &File, operation: libc::c_int) -> std::io::Result<()> {
+        loop {
+            if unsafe { libc::flock(file.as_raw_fd(), operation) } == 0 {
+                return Ok(());
+            }
+            let e = std::io::Error::last_os_error();
+            if e.kind() != std::io::ErrorKind::Interrupted {
+                return Err(e);
+            }
+        }
+    }
+
+    pub fn lock(file: &File) -> std::io::Result<()> {
+        flock(file, libc::LOCK_EX)
+    }
+
+    pub fn unlock(file: &File) -> std::io::Result<()> {
+        flock(file, libc::LOCK_UN)
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    use std::ffi::c_void;
+    use std::fs::File;
+    use std::os::windows::io::AsRawHandle;
+
+    const LOCKFILE_EXCLUSIVE_LOCK: u32 = 0x0000_0002;
+
+    #[repr(C)]
+    struct Overlapped {
+        internal: usize,
+        internal_high: usize,
+        offset: u32,
+        offset_high: u32,
+        event: *mut c_void,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn LockFileEx(
+            file: *mut c_void,
+            flags: u32,
+            reserved: u32,
+            bytes_low: u32,
+            bytes_high: u32,
+            overlapped: *mut Overlapped,
+        ) -> i32;
+        fn UnlockFileEx(
+            file: *mut c_void,
+            reserved: u32,
+            bytes_low: u32,
+            bytes_high: u32,
+            overlapped: *mut Overlapped,
+        ) -> i32;
+    }
+
+    fn overlapped() -> Overlapped {
+        Overlapped {
+            internal: 0,
+            internal_high: 0,
+            offset: 0,
+            offset_high: 0,
+            event: std::ptr::null_mut(),
+        }
+    }
+
+    pub fn lock(file: &File) -> std::io::Result<()> {
+        let mut o = overlapped();
+        let r = unsafe {
+            LockFileEx(
+                file.as_raw_handle() as *mut c_void,
+                LOCKFILE_EXCLUSIVE_LOCK,
+                0,
+                u32::MAX,
+                u32::MAX,
+                &mut o,
+            )
+        };
+        if r == 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    pub fn unlock(file: &File) -> std::io::Result<()> {
+        let mut o = overlapped();
+        let r = unsafe {
+            UnlockFileEx(
+                file.as_raw_handle() as *mut c_void,
+                0,
+                u32::MAX,
+                u32::MAX,
+                &mut o,
+            )
+        };
+        if r == 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod sys {
+    use std::fs::File;
+
+    pub fn lock(_file: &File) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    pub fn unlock(_file: &File) -> std::io::Result<()> {
+        Ok(())
+    }
+}
@@ -21,7 +21,7 @@ impl MmapFile {
         let log_file_path = log_file_path.replace("\\", "/");
         if let Some(right) = log_file_path.rfind("/") {
             let path = &log_file_path[0..right];
-            let _ = std::fs::create_dir_all(path);
+            std::fs::create_dir_all(path)?;
         }
         let file = OpenOptions::new()
             .write(true)
@@ -30,10 +30,9 @@ impl MmapFile {
             .open(&log_file_path)?;
         file.set_len(size.get_len() as u64)?;
         let mmap = unsafe {
-            MmapOptions::new().map(&file).map_err(|e| {
-                println!("e={}", e);
-                LogError::from(format!("{}", e.to_string()))
-            })?
+            MmapOptions::new()
+                .map(&file)
+                .map_err(|e| LogError::from(format!("{}", e.to_string())))?
         };
         let s = Self {
             file: UnsafeCell::new(file),
@@ -0,0 +1,173 @@
+/// permissions of created log files and directories.
+/// on unix the modes are mode bits(still masked by the umask when a file is created) and `owner` is a chown,
+/// on windows a file mode without group/other bits(for example 0o600) replaces the ACL with
+/// full access of the owner, SYSTEM and Administrators only
+/// ```rust,no_run
+/// use fast_log::plugin::file::FileAppender;
+/// use fast_log::plugin::file_mode::FileMode;
+/// let appender =
+///     FileAppender::with_mode("target/logs/app.log", FileMode::new(0o640).dir(0o750)).unwrap();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct FileMode {
+    file: Option<u32>,
+    dir: Option<u32>,
+    owner: Option<(u32, u32)>,
+}
+
+impl FileMode {
+    pub fn new(file: u32) -> Self {
+        Self {
+            file: Some(file),
+            dir: None,
+            owner: None,
+        }
+    }
+
+    /// the mode of created directories, for example 0o750
+    pub fn dir(mut self, dir: u32) -> Self {
+        self.dir = Some(dir);
+        self
+    }
+
+    /// chown files and directories to `uid`:`gid`, unix only. the process needs the privilege
+    pub fn owner(mut self, uid: u32, gid: u32) -> Self {
+        self.owner = Some((uid, gid));
+        self
+    }
+
+    /// create the file with the mode if it does not exist, then set the mode and owner
+    pub fn create_file(&self, path: &str) -> std::io::Result<()> {
+        let mut options = std::fs::OpenOptions::new();
+        options.create(true).append(true);
+        #[cfg(unix)]
+        if let Some(mode) = self.file {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(mode);
+        }
+        options.open(path)?;
+        self.apply_file(path)
+    }
+
+    /// set the mode and owner of an existing file
+    pub fn apply_file(&self, path: &str) -> std::io::Result<()> {
+        if let Some(mode) = self.file {
+            sys::set_mode(path, mode, false)?;
+        }
+        self.chown(path)
+    }
+
+    /// create the directory and its missing parents, the created ones get the dir mode and owner.
+    /// existing directories are not changed
+    pub fn create_dir(&self, path: &str) -> std::io::Result<()> {
+        let missing: Vec<&std::path::Path> = std::path::Path::new(path)
+            .ancestors()
+            .take_while(|v| !v.as_os_str().is_empty() && !v.exists())
+            .collect();
+        let mut builder = std::fs::DirBuilder::new();
+        builder.recursive(true);
+        #[cfg(unix)]
+        if let Some(mode) = self.dir {
+            use std::os::unix::fs::DirBuilderExt;
+            builder.mode(mode);
+        }
+        builder.create(path)?;
+        for dir in missing.iter().rev() {
+            let dir = dir.to_str().unwrap_or_default();
+            if let Some(mode) = self.dir {
+                sys::set_mode(dir, mode, true)?;
+            }
+            self.chown(dir)?;
+        }
+        Ok(())
+    }
+
+    fn chown(&self, path: &str) -> std::io::Result<()> {
+        #[cfg(unix)]
+        if let Some((uid, gid)) = self.owner {
+            std::os::unix::fs::chown(path, Some(uid), Some(gid))?;
+        }
+        #[cfg(not(unix))]
+        let _ = path;
+        Ok(())
+    }
+}
+
+#[cfg(unix)]
+mod sys {
+    use std::os::unix::fs::PermissionsExt;
+
+    pub fn set_mode(path: &str, mode: u32, _dir: bool) -> std::io::Result<()> {
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    use std::ffi::c_void;
+
+    const SDDL_REVISION_1: u32 = 1;
+    const DACL_SECURITY_INFORMATION: u32 = 0x0000_0004;
+    const PROTECTED_DACL_SECURITY_INFORMATION: u32 = 0x8000_0000;
+    /// owner rights, SYSTEM and Administrators, not inherited from the parent
+    const FILE_SDDL: &str = "D:P(A;;FA;;;OW)(A;;FA;;;SY)(A;;FA;;;BA)";
+    const DIR_SDDL: &str = "D:P(A;OICI;FA;;;OW)(A;OICI;FA;;;SY)(A;OICI;FA;;;BA)";
+
+    #[link(name = "advapi32")]
+    extern "system" {
+        fn ConvertStringSecurityDescriptorToSecurityDescriptorW(
+            sddl: *const u16,
+            revision: u32,
+            descriptor: *mut *mut c_void,
+            size: *mut u32,
+        ) -> i32;
+        fn SetFileSecurityW(path: *const u16, information: u32, descriptor: *mut c_void) -> i32;
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn LocalFree(mem: *mut c_void) -> *mut c_void;
+    }
+
+    fn wide(s: &str) -> Vec<u16> {
+        s.encode_utf16().chain(std::iter::once(0)).collect()
+    }
+
+    pub fn set_mode(path: &str, mode: u32, dir: bool) -> std::io::Result<()> {
+        if mode & 0o077 != 0 {
+            return Ok(());
+        }
+        let sddl = wide(if dir { DIR_SDDL } else { FILE_SDDL });
+        let path = wide(path);
+        let mut descriptor: *mut c_void = std::ptr::null_mut();
+        unsafe {
+            if ConvertStringSecurityDescriptorToSecurityDescriptorW(
+                sddl.as_ptr(),
+                SDDL_REVISION_1,
+                &mut descriptor,
+                std::ptr::null_mut(),
+            ) == 0
+            {
+                return Err(std::io::Error::last_os_error());
+            }
+            let r = SetFileSecurityW(
+                path.as_ptr(),
+                DACL_SECURITY_INFORMATION | PROTECTED_DACL_SECURITY_INFORMATION,
+                descriptor,
+            );
+            let e = std::io::Error::last_os_error();
+            LocalFree(descriptor);
+            if r == 0 {
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod sys {
+    pub fn set_mode(_path: &str, _mode: u32, _dir: bool) -> std::io::Result<()> {
+        Ok(())
+    }
+}
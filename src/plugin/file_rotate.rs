@@ -3,7 +3,7 @@ use crate::consts::LogSize;
 use crate::error::LogError;
 use crate::plugin::file_name::FileName;
 use crate::plugin::file_split::{Keep, LogPack, Packer, SplitFile};
-use crate::{chan, Receiver, Sender};
+use crate::{chan, report_error, Receiver, Sender};
 use std::cell::RefCell;
 use std::io::SeekFrom;
 use std::path::Path;
@@ -41,11 +41,16 @@ where
                 dir_path = v.to_str().unwrap_or_default().to_string();
             }
         }
-        let _ = std::fs::create_dir_all(&dir_path);
+        std::fs::create_dir_all(&dir_path)?;
         let path = Path::new(&dir_path);
         let temp_file = path.join(rolling_type.init(&dir_path, &packer));
         let temp_bytes = AtomicUsize::new(0);
-        let file = F::new(temp_file.to_str().unwrap(), temp_size)?;
+        let file = F::new(
+            temp_file
+                .to_str()
+                .ok_or_else(|| LogError::from(format!("[fast_log] bad path {:?}", temp_file)))?,
+            temp_size,
+        )?;
         let mut offset = file.offset();
         if offset != 0 {
             offset += 1;
@@ -73,6 +78,7 @@ where
 
     /// send data truncate data, and make an pack.
     fn send_pack(&self, record: &FastLogRecord) {
+        crate::fast_log::with_logger(|l| l.rotations.fetch_add(1, Ordering::Relaxed));
         let current_filename = Path::new(&self.dir_path).join(self.rolling_type.current());
         self.rolling_type.next(record);
         self.truncate();
@@ -94,7 +100,7 @@ where
                 *self.file.borrow_mut() = f;
                 self.temp_bytes.store(0, Ordering::SeqCst);
             }
-            Err(e) => eprintln!("{:?}", e),
+            Err(e) => report_error(&e),
         }
     }
 }
@@ -134,6 +140,17 @@ where
                     temp.push_str(x.formated.as_str());
                 }
                 Command::CommandExit => {}
+                Command::CommandAddAppender(_) | Command::CommandRemoveAppender(_) => {}
+                Command::CommandRotate => {
+                    self.temp_bytes.fetch_add(
+                        self.file.borrow().write(temp.as_bytes()).unwrap_or(0),
+                        Ordering::SeqCst,
+                    );
+                    temp.clear();
+                    if self.temp_bytes.load(Ordering::Relaxed) != 0 {
+                        self.send_pack(x);
+                    }
+                }
                 Command::CommandFlush(ref w) => {
                     let _ = self.sender.send(LogPack {
                         dir: "".to_string(),
@@ -191,15 +208,20 @@ fn spawn_saver<R: Keep + Sync + 'static>(
         loop {
             if let Ok(pack) = r.recv() {
                 if pack.wg.is_some() {
-                    return;
+                    //flush: all packs before it are done, drop wg to notify
+                    continue;
                 }
                 let log_file_path = pack.new_log_name.clone();
                 //do save pack
-                let remove = pack.do_pack(packer.as_ref());
-                if let Ok(remove) = remove {
-                    if remove {
+                match pack.do_pack(packer.as_ref()) {
+                    Ok(true) => {
                         let _ = std::fs::remove_file(log_file_path);
                     }
+                    Ok(false) => {}
+                    Err(e) => report_error(&LogError::from(format!(
+                        "[fast_log] pack {} fail: {}",
+                        log_file_path, e
+                    ))),
                 }
                 //do rolling
                 rolling_type.do_keep(&pack.dir, &temp_name);
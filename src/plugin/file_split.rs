@@ -1,15 +1,21 @@
 use crate::appender::{Command, FastLogRecord, LogAppender};
+use crate::clock::local_now;
 use crate::consts::LogSize;
 use crate::error::LogError;
+use crate::plugin::file_lock::FileLock;
+use crate::plugin::file_mode::FileMode;
 use crate::plugin::file_name::FileName;
-use crate::{chan, Receiver, Sender, WaitGroup};
+use crate::plugin::write_policy::WritePolicy;
+use crate::{chan, report_error, Receiver, Sender, WaitGroup};
 use fastdate::DateTime;
-use std::cell::RefCell;
+use parking_lot::Mutex;
+use std::cell::{Cell, RefCell};
 use std::fs::{DirEntry, File, OpenOptions};
 use std::io::{Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 pub trait SplitFile: Send {
     fn new(path: &str, temp_size: LogSize) -> Result<Self, LogError>
@@ -21,6 +27,10 @@ pub trait SplitFile: Send {
     fn flush(&self);
     fn len(&self) -> usize;
     fn offset(&self) -> usize;
+    /// true if `path` no longer points to this file(deleted or moved), the file is created again
+    fn moved(&self, path: &str) -> bool {
+        !Path::new(path).exists()
+    }
 }
 
 ///only use File
@@ -85,13 +95,55 @@ impl SplitFile for RawFile {
         }
         offset
     }
+
+    fn moved(&self, path: &str) -> bool {
+        crate::plugin::file::file_moved(&self.inner.borrow(), path)
+    }
+}
+
+/// what Packer::pack made of a log file
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Packed {
+    /// nothing was made, the log file is kept(for example LogPacker)
+    Kept,
+    /// the archive made of the log file, the log file is removed
+    Archive(PathBuf),
+    /// the log file was packed by `do_pack` and is removed, the archive it made is not known
+    Unknown,
+}
+
+impl Packed {
+    /// the file made of `log_file`, the log file itself if it is kept. None if it is not known
+    pub fn path(&self, log_file: &Path) -> Option<PathBuf> {
+        match self {
+            Packed::Kept => Some(log_file.to_path_buf()),
+            Packed::Archive(archive) => Some(archive.clone()),
+            Packed::Unknown => None,
+        }
+    }
 }
 
 /// .zip or .lz4 or any one packer
 pub trait Packer: Send + Sync {
     fn pack_name(&self) -> &'static str;
+
     //return bool: remove_log_file
     fn do_pack(&self, log_file: File, log_file_path: &str) -> Result<bool, LogError>;
+
+    /// pack the log file. the default opens it and calls `do_pack`, which does not tell the archive:
+    /// override it to return Packed::Archive, so pack hooks(see FileSplitAppender::on_pack) and
+    /// wrappers like EncryptedPacker get the archive path
+    fn pack(&self, log_file: &Path) -> Result<Packed, LogError> {
+        let log_file_path = log_file.to_str().unwrap_or_default();
+        let file = File::open(log_file).map_err(|e| {
+            LogError::from(format!("open(log_file_path={}) fail:{}", log_file_path, e))
+        })?;
+        match self.do_pack(file, log_file_path)? {
+            true => Ok(Packed::Unknown),
+            false => Ok(Packed::Kept),
+        }
+    }
+
     /// default 0 is not retry pack. if retry > 0 ,it will trying rePack
     fn retry(&self) -> i32 {
         return 0;
@@ -104,7 +156,7 @@ pub trait Packer: Send + Sync {
         match point {
             None => {
                 new_log_name.push_str(
-                    &DateTime::now()
+                    &local_now()
                         .display_stand()
                         .to_string()
                         .replace(" ", "T")
@@ -116,7 +168,7 @@ pub trait Packer: Send + Sync {
                 new_log_name = format!(
                     "{}{}{}",
                     name,
-                    DateTime::now()
+                    local_now()
                         .display_stand()
                         .to_string()
                         .replace(" ", "T")
@@ -130,10 +182,66 @@ pub trait Packer: Send + Sync {
     }
 }
 
+/// called on the saver thread with the path of every finished pack(the archive, or the log file of LogPacker),
+/// for example checksums or uploads, see FileSplitAppender::on_pack
+pub type PackHook = Box<dyn Fn(&Path) + Send + Sync>;
+
+/// split the temp file at local time boundaries,
+/// the packed file is named with the period, for example `temp2024-05-01.log`
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Period {
+    /// every hour, for example `temp2024-05-01T13.log`
+    Hour,
+    /// every day at 00:00, for example `temp2024-05-01.log`
+    Day,
+    /// every monday at 00:00, named with the date of monday
+    Week,
+}
+
+impl Period {
+    /// return the (start,end) of the period which contains `time`, in local unix seconds
+    pub fn range(&self, time: SystemTime) -> (i64, i64) {
+        let secs = match time.duration_since(UNIX_EPOCH) {
+            Ok(v) => v.as_secs() as i64,
+            Err(e) => -(e.duration().as_secs() as i64),
+        };
+        //the offset at `time` itself, it changes with daylight saving time
+        let offset = fastdate::sys::Timespec { sec: secs, nsec: 0 }
+            .local()
+            .tm_utcoff;
+        let local = secs + offset as i64;
+        match self {
+            Period::Hour => {
+                let start = local.div_euclid(3600) * 3600;
+                (start, start + 3600)
+            }
+            Period::Day => {
+                let start = local.div_euclid(86400) * 86400;
+                (start, start + 86400)
+            }
+            Period::Week => {
+                let day = local.div_euclid(86400);
+                //1970-01-01 is thursday
+                let start = (day - (day + 3).rem_euclid(7)) * 86400;
+                (start, start + 7 * 86400)
+            }
+        }
+    }
+
+    /// name of the period which starts at `start`(local unix seconds)
+    pub fn name(&self, start: i64) -> String {
+        let date = DateTime::from_timestamp(start);
+        match self {
+            Period::Hour => date.format("YYYY-MM-DDThh"),
+            Period::Day | Period::Week => date.format("YYYY-MM-DD"),
+        }
+    }
+}
+
 /// split log file allow pack compress log
 /// Memory space swop running time , reduces the number of repeated queries for IO
 pub struct FileSplitAppender<F: SplitFile> {
-    file: F,
+    file: RefCell<F>,
     packer: Arc<Box<dyn Packer>>,
     dir_path: String,
     sender: Sender<LogPack>,
@@ -141,6 +249,15 @@ pub struct FileSplitAppender<F: SplitFile> {
     //cache data
     temp_bytes: AtomicUsize,
     temp_name: String,
+    period: Option<Period>,
+    // (start,end) of current period
+    period_range: Cell<(i64, i64)>,
+    name_template: Option<String>,
+    lock: Option<FileLock>,
+    hooks: Arc<Mutex<Vec<PackHook>>>,
+    current_link: Option<String>,
+    mode: FileMode,
+    write_policy: WritePolicy,
 }
 
 impl<F: SplitFile> FileSplitAppender<F> {
@@ -149,6 +266,24 @@ impl<F: SplitFile> FileSplitAppender<F> {
         temp_size: LogSize,
         rolling_type: R,
         packer: Box<dyn Packer>,
+    ) -> Result<FileSplitAppender<F>, LogError> {
+        Self::with_mode(
+            file_path,
+            temp_size,
+            rolling_type,
+            packer,
+            FileMode::default(),
+        )
+    }
+
+    /// create the temp file and missing dirs with the permissions of `mode`(see FileMode),
+    /// also the temp files of later rotations and the packs
+    pub fn with_mode<R: Keep + 'static>(
+        file_path: &str,
+        temp_size: LogSize,
+        rolling_type: R,
+        packer: Box<dyn Packer>,
+        mode: FileMode,
     ) -> Result<FileSplitAppender<F>, LogError> {
         let temp_name = {
             let mut name = file_path.extract_file_name().to_string();
@@ -163,12 +298,15 @@ impl<F: SplitFile> FileSplitAppender<F> {
                 dir_path = v.to_str().unwrap_or_default().to_string();
             }
         }
-        let _ = std::fs::create_dir_all(&dir_path);
+        mode.create_dir(&dir_path).map_err(|e| {
+            LogError::from(format!("[fast_log] create dir {} fail: {}", dir_path, e))
+        })?;
         let mut sp = "";
         if !dir_path.is_empty() {
             sp = "/";
         }
         let temp_file = format!("{}{}{}", dir_path, sp, temp_name);
+        mode.create_file(&temp_file)?;
         let temp_bytes = AtomicUsize::new(0);
         let file = F::new(&temp_file, temp_size)?;
         let mut offset = file.offset();
@@ -179,43 +317,291 @@ impl<F: SplitFile> FileSplitAppender<F> {
         let _ = file.seek(SeekFrom::Start(temp_bytes.load(Ordering::Relaxed) as u64));
         let (sender, receiver) = chan(None);
         let arc_packer = Arc::new(packer);
+        let hooks: Arc<Mutex<Vec<PackHook>>> = Arc::new(Mutex::new(vec![]));
+        let pack_mode = mode.clone();
+        hooks.lock().push(Box::new(move |pack| {
+            if let Err(e) = pack_mode.apply_file(pack.to_str().unwrap_or_default()) {
+                report_error(&LogError::from(format!(
+                    "[fast_log] set mode of {:?} fail: {}",
+                    pack, e
+                )));
+            }
+        }));
         spawn_saver(
             temp_name.clone(),
             receiver,
             rolling_type,
             arc_packer.clone(),
+            hooks.clone(),
         );
         Ok(Self {
             temp_bytes,
             dir_path: dir_path.to_string(),
-            file,
+            file: RefCell::new(file),
             sender,
             temp_size,
             temp_name,
             packer: arc_packer,
+            period: None,
+            period_range: Cell::new((0, 0)),
+            name_template: None,
+            lock: None,
+            hooks,
+            current_link: None,
+            mode,
+            write_policy: WritePolicy::default(),
         })
     }
-    /// send data make an pack,and truncate data when finish.
-    pub fn send_pack(&self) {
+
+    /// also split the temp file at time boundaries, the size limit still works.
+    pub fn period(mut self, period: Period) -> Self {
+        let mut time = crate::now();
+        if self.temp_bytes.load(Ordering::Relaxed) != 0 {
+            //the temp file may be written at an old period
+            if let Ok(m) = std::fs::metadata(self.temp_file_path()) {
+                if let Ok(modified) = m.modified() {
+                    time = modified;
+                }
+            }
+        }
+        self.period_range.set(period.range(time));
+        self.period = Some(period);
+        self
+    }
+
+    /// take an OS advisory lock(see FileLock) around every batch and rotation,
+    /// so processes sharing the temp file(RawFile) append whole batches and roll it once
+    pub fn lock(mut self, lock: bool) -> Self {
+        self.lock = match lock {
+            true => Some(FileLock::of(&self.temp_file_path())),
+            false => None,
+        };
+        self
+    }
+
+    /// what to do when a write of the temp file fails, default WritePolicy::Drop
+    pub fn write_policy(mut self, policy: WritePolicy) -> Self {
+        self.write_policy = policy;
+        self
+    }
+
+    /// write the batch `temp` of `records` into the temp file
+    fn write_temp(&self, temp: &str, records: &[FastLogRecord]) {
+        if temp.is_empty() {
+            return;
+        }
+        let write = || {
+            let w = self.file.borrow().write(temp.as_bytes())?;
+            self.temp_bytes.fetch_add(w, Ordering::SeqCst);
+            Ok(())
+        };
+        if let Err(e) = write() {
+            self.write_policy
+                .on_error(&self.temp_file_path(), records, e, write);
+        }
+    }
+
+    /// keep a link(for example `current.log`) in the dir pointing at the temp file,
+    /// refreshed after every rotation
+    pub fn current_link(mut self, link: &str) -> Self {
+        self.current_link = Some(link.to_string());
+        self.refresh_link();
+        self
+    }
+
+    fn refresh_link(&self) {
+        if let Some(link) = &self.current_link {
+            crate::plugin::file::link_current(&self.dir_path, link, &self.temp_name);
+        }
+    }
+
+    /// call `hook` with the path of every finished pack, after packing and before the rolling
+    pub fn on_pack<H: Fn(&Path) + Send + Sync + 'static>(self, hook: H) -> Self {
+        self.hooks.lock().push(Box::new(hook));
+        self
+    }
+
+    /// write checksum sidecars and the manifest of packs, see Integrity
+    #[cfg(feature = "integrity")]
+    pub fn integrity(self, integrity: crate::plugin::integrity::Integrity) -> Self {
+        self.on_pack(move |pack| {
+            if let Err(e) = integrity.record(pack) {
+                report_error(&e);
+            }
+        })
+    }
+
+    /// name the packs with a template, for example `"{dir}/{name}-{date}-{index}.log"`
+    /// * `{dir}` the dir of the temp file
+    /// * `{name}` the temp file name without the extension, for example `app` of `logs/app.log`
+    /// * `{date}` the date of the period(or today `YYYY-MM-DD` without a period)
+    /// * `{time}` the time of the pack `YYYY-MM-DDThh-mm-ss`
+    /// * `{index}` the first free sequence number of the name, zero-padded to 3 digits(`{index:N}` pads to N)
+    ///
+    /// keep the `.log` extension so packers(zip,lz4...) can replace it,
+    /// and start names with `{name}` so the rolling(KeepType) can find them
+    pub fn name_template(mut self, template: &str) -> Self {
+        self.name_template = Some(template.to_string());
+        self
+    }
+
+    /// the pack name of the template
+    fn template_name(&self, template: &str) -> String {
+        let date = match &self.period {
+            Some(period) => period.name(self.period_range.get().0),
+            None => local_now().format("YYYY-MM-DD"),
+        };
+        let name = template
+            .replace("{dir}", self.dir_path.trim_end_matches("/"))
+            .replace("{name}", &get_base_name(&self.temp_name))
+            .replace("{date}", &date)
+            .replace("{time}", &local_now().format("YYYY-MM-DDThh-mm-ss"));
+        let start = match name.find("{index") {
+            None => return name,
+            Some(v) => v,
+        };
+        let end = match name[start..].find("}") {
+            None => return name,
+            Some(v) => start + v + 1,
+        };
+        let width = name[start..end]
+            .trim_start_matches("{index")
+            .trim_start_matches(":")
+            .trim_end_matches("}")
+            .parse::<usize>()
+            .unwrap_or(3);
+        let mut index = 1;
+        loop {
+            let new_log_name = format!(
+                "{}{:0width$}{}",
+                &name[..start],
+                index,
+                &name[end..],
+                width = width
+            );
+            if !self.pack_exists(&new_log_name) {
+                return new_log_name;
+            }
+            index += 1;
+        }
+    }
+
+    fn temp_file_path(&self) -> String {
         let mut sp = "";
         if !self.dir_path.is_empty() && !self.dir_path.ends_with("/") {
             sp = "/";
         }
-        let first_file_path = format!("{}{}{}", self.dir_path, sp, &self.temp_name);
-        let new_log_name = self.packer.log_name_create(&first_file_path);
-        self.file.flush();
-        let _ = std::fs::copy(&first_file_path, &new_log_name);
+        format!("{}{}{}", self.dir_path, sp, &self.temp_name)
+    }
+
+    /// send data make an pack,and truncate data when finish.
+    pub fn send_pack(&self) {
+        let first_file_path = self.temp_file_path();
+        let new_log_name = match &self.name_template {
+            Some(template) => self.template_name(template),
+            None => self.packer.log_name_create(&first_file_path),
+        };
+        self.send_pack_name(&first_file_path, new_log_name);
+    }
+
+    /// make an pack named with the current period, for example `temp2024-05-01.log`
+    fn send_period_pack(&self, period: &Period) {
+        let first_file_path = self.temp_file_path();
+        if let Some(template) = &self.name_template {
+            let new_log_name = self.template_name(template);
+            self.send_pack_name(&first_file_path, new_log_name);
+            return;
+        }
+        let file_name = first_file_path.extract_file_name();
+        let dir = first_file_path.trim_end_matches(&file_name).to_string();
+        let (name, ext) = match file_name.rfind(".") {
+            None => (file_name.as_str(), ""),
+            Some(i) => file_name.split_at(i),
+        };
+        let period_name = period.name(self.period_range.get().0);
+        let mut new_log_name = format!("{}{}{}{}", dir, name, period_name, ext);
+        let mut index = 1;
+        //size split may already made an pack of this period
+        while self.pack_exists(&new_log_name) {
+            new_log_name = format!("{}{}{}_{}{}", dir, name, period_name, index, ext);
+            index += 1;
+        }
+        self.send_pack_name(&first_file_path, new_log_name);
+    }
+
+    fn pack_exists(&self, log_name: &str) -> bool {
+        let pack_name = self.packer.pack_name();
+        Path::new(log_name).exists()
+            || Path::new(&log_name.replace(".log", &format!(".{}", pack_name))).exists()
+            || Path::new(&format!("{}.{}", log_name, pack_name)).exists()
+    }
+
+    fn send_pack_name(&self, first_file_path: &str, new_log_name: String) {
+        crate::fast_log::with_logger(|l| l.rotations.fetch_add(1, Ordering::Relaxed));
+        self.file.borrow().flush();
+        //move the temp file and open a new one, so big files are never copied
+        if std::fs::rename(first_file_path, &new_log_name).is_ok() {
+            let _ = self.mode.create_file(first_file_path);
+            match F::new(first_file_path, self.temp_size) {
+                Ok(f) => {
+                    *self.file.borrow_mut() = f;
+                    self.temp_bytes.store(0, Ordering::SeqCst);
+                    self.refresh_link();
+                    let _ = self.sender.send(LogPack {
+                        dir: self.dir_path.clone(),
+                        new_log_name,
+                        wg: None,
+                    });
+                    return;
+                }
+                Err(e) => {
+                    report_error(&LogError::from(format!(
+                        "[fast_log] create {} fail: {}",
+                        first_file_path, e
+                    )));
+                    let _ = std::fs::rename(&new_log_name, first_file_path);
+                }
+            }
+        }
+        //rename fail(for example an open file on windows), copy and truncate
+        let _ = std::fs::copy(first_file_path, &new_log_name);
         let _ = self.sender.send(LogPack {
             dir: self.dir_path.clone(),
-            new_log_name: new_log_name,
+            new_log_name,
             wg: None,
         });
         self.truncate();
+        self.refresh_link();
+    }
+
+    /// create the temp file again if it is deleted or moved
+    fn reopen_moved(&self) {
+        let temp_file = self.temp_file_path();
+        if !self.file.borrow().moved(&temp_file) {
+            return;
+        }
+        let _ = self.mode.create_dir(&self.dir_path);
+        let _ = self.mode.create_file(&temp_file);
+        match F::new(&temp_file, self.temp_size) {
+            Ok(file) => {
+                let mut offset = file.offset();
+                if offset != 0 {
+                    offset += 1;
+                }
+                let _ = file.seek(SeekFrom::Start(offset as u64));
+                self.temp_bytes.store(offset, Ordering::SeqCst);
+                *self.file.borrow_mut() = file;
+            }
+            Err(e) => report_error(&LogError::from(format!(
+                "[fast_log] reopen {} fail: {}",
+                temp_file, e
+            ))),
+        }
     }
 
     pub fn truncate(&self) {
         //reset data
-        let _ = self.file.truncate();
+        let _ = self.file.borrow().truncate();
         self.temp_bytes.store(0, Ordering::SeqCst);
     }
 }
@@ -229,35 +615,24 @@ pub struct LogPack {
 impl LogPack {
     /// write an Pack to zip file
     pub fn do_pack(&self, packer: &Box<dyn Packer>) -> Result<bool, LogError> {
+        Ok(self.pack(packer.as_ref())? != Packed::Kept)
+    }
+
+    /// pack with retry
+    pub fn pack(&self, packer: &dyn Packer) -> Result<Packed, LogError> {
         let log_file_path = self.new_log_name.as_str();
         if log_file_path.is_empty() {
             return Err(LogError::from("log_file_path.is_empty"));
         }
-        let log_file = OpenOptions::new()
-            .write(true)
-            .read(true)
-            .open(log_file_path);
-        if log_file.is_err() {
-            return Err(LogError::from(format!(
-                "open(log_file_path={}) fail",
-                log_file_path
-            )));
-        }
+        let log_file = Path::new(log_file_path);
         //make
-        let r = packer.do_pack(log_file.unwrap(), log_file_path);
-        if r.is_err() && packer.retry() > 0 {
-            let mut retry = 1;
-            while let Err(_packs) = self.do_pack(packer) {
-                retry += 1;
-                if retry > packer.retry() {
-                    break;
-                }
-            }
-        }
-        if let Ok(b) = r {
-            return Ok(b);
+        let mut r = packer.pack(log_file);
+        let mut retry = 0;
+        while r.is_err() && retry < packer.retry() {
+            retry += 1;
+            r = packer.pack(log_file);
         }
-        return Ok(false);
+        r
     }
 }
 
@@ -280,7 +655,7 @@ pub trait Keep: Send {
                             if v == temp_name {
                                 continue;
                             }
-                            if !v.starts_with(&base_name) {
+                            if !v.starts_with(&base_name) || v.ends_with(CHECKSUM_EXT) {
                                 continue;
                             }
                         }
@@ -311,6 +686,11 @@ pub enum KeepType {
     KeepTime(Duration),
     /// keep log pack num(.log,.zip.lz4...more)
     KeepNum(i64),
+    /// keep the total size of log packs under the budget, the oldest packs are deleted first,
+    /// for example:
+    /// // keep at most 2GB of log packs
+    /// (LogSize::GB(2))
+    KeepSize(LogSize),
 }
 
 impl Keep for KeepType {
@@ -322,27 +702,39 @@ impl Keep for KeepType {
                 for index in 0..paths_vec.len() {
                     if index >= (*n) as usize {
                         let item = &paths_vec[index];
-                        let _ = std::fs::remove_file(item.path());
+                        remove_pack(&item.path());
                         removed += 1;
                     }
                 }
             }
             KeepType::KeepTime(duration) => {
                 let paths_vec = self.read_paths(dir, temp_name);
-                let now = DateTime::now();
+                let now = local_now();
                 for index in 0..paths_vec.len() {
                     let item = &paths_vec[index];
                     if let Ok(m) = item.metadata() {
-                        if let Ok(c) = m.created() {
+                        //created() is not supported by every filesystem
+                        if let Ok(c) = m.created().or_else(|_| m.modified()) {
                             let time = DateTime::from(c);
                             if now.clone().sub(duration.clone()) > time {
-                                let _ = std::fs::remove_file(item.path());
+                                remove_pack(&item.path());
                                 removed += 1;
                             }
                         }
                     }
                 }
             }
+            KeepType::KeepSize(budget) => {
+                let paths_vec = self.read_paths(dir, temp_name);
+                let mut total = 0;
+                for item in paths_vec {
+                    total += item.metadata().map(|m| m.len() as usize).unwrap_or(0);
+                    if total > budget.get_len() {
+                        remove_pack(&item.path());
+                        removed += 1;
+                    }
+                }
+            }
             _ => {}
         }
         removed
@@ -351,33 +743,67 @@ impl Keep for KeepType {
 
 impl<F: SplitFile> LogAppender for FileSplitAppender<F> {
     fn do_logs(&self, records: &[FastLogRecord]) {
+        let _guard = match &self.lock {
+            None => None,
+            Some(lock) => match lock.lock() {
+                Ok(guard) => Some(guard),
+                Err(e) => {
+                    report_error(&LogError::from(format!("[fast_log] lock fail: {}", e)));
+                    None
+                }
+            },
+        };
+        self.reopen_moved();
+        if self.lock.is_some() {
+            //other processes may have appended
+            let len = self.file.borrow().len();
+            let _ = self.file.borrow().seek(SeekFrom::Start(len as u64));
+            self.temp_bytes.store(len, Ordering::SeqCst);
+        }
         //if temp_bytes is full,must send pack
         let mut temp = String::with_capacity(records.len() * 10);
-        for x in records {
+        //the index of the first record in temp
+        let mut first = 0;
+        for (i, x) in records.iter().enumerate() {
             match x.command {
                 Command::CommandRecord => {
+                    if let Some(period) = &self.period {
+                        let (start, end) = period.range(x.now);
+                        if start >= self.period_range.get().1 {
+                            self.write_temp(&temp, &records[first..i]);
+                            temp.clear();
+                            first = i;
+                            if self.temp_bytes.load(Ordering::Relaxed) != 0 {
+                                self.send_period_pack(period);
+                            }
+                            self.period_range.set((start, end));
+                        }
+                    }
                     if (self.temp_bytes.load(Ordering::Relaxed)
                         + temp.as_bytes().len()
                         + x.formated.as_bytes().len())
                         >= self.temp_size.get_len()
                     {
-                        self.temp_bytes.fetch_add(
-                            {
-                                let w = self.file.write(temp.as_bytes());
-                                if let Ok(w) = w {
-                                    w
-                                } else {
-                                    0
-                                }
-                            },
-                            Ordering::SeqCst,
-                        );
+                        self.write_temp(&temp, &records[first..i]);
                         temp.clear();
+                        first = i;
                         self.send_pack();
                     }
                     temp.push_str(x.formated.as_str());
                 }
                 Command::CommandExit => {}
+                Command::CommandAddAppender(_) | Command::CommandRemoveAppender(_) => {}
+                Command::CommandRotate => {
+                    self.write_temp(&temp, &records[first..i]);
+                    temp.clear();
+                    first = i;
+                    if self.temp_bytes.load(Ordering::Relaxed) != 0 {
+                        match &self.period {
+                            Some(period) => self.send_period_pack(period),
+                            None => self.send_pack(),
+                        }
+                    }
+                }
                 Command::CommandFlush(ref w) => {
                     let _ = self.sender.send(LogPack {
                         dir: "".to_string(),
@@ -387,19 +813,7 @@ impl<F: SplitFile> LogAppender for FileSplitAppender<F> {
                 }
             }
         }
-        if !temp.is_empty() {
-            let _ = self.temp_bytes.fetch_add(
-                {
-                    let w = self.file.write(temp.as_bytes());
-                    if let Ok(w) = w {
-                        w
-                    } else {
-                        0
-                    }
-                },
-                Ordering::SeqCst,
-            );
-        }
+        self.write_temp(&temp, &records[first..]);
     }
 }
 
@@ -409,20 +823,40 @@ fn spawn_saver<R: Keep + 'static>(
     r: Receiver<LogPack>,
     rolling_type: R,
     packer: Arc<Box<dyn Packer>>,
+    hooks: Arc<Mutex<Vec<PackHook>>>,
 ) {
     std::thread::spawn(move || {
         loop {
             if let Ok(pack) = r.recv() {
                 if pack.wg.is_some() {
-                    return;
+                    //flush: all packs before it are done, drop wg to notify
+                    continue;
                 }
                 let log_file_path = pack.new_log_name.clone();
                 //do save pack
-                let remove = pack.do_pack(packer.as_ref());
-                if let Ok(remove) = remove {
-                    if remove {
-                        let _ = std::fs::remove_file(log_file_path);
+                match pack.pack(packer.as_ref().as_ref()) {
+                    Ok(packed) => {
+                        if packed != Packed::Kept {
+                            let _ = std::fs::remove_file(&log_file_path);
+                        }
+                        let hooks = hooks.lock();
+                        match packed.path(Path::new(&log_file_path)) {
+                            Some(archive) => {
+                                for hook in hooks.iter() {
+                                    hook(&archive);
+                                }
+                            }
+                            None if !hooks.is_empty() => report_error(&LogError::from(format!(
+                                "[fast_log] pack hooks skipped, the {} packer does not return its archive from Packer::pack",
+                                packer.pack_name()
+                            ))),
+                            None => {}
+                        }
                     }
+                    Err(e) => report_error(&LogError::from(format!(
+                        "[fast_log] pack {} fail: {}",
+                        log_file_path, e
+                    ))),
                 }
                 //do rolling
                 rolling_type.do_keep(&pack.dir, &temp_name);
@@ -433,6 +867,17 @@ fn spawn_saver<R: Keep + 'static>(
     });
 }
 
+/// the extension of checksum sidecars, see plugin::integrity
+pub(crate) const CHECKSUM_EXT: &str = ".sha256";
+
+/// remove a pack and its checksum sidecar
+fn remove_pack(path: &Path) {
+    let _ = std::fs::remove_file(path);
+    let mut sidecar = path.as_os_str().to_os_string();
+    sidecar.push(CHECKSUM_EXT);
+    let _ = std::fs::remove_file(sidecar);
+}
+
 fn get_base_name(path: &str) -> String {
     let file_name = path.extract_file_name();
     let p = file_name.rfind(".");
@@ -0,0 +1,355 @@
+use crate::appender::{Command, FastLogRecord, LogAppender};
+use crate::error::LogError;
+use crate::plugin::base64;
+use crate::plugin::msgpack::{write_array_len, write_map_len, write_str, write_uint, Cursor};
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+/// keys of the record map, key_values with the same key are left out
+const KEYS: [&str; 12] = [
+    "message",
+    "level",
+    "target",
+    "module_path",
+    "file",
+    "line",
+    "thread",
+    "host",
+    "pid",
+    "seq",
+    "trace_id",
+    "span_id",
+];
+
+/// send records to fluentd(or fluent-bit) `in_forward` over tcp, in the msgpack forward mode
+/// `[tag, [[time, record], ...], option]`. the record map has `message` `level` `target` `module_path`
+/// `file` `line` `thread` `host` `pid` `seq`, `trace_id` `span_id` if set, the key_values and the fields.
+///
+/// with `require_ack(true)` every message carries a chunk id and is resent until fluentd acks it.
+/// messages are buffered while disconnected and the connection is retried with backoff
+/// for example:
+/// ```rust,no_run
+/// use fast_log::Config;
+/// use fast_log::plugin::fluentd::FluentdAppender;
+/// fast_log::init(Config::new().add_appender(
+///     FluentdAppender::new("127.0.0.1:24224", "app")
+///         .target_tag(true)
+///         .field("pod", "web-0")
+///         .require_ack(true),
+/// ))
+/// .unwrap();
+/// ```
+pub struct FluentdAppender {
+    addr: String,
+    tag: String,
+    target_tag: bool,
+    fields: Vec<(String, String)>,
+    require_ack: bool,
+    ack_timeout: Duration,
+    conn: RefCell<Option<TcpStream>>,
+    /// encoded messages not written(or not acked) yet, with their chunk id
+    buffer: RefCell<VecDeque<(String, Vec<u8>)>>,
+    buffer_len: usize,
+    dropped: Cell<u64>,
+    backoff: Cell<Duration>,
+    max_backoff: Duration,
+    next_connect: Cell<Option<Instant>>,
+}
+
+impl FluentdAppender {
+    /// `addr` of the forward input(default port 24224), `tag` of the events
+    pub fn new(addr: &str, tag: &str) -> Self {
+        Self {
+            addr: addr.to_string(),
+            tag: tag.to_string(),
+            target_tag: false,
+            fields: vec![],
+            require_ack: false,
+            ack_timeout: Duration::from_secs(5),
+            conn: RefCell::new(None),
+            buffer: RefCell::new(VecDeque::new()),
+            buffer_len: 1000,
+            dropped: Cell::new(0),
+            backoff: Cell::new(Duration::from_millis(100)),
+            max_backoff: Duration::from_secs(30),
+            next_connect: Cell::new(None),
+        }
+    }
+
+    /// append the target to the tag, `app` and target `my_app::db` is tagged `app.my_app.db`
+    pub fn target_tag(mut self, enable: bool) -> Self {
+        self.target_tag = enable;
+        self
+    }
+
+    /// add a field to every record, for example the pod or namespace
+    pub fn field(mut self, key: &str, value: &str) -> Self {
+        self.fields.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// wait for the ack of every message, resend it on reconnect if none arrives. default false
+    pub fn require_ack(mut self, enable: bool) -> Self {
+        self.require_ack = enable;
+        self
+    }
+
+    /// max wait for an ack, default 5s
+    pub fn ack_timeout(mut self, timeout: Duration) -> Self {
+        self.ack_timeout = timeout;
+        self
+    }
+
+    /// max messages(one per tag of a batch) buffered while disconnected, the oldest are dropped. default 1000
+    pub fn buffer_len(mut self, len: usize) -> Self {
+        self.buffer_len = len.max(1);
+        self
+    }
+
+    /// max wait between reconnects, default 30s
+    pub fn max_backoff(mut self, max: Duration) -> Self {
+        self.max_backoff = max;
+        self
+    }
+
+    /// messages dropped because the buffer was full
+    pub fn dropped(&self) -> u64 {
+        self.dropped.get()
+    }
+
+    fn tag(&self, target: &str) -> String {
+        if !self.target_tag || target.is_empty() {
+            return self.tag.clone();
+        }
+        format!("{}.{}", self.tag, target.replace("::", "."))
+    }
+
+    /// encode a forward mode message of the records of one tag
+    fn message(&self, tag: &str, records: &[&FastLogRecord], chunk: &str) -> Vec<u8> {
+        let mut out = Vec::with_capacity(256 * records.len());
+        write_array_len(&mut out, 3);
+        write_str(&mut out, tag);
+        write_array_len(&mut out, records.len() as u32);
+        for x in records {
+            write_array_len(&mut out, 2);
+            //EventTime, ext type 0 of seconds and nanoseconds
+            let time = x.now.duration_since(UNIX_EPOCH).unwrap_or_default();
+            out.extend_from_slice(&[0xd7, 0x00]);
+            out.extend_from_slice(&(time.as_secs() as u32).to_be_bytes());
+            out.extend_from_slice(&time.subsec_nanos().to_be_bytes());
+            self.record(&mut out, x);
+        }
+        write_map_len(&mut out, 1 + self.require_ack as u32);
+        write_str(&mut out, "size");
+        write_uint(&mut out, records.len() as u64);
+        if self.require_ack {
+            write_str(&mut out, "chunk");
+            write_str(&mut out, chunk);
+        }
+        out
+    }
+
+    fn record(&self, out: &mut Vec<u8>, x: &FastLogRecord) {
+        let extra = x
+            .key_values
+            .iter()
+            .chain(self.fields.iter())
+            .filter(|(k, _)| !KEYS.contains(&k.as_str()));
+        let len = 9
+            + x.line.is_some() as usize
+            + x.trace_id.is_some() as usize
+            + x.span_id.is_some() as usize
+            + extra.clone().count();
+        write_map_len(out, len as u32);
+        write_str(out, "message");
+        write_str(out, &x.args);
+        write_str(out, "level");
+        write_str(out, x.level.as_str());
+        write_str(out, "target");
+        write_str(out, &x.target);
+        write_str(out, "module_path");
+        write_str(out, &x.module_path);
+        write_str(out, "file");
+        write_str(out, &x.file);
+        if let Some(line) = x.line {
+            write_str(out, "line");
+            write_uint(out, line as u64);
+        }
+        write_str(out, "thread");
+        write_str(out, &x.thread_name);
+        write_str(out, "host");
+        write_str(out, &x.hostname);
+        write_str(out, "pid");
+        write_uint(out, x.pid as u64);
+        write_str(out, "seq");
+        write_uint(out, x.seq);
+        if let Some(trace_id) = &x.trace_id {
+            write_str(out, "trace_id");
+            write_str(out, trace_id);
+        }
+        if let Some(span_id) = &x.span_id {
+            write_str(out, "span_id");
+            write_str(out, span_id);
+        }
+        for (k, v) in extra {
+            write_str(out, k);
+            write_str(out, v);
+        }
+    }
+
+    fn connect(&self) -> std::io::Result<TcpStream> {
+        let mut last_err = std::io::Error::new(
+            std::io::ErrorKind::AddrNotAvailable,
+            format!("[fast_log] no address of {}", self.addr),
+        );
+        for addr in self.addr.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&addr, Duration::from_secs(3)) {
+                Ok(stream) => {
+                    let _ = stream.set_nodelay(true);
+                    stream.set_read_timeout(Some(self.ack_timeout))?;
+                    return Ok(stream);
+                }
+                Err(e) => last_err = e,
+            }
+        }
+        Err(last_err)
+    }
+
+    /// make sure connected, return false if not connected and not time to retry
+    fn ensure_conn(&self) -> bool {
+        if self.conn.borrow().is_some() {
+            return true;
+        }
+        if let Some(next) = self.next_connect.get() {
+            if Instant::now() < next {
+                return false;
+            }
+        }
+        match self.connect() {
+            Ok(conn) => {
+                *self.conn.borrow_mut() = Some(conn);
+                self.backoff.set(Duration::from_millis(100));
+                self.next_connect.set(None);
+                true
+            }
+            Err(_) => {
+                let backoff = self.backoff.get();
+                self.next_connect.set(Some(Instant::now() + backoff));
+                self.backoff.set((backoff * 2).min(self.max_backoff));
+                false
+            }
+        }
+    }
+
+    /// send the buffered messages in order, stop at the first failure
+    fn send_buffer(&self) {
+        let mut buffer = self.buffer.borrow_mut();
+        while let Some((chunk, data)) = buffer.front() {
+            if !self.ensure_conn() {
+                return;
+            }
+            let mut conn = self.conn.borrow_mut();
+            let stream = conn.as_mut().unwrap();
+            let mut result = stream.write_all(data).map_err(LogError::from);
+            if result.is_ok() && self.require_ack {
+                result = read_ack(stream, chunk);
+            }
+            match result {
+                Ok(_) => {
+                    buffer.pop_front();
+                }
+                Err(e) => {
+                    crate::report_error(&LogError::from(format!(
+                        "[fast_log] fluentd send to {} fail: {}",
+                        self.addr, e
+                    )));
+                    //reconnect and resend later
+                    *conn = None;
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// read the `{"ack": chunk}` response of a message
+fn read_ack(stream: &mut TcpStream, chunk: &str) -> Result<(), LogError> {
+    let mut data = Vec::with_capacity(64);
+    let mut buf = [0u8; 256];
+    loop {
+        let n = stream.read(&mut buf)?;
+        if n == 0 {
+            return Err(LogError::from("connection closed before ack"));
+        }
+        data.extend_from_slice(&buf[..n]);
+        match parse_ack(&data) {
+            Ok(ack) if ack == chunk => return Ok(()),
+            Ok(ack) => return Err(LogError::from(format!("ack {} of chunk {}", ack, chunk))),
+            //the rest of the response has not arrived yet
+            Err(_) if data.len() < 4096 => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+fn parse_ack(data: &[u8]) -> Result<String, LogError> {
+    let mut c = Cursor::new(data);
+    let mut ack = None;
+    for _ in 0..c.map_len()? {
+        match c.str()? {
+            "ack" => ack = Some(c.str()?.to_string()),
+            _ => c.skip()?,
+        }
+    }
+    ack.ok_or_else(|| LogError::from("response without ack"))
+}
+
+/// a unique chunk id, base64 of 16 bytes
+fn chunk_id() -> String {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    let nanos = UNIX_EPOCH.elapsed().unwrap_or_default().as_nanos() as u64;
+    let mut id = [0u8; 16];
+    id[..8].copy_from_slice(&nanos.to_be_bytes());
+    id[8..12].copy_from_slice(&std::process::id().to_be_bytes());
+    id[12..].copy_from_slice(&(NEXT.fetch_add(1, Ordering::Relaxed) as u32).to_be_bytes());
+    base64::encode(&id)
+}
+
+impl LogAppender for FluentdAppender {
+    fn do_logs(&self, records: &[FastLogRecord]) {
+        //(tag, records) in the order tags first appear
+        let mut tags: Vec<(String, Vec<&FastLogRecord>)> = vec![];
+        for x in records {
+            match x.command {
+                Command::CommandRecord => {
+                    let tag = self.tag(&x.target);
+                    match tags.iter_mut().find(|(t, _)| *t == tag) {
+                        Some((_, v)) => v.push(x),
+                        None => tags.push((tag, vec![x])),
+                    }
+                }
+                Command::CommandExit => {}
+                Command::CommandAddAppender(_) | Command::CommandRemoveAppender(_) => {}
+                Command::CommandRotate => {}
+                Command::CommandFlush(_) => {}
+            }
+        }
+        {
+            let mut buffer = self.buffer.borrow_mut();
+            for (tag, records) in tags {
+                if buffer.len() >= self.buffer_len {
+                    buffer.pop_front();
+                    self.dropped.set(self.dropped.get() + 1);
+                }
+                let chunk = chunk_id();
+                let data = self.message(&tag, &records, &chunk);
+                buffer.push_back((chunk, data));
+            }
+        }
+        self.send_buffer();
+    }
+}
@@ -0,0 +1,37 @@
+use crate::appender::{Command, FastLogRecord, LogAppender, RecordFormat};
+
+/// give an appender its own RecordFormat instead of the Config format
+/// for example json into the file and the default format on the console:
+/// ```rust,no_run
+/// use fast_log::{Config, FastLogFormatJson};
+/// use fast_log::plugin::file::FileAppender;
+/// use fast_log::plugin::format::FormatAppender;
+/// fast_log::init(Config::new().console().add_appender(FormatAppender::new(
+///     FileAppender::new("target/logs/app.json").unwrap(),
+///     FastLogFormatJson::new(),
+/// )))
+/// .unwrap();
+/// ```
+pub struct FormatAppender<A: LogAppender, F: RecordFormat> {
+    appender: A,
+    format: F,
+}
+
+impl<A: LogAppender, F: RecordFormat> FormatAppender<A, F> {
+    pub fn new(appender: A, format: F) -> Self {
+        Self { appender, format }
+    }
+}
+
+impl<A: LogAppender, F: RecordFormat> LogAppender for FormatAppender<A, F> {
+    fn do_logs(&self, records: &[FastLogRecord]) {
+        let mut records = records.to_vec();
+        for x in &mut records {
+            //records of fast_log::print() have no metadata, keep the raw text
+            if x.command == Command::CommandRecord && !x.module_path.is_empty() {
+                self.format.do_format(x);
+            }
+        }
+        self.appender.do_logs(&records);
+    }
+}
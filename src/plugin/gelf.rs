@@ -0,0 +1,234 @@
+use crate::appender::{Command, FastLogRecord, LogAppender};
+use crate::error::LogError;
+use crate::fast_log::hostname;
+use crate::formats::escape_json;
+use crate::plugin::syslog::severity;
+use crate::report_error;
+use std::cell::{Cell, RefCell};
+use std::io::Write;
+use std::net::{TcpStream, UdpSocket};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// max chunks of one udp message, see GELF spec
+const MAX_CHUNKS: usize = 128;
+/// chunk header: magic(2) + message id(8) + sequence number(1) + sequence count(1)
+const CHUNK_HEADER: usize = 12;
+
+/// where GELF messages are sent
+#[derive(Clone, Debug)]
+pub enum GelfTransport {
+    /// udp address, large messages are chunked
+    Udp(String),
+    /// tcp address, messages are null byte delimited and never compressed
+    Tcp(String),
+}
+
+/// send records to graylog as GELF 1.1
+/// for example:
+/// ```rust,no_run
+/// use fast_log::Config;
+/// use fast_log::plugin::gelf::{GelfAppender, GelfTransport};
+/// fast_log::init(Config::new().add_appender(
+///     GelfAppender::new(GelfTransport::Udp("127.0.0.1:12201".to_string()))
+///         .tcp_fallback("127.0.0.1:12201")
+///         .field("app", "my_app"),
+/// ))
+/// .unwrap();
+/// ```
+pub struct GelfAppender {
+    transport: GelfTransport,
+    tcp_fallback: Option<String>,
+    host: String,
+    fields: Vec<(String, String)>,
+    chunk_size: usize,
+    #[cfg(feature = "gzip")]
+    compress: bool,
+    udp: RefCell<Option<UdpSocket>>,
+    tcp: RefCell<Option<TcpStream>>,
+    message_id: Cell<u64>,
+}
+
+impl GelfAppender {
+    pub fn new(transport: GelfTransport) -> Self {
+        Self {
+            transport,
+            tcp_fallback: None,
+            host: hostname(),
+            fields: vec![],
+            chunk_size: 1420,
+            #[cfg(feature = "gzip")]
+            compress: false,
+            udp: RefCell::new(None),
+            tcp: RefCell::new(None),
+            message_id: Cell::new(
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_nanos() as u64,
+            ),
+        }
+    }
+
+    /// set `host` field, default the hostname of the os
+    pub fn host(mut self, host: &str) -> Self {
+        self.host = host.to_string();
+        self
+    }
+
+    /// add an additional field, `_` is prepended to the key
+    pub fn field(mut self, key: &str, value: &str) -> Self {
+        self.fields.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// max udp datagram size, default 1420 (fits the usual ethernet mtu)
+    pub fn chunk_size(mut self, size: usize) -> Self {
+        self.chunk_size = size.max(CHUNK_HEADER + 1);
+        self
+    }
+
+    /// send udp messages too large for 128 chunks over tcp instead of dropping them
+    pub fn tcp_fallback(mut self, addr: &str) -> Self {
+        self.tcp_fallback = Some(addr.to_string());
+        self
+    }
+
+    /// zlib compress udp messages
+    #[cfg(feature = "gzip")]
+    pub fn compress(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
+    }
+
+    /// format record into a GELF json message
+    pub fn message(&self, record: &FastLogRecord) -> String {
+        let (short, full) = match record.args.split_once('\n') {
+            None => (record.args.as_str(), None),
+            Some((short, _)) => (short, Some(record.args.as_str())),
+        };
+        let time = record.now.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let mut msg = format!(
+            "{{\"version\":\"1.1\",\"host\":\"{}\",\"short_message\":\"{}\"",
+            escape_json(&self.host),
+            escape_json(short)
+        );
+        if let Some(full) = full {
+            msg.push_str(&format!(",\"full_message\":\"{}\"", escape_json(full)));
+        }
+        msg.push_str(&format!(
+            ",\"timestamp\":{}.{:03},\"level\":{}",
+            time.as_secs(),
+            time.subsec_millis(),
+            severity(record.level)
+        ));
+        msg.push_str(&format!(
+            ",\"_target\":\"{}\",\"_module_path\":\"{}\",\"_file\":\"{}\"",
+            escape_json(&record.target),
+            escape_json(&record.module_path),
+            escape_json(&record.file)
+        ));
+        if let Some(line) = record.line {
+            msg.push_str(&format!(",\"_line\":{}", line));
+        }
+        for (k, v) in &self.fields {
+            msg.push_str(&format!(",\"_{}\":\"{}\"", escape_json(k), escape_json(v)));
+        }
+        msg.push('}');
+        msg
+    }
+
+    fn send_udp(&self, addr: &str, msg: &str) -> std::io::Result<()> {
+        let data = self.encode(msg.as_bytes());
+        let mut udp = self.udp.borrow_mut();
+        if udp.is_none() {
+            let socket = UdpSocket::bind("0.0.0.0:0")?;
+            socket.connect(addr)?;
+            *udp = Some(socket);
+        }
+        let socket = udp.as_ref().unwrap();
+        if data.len() <= self.chunk_size {
+            socket.send(&data)?;
+            return Ok(());
+        }
+        let body = self.chunk_size - CHUNK_HEADER;
+        let count = data.len().div_ceil(body);
+        if count > MAX_CHUNKS {
+            drop(udp);
+            return match &self.tcp_fallback {
+                None => Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "[fast_log] gelf message too large",
+                )),
+                Some(addr) => self.send_tcp(addr, msg),
+            };
+        }
+        let id = self.message_id.get().wrapping_add(1);
+        self.message_id.set(id);
+        for (seq, chunk) in data.chunks(body).enumerate() {
+            let mut datagram = Vec::with_capacity(CHUNK_HEADER + chunk.len());
+            datagram.extend_from_slice(&[0x1e, 0x0f]);
+            datagram.extend_from_slice(&id.to_be_bytes());
+            datagram.push(seq as u8);
+            datagram.push(count as u8);
+            datagram.extend_from_slice(chunk);
+            socket.send(&datagram)?;
+        }
+        Ok(())
+    }
+
+    fn send_tcp(&self, addr: &str, msg: &str) -> std::io::Result<()> {
+        let mut tcp = self.tcp.borrow_mut();
+        if tcp.is_none() {
+            *tcp = Some(TcpStream::connect(addr)?);
+        }
+        let mut data = msg.as_bytes().to_vec();
+        data.push(0);
+        let result = tcp.as_mut().unwrap().write_all(&data);
+        if result.is_err() {
+            //reconnect next time
+            *tcp = None;
+        }
+        result
+    }
+
+    #[cfg(feature = "gzip")]
+    fn encode(&self, data: &[u8]) -> Vec<u8> {
+        if !self.compress {
+            return data.to_vec();
+        }
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        let _ = encoder.write_all(data);
+        encoder.finish().unwrap_or_else(|_| data.to_vec())
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    fn encode(&self, data: &[u8]) -> Vec<u8> {
+        data.to_vec()
+    }
+}
+
+impl LogAppender for GelfAppender {
+    fn do_logs(&self, records: &[FastLogRecord]) {
+        for x in records {
+            match x.command {
+                Command::CommandRecord => {
+                    let msg = self.message(x);
+                    let result = match &self.transport {
+                        GelfTransport::Udp(addr) => self.send_udp(addr, &msg),
+                        GelfTransport::Tcp(addr) => self
+                            .send_tcp(addr, &msg)
+                            .or_else(|_| self.send_tcp(addr, &msg)),
+                    };
+                    if let Err(e) = result {
+                        report_error(&LogError::from(format!("[fast_log] gelf send fail: {}", e)));
+                    }
+                }
+                Command::CommandExit => {}
+                Command::CommandAddAppender(_) | Command::CommandRemoveAppender(_) => {}
+                Command::CommandRotate => {}
+                Command::CommandFlush(_) => {}
+            }
+        }
+    }
+}
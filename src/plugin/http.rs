@@ -0,0 +1,196 @@
+use crate::error::LogError;
+use crate::report_error;
+use isahc::config::Configurable;
+use isahc::{HttpClient, ReadResponseExt, Request};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// request settings of the batching http appenders
+#[derive(Clone, Debug)]
+pub(crate) struct HttpOptions {
+    pub retries: u32,
+    pub backoff: Duration,
+    pub timeout: Duration,
+    /// max bytes of records waiting to be sent(queued or being retried)
+    pub max_in_flight: usize,
+    #[cfg(feature = "gzip")]
+    pub gzip: bool,
+}
+
+impl Default for HttpOptions {
+    fn default() -> Self {
+        Self {
+            retries: 3,
+            backoff: Duration::from_millis(500),
+            timeout: Duration::from_secs(10),
+            max_in_flight: 16 * 1024 * 1024,
+            #[cfg(feature = "gzip")]
+            gzip: false,
+        }
+    }
+}
+
+impl HttpOptions {
+    /// `Content-Encoding: gzip` when the body is gzipped
+    pub fn content_encoding(&self) -> Option<(String, String)> {
+        #[cfg(feature = "gzip")]
+        if self.gzip {
+            return Some(("Content-Encoding".to_string(), "gzip".to_string()));
+        }
+        None
+    }
+}
+
+/// http client shared by the http appenders
+pub(crate) struct Http {
+    client: HttpClient,
+    pub timeout: Duration,
+    pub retries: u32,
+    pub backoff: Duration,
+}
+
+impl Http {
+    pub fn new() -> Result<Self, LogError> {
+        Ok(Self {
+            client: HttpClient::new().map_err(|e| LogError::from(e.to_string()))?,
+            timeout: Duration::from_secs(10),
+            retries: 3,
+            backoff: Duration::from_millis(500),
+        })
+    }
+
+    /// a client with the retries and timeout of `options`
+    pub fn with_options(options: &HttpOptions) -> Result<Self, LogError> {
+        let mut http = Self::new()?;
+        http.retries = options.retries;
+        http.backoff = options.backoff;
+        http.timeout = options.timeout;
+        Ok(http)
+    }
+
+    /// encode the body, gzip it if `options.gzip`
+    #[cfg(feature = "gzip")]
+    pub fn gzip_encoder(options: &HttpOptions) -> fn(Vec<u8>) -> Vec<u8> {
+        fn gzip(data: Vec<u8>) -> Vec<u8> {
+            use std::io::Write;
+            let mut encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            let _ = encoder.write_all(&data);
+            encoder.finish().unwrap_or(data)
+        }
+        match options.gzip {
+            true => gzip,
+            false => std::convert::identity,
+        }
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    pub fn gzip_encoder(_options: &HttpOptions) -> fn(Vec<u8>) -> Vec<u8> {
+        std::convert::identity
+    }
+
+    /// send one request, return the response body when status is 2xx
+    pub fn send(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &[(String, String)],
+        body: &[u8],
+    ) -> Result<Vec<u8>, HttpError> {
+        let mut builder = Request::builder()
+            .method(method)
+            .uri(url)
+            .timeout(self.timeout);
+        for (k, v) in headers {
+            builder = builder.header(k.as_str(), v.as_str());
+        }
+        let request = builder
+            .body(body.to_vec())
+            .map_err(|e| HttpError::Fatal(e.to_string()))?;
+        let mut response = self
+            .client
+            .send(request)
+            .map_err(|e| HttpError::Retry(e.to_string()))?;
+        let status = response.status();
+        let data = response.bytes().unwrap_or_default();
+        if status.is_success() {
+            Ok(data)
+        } else if status.as_u16() == 429 || status.is_server_error() {
+            Err(HttpError::Retry(format!(
+                "http status {} from {}",
+                status, url
+            )))
+        } else {
+            Err(HttpError::Fatal(format!(
+                "http status {} from {}: {}",
+                status,
+                url,
+                String::from_utf8_lossy(&data)
+            )))
+        }
+    }
+
+    /// send with retries, wait `backoff` `backoff*2`... between attempts
+    pub fn send_retry(
+        &self,
+        method: &str,
+        url: &str,
+        headers: &[(String, String)],
+        body: &[u8],
+    ) -> Result<Vec<u8>, LogError> {
+        let mut backoff = self.backoff;
+        let mut attempt = 0;
+        loop {
+            match self.send(method, url, headers, body) {
+                Ok(v) => return Ok(v),
+                Err(HttpError::Retry(_)) if attempt < self.retries => {
+                    std::thread::sleep(backoff);
+                    backoff *= 2;
+                    attempt += 1;
+                }
+                Err(HttpError::Retry(e)) | Err(HttpError::Fatal(e)) => {
+                    return Err(LogError::from(format!("[fast_log] {}", e)));
+                }
+            }
+        }
+    }
+}
+
+pub(crate) enum HttpError {
+    /// network error, 429 or 5xx
+    Retry(String),
+    Fatal(String),
+}
+
+/// bytes of records waiting to be sent, shared by an appender and its batch thread.
+/// records that do not fit in `max` bytes are dropped
+#[derive(Clone, Default)]
+pub(crate) struct InFlight {
+    bytes: Arc<AtomicUsize>,
+    /// records are being dropped, report it once until there is room again
+    dropping: Arc<AtomicBool>,
+}
+
+impl InFlight {
+    /// add `len` bytes, false if they exceed `max`. `name` is the appender in the error
+    pub fn add(&self, len: usize, max: usize, name: &str) -> bool {
+        if self.bytes.load(Ordering::Relaxed) + len > max {
+            if !self.dropping.swap(true, Ordering::Relaxed) {
+                report_error(&LogError::from(format!(
+                    "[fast_log] {} in-flight buffer of {} bytes is full, dropping records",
+                    name, max
+                )));
+            }
+            return false;
+        }
+        self.dropping.store(false, Ordering::Relaxed);
+        self.bytes.fetch_add(len, Ordering::Relaxed);
+        true
+    }
+
+    /// the bytes were sent or given up
+    pub fn remove(&self, len: usize) {
+        self.bytes.fetch_sub(len, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,143 @@
+use crate::error::LogError;
+use crate::plugin::file_split::CHECKSUM_EXT;
+use hmac::{Hmac, Mac};
+use parking_lot::Mutex;
+use sha2::{Digest, Sha256};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::Path;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// you need enable fast_log = { ... ,features=["integrity"]}
+/// tamper-evidence of packs: every pack gets a `<pack>.sha256` sidecar(sha256sum format),
+/// and a line `<pack path> <sha256> <hmac>` is appended to the manifest.
+/// the hmac is HMAC-SHA256(key, previous hmac + pack path + sha256), so edited, removed or
+/// reordered lines are found by `verify`. keep the manifest name apart from the log name, for example `logs/MANIFEST`
+/// ```rust,no_run
+/// use fast_log::consts::LogSize;
+/// use fast_log::plugin::file_split::{FileSplitAppender, KeepType, RawFile};
+/// use fast_log::plugin::integrity::Integrity;
+/// use fast_log::plugin::packer::LogPacker;
+/// let appender = FileSplitAppender::<RawFile>::new("target/logs/", LogSize::MB(100), KeepType::All, Box::new(LogPacker {}))
+///     .unwrap()
+///     .integrity(Integrity::new("target/logs/MANIFEST", b"secret key"));
+/// fast_log::init(fast_log::Config::new().custom(appender)).unwrap();
+/// ```
+pub struct Integrity {
+    manifest: String,
+    key: Vec<u8>,
+    /// the hmac of the last manifest line, read from the manifest on the first record
+    last: Mutex<Option<String>>,
+}
+
+impl Integrity {
+    pub fn new(manifest: &str, key: &[u8]) -> Self {
+        Self {
+            manifest: manifest.to_string(),
+            key: key.to_vec(),
+            last: Mutex::new(None),
+        }
+    }
+
+    /// write the sidecar of `pack` and append it to the manifest
+    pub fn record(&self, pack: &Path) -> Result<(), LogError> {
+        let path = pack.to_str().unwrap_or_default().replace("\\", "/");
+        let sha256 = sha256_file(pack)?;
+        let file_name = path.rsplit('/').next().unwrap_or_default();
+        std::fs::write(
+            format!("{}{}", path, CHECKSUM_EXT),
+            format!("{}  {}\n", sha256, file_name),
+        )?;
+        let mut last = self.last.lock();
+        if last.is_none() {
+            *last = Some(self.read_last()?);
+        }
+        let hmac = self.sign(last.as_deref().unwrap_or_default(), &path, &sha256);
+        let mut manifest = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.manifest)?;
+        manifest.write_all(format!("{} {} {}\n", path, sha256, hmac).as_bytes())?;
+        manifest.flush()?;
+        *last = Some(hmac);
+        Ok(())
+    }
+
+    /// check the hmac chain of the manifest and the sha256 of packs which still exist,
+    /// return the number of lines
+    pub fn verify(&self) -> Result<usize, LogError> {
+        let manifest = std::fs::read_to_string(&self.manifest)?;
+        let mut last = String::new();
+        let mut lines = 0;
+        for line in manifest.lines() {
+            lines += 1;
+            let mut fields = line.rsplitn(3, ' ');
+            let (hmac, sha256, path) = match (fields.next(), fields.next(), fields.next()) {
+                (Some(hmac), Some(sha256), Some(path)) => (hmac, sha256, path),
+                _ => {
+                    return Err(LogError::from(format!(
+                        "[fast_log] bad manifest line {}",
+                        lines
+                    )))
+                }
+            };
+            if self.sign(&last, path, sha256) != hmac {
+                return Err(LogError::from(format!(
+                    "[fast_log] manifest line {} is tampered",
+                    lines
+                )));
+            }
+            if Path::new(path).exists() && sha256_file(Path::new(path))? != sha256 {
+                return Err(LogError::from(format!("[fast_log] {} is tampered", path)));
+            }
+            last = hmac.to_string();
+        }
+        Ok(lines)
+    }
+
+    fn sign(&self, last: &str, path: &str, sha256: &str) -> String {
+        let mut mac = HmacSha256::new_from_slice(&self.key).expect("hmac accepts any key size");
+        mac.update(last.as_bytes());
+        mac.update(path.as_bytes());
+        mac.update(sha256.as_bytes());
+        hex(&mac.finalize().into_bytes())
+    }
+
+    fn read_last(&self) -> Result<String, LogError> {
+        match std::fs::read_to_string(&self.manifest) {
+            Ok(v) => Ok(v
+                .lines()
+                .last()
+                .and_then(|line| line.rsplit(' ').next())
+                .unwrap_or_default()
+                .to_string()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// the sha256 of a file as lowercase hex
+pub fn sha256_file(path: &Path) -> Result<String, LogError> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hex(&hasher.finalize()))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    use std::fmt::Write;
+    let mut s = String::with_capacity(bytes.len() * 2);
+    for b in bytes {
+        let _ = write!(s, "{:02x}", b);
+    }
+    s
+}
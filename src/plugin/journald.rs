@@ -0,0 +1,104 @@
+use crate::appender::{Command, FastLogRecord, LogAppender};
+use crate::error::LogError;
+use crate::plugin::syslog::severity;
+use crate::report_error;
+use std::os::unix::net::UnixDatagram;
+
+/// send records to systemd-journald by the native protocol.
+/// fields: MESSAGE, PRIORITY, SYSLOG_IDENTIFIER, TARGET, CODE_MODULE, CODE_FILE, CODE_LINE
+/// and the key_values of the record(upper case, invalid chars replaced by `_`)
+/// for example:
+/// ```rust,no_run
+/// use fast_log::Config;
+/// use fast_log::plugin::journald::JournaldAppender;
+/// fast_log::init(Config::new().add_appender(JournaldAppender::new("my_service").unwrap())).unwrap();
+/// ```
+pub struct JournaldAppender {
+    socket: UnixDatagram,
+    path: String,
+    identifier: String,
+}
+
+impl JournaldAppender {
+    pub fn new(identifier: &str) -> Result<Self, LogError> {
+        Ok(Self {
+            socket: UnixDatagram::unbound()?,
+            path: "/run/systemd/journal/socket".to_string(),
+            identifier: identifier.to_string(),
+        })
+    }
+
+    /// the journald socket path, default `/run/systemd/journal/socket`
+    pub fn socket(mut self, path: &str) -> Self {
+        self.path = path.to_string();
+        self
+    }
+
+    /// encode record into a journal entry
+    pub fn entry(&self, record: &FastLogRecord) -> Vec<u8> {
+        let mut entry = Vec::with_capacity(record.args.len() + 128);
+        push_field(&mut entry, "MESSAGE", &record.args);
+        push_field(&mut entry, "PRIORITY", &severity(record.level).to_string());
+        push_field(&mut entry, "SYSLOG_IDENTIFIER", &self.identifier);
+        push_field(&mut entry, "TARGET", &record.target);
+        if !record.module_path.is_empty() {
+            push_field(&mut entry, "CODE_MODULE", &record.module_path);
+        }
+        if !record.file.is_empty() {
+            push_field(&mut entry, "CODE_FILE", &record.file);
+        }
+        if let Some(line) = record.line {
+            push_field(&mut entry, "CODE_LINE", &line.to_string());
+        }
+        for (k, v) in &record.key_values {
+            push_field(&mut entry, &field_name(k), v);
+        }
+        entry
+    }
+}
+
+impl LogAppender for JournaldAppender {
+    fn do_logs(&self, records: &[FastLogRecord]) {
+        for x in records {
+            if x.command == Command::CommandRecord {
+                if let Err(e) = self.socket.send_to(&self.entry(x), &self.path) {
+                    report_error(&LogError::from(format!(
+                        "[fast_log] send to journald fail: {}",
+                        e
+                    )));
+                }
+            }
+        }
+    }
+}
+
+/// `KEY=value\n`, or `KEY\n<u64 le length>value\n` if the value contains a newline
+fn push_field(entry: &mut Vec<u8>, key: &str, value: &str) {
+    entry.extend_from_slice(key.as_bytes());
+    if value.contains('\n') {
+        entry.push(b'\n');
+        entry.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    } else {
+        entry.push(b'=');
+    }
+    entry.extend_from_slice(value.as_bytes());
+    entry.push(b'\n');
+}
+
+/// journal field names are `[A-Z0-9_]`, can not start with `_` or a digit
+fn field_name(key: &str) -> String {
+    let name: String = key
+        .chars()
+        .map(|c| match c {
+            'a'..='z' => c.to_ascii_uppercase(),
+            'A'..='Z' | '0'..='9' => c,
+            _ => '_',
+        })
+        .collect();
+    let name = name.trim_start_matches(|c: char| c == '_' || c.is_ascii_digit());
+    if name.is_empty() {
+        "FIELD".to_string()
+    } else {
+        name.to_string()
+    }
+}
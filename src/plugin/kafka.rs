@@ -0,0 +1,264 @@
+use crate::appender::{Command, FastLogRecord, LogAppender};
+use crate::error::LogError;
+use crate::report_error;
+use std::cell::RefCell;
+#[cfg(feature = "kafka")]
+use std::time::Duration;
+
+/// the rdkafka config of RdKafkaProducer::from_config
+#[cfg(feature = "kafka")]
+pub use rdkafka::ClientConfig;
+
+/// called with the undelivered message and the error
+pub type KafkaErrorHandler = Box<dyn Fn(&KafkaMessage, &LogError) + Send>;
+
+/// one message for kafka
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KafkaMessage {
+    pub key: Option<String>,
+    pub payload: String,
+}
+
+/// which field is used as kafka message key
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum KafkaKey {
+    /// no key, messages are spread over partitions
+    #[default]
+    None,
+    /// record target
+    Target,
+    /// record level, for example "INFO"
+    Level,
+}
+
+/// the kafka client. with the `kafka` feature RdKafkaProducer implements it with rdkafka,
+/// or implement it with your client: `send` enqueue a message and `flush` wait for the delivery
+pub trait KafkaProducer: Send {
+    /// enqueue or send one message
+    fn send(&self, topic: &str, message: &KafkaMessage) -> Result<(), LogError>;
+
+    /// wait for all enqueued messages delivered, Err if any of them is not
+    fn flush(&self) -> Result<(), LogError> {
+        Ok(())
+    }
+}
+
+/// publish formatted records to a kafka topic
+/// for example:
+/// ```rust,no_run
+/// use fast_log::Config;
+/// use fast_log::error::LogError;
+/// use fast_log::plugin::kafka::{KafkaAppender, KafkaKey, KafkaMessage, KafkaProducer};
+/// struct MyProducer {}
+/// impl KafkaProducer for MyProducer {
+///     fn send(&self, topic: &str, message: &KafkaMessage) -> Result<(), LogError> {
+///         //call your kafka client
+///         Ok(())
+///     }
+/// }
+/// fast_log::init(Config::new().json().add_appender(
+///     KafkaAppender::new("logs", MyProducer {})
+///         .key(KafkaKey::Target)
+///         .on_error(|msg, e| eprintln!("kafka delivery failed: {} {}", e, msg.payload)),
+/// ))
+/// .unwrap();
+/// ```
+pub struct KafkaAppender<P: KafkaProducer> {
+    topic: String,
+    producer: P,
+    key: KafkaKey,
+    batch_size: usize,
+    /// messages sent since the last flush
+    pending: RefCell<Vec<KafkaMessage>>,
+    on_error: Option<KafkaErrorHandler>,
+}
+
+impl<P: KafkaProducer> KafkaAppender<P> {
+    pub fn new(topic: &str, producer: P) -> Self {
+        Self {
+            topic: topic.to_string(),
+            producer,
+            key: KafkaKey::None,
+            batch_size: 1000,
+            pending: RefCell::new(vec![]),
+            on_error: None,
+        }
+    }
+
+    /// set message key, default KafkaKey::None
+    pub fn key(mut self, key: KafkaKey) -> Self {
+        self.key = key;
+        self
+    }
+
+    /// call `KafkaProducer::flush` after every `size` messages and on log flush. default 1000
+    pub fn batch_size(mut self, size: usize) -> Self {
+        self.batch_size = size.max(1);
+        self
+    }
+
+    /// called when a message can not be delivered. default report_error.
+    /// when `KafkaProducer::flush` fails it is called with every message sent since the last flush
+    pub fn on_error<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&KafkaMessage, &LogError) + Send + 'static,
+    {
+        self.on_error = Some(Box::new(f));
+        self
+    }
+
+    /// make message from record
+    pub fn message(&self, record: &FastLogRecord) -> KafkaMessage {
+        KafkaMessage {
+            key: match self.key {
+                KafkaKey::None => None,
+                KafkaKey::Target => Some(record.target.to_string()),
+                KafkaKey::Level => Some(record.level.to_string()),
+            },
+            payload: record.formated.clone(),
+        }
+    }
+
+    fn report(&self, message: &KafkaMessage, e: &LogError) {
+        match &self.on_error {
+            None => report_error(&LogError::from(format!(
+                "[fast_log] kafka send fail: {}",
+                e
+            ))),
+            Some(f) => f(message, e),
+        }
+    }
+
+    fn flush(&self) {
+        let pending = std::mem::take(&mut *self.pending.borrow_mut());
+        if pending.is_empty() {
+            return;
+        }
+        if let Err(e) = self.producer.flush() {
+            for message in &pending {
+                self.report(message, &e);
+            }
+        }
+    }
+}
+
+impl<P: KafkaProducer> LogAppender for KafkaAppender<P> {
+    fn do_logs(&self, records: &[FastLogRecord]) {
+        for x in records {
+            match x.command {
+                Command::CommandRecord => {
+                    let message = self.message(x);
+                    if let Err(e) = self.producer.send(&self.topic, &message) {
+                        self.report(&message, &e);
+                        continue;
+                    }
+                    self.pending.borrow_mut().push(message);
+                    if self.pending.borrow().len() >= self.batch_size {
+                        self.flush();
+                    }
+                }
+                Command::CommandExit => self.flush(),
+                Command::CommandAddAppender(_) | Command::CommandRemoveAppender(_) => {}
+                Command::CommandRotate => {}
+                Command::CommandFlush(_) => self.flush(),
+            }
+        }
+    }
+}
+
+/// you need enable fast_log = { ... ,features=["kafka"]}
+/// a KafkaProducer of the rdkafka `BaseProducer`(librdkafka is built from source).
+/// messages that failed to be delivered make the next `flush` return Err
+/// ```rust,no_run
+/// use fast_log::Config;
+/// use fast_log::plugin::kafka::{KafkaAppender, RdKafkaProducer};
+/// fast_log::init(Config::new().json().add_appender(KafkaAppender::new(
+///     "logs",
+///     RdKafkaProducer::new("127.0.0.1:9092").unwrap(),
+/// )))
+/// .unwrap();
+/// ```
+#[cfg(feature = "kafka")]
+pub struct RdKafkaProducer {
+    producer: rdkafka::producer::BaseProducer<DeliveryContext>,
+    timeout: Duration,
+}
+
+#[cfg(feature = "kafka")]
+impl RdKafkaProducer {
+    /// `brokers` is `bootstrap.servers`, for example "127.0.0.1:9092"
+    pub fn new(brokers: &str) -> Result<Self, LogError> {
+        let mut config = ClientConfig::new();
+        config.set("bootstrap.servers", brokers);
+        Self::from_config(&config)
+    }
+
+    /// create the producer with your rdkafka config, for example with `security.protocol`
+    pub fn from_config(config: &ClientConfig) -> Result<Self, LogError> {
+        let producer = config
+            .create_with_context(DeliveryContext::default())
+            .map_err(|e| LogError::from(format!("[fast_log] kafka producer fail: {}", e)))?;
+        Ok(Self {
+            producer,
+            timeout: Duration::from_secs(10),
+        })
+    }
+
+    /// the max wait of `flush`, default 10s
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+#[cfg(feature = "kafka")]
+impl KafkaProducer for RdKafkaProducer {
+    fn send(&self, topic: &str, message: &KafkaMessage) -> Result<(), LogError> {
+        //serve the delivery callbacks of messages sent before
+        self.producer.poll(Duration::ZERO);
+        let mut record =
+            rdkafka::producer::BaseRecord::<str, str>::to(topic).payload(message.payload.as_str());
+        if let Some(key) = &message.key {
+            record = record.key(key.as_str());
+        }
+        self.producer
+            .send(record)
+            .map_err(|(e, _)| LogError::from(format!("[fast_log] kafka send fail: {}", e)))
+    }
+
+    fn flush(&self) -> Result<(), LogError> {
+        use rdkafka::producer::Producer;
+        let flushed = self.producer.flush(self.timeout);
+        let failed = std::mem::take(&mut *self.producer.context().failed.lock());
+        flushed.map_err(|e| LogError::from(format!("[fast_log] kafka flush fail: {}", e)))?;
+        match failed.first() {
+            None => Ok(()),
+            Some(e) => Err(LogError::from(format!(
+                "[fast_log] kafka delivery of {} messages fail: {}",
+                failed.len(),
+                e
+            ))),
+        }
+    }
+}
+
+/// collects the errors of failed deliveries until the next flush
+#[cfg(feature = "kafka")]
+#[derive(Default)]
+struct DeliveryContext {
+    failed: parking_lot::Mutex<Vec<String>>,
+}
+
+#[cfg(feature = "kafka")]
+impl rdkafka::ClientContext for DeliveryContext {}
+
+#[cfg(feature = "kafka")]
+impl rdkafka::producer::ProducerContext for DeliveryContext {
+    type DeliveryOpaque = ();
+
+    fn delivery(&self, result: &rdkafka::producer::DeliveryResult<'_>, _: ()) {
+        if let Err((e, _)) = result {
+            self.failed.lock().push(e.to_string());
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use crate::appender::{Command, FastLogRecord, LogAppender};
+use crate::error::LogError;
+use crate::formats::escape_json;
+use crate::plugin::batch::{flush_wait, spawn_batch, BatchMsg};
+use crate::plugin::http::{Http, HttpOptions, InFlight};
+use crate::{report_error, Sender};
+use once_cell::sync::OnceCell;
+use std::collections::BTreeMap;
+use std::time::{Duration, UNIX_EPOCH};
+
+/// one log line of a loki stream
+pub(crate) struct LokiEntry {
+    level: log::Level,
+    /// unix epoch in nanoseconds
+    timestamp: u128,
+    line: String,
+}
+
+/// push records to grafana loki `/loki/api/v1/push`
+/// for example:
+/// ```rust,no_run
+/// use fast_log::Config;
+/// use fast_log::plugin::loki::LokiAppender;
+/// fast_log::init(Config::new().add_appender(
+///     LokiAppender::new("http://127.0.0.1:3100")
+///         .label("app", "my_app")
+///         .label("env", "prod"),
+/// ))
+/// .unwrap();
+/// ```
+pub struct LokiAppender {
+    url: String,
+    labels: BTreeMap<String, String>,
+    level_label: bool,
+    headers: Vec<(String, String)>,
+    batch_size: usize,
+    flush_interval: Duration,
+    options: HttpOptions,
+    in_flight: InFlight,
+    sender: OnceCell<Sender<BatchMsg<LokiEntry>>>,
+}
+
+impl LokiAppender {
+    /// `url` is the loki address, `/loki/api/v1/push` is appended if missing
+    pub fn new(url: &str) -> Self {
+        let mut url = url.trim_end_matches('/').to_string();
+        if !url.ends_with("/loki/api/v1/push") {
+            url.push_str("/loki/api/v1/push");
+        }
+        Self {
+            url,
+            labels: BTreeMap::new(),
+            level_label: true,
+            headers: vec![],
+            batch_size: 1000,
+            flush_interval: Duration::from_secs(1),
+            options: HttpOptions::default(),
+            in_flight: InFlight::default(),
+            sender: OnceCell::new(),
+        }
+    }
+
+    /// add a stream label, for example `label("app", "my_app")`
+    pub fn label(mut self, key: &str, value: &str) -> Self {
+        self.labels.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// add label `level="info"`, default true
+    pub fn level_label(mut self, enable: bool) -> Self {
+        self.level_label = enable;
+        self
+    }
+
+    /// add an http header, for example `X-Scope-OrgID` or `Authorization`
+    pub fn header(mut self, key: &str, value: &str) -> Self {
+        self.headers.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// push when `size` records collected, default 1000
+    pub fn batch_size(mut self, size: usize) -> Self {
+        self.batch_size = size.max(1);
+        self
+    }
+
+    /// push at least every `interval`, default 1s
+    pub fn flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = interval;
+        self
+    }
+
+    /// retry failed pushes(network errors, 429 and 5xx) `retries` times, default 3
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.options.retries = retries;
+        self
+    }
+
+    /// the wait before the first retry, doubled after each retry. default 500ms
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.options.backoff = backoff;
+        self
+    }
+
+    /// timeout of one request, default 10s
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.options.timeout = timeout;
+        self
+    }
+
+    /// max bytes of lines waiting to be pushed(queued or being retried), default 16MB.
+    /// records logged while it is full are dropped
+    pub fn max_in_flight(mut self, bytes: usize) -> Self {
+        self.options.max_in_flight = bytes.max(1);
+        self
+    }
+
+    /// gzip the body, `Content-Encoding: gzip`
+    #[cfg(feature = "gzip")]
+    pub fn gzip(mut self, gzip: bool) -> Self {
+        self.options.gzip = gzip;
+        self
+    }
+
+    fn sender(&self) -> &Sender<BatchMsg<LokiEntry>> {
+        self.sender.get_or_init(|| {
+            let url = self.url.clone();
+            let labels = self.labels.clone();
+            let level_label = self.level_label;
+            let encode = Http::gzip_encoder(&self.options);
+            let mut headers = self.headers.clone();
+            headers.push(("Content-Type".to_string(), "application/json".to_string()));
+            headers.extend(self.options.content_encoding());
+            let in_flight = self.in_flight.clone();
+            let http = Http::with_options(&self.options);
+            spawn_batch(self.batch_size, self.flush_interval, move |entries| {
+                let len: usize = entries.iter().map(|v: &LokiEntry| v.line.len()).sum();
+                let result = match &http {
+                    Ok(http) => {
+                        let body = encode(push_body(&labels, level_label, &entries).into_bytes());
+                        http.send_retry("POST", &url, &headers, &body)
+                    }
+                    Err(e) => Err(LogError::from(format!(
+                        "[fast_log] loki client fail: {}",
+                        e
+                    ))),
+                };
+                if let Err(e) = result {
+                    report_error(&LogError::from(format!("[fast_log] loki push fail: {}", e)));
+                }
+                in_flight.remove(len);
+            })
+        })
+    }
+}
+
+impl LogAppender for LokiAppender {
+    fn do_logs(&self, records: &[FastLogRecord]) {
+        let sender = self.sender();
+        for x in records {
+            match x.command {
+                Command::CommandRecord => {
+                    let line = x.formated.trim_end_matches('\n').to_string();
+                    if !self
+                        .in_flight
+                        .add(line.len(), self.options.max_in_flight, "loki")
+                    {
+                        continue;
+                    }
+                    let _ = sender.send(BatchMsg::Item(LokiEntry {
+                        level: x.level,
+                        timestamp: x
+                            .now
+                            .duration_since(UNIX_EPOCH)
+                            .unwrap_or_default()
+                            .as_nanos(),
+                        line,
+                    }));
+                }
+                Command::CommandExit => flush_wait(sender),
+                Command::CommandAddAppender(_) | Command::CommandRemoveAppender(_) => {}
+                Command::CommandRotate => {}
+                Command::CommandFlush(ref w) => {
+                    let _ = sender.send(BatchMsg::Flush(w.clone()));
+                }
+            }
+        }
+    }
+}
+
+/// build the json body of a push request, one stream per level
+pub(crate) fn push_body(
+    labels: &BTreeMap<String, String>,
+    level_label: bool,
+    entries: &[LokiEntry],
+) -> String {
+    let mut streams: BTreeMap<Option<log::Level>, Vec<&LokiEntry>> = BTreeMap::new();
+    for x in entries {
+        let key = if level_label { Some(x.level) } else { None };
+        streams.entry(key).or_default().push(x);
+    }
+    let mut body = String::from("{\"streams\":[");
+    for (i, (level, entries)) in streams.iter().enumerate() {
+        if i != 0 {
+            body.push(',');
+        }
+        let mut stream = labels.clone();
+        if let Some(level) = level {
+            stream.insert("level".to_string(), level.as_str().to_lowercase());
+        }
+        body.push_str("{\"stream\":{");
+        for (j, (k, v)) in stream.iter().enumerate() {
+            if j != 0 {
+                body.push(',');
+            }
+            body.push_str(&format!("\"{}\":\"{}\"", escape_json(k), escape_json(v)));
+        }
+        body.push_str("},\"values\":[");
+        for (j, x) in entries.iter().enumerate() {
+            if j != 0 {
+                body.push(',');
+            }
+            body.push_str(&format!(
+                "[\"{}\",\"{}\"]",
+                x.timestamp,
+                escape_json(&x.line)
+            ));
+        }
+        body.push_str("]}");
+    }
+    body.push_str("]}");
+    body
+}
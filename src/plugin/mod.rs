@@ -1,9 +1,59 @@
+#[cfg(feature = "admin")]
+pub mod admin;
+pub(crate) mod base64;
+pub(crate) mod batch;
+#[cfg(feature = "cloudwatch")]
+pub mod cloudwatch;
 pub mod console;
+#[cfg(unix)]
+pub mod control;
+#[cfg(feature = "http")]
+pub mod datadog;
+pub mod dedup;
+pub mod email;
+#[cfg(feature = "encrypt")]
+pub mod encrypt;
 pub mod file;
+pub mod file_audit;
+pub mod file_date;
+pub mod file_level;
+pub mod file_lock;
+pub mod file_mode;
 pub mod file_loop;
 #[cfg(feature = "mmap")]
 pub mod file_mmap;
 pub mod file_name;
 pub mod file_rotate;
 pub mod file_split;
+pub mod fluentd;
+pub mod format;
+pub mod gelf;
+#[cfg(feature = "http")]
+pub(crate) mod http;
+#[cfg(feature = "integrity")]
+pub mod integrity;
+#[cfg(unix)]
+pub mod journald;
+pub mod kafka;
+#[cfg(feature = "http")]
+pub mod loki;
+pub mod msgpack;
+#[cfg(feature = "http")]
+pub mod ndjson;
 pub mod packer;
+pub mod ring_buffer;
+#[cfg(feature = "s3")]
+pub mod s3;
+#[cfg(any(feature = "s3", feature = "cloudwatch"))]
+pub(crate) mod sigv4;
+#[cfg(feature = "http")]
+pub mod sentry;
+pub mod syslog;
+pub mod tcp;
+pub mod test;
+pub mod udp;
+#[cfg(feature = "http")]
+pub mod webhook;
+#[cfg(all(windows, feature = "winlog"))]
+pub mod winlog;
+pub mod write_policy;
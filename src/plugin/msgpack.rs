@@ -0,0 +1,455 @@
+use crate::appender::{Command, FastLogRecord, LogAppender};
+use crate::error::LogError;
+use crate::plugin::file::{create_with_mode, file_moved};
+use crate::plugin::file_mode::FileMode;
+use std::cell::RefCell;
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// frames larger than this are treated as a corrupt file by MsgPackReader
+const MAX_FRAME: usize = 64 * 1024 * 1024;
+
+/// append records to a binary file of frames, each frame is a u32 big endian length and a MessagePack map of
+/// `ts`(unix nanos) `level` `target` `msg` `module_path` `file` `line` `seq` `pid` `thread_id` `thread` `host`
+/// `trace_id` `span_id`(if set) and `kv`(a map of the key_values). the records are encoded by the appender,
+/// so the format of Config is not used. read them back with MsgPackReader:
+/// ```rust,no_run
+/// use fast_log::Config;
+/// use fast_log::plugin::msgpack::MsgPackReader;
+/// fast_log::init(Config::new().file_msgpack("target/logs/trace.mpk")).unwrap();
+/// log::info!("captured");
+/// log::logger().flush();
+/// for record in MsgPackReader::open("target/logs/trace.mpk").unwrap() {
+///     let record = record.unwrap();
+///     println!("{} {} {}", record.level, record.target, record.args);
+/// }
+/// ```
+pub struct MsgPackFileAppender {
+    path: String,
+    file: RefCell<BufWriter<File>>,
+    mode: FileMode,
+    buf: RefCell<Vec<u8>>,
+}
+
+impl MsgPackFileAppender {
+    pub fn new(log_file_path: &str) -> Result<MsgPackFileAppender, LogError> {
+        Self::with_mode(log_file_path, FileMode::default())
+    }
+
+    /// create the file and missing dirs with the permissions of `mode`, see FileMode
+    pub fn with_mode(log_file_path: &str, mode: FileMode) -> Result<MsgPackFileAppender, LogError> {
+        let path = log_file_path.replace("\\", "/");
+        let file = open(&mode, &path)?;
+        Ok(Self {
+            path,
+            file: RefCell::new(BufWriter::with_capacity(64 * 1024, file)),
+            mode,
+            buf: RefCell::new(Vec::with_capacity(256)),
+        })
+    }
+}
+
+fn open(mode: &FileMode, path: &str) -> std::io::Result<File> {
+    create_with_mode(mode, path)?;
+    OpenOptions::new().append(true).open(path)
+}
+
+impl LogAppender for MsgPackFileAppender {
+    fn do_logs(&self, records: &[FastLogRecord]) {
+        let mut file = self.file.borrow_mut();
+        let mut buf = self.buf.borrow_mut();
+        if file_moved(file.get_ref(), &self.path) {
+            let _ = file.flush();
+            match open(&self.mode, &self.path) {
+                Ok(new) => *file = BufWriter::with_capacity(64 * 1024, new),
+                Err(e) => crate::report_error(&LogError::from(format!(
+                    "[fast_log] reopen {} fail: {}",
+                    self.path, e
+                ))),
+            }
+        }
+        for x in records {
+            match &x.command {
+                Command::CommandRecord => {
+                    buf.clear();
+                    buf.extend_from_slice(&[0; 4]);
+                    encode(x, &mut buf);
+                    let len = (buf.len() - 4) as u32;
+                    buf[..4].copy_from_slice(&len.to_be_bytes());
+                    if let Err(e) = file.write_all(&buf) {
+                        crate::report_error(&LogError::from(format!(
+                            "[fast_log] write {} fail: {}",
+                            self.path, e
+                        )));
+                    }
+                }
+                Command::CommandExit | Command::CommandFlush(_) => {}
+                Command::CommandAddAppender(_) | Command::CommandRemoveAppender(_) => {}
+                Command::CommandRotate => {}
+            }
+        }
+        //frames are only complete on disk after a flush
+        if let Err(e) = file.flush() {
+            crate::report_error(&LogError::from(format!(
+                "[fast_log] flush {} fail: {}",
+                self.path, e
+            )));
+        }
+        if buf.capacity() > 64 * 1024 {
+            *buf = Vec::with_capacity(256);
+        }
+    }
+}
+
+/// append the MessagePack map of a record to `out`, see MsgPackFileAppender for the keys
+pub fn encode(record: &FastLogRecord, out: &mut Vec<u8>) {
+    let optional = record.trace_id.is_some() as u32 + record.span_id.is_some() as u32;
+    write_map_len(out, 13 + optional);
+    write_str(out, "ts");
+    write_uint(out, unix_nanos(record.now));
+    write_str(out, "level");
+    write_str(out, record.level.as_str());
+    write_str(out, "target");
+    write_str(out, &record.target);
+    write_str(out, "msg");
+    write_str(out, &record.args);
+    write_str(out, "module_path");
+    write_str(out, &record.module_path);
+    write_str(out, "file");
+    write_str(out, &record.file);
+    write_str(out, "line");
+    match record.line {
+        Some(line) => write_uint(out, line as u64),
+        None => out.push(0xc0),
+    }
+    write_str(out, "seq");
+    write_uint(out, record.seq);
+    write_str(out, "pid");
+    write_uint(out, record.pid as u64);
+    write_str(out, "thread_id");
+    write_uint(out, record.thread_id);
+    write_str(out, "thread");
+    write_str(out, &record.thread_name);
+    write_str(out, "host");
+    write_str(out, &record.hostname);
+    if let Some(trace_id) = &record.trace_id {
+        write_str(out, "trace_id");
+        write_str(out, trace_id);
+    }
+    if let Some(span_id) = &record.span_id {
+        write_str(out, "span_id");
+        write_str(out, span_id);
+    }
+    write_str(out, "kv");
+    write_map_len(out, record.key_values.len() as u32);
+    for (k, v) in &record.key_values {
+        write_str(out, k);
+        write_str(out, v);
+    }
+}
+
+pub(crate) fn write_map_len(out: &mut Vec<u8>, len: u32) {
+    if len < 16 {
+        out.push(0x80 | len as u8);
+    } else if len <= u16::MAX as u32 {
+        out.push(0xde);
+        out.extend_from_slice(&(len as u16).to_be_bytes());
+    } else {
+        out.push(0xdf);
+        out.extend_from_slice(&len.to_be_bytes());
+    }
+}
+
+pub(crate) fn write_array_len(out: &mut Vec<u8>, len: u32) {
+    if len < 16 {
+        out.push(0x90 | len as u8);
+    } else if len <= u16::MAX as u32 {
+        out.push(0xdc);
+        out.extend_from_slice(&(len as u16).to_be_bytes());
+    } else {
+        out.push(0xdd);
+        out.extend_from_slice(&len.to_be_bytes());
+    }
+}
+
+pub(crate) fn write_str(out: &mut Vec<u8>, s: &str) {
+    let len = s.len();
+    if len < 32 {
+        out.push(0xa0 | len as u8);
+    } else if len <= u8::MAX as usize {
+        out.push(0xd9);
+        out.push(len as u8);
+    } else if len <= u16::MAX as usize {
+        out.push(0xda);
+        out.extend_from_slice(&(len as u16).to_be_bytes());
+    } else {
+        out.push(0xdb);
+        out.extend_from_slice(&(len as u32).to_be_bytes());
+    }
+    out.extend_from_slice(s.as_bytes());
+}
+
+pub(crate) fn write_uint(out: &mut Vec<u8>, v: u64) {
+    if v < 128 {
+        out.push(v as u8);
+    } else if v <= u8::MAX as u64 {
+        out.push(0xcc);
+        out.push(v as u8);
+    } else if v <= u16::MAX as u64 {
+        out.push(0xcd);
+        out.extend_from_slice(&(v as u16).to_be_bytes());
+    } else if v <= u32::MAX as u64 {
+        out.push(0xce);
+        out.extend_from_slice(&(v as u32).to_be_bytes());
+    } else {
+        out.push(0xcf);
+        out.extend_from_slice(&v.to_be_bytes());
+    }
+}
+
+/// read the frames of a MsgPackFileAppender file as records(command CommandRecord, formated empty),
+/// so they can be formatted again or sent to other appenders.
+/// a truncated last frame(the process died while writing) is returned as an error
+pub struct MsgPackReader<R: Read> {
+    reader: R,
+    done: bool,
+}
+
+impl MsgPackReader<BufReader<File>> {
+    pub fn open(path: &str) -> Result<Self, LogError> {
+        Ok(Self::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+impl<R: Read> MsgPackReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            done: false,
+        }
+    }
+
+    fn frame(&mut self) -> Result<Option<Vec<u8>>, LogError> {
+        let mut len = [0u8; 4];
+        let mut read = 0;
+        while read < 4 {
+            match self.reader.read(&mut len[read..]) {
+                Ok(0) if read == 0 => return Ok(None),
+                Ok(0) => return Err(LogError::from("[fast_log] truncated msgpack frame")),
+                Ok(n) => read += n,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        let len = u32::from_be_bytes(len) as usize;
+        if len > MAX_FRAME {
+            return Err(LogError::from(format!(
+                "[fast_log] msgpack frame of {} bytes",
+                len
+            )));
+        }
+        let mut frame = vec![0; len];
+        self.reader
+            .read_exact(&mut frame)
+            .map_err(|_| LogError::from("[fast_log] truncated msgpack frame"))?;
+        Ok(Some(frame))
+    }
+}
+
+impl<R: Read> Iterator for MsgPackReader<R> {
+    type Item = Result<FastLogRecord, LogError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = match self.frame() {
+            Ok(None) => None,
+            Ok(Some(frame)) => Some(decode(&frame)),
+            Err(e) => Some(Err(e)),
+        };
+        if !matches!(result, Some(Ok(_))) {
+            self.done = true;
+        }
+        result
+    }
+}
+
+/// decode the MessagePack map written by `encode`, unknown keys are skipped
+pub fn decode(data: &[u8]) -> Result<FastLogRecord, LogError> {
+    let mut c = Cursor::new(data);
+    let mut record = FastLogRecord {
+        command: Command::CommandRecord,
+        level: log::Level::Info,
+        target: "".into(),
+        args: String::new(),
+        module_path: "".into(),
+        file: "".into(),
+        line: None,
+        now: UNIX_EPOCH,
+        formated: String::new(),
+        key_values: vec![],
+        pid: 0,
+        thread_id: 0,
+        thread_name: "".into(),
+        hostname: "".into(),
+        trace_id: None,
+        span_id: None,
+        seq: 0,
+    };
+    for _ in 0..c.map_len()? {
+        match c.str()? {
+            "ts" => record.now = UNIX_EPOCH + Duration::from_nanos(c.uint()?),
+            "level" => {
+                let level = c.str()?;
+                record.level = log::Level::from_str(level)
+                    .map_err(|_| LogError::from(format!("[fast_log] unknown level {}", level)))?;
+            }
+            "target" => record.target = c.str()?.into(),
+            "msg" => record.args = c.str()?.to_string(),
+            "module_path" => record.module_path = c.str()?.into(),
+            "file" => record.file = c.str()?.into(),
+            "line" => record.line = c.nil_or(Cursor::uint)?.map(|v| v as u32),
+            "seq" => record.seq = c.uint()?,
+            "pid" => record.pid = c.uint()? as u32,
+            "thread_id" => record.thread_id = c.uint()?,
+            "thread" => record.thread_name = c.str()?.into(),
+            "host" => record.hostname = c.str()?.into(),
+            "trace_id" => record.trace_id = c.nil_or(Cursor::str)?.map(|v| v.to_string()),
+            "span_id" => record.span_id = c.nil_or(Cursor::str)?.map(|v| v.to_string()),
+            "kv" => {
+                for _ in 0..c.map_len()? {
+                    let k = c.str()?.to_string();
+                    let v = c.str()?.to_string();
+                    record.key_values.push((k, v));
+                }
+            }
+            _ => c.skip()?,
+        }
+    }
+    Ok(record)
+}
+
+/// read MessagePack values from a slice
+pub(crate) struct Cursor<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], LogError> {
+        if self.data.len() - self.pos < n {
+            return Err(LogError::from("[fast_log] truncated msgpack value"));
+        }
+        let v = &self.data[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(v)
+    }
+
+    fn byte(&mut self) -> Result<u8, LogError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn be(&mut self, n: usize) -> Result<u64, LogError> {
+        Ok(self.take(n)?.iter().fold(0, |v, b| (v << 8) | *b as u64))
+    }
+
+    fn unexpected(&self, b: u8) -> LogError {
+        LogError::from(format!(
+            "[fast_log] unexpected msgpack type 0x{:02x} at {}",
+            b,
+            self.pos - 1
+        ))
+    }
+
+    pub fn map_len(&mut self) -> Result<usize, LogError> {
+        match self.byte()? {
+            b @ 0x80..=0x8f => Ok((b & 0x0f) as usize),
+            0xde => Ok(self.be(2)? as usize),
+            0xdf => Ok(self.be(4)? as usize),
+            b => Err(self.unexpected(b)),
+        }
+    }
+
+    pub fn str(&mut self) -> Result<&'a str, LogError> {
+        let len = match self.byte()? {
+            b @ 0xa0..=0xbf => (b & 0x1f) as usize,
+            0xd9 => self.be(1)? as usize,
+            0xda => self.be(2)? as usize,
+            0xdb => self.be(4)? as usize,
+            b => return Err(self.unexpected(b)),
+        };
+        std::str::from_utf8(self.take(len)?)
+            .map_err(|_| LogError::from("[fast_log] msgpack str is not utf-8"))
+    }
+
+    fn uint(&mut self) -> Result<u64, LogError> {
+        match self.byte()? {
+            b @ 0x00..=0x7f => Ok(b as u64),
+            0xcc => self.be(1),
+            0xcd => self.be(2),
+            0xce => self.be(4),
+            0xcf => self.be(8),
+            b => Err(self.unexpected(b)),
+        }
+    }
+
+    fn nil_or<T>(
+        &mut self,
+        f: fn(&mut Self) -> Result<T, LogError>,
+    ) -> Result<Option<T>, LogError> {
+        if self.data.get(self.pos) == Some(&0xc0) {
+            self.pos += 1;
+            return Ok(None);
+        }
+        f(self).map(Some)
+    }
+
+    /// skip a value of any type
+    pub fn skip(&mut self) -> Result<(), LogError> {
+        let b = self.byte()?;
+        //(bytes, nested values) of the value
+        let (bytes, values) = match b {
+            0x00..=0x7f | 0xe0..=0xff | 0xc0 | 0xc2 | 0xc3 => (0, 0),
+            0x80..=0x8f => (0, (b & 0x0f) as usize * 2),
+            0x90..=0x9f => (0, (b & 0x0f) as usize),
+            0xa0..=0xbf => ((b & 0x1f) as usize, 0),
+            0xcc | 0xd0 => (1, 0),
+            0xcd | 0xd1 | 0xd4 => (2, 0),
+            0xd5 => (3, 0),
+            0xca | 0xce | 0xd2 => (4, 0),
+            0xd6 => (5, 0),
+            0xcb | 0xcf | 0xd3 => (8, 0),
+            0xd7 => (9, 0),
+            0xd8 => (17, 0),
+            0xc4 | 0xd9 => (self.be(1)? as usize, 0),
+            0xc5 | 0xda => (self.be(2)? as usize, 0),
+            0xc6 | 0xdb => (self.be(4)? as usize, 0),
+            0xc7 => (self.be(1)? as usize + 1, 0),
+            0xc8 => (self.be(2)? as usize + 1, 0),
+            0xc9 => (self.be(4)? as usize + 1, 0),
+            0xdc => (0, self.be(2)? as usize),
+            0xdd => (0, self.be(4)? as usize),
+            0xde => (0, self.be(2)? as usize * 2),
+            0xdf => (0, self.be(4)? as usize * 2),
+            _ => return Err(self.unexpected(b)),
+        };
+        self.take(bytes)?;
+        for _ in 0..values {
+            self.skip()?;
+        }
+        Ok(())
+    }
+}
+
+fn unix_nanos(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default()
+}
@@ -0,0 +1,190 @@
+use crate::appender::{Command, FastLogRecord, LogAppender, RecordFormat};
+use crate::error::LogError;
+use crate::plugin::base64;
+use crate::plugin::batch::{flush_wait, spawn_batch, BatchMsg};
+use crate::plugin::http::{Http, HttpOptions, InFlight};
+use crate::{report_error, FastLogFormatJson, Sender};
+use once_cell::sync::OnceCell;
+use std::time::Duration;
+
+/// POST records as NDJSON batches(one json object per line) to an http endpoint,
+/// for example a Vector or Fluentd http source or a custom ingestion service.
+///
+/// records are formatted by the appender(default FastLogFormatJson, not the format of Config),
+/// failed batches are retried with exponential backoff. records waiting to be sent are limited by
+/// `max_in_flight` bytes, records logged while it is full are dropped.
+/// for example:
+/// ```rust,no_run
+/// use fast_log::Config;
+/// use fast_log::plugin::ndjson::HttpAppender;
+/// fast_log::init(Config::new().console().add_appender(
+///     HttpAppender::new("http://127.0.0.1:8080/logs")
+///         .bearer_auth("secret")
+///         .batch_size(500),
+/// ))
+/// .unwrap();
+/// ```
+pub struct HttpAppender {
+    url: String,
+    headers: Vec<(String, String)>,
+    format: Box<dyn RecordFormat>,
+    batch_size: usize,
+    flush_interval: Duration,
+    options: HttpOptions,
+    in_flight: InFlight,
+    sender: OnceCell<Sender<BatchMsg<String>>>,
+}
+
+impl HttpAppender {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            headers: vec![],
+            format: Box::new(FastLogFormatJson::new()),
+            batch_size: 1000,
+            flush_interval: Duration::from_secs(1),
+            options: HttpOptions::default(),
+            in_flight: InFlight::default(),
+            sender: OnceCell::new(),
+        }
+    }
+
+    /// add an http header, for example `X-Api-Key`
+    pub fn header(mut self, key: &str, value: &str) -> Self {
+        self.headers.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// `Authorization: Bearer <token>`
+    pub fn bearer_auth(self, token: &str) -> Self {
+        self.header("Authorization", &format!("Bearer {}", token))
+    }
+
+    /// `Authorization: Basic <base64(user:password)>`
+    pub fn basic_auth(self, user: &str, password: &str) -> Self {
+        let token = base64::encode(format!("{}:{}", user, password).as_bytes());
+        self.header("Authorization", &format!("Basic {}", token))
+    }
+
+    /// the format of one line, it must not contain newlines. default FastLogFormatJson
+    pub fn format<F: RecordFormat + 'static>(mut self, format: F) -> Self {
+        self.format = Box::new(format);
+        self
+    }
+
+    /// send when `size` records collected, default 1000
+    pub fn batch_size(mut self, size: usize) -> Self {
+        self.batch_size = size.max(1);
+        self
+    }
+
+    /// send at least every `interval`, default 1s
+    pub fn flush_interval(mut self, interval: Duration) -> Self {
+        self.flush_interval = interval;
+        self
+    }
+
+    /// retry failed batches(network errors, 429 and 5xx) `retries` times, default 3
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.options.retries = retries;
+        self
+    }
+
+    /// the wait before the first retry, doubled after each retry. default 500ms
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.options.backoff = backoff;
+        self
+    }
+
+    /// timeout of one request, default 10s
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.options.timeout = timeout;
+        self
+    }
+
+    /// max bytes of records waiting to be sent(queued or being retried), default 16MB
+    pub fn max_in_flight(mut self, bytes: usize) -> Self {
+        self.options.max_in_flight = bytes.max(1);
+        self
+    }
+
+    /// gzip the body, `Content-Encoding: gzip`
+    #[cfg(feature = "gzip")]
+    pub fn gzip(mut self, gzip: bool) -> Self {
+        self.options.gzip = gzip;
+        self
+    }
+
+    fn sender(&self) -> &Sender<BatchMsg<String>> {
+        self.sender.get_or_init(|| {
+            let url = self.url.clone();
+            let encode = Http::gzip_encoder(&self.options);
+            let mut headers = self.headers.clone();
+            headers.push((
+                "Content-Type".to_string(),
+                "application/x-ndjson".to_string(),
+            ));
+            headers.extend(self.options.content_encoding());
+            let in_flight = self.in_flight.clone();
+            let http = Http::with_options(&self.options);
+            spawn_batch(
+                self.batch_size,
+                self.flush_interval,
+                move |lines: Vec<String>| {
+                    let len: usize = lines.iter().map(|v| v.len()).sum();
+                    let mut body = Vec::with_capacity(len);
+                    for line in &lines {
+                        body.extend_from_slice(line.as_bytes());
+                    }
+                    let result = match &http {
+                        Ok(http) => http.send_retry("POST", &url, &headers, &encode(body)),
+                        Err(e) => Err(LogError::from(format!(
+                            "[fast_log] http client fail: {}",
+                            e
+                        ))),
+                    };
+                    if let Err(e) = result {
+                        report_error(&LogError::from(format!(
+                            "[fast_log] http send {} records to {} fail: {}",
+                            lines.len(),
+                            url,
+                            e
+                        )));
+                    }
+                    in_flight.remove(len);
+                },
+            )
+        })
+    }
+}
+
+impl LogAppender for HttpAppender {
+    fn do_logs(&self, records: &[FastLogRecord]) {
+        let sender = self.sender();
+        for x in records {
+            match &x.command {
+                Command::CommandRecord => {
+                    let mut record = x.clone();
+                    self.format.do_format(&mut record);
+                    let mut line = record.formated;
+                    if !line.ends_with('\n') {
+                        line.push('\n');
+                    }
+                    if !self
+                        .in_flight
+                        .add(line.len(), self.options.max_in_flight, "http")
+                    {
+                        continue;
+                    }
+                    let _ = sender.send(BatchMsg::Item(line));
+                }
+                Command::CommandExit => flush_wait(sender),
+                Command::CommandAddAppender(_) | Command::CommandRemoveAppender(_) => {}
+                Command::CommandRotate => {}
+                Command::CommandFlush(w) => {
+                    let _ = sender.send(BatchMsg::Flush(w.clone()));
+                }
+            }
+        }
+    }
+}
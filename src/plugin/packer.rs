@@ -1,6 +1,7 @@
 use crate::error::LogError;
-use crate::plugin::file_split::Packer;
+use crate::plugin::file_split::{Packed, Packer};
 use std::fs::File;
+use std::path::Path;
 
 /// keep temp{date}.log
 #[derive(Clone)]
@@ -10,9 +11,13 @@ impl Packer for LogPacker {
         "log"
     }
 
-    fn do_pack(&self, _log_file: File, _log_file_path: &str) -> Result<bool, LogError> {
+    fn do_pack(&self, _log_file: File, log_file_path: &str) -> Result<bool, LogError> {
+        Ok(self.pack(Path::new(log_file_path))? != Packed::Kept)
+    }
+
+    fn pack(&self, _log_file: &Path) -> Result<Packed, LogError> {
         //do nothing,and not remove file
-        return Ok(false);
+        Ok(Packed::Kept)
     }
 }
 
@@ -24,6 +29,7 @@ use zip::write::FileOptions;
 /// you need enable fast_log = { ... ,features=["zip"]}
 /// the zip compress
 #[cfg(feature = "zip")]
+#[derive(Clone)]
 pub struct ZipPacker {}
 
 #[cfg(feature = "zip")]
@@ -32,7 +38,13 @@ impl Packer for ZipPacker {
         "zip"
     }
 
-    fn do_pack(&self, mut log_file: File, log_file_path: &str) -> Result<bool, LogError> {
+    fn do_pack(&self, _log_file: File, log_file_path: &str) -> Result<bool, LogError> {
+        Ok(self.pack(Path::new(log_file_path))? != Packed::Kept)
+    }
+
+    fn pack(&self, log_file: &Path) -> Result<Packed, LogError> {
+        let log_file_path = log_file.to_str().unwrap_or_default();
+        let mut log_file = File::open(log_file)?;
         use std::io::Write;
         let mut log_name = log_file_path.replace("\\", "/").to_string();
         if let Some(v) = log_file_path.rfind("/") {
@@ -63,7 +75,7 @@ impl Packer for ZipPacker {
                 finish.err()
             )));
         }
-        return Ok(true);
+        Ok(Packed::Archive(zip_path.into()))
     }
 }
 
@@ -73,6 +85,7 @@ use lz4_flex::frame::FrameEncoder;
 
 /// the zip compress
 #[cfg(feature = "lz4")]
+#[derive(Clone)]
 pub struct LZ4Packer {}
 
 #[cfg(feature = "lz4")]
@@ -81,7 +94,13 @@ impl Packer for LZ4Packer {
         "lz4"
     }
 
-    fn do_pack(&self, mut log_file: File, log_file_path: &str) -> Result<bool, LogError> {
+    fn do_pack(&self, _log_file: File, log_file_path: &str) -> Result<bool, LogError> {
+        Ok(self.pack(Path::new(log_file_path))? != Packed::Kept)
+    }
+
+    fn pack(&self, log_file: &Path) -> Result<Packed, LogError> {
+        let log_file_path = log_file.to_str().unwrap_or_default();
+        let mut log_file = File::open(log_file)?;
         let lz4_path = log_file_path.replace(".log", ".lz4");
         let lz4_file = File::create(&lz4_path);
         if lz4_file.is_err() {
@@ -103,7 +122,7 @@ impl Packer for LZ4Packer {
                 result.err()
             )));
         }
-        return Ok(true);
+        Ok(Packed::Archive(lz4_path.into()))
     }
 }
 
@@ -112,7 +131,10 @@ use flate2::write::GzEncoder;
 #[cfg(feature = "gzip")]
 use flate2::Compression;
 
+/// you need enable fast_log = { ... ,features=["gzip"]}
+/// the gzip compress, write `temp{date}.log.gz` which can be read by most log shippers
 #[cfg(feature = "gzip")]
+#[derive(Clone)]
 pub struct GZipPacker {}
 
 #[cfg(feature = "gzip")]
@@ -121,9 +143,15 @@ impl Packer for GZipPacker {
         "gz"
     }
 
-    fn do_pack(&self, mut log_file: File, log_file_path: &str) -> Result<bool, LogError> {
+    fn do_pack(&self, _log_file: File, log_file_path: &str) -> Result<bool, LogError> {
+        Ok(self.pack(Path::new(log_file_path))? != Packed::Kept)
+    }
+
+    fn pack(&self, log_file: &Path) -> Result<Packed, LogError> {
+        let log_file_path = log_file.to_str().unwrap_or_default();
+        let mut log_file = File::open(log_file)?;
         use std::io::Write;
-        let zip_path = log_file_path.replace(".log", ".gz");
+        let zip_path = format!("{}.gz", log_file_path);
         let zip_file = File::create(&zip_path);
         if zip_file.is_err() {
             return Err(LogError::from(format!(
@@ -144,6 +172,154 @@ impl Packer for GZipPacker {
                 finish.err()
             )));
         }
-        return Ok(true);
+        Ok(Packed::Archive(zip_path.into()))
+    }
+}
+
+/// you need enable fast_log = { ... ,features=["gzip"]}
+/// append every log file into one `{prefix}-YYYY-MM-DD.tar.gz` of the day(local time), less files
+/// for services which rotate often. every log file is a gzip member holding one tar entry, the archive
+/// has no end-of-archive blocks so it can be appended, `tar -xzf` reads it.
+/// use the log name as prefix(for example `temp`) so the rolling(KeepType) can find the archives
+#[cfg(feature = "gzip")]
+#[derive(Clone)]
+pub struct TarGzPacker {
+    pub prefix: String,
+}
+
+#[cfg(feature = "gzip")]
+impl TarGzPacker {
+    pub fn new(prefix: &str) -> Self {
+        Self {
+            prefix: prefix.to_string(),
+        }
+    }
+
+    /// the ustar header of a regular file
+    fn header(name: &str, size: u64, mtime: u64) -> [u8; 512] {
+        let mut header = [0u8; 512];
+        let name = name.as_bytes();
+        let name = &name[..name.len().min(99)];
+        header[..name.len()].copy_from_slice(name);
+        let octal = |header: &mut [u8; 512], at: usize, len: usize, v: u64| {
+            let s = format!("{:0width$o}", v, width = len - 1);
+            header[at..at + len - 1].copy_from_slice(s.as_bytes());
+        };
+        octal(&mut header, 100, 8, 0o644);
+        octal(&mut header, 108, 8, 0);
+        octal(&mut header, 116, 8, 0);
+        octal(&mut header, 124, 12, size);
+        octal(&mut header, 136, 12, mtime);
+        header[156] = b'0';
+        header[257..263].copy_from_slice(b"ustar\0");
+        header[263..265].copy_from_slice(b"00");
+        //the checksum is counted with spaces in its field
+        header[148..156].copy_from_slice(b"        ");
+        let sum: u32 = header.iter().map(|v| *v as u32).sum();
+        let s = format!("{:06o}\0 ", sum);
+        header[148..156].copy_from_slice(s.as_bytes());
+        header
+    }
+
+    fn append(&self, log_file: &Path, archive: &mut File) -> std::io::Result<()> {
+        use std::io::{Read, Write};
+        let file = File::open(log_file)?;
+        let meta = file.metadata()?;
+        let mtime = meta
+            .modified()
+            .ok()
+            .and_then(|v| v.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|v| v.as_secs())
+            .unwrap_or_default();
+        let name = log_file
+            .file_name()
+            .and_then(|v| v.to_str())
+            .unwrap_or_default();
+        let mut gz = GzEncoder::new(archive, Compression::default());
+        gz.write_all(&Self::header(name, meta.len(), mtime))?;
+        let copied = std::io::copy(&mut (&file).take(meta.len()), &mut gz)?;
+        let padding = (512 - copied % 512) % 512;
+        gz.write_all(&vec![0u8; padding as usize])?;
+        gz.finish()?.flush()
+    }
+}
+
+#[cfg(feature = "gzip")]
+impl Packer for TarGzPacker {
+    fn pack_name(&self) -> &'static str {
+        "tar.gz"
+    }
+
+    fn do_pack(&self, _log_file: File, log_file_path: &str) -> Result<bool, LogError> {
+        Ok(self.pack(Path::new(log_file_path))? != Packed::Kept)
+    }
+
+    fn pack(&self, log_file: &Path) -> Result<Packed, LogError> {
+        let dir = log_file.parent().unwrap_or(Path::new(""));
+        let date = crate::clock::local_now().format("YYYY-MM-DD");
+        let archive_path = dir.join(format!("{}-{}.tar.gz", self.prefix, date));
+        let mut archive = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&archive_path)?;
+        let len = archive.metadata()?.len();
+        if let Err(e) = self.append(log_file, &mut archive) {
+            //drop the broken member, so the archive can be appended again
+            let _ = archive.set_len(len);
+            return Err(LogError::from(format!(
+                "[fast_log] append {:?} fail: {}",
+                archive_path, e
+            )));
+        }
+        Ok(Packed::Archive(archive_path))
+    }
+}
+
+/// you need enable fast_log = { ... ,features=["zstd"]}
+/// the zstd compress, write `temp{date}.log.zst`
+#[cfg(feature = "zstd")]
+#[derive(Clone)]
+pub struct ZstdPacker {
+    /// compression level 1..=22, 0 means zstd default(3)
+    pub level: i32,
+}
+
+#[cfg(feature = "zstd")]
+impl ZstdPacker {
+    pub fn new(level: i32) -> Self {
+        Self { level }
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl Default for ZstdPacker {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl Packer for ZstdPacker {
+    fn pack_name(&self) -> &'static str {
+        "zst"
+    }
+
+    fn do_pack(&self, _log_file: File, log_file_path: &str) -> Result<bool, LogError> {
+        Ok(self.pack(Path::new(log_file_path))? != Packed::Kept)
+    }
+
+    fn pack(&self, log_file: &Path) -> Result<Packed, LogError> {
+        let log_file_path = log_file.to_str().unwrap_or_default();
+        let mut log_file = File::open(log_file)?;
+        let zst_path = format!("{}.zst", log_file_path);
+        let zst_file = File::create(&zst_path).map_err(|e| {
+            LogError::from(format!("[fast_log] create(&{}) fail:{}", zst_path, e))
+        })?;
+        let mut encoder = zstd::stream::write::Encoder::new(zst_file, self.level)?;
+        std::io::copy(&mut log_file, &mut encoder)?;
+        encoder
+            .finish()
+            .map_err(|e| LogError::from(format!("[fast_log] try zstd fail{:?}", e)))?;
+        Ok(Packed::Archive(zst_path.into()))
     }
 }
@@ -0,0 +1,75 @@
+use crate::appender::{Command, FastLogRecord, LogAppender};
+use crate::error::LogError;
+use std::collections::VecDeque;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+
+/// keep the last N records in memory, clones share the same buffer.
+/// dump them when something goes wrong, for example write the context of an error to a file:
+/// ```rust,no_run
+/// use fast_log::Config;
+/// use fast_log::plugin::ring_buffer::RingBufferAppender;
+/// let ring = RingBufferAppender::new(1000);
+/// fast_log::init(Config::new().add_appender(ring.clone())).unwrap();
+/// //...
+/// fast_log::flush().unwrap().wait();
+/// ring.dump_to_file("target/logs/crash.log").unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct RingBufferAppender {
+    capacity: usize,
+    records: Arc<Mutex<VecDeque<FastLogRecord>>>,
+}
+
+impl RingBufferAppender {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            records: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+        }
+    }
+
+    /// the records in the buffer, oldest first
+    pub fn records(&self) -> Vec<FastLogRecord> {
+        self.records.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// write the formatted records, oldest first
+    pub fn dump<W: Write>(&self, writer: &mut W) -> Result<(), LogError> {
+        for x in self.records.lock().unwrap().iter() {
+            writer.write_all(x.formated.as_bytes())?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// create(or truncate) the file and dump to it
+    pub fn dump_to_file(&self, path: &str) -> Result<(), LogError> {
+        if let Some(parent) = std::path::Path::new(path).parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = std::fs::File::create(path)?;
+        self.dump(&mut file)
+    }
+
+    pub fn clear(&self) {
+        self.records.lock().unwrap().clear();
+    }
+}
+
+impl LogAppender for RingBufferAppender {
+    fn do_logs(&self, records: &[FastLogRecord]) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut buffer = self.records.lock().unwrap();
+        for x in records {
+            if x.command == Command::CommandRecord {
+                if buffer.len() == self.capacity {
+                    buffer.pop_front();
+                }
+                buffer.push_back(x.clone());
+            }
+        }
+    }
+}
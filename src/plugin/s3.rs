@@ -0,0 +1,191 @@
+use crate::error::LogError;
+use crate::plugin::file_split::{Packed, Packer};
+use crate::plugin::http::Http;
+use crate::plugin::sigv4::{self, endpoint_host, payload_hash, Credentials};
+use fastdate::DateTime;
+use once_cell::sync::OnceCell;
+use std::fs::File;
+use std::path::Path;
+
+/// you need enable fast_log = { ... ,features=["s3"]}
+/// pack with the inner packer(for example ZipPacker), then PUT the archive to `s3://{bucket}/{prefix}{file name}`
+/// signed with AWS Signature V4, retrying network errors and 5xx. the credentials default to the env
+/// `AWS_ACCESS_KEY_ID` `AWS_SECRET_ACCESS_KEY` `AWS_SESSION_TOKEN`, `endpoint` sends path style requests
+/// to other object storages(for example minio)
+/// ```rust,no_run
+/// use fast_log::consts::LogSize;
+/// use fast_log::plugin::file_split::KeepType;
+/// use fast_log::plugin::packer::LogPacker;
+/// use fast_log::plugin::s3::S3Packer;
+/// use fast_log::Config;
+/// fast_log::init(Config::new().file_split(
+///     "target/logs/",
+///     LogSize::MB(100),
+///     KeepType::KeepNum(7),
+///     S3Packer::new(LogPacker {}, "my-bucket", "us-east-1")
+///         .prefix("app/")
+///         .delete_local(true),
+/// ))
+/// .unwrap();
+/// ```
+pub struct S3Packer<P: Packer> {
+    inner: P,
+    bucket: String,
+    region: String,
+    endpoint: Option<String>,
+    prefix: String,
+    credentials: Credentials,
+    delete_local: bool,
+    retries: u32,
+    http: OnceCell<Http>,
+}
+
+impl<P: Packer> S3Packer<P> {
+    pub fn new(inner: P, bucket: &str, region: &str) -> Self {
+        Self {
+            inner,
+            bucket: bucket.to_string(),
+            region: region.to_string(),
+            endpoint: None,
+            prefix: String::new(),
+            credentials: Credentials::from_env(),
+            delete_local: false,
+            retries: 3,
+            http: OnceCell::new(),
+        }
+    }
+
+    pub fn credentials(mut self, access_key: &str, secret_key: &str) -> Self {
+        self.credentials.access_key = access_key.to_string();
+        self.credentials.secret_key = secret_key.to_string();
+        self
+    }
+
+    pub fn session_token(mut self, session_token: &str) -> Self {
+        self.credentials.session_token = Some(session_token.to_string());
+        self
+    }
+
+    /// for example `http://127.0.0.1:9000`, the request is `{endpoint}/{bucket}/{key}`
+    pub fn endpoint(mut self, endpoint: &str) -> Self {
+        self.endpoint = Some(endpoint.trim_end_matches('/').to_string());
+        self
+    }
+
+    /// the key prefix, for example `app/`
+    pub fn prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    /// remove the archive after it is uploaded, default false
+    pub fn delete_local(mut self, delete_local: bool) -> Self {
+        self.delete_local = delete_local;
+        self
+    }
+
+    /// retries of network errors and 5xx, default 3
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// PUT the body as object `key`
+    pub fn upload(&self, key: &str, body: &[u8]) -> Result<(), LogError> {
+        let http = self.http.get_or_try_init(|| {
+            Http::new().map(|mut http| {
+                http.retries = self.retries;
+                http
+            })
+        })?;
+        let key = uri_encode(key);
+        let (url, host, uri) = match &self.endpoint {
+            Some(endpoint) => {
+                let host = endpoint_host(endpoint);
+                let uri = format!("/{}/{}", self.bucket, key);
+                (format!("{}{}", endpoint, uri), host, uri)
+            }
+            None => {
+                let host = format!("{}.s3.{}.amazonaws.com", self.bucket, self.region);
+                let uri = format!("/{}", key);
+                (format!("https://{}{}", host, uri), host, uri)
+            }
+        };
+        let amz_date = DateTime::utc().format("YYYYMMDDThhmmssZ");
+        let payload_hash = payload_hash(body);
+        let mut headers = vec![
+            ("host", host),
+            ("x-amz-content-sha256", payload_hash.clone()),
+            ("x-amz-date", amz_date.clone()),
+        ];
+        if let Some(token) = &self.credentials.session_token {
+            headers.push(("x-amz-security-token", token.clone()));
+        }
+        let authorization = sigv4::authorization(
+            &self.credentials,
+            &self.region,
+            "s3",
+            "PUT",
+            &uri,
+            &headers,
+            &payload_hash,
+            &amz_date,
+        );
+        let mut send_headers: Vec<(String, String)> = headers
+            .into_iter()
+            .filter(|(k, _)| *k != "host")
+            .map(|(k, v)| (k.to_string(), v))
+            .collect();
+        send_headers.push(("authorization".to_string(), authorization));
+        http.send_retry("PUT", &url, &send_headers, body)?;
+        Ok(())
+    }
+}
+
+impl<P: Packer> Packer for S3Packer<P> {
+    fn pack_name(&self) -> &'static str {
+        self.inner.pack_name()
+    }
+
+    fn do_pack(&self, _log_file: File, log_file_path: &str) -> Result<bool, LogError> {
+        Ok(self.pack(Path::new(log_file_path))? != Packed::Kept)
+    }
+
+    fn pack(&self, log_file: &Path) -> Result<Packed, LogError> {
+        let packed = self.inner.pack(log_file)?;
+        let archive = packed.path(log_file).ok_or_else(|| {
+            LogError::from(format!(
+                "[fast_log] the {} packer does not return its archive from Packer::pack",
+                self.inner.pack_name()
+            ))
+        })?;
+        let name = archive
+            .file_name()
+            .and_then(|v| v.to_str())
+            .unwrap_or_default();
+        let body = std::fs::read(&archive)?;
+        self.upload(&format!("{}{}", self.prefix, name), &body)?;
+        if self.delete_local {
+            let _ = std::fs::remove_file(&archive);
+        }
+        Ok(packed)
+    }
+
+    fn retry(&self) -> i32 {
+        self.inner.retry()
+    }
+}
+
+/// encode the key of the uri, `/` is kept
+fn uri_encode(key: &str) -> String {
+    let mut s = String::with_capacity(key.len());
+    for b in key.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                s.push(b as char)
+            }
+            _ => s.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    s
+}
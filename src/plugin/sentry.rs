@@ -0,0 +1,274 @@
+use crate::appender::{Command, FastLogRecord, LogAppender};
+use crate::error::LogError;
+use crate::fast_log::hostname;
+use crate::formats::escape_json;
+use crate::plugin::batch::{flush_wait, spawn_batch, BatchMsg};
+use crate::plugin::http::{Http, HttpOptions, InFlight};
+use crate::{report_error, Sender};
+use log::Level;
+use once_cell::sync::OnceCell;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// parsed sentry dsn `{scheme}://{public_key}@{host}/{project_id}`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Dsn {
+    pub dsn: String,
+    pub public_key: String,
+    /// `{scheme}://{host}{path}`, without the project id
+    pub base: String,
+    pub project_id: String,
+}
+
+impl Dsn {
+    pub fn parse(dsn: &str) -> Result<Self, LogError> {
+        let err = || LogError::from(format!("[fast_log] invalid sentry dsn: {}", dsn));
+        let (scheme, rest) = dsn.split_once("://").ok_or_else(err)?;
+        let (auth, rest) = rest.split_once('@').ok_or_else(err)?;
+        let public_key = auth.split(':').next().unwrap_or_default();
+        let (path, project_id) = rest
+            .trim_end_matches('/')
+            .rsplit_once('/')
+            .ok_or_else(err)?;
+        if public_key.is_empty() || project_id.is_empty() || path.is_empty() {
+            return Err(err());
+        }
+        Ok(Self {
+            dsn: dsn.to_string(),
+            public_key: public_key.to_string(),
+            base: format!("{}://{}", scheme, path),
+            project_id: project_id.to_string(),
+        })
+    }
+
+    /// the envelope endpoint
+    pub fn envelope_url(&self) -> String {
+        format!("{}/api/{}/envelope/", self.base, self.project_id)
+    }
+}
+
+/// send Error (and optionally Warn) records to sentry as events
+/// for example:
+/// ```rust,no_run
+/// use fast_log::Config;
+/// use fast_log::plugin::sentry::SentryAppender;
+/// fast_log::init(Config::new().console().add_appender(
+///     SentryAppender::new("https://key@o0.ingest.sentry.io/42")
+///         .unwrap()
+///         .level(log::Level::Warn)
+///         .environment("prod"),
+/// ))
+/// .unwrap();
+/// ```
+pub struct SentryAppender {
+    dsn: Dsn,
+    level: Level,
+    environment: Option<String>,
+    release: Option<String>,
+    server_name: String,
+    options: HttpOptions,
+    in_flight: InFlight,
+    sender: OnceCell<Sender<BatchMsg<String>>>,
+}
+
+impl SentryAppender {
+    pub fn new(dsn: &str) -> Result<Self, LogError> {
+        Ok(Self {
+            dsn: Dsn::parse(dsn)?,
+            level: Level::Error,
+            environment: None,
+            release: None,
+            server_name: hostname(),
+            options: HttpOptions::default(),
+            in_flight: InFlight::default(),
+            sender: OnceCell::new(),
+        })
+    }
+
+    /// lowest level sent to sentry, default Level::Error
+    pub fn level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    pub fn environment(mut self, environment: &str) -> Self {
+        self.environment = Some(environment.to_string());
+        self
+    }
+
+    pub fn release(mut self, release: &str) -> Self {
+        self.release = Some(release.to_string());
+        self
+    }
+
+    /// set `server_name`, default the hostname of the os
+    pub fn server_name(mut self, name: &str) -> Self {
+        self.server_name = name.to_string();
+        self
+    }
+
+    /// max bytes of events waiting to be sent(queued or being retried), default 16MB.
+    /// records logged while it is full are dropped
+    pub fn max_in_flight(mut self, bytes: usize) -> Self {
+        self.options.max_in_flight = bytes.max(1);
+        self
+    }
+
+    /// build the sentry event json of a record
+    pub fn event(&self, event_id: &str, record: &FastLogRecord) -> String {
+        let time = record.now.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let level = match record.level {
+            Level::Error => "error",
+            Level::Warn => "warning",
+            Level::Info => "info",
+            Level::Debug | Level::Trace => "debug",
+        };
+        let mut frame = format!(
+            "{{\"filename\":\"{}\",\"module\":\"{}\"",
+            escape_json(&record.file),
+            escape_json(&record.module_path)
+        );
+        if let Some(line) = record.line {
+            frame.push_str(&format!(",\"lineno\":{}", line));
+        }
+        frame.push_str(",\"in_app\":true}");
+        let mut event = format!(
+            "{{\"event_id\":\"{}\",\"timestamp\":{}.{:03},\"platform\":\"rust\",\"level\":\"{}\",\"logger\":\"{}\",\"server_name\":\"{}\"",
+            event_id,
+            time.as_secs(),
+            time.subsec_millis(),
+            level,
+            escape_json(&record.target),
+            escape_json(&self.server_name)
+        );
+        if let Some(v) = &self.environment {
+            event.push_str(&format!(",\"environment\":\"{}\"", escape_json(v)));
+        }
+        if let Some(v) = &self.release {
+            event.push_str(&format!(",\"release\":\"{}\"", escape_json(v)));
+        }
+        event.push_str(&format!(
+            ",\"logentry\":{{\"formatted\":\"{}\"}}",
+            escape_json(&record.args)
+        ));
+        event.push_str(&format!(
+            ",\"exception\":{{\"values\":[{{\"type\":\"{}\",\"value\":\"{}\",\"module\":\"{}\",\"stacktrace\":{{\"frames\":[{}]}}}}]}}}}",
+            record.level,
+            escape_json(&record.args),
+            escape_json(&record.module_path),
+            frame
+        ));
+        event
+    }
+
+    /// build the envelope body of a record
+    pub fn envelope(&self, record: &FastLogRecord) -> String {
+        let event_id = event_id();
+        let event = self.event(&event_id, record);
+        let sent_at =
+            fastdate::DateTime::from(SystemTime::now()).format("YYYY-MM-DDThh:mm:ss.000Z");
+        format!(
+            "{{\"event_id\":\"{}\",\"dsn\":\"{}\",\"sent_at\":\"{}\"}}\n{{\"type\":\"event\",\"length\":{}}}\n{}\n",
+            event_id,
+            escape_json(&self.dsn.dsn),
+            sent_at,
+            event.len(),
+            event
+        )
+    }
+
+    fn sender(&self) -> &Sender<BatchMsg<String>> {
+        self.sender.get_or_init(|| {
+            let url = self.dsn.envelope_url();
+            let headers = vec![
+                (
+                    "Content-Type".to_string(),
+                    "application/x-sentry-envelope".to_string(),
+                ),
+                (
+                    "X-Sentry-Auth".to_string(),
+                    format!(
+                        "Sentry sentry_version=7, sentry_client=fast_log/{}, sentry_key={}",
+                        env!("CARGO_PKG_VERSION"),
+                        self.dsn.public_key
+                    ),
+                ),
+            ];
+            let in_flight = self.in_flight.clone();
+            let http = Http::with_options(&self.options);
+            spawn_batch(
+                100,
+                Duration::from_secs(1),
+                move |envelopes: Vec<String>| {
+                    for envelope in envelopes {
+                        let result = match &http {
+                            Ok(http) => {
+                                http.send_retry("POST", &url, &headers, envelope.as_bytes())
+                            }
+                            Err(e) => Err(LogError::from(format!(
+                                "[fast_log] sentry client fail: {}",
+                                e
+                            ))),
+                        };
+                        if let Err(e) = result {
+                            report_error(&LogError::from(format!(
+                                "[fast_log] sentry send fail: {}",
+                                e
+                            )));
+                        }
+                        in_flight.remove(envelope.len());
+                    }
+                },
+            )
+        })
+    }
+}
+
+impl LogAppender for SentryAppender {
+    fn do_logs(&self, records: &[FastLogRecord]) {
+        let sender = self.sender();
+        for x in records {
+            match x.command {
+                Command::CommandRecord => {
+                    if x.level > self.level {
+                        continue;
+                    }
+                    let envelope = self.envelope(x);
+                    if !self
+                        .in_flight
+                        .add(envelope.len(), self.options.max_in_flight, "sentry")
+                    {
+                        continue;
+                    }
+                    let _ = sender.send(BatchMsg::Item(envelope));
+                }
+                Command::CommandExit => flush_wait(sender),
+                Command::CommandAddAppender(_) | Command::CommandRemoveAppender(_) => {}
+                Command::CommandRotate => {}
+                Command::CommandFlush(ref w) => {
+                    let _ = sender.send(BatchMsg::Flush(w.clone()));
+                }
+            }
+        }
+    }
+}
+
+/// random uuid v4 as 32 hex chars
+fn event_id() -> String {
+    let mut id = [0u8; 16];
+    for (i, v) in id.chunks_mut(8).enumerate() {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u128(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos(),
+        );
+        hasher.write_usize(i);
+        v.copy_from_slice(&hasher.finish().to_be_bytes());
+    }
+    id[6] = (id[6] & 0x0f) | 0x40;
+    id[8] = (id[8] & 0x3f) | 0x80;
+    id.iter().map(|v| format!("{:02x}", v)).collect()
+}
@@ -0,0 +1,198 @@
+use crate::appender::{Command, FastLogRecord, LogAppender};
+use crate::error::LogError;
+use crate::fast_log::hostname;
+use crate::report_error;
+use log::Level;
+use std::cell::RefCell;
+use std::io::Write;
+use std::net::{TcpStream, UdpSocket};
+#[cfg(unix)]
+use std::os::unix::net::UnixDatagram;
+
+/// syslog facility, see RFC 5424 6.2.1
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Facility {
+    Kern = 0,
+    User = 1,
+    Mail = 2,
+    Daemon = 3,
+    Auth = 4,
+    Syslog = 5,
+    Lpr = 6,
+    News = 7,
+    Uucp = 8,
+    Cron = 9,
+    AuthPriv = 10,
+    Ftp = 11,
+    Local0 = 16,
+    Local1 = 17,
+    Local2 = 18,
+    Local3 = 19,
+    Local4 = 20,
+    Local5 = 21,
+    Local6 = 22,
+    Local7 = 23,
+}
+
+/// where syslog messages are sent
+#[derive(Clone, Debug)]
+pub enum SyslogTransport {
+    /// udp address, for example "127.0.0.1:514"
+    Udp(String),
+    /// tcp address, messages use octet counting framing (RFC 6587)
+    Tcp(String),
+    /// unix datagram socket path, for example "/dev/log"
+    Unix(String),
+}
+
+enum Conn {
+    Udp(UdpSocket),
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixDatagram),
+}
+
+/// send RFC 5424 messages to syslog
+/// for example:
+/// ```rust,no_run
+/// use fast_log::Config;
+/// use fast_log::plugin::syslog::{Facility, SyslogAppender, SyslogTransport};
+/// fast_log::init(Config::new().add_appender(
+///     SyslogAppender::new("my_app", SyslogTransport::Udp("127.0.0.1:514".to_string()))
+///         .facility(Facility::Local0),
+/// ))
+/// .unwrap();
+/// ```
+pub struct SyslogAppender {
+    facility: Facility,
+    app_name: String,
+    hostname: String,
+    transport: SyslogTransport,
+    conn: RefCell<Option<Conn>>,
+}
+
+impl SyslogAppender {
+    pub fn new(app_name: &str, transport: SyslogTransport) -> Self {
+        Self {
+            facility: Facility::User,
+            app_name: header_field(app_name, 48),
+            hostname: header_field(&hostname(), 255),
+            transport,
+            conn: RefCell::new(None),
+        }
+    }
+
+    /// set facility, default is Facility::User
+    pub fn facility(mut self, facility: Facility) -> Self {
+        self.facility = facility;
+        self
+    }
+
+    /// set HOSTNAME field, default the hostname of the os
+    pub fn hostname(mut self, hostname: &str) -> Self {
+        self.hostname = header_field(hostname, 255);
+        self
+    }
+
+    /// format record into an RFC 5424 message
+    pub fn message(&self, record: &FastLogRecord) -> String {
+        let pri = self.facility as u8 * 8 + severity(record.level);
+        let time = fastdate::DateTime::from(record.now).format("YYYY-MM-DDThh:mm:ss.000000Z");
+        format!(
+            "<{}>1 {} {} {} {} - - {}",
+            pri,
+            time,
+            self.hostname,
+            self.app_name,
+            std::process::id(),
+            record.args
+        )
+    }
+
+    fn connect(&self) -> std::io::Result<Conn> {
+        match &self.transport {
+            SyslogTransport::Udp(addr) => {
+                let socket = UdpSocket::bind("0.0.0.0:0")?;
+                socket.connect(addr)?;
+                Ok(Conn::Udp(socket))
+            }
+            SyslogTransport::Tcp(addr) => Ok(Conn::Tcp(TcpStream::connect(addr)?)),
+            #[cfg(unix)]
+            SyslogTransport::Unix(path) => {
+                let socket = UnixDatagram::unbound()?;
+                socket.connect(path)?;
+                Ok(Conn::Unix(socket))
+            }
+            #[cfg(not(unix))]
+            SyslogTransport::Unix(_) => Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "unix socket syslog is not supported on this platform",
+            )),
+        }
+    }
+
+    fn send(&self, msg: &str) -> std::io::Result<()> {
+        let mut conn = self.conn.borrow_mut();
+        if conn.is_none() {
+            *conn = Some(self.connect()?);
+        }
+        let result = match conn.as_mut().unwrap() {
+            Conn::Udp(socket) => socket.send(msg.as_bytes()).map(|_| ()),
+            Conn::Tcp(stream) => stream.write_all(format!("{} {}", msg.len(), msg).as_bytes()),
+            #[cfg(unix)]
+            Conn::Unix(socket) => socket.send(msg.as_bytes()).map(|_| ()),
+        };
+        if result.is_err() {
+            //reconnect next time
+            *conn = None;
+        }
+        result
+    }
+}
+
+impl LogAppender for SyslogAppender {
+    fn do_logs(&self, records: &[FastLogRecord]) {
+        for x in records {
+            match x.command {
+                Command::CommandRecord => {
+                    let msg = self.message(x);
+                    //retry once with a new connection
+                    if let Err(e) = self.send(&msg).or_else(|_| self.send(&msg)) {
+                        report_error(&LogError::from(format!(
+                            "[fast_log] syslog send fail: {}",
+                            e
+                        )));
+                    }
+                }
+                Command::CommandExit => {}
+                Command::CommandAddAppender(_) | Command::CommandRemoveAppender(_) => {}
+                Command::CommandRotate => {}
+                Command::CommandFlush(_) => {}
+            }
+        }
+    }
+}
+
+pub(crate) fn severity(level: Level) -> u8 {
+    match level {
+        Level::Error => 3,
+        Level::Warn => 4,
+        Level::Info => 6,
+        Level::Debug => 7,
+        Level::Trace => 7,
+    }
+}
+
+/// header fields must be printable ascii without space, "-" means nil
+fn header_field(arg: &str, max_len: usize) -> String {
+    let s: String = arg
+        .chars()
+        .filter(|c| c.is_ascii_graphic())
+        .take(max_len)
+        .collect();
+    if s.is_empty() {
+        "-".to_string()
+    } else {
+        s
+    }
+}
@@ -0,0 +1,200 @@
+use crate::appender::{Command, FastLogRecord, LogAppender};
+use crate::error::LogError;
+use crate::report_error;
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::io::Write;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+/// wrap the connected TcpStream, for example into a TLS stream of your tls crate
+pub type TcpConnector = Box<dyn Fn(TcpStream) -> std::io::Result<Box<dyn Write + Send>> + Send>;
+
+/// write formatted records to a tcp server(for example Logstash tcp input).
+/// records are buffered while disconnected, the connection is retried with backoff when records
+/// are logged and at once on flush and exit. connect and write errors are reported to report_error.
+/// fast_log does not ship TLS, wrap the stream with `connector` for a TLS server.
+/// for example:
+/// ```rust,no_run
+/// use fast_log::Config;
+/// use fast_log::plugin::tcp::TcpAppender;
+/// fast_log::init(Config::new().json().add_appender(TcpAppender::new("127.0.0.1:5000"))).unwrap();
+/// ```
+pub struct TcpAppender {
+    addr: String,
+    connector: Option<TcpConnector>,
+    conn: RefCell<Option<Box<dyn Write + Send>>>,
+    buffer: RefCell<VecDeque<String>>,
+    buffer_len: usize,
+    dropped: Cell<u64>,
+    backoff: Cell<Duration>,
+    max_backoff: Duration,
+    next_connect: Cell<Option<Instant>>,
+}
+
+impl TcpAppender {
+    pub fn new(addr: &str) -> Self {
+        Self {
+            addr: addr.to_string(),
+            connector: None,
+            conn: RefCell::new(None),
+            buffer: RefCell::new(VecDeque::new()),
+            buffer_len: 10000,
+            dropped: Cell::new(0),
+            backoff: Cell::new(Duration::from_millis(100)),
+            max_backoff: Duration::from_secs(30),
+            next_connect: Cell::new(None),
+        }
+    }
+
+    /// max records buffered while disconnected, the oldest records are dropped. default 10000
+    pub fn buffer_len(mut self, len: usize) -> Self {
+        self.buffer_len = len;
+        self
+    }
+
+    /// max wait between reconnects, default 30s
+    pub fn max_backoff(mut self, max: Duration) -> Self {
+        self.max_backoff = max;
+        self
+    }
+
+    /// wrap the stream after connect, this is how TLS is done. for example with native-tls:
+    /// `.connector(Box::new(move |stream| {`
+    /// `    Ok(Box::new(tls.connect("host", stream).map_err(std::io::Error::other)?))`
+    /// `}))`
+    pub fn connector(mut self, connector: TcpConnector) -> Self {
+        self.connector = Some(connector);
+        self
+    }
+
+    /// records dropped because the buffer was full
+    pub fn dropped(&self) -> u64 {
+        self.dropped.get()
+    }
+
+    fn connect(&self) -> std::io::Result<Box<dyn Write + Send>> {
+        let mut last_err = std::io::Error::new(
+            std::io::ErrorKind::AddrNotAvailable,
+            format!("[fast_log] no address of {}", self.addr),
+        );
+        for addr in self.addr.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&addr, Duration::from_secs(3)) {
+                Ok(stream) => {
+                    let _ = stream.set_nodelay(true);
+                    return match &self.connector {
+                        None => Ok(Box::new(stream)),
+                        Some(connector) => connector(stream),
+                    };
+                }
+                Err(e) => last_err = e,
+            }
+        }
+        Err(last_err)
+    }
+
+    /// make sure connected, return false if not connected and not time to retry
+    fn ensure_conn(&self) -> bool {
+        if self.conn.borrow().is_some() {
+            return true;
+        }
+        if let Some(next) = self.next_connect.get() {
+            if Instant::now() < next {
+                return false;
+            }
+        }
+        match self.connect() {
+            Ok(conn) => {
+                *self.conn.borrow_mut() = Some(conn);
+                self.backoff.set(Duration::from_millis(100));
+                self.next_connect.set(None);
+                true
+            }
+            Err(e) => {
+                //report the first failure of an outage, not every retry
+                if self.backoff.get() == Duration::from_millis(100) {
+                    report_error(&LogError::from(format!(
+                        "[fast_log] tcp connect {} fail: {}",
+                        self.addr, e
+                    )));
+                }
+                let backoff = self.backoff.get();
+                self.next_connect.set(Some(Instant::now() + backoff));
+                self.backoff.set((backoff * 2).min(self.max_backoff));
+                false
+            }
+        }
+    }
+
+    /// send all buffered records, records fully written are removed from the buffer
+    fn send_buffer(&self) {
+        if self.buffer.borrow().is_empty() || !self.ensure_conn() {
+            return;
+        }
+        let mut buffer = self.buffer.borrow_mut();
+        let mut data = String::new();
+        for x in buffer.iter() {
+            data.push_str(x);
+        }
+        let mut conn = self.conn.borrow_mut();
+        let c = match conn.as_mut() {
+            Some(c) => c,
+            None => return,
+        };
+        let mut written = 0;
+        let mut result = Ok(());
+        while written < data.len() {
+            match c.write(&data.as_bytes()[written..]) {
+                Ok(0) => {
+                    result = Err(std::io::ErrorKind::WriteZero.into());
+                    break;
+                }
+                Ok(n) => written += n,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            }
+        }
+        if let Err(e) = result.and_then(|_| c.flush()) {
+            report_error(&LogError::from(format!(
+                "[fast_log] tcp write {} fail: {}",
+                self.addr, e
+            )));
+            //reconnect and resend the rest later, a record written in part is resent whole
+            *conn = None;
+        }
+        while let Some(x) = buffer.front() {
+            if x.len() > written {
+                break;
+            }
+            written -= x.len();
+            buffer.pop_front();
+        }
+    }
+}
+
+impl LogAppender for TcpAppender {
+    fn do_logs(&self, records: &[FastLogRecord]) {
+        for x in records {
+            match x.command {
+                Command::CommandRecord => {
+                    let mut buffer = self.buffer.borrow_mut();
+                    if buffer.len() >= self.buffer_len {
+                        buffer.pop_front();
+                        self.dropped.set(self.dropped.get() + 1);
+                    }
+                    buffer.push_back(x.formated.clone());
+                }
+                Command::CommandExit | Command::CommandFlush(_) => {
+                    //retry now, do not wait for the backoff
+                    self.next_connect.set(None);
+                }
+                Command::CommandAddAppender(_) | Command::CommandRemoveAppender(_) => {}
+                Command::CommandRotate => {}
+            }
+        }
+        self.send_buffer();
+    }
+}
@@ -0,0 +1,74 @@
+use crate::appender::{Command, FastLogRecord, LogAppender};
+use log::Level;
+use std::sync::{Arc, Mutex};
+
+/// keep records in memory for tests, clones share the same records.
+/// records are written by the appender thread, use `Config::sync(true)` or `fast_log::flush()` before asserting.
+/// for example:
+/// ```rust
+/// use fast_log::Config;
+/// use fast_log::plugin::test::TestAppender;
+/// use log::Level;
+/// let appender = TestAppender::new();
+/// fast_log::init(Config::new().add_appender(appender.clone()).sync(true)).unwrap();
+/// log::warn!("disk full");
+/// appender.assert_logged(Level::Warn, "disk full");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TestAppender {
+    pub records: Arc<Mutex<Vec<FastLogRecord>>>,
+}
+
+impl TestAppender {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// true if a record of this level contains the substring in args or formated
+    pub fn logged(&self, level: Level, substring: &str) -> bool {
+        self.records.lock().unwrap().iter().any(|x| {
+            x.level == level && (x.args.contains(substring) || x.formated.contains(substring))
+        })
+    }
+
+    /// panic with the logged records if no record matches, see TestAppender::logged
+    pub fn assert_logged(&self, level: Level, substring: &str) {
+        if !self.logged(level, substring) {
+            let records: Vec<String> = self
+                .records
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|x| format!("{} {}", x.level, x.args))
+                .collect();
+            panic!(
+                "no {} record contains {:?}, logged: {:#?}",
+                level, substring, records
+            );
+        }
+    }
+
+    /// remove and return all records
+    pub fn take_records(&self) -> Vec<FastLogRecord> {
+        std::mem::take(&mut *self.records.lock().unwrap())
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl LogAppender for TestAppender {
+    fn do_logs(&self, records: &[FastLogRecord]) {
+        let mut logged = self.records.lock().unwrap();
+        for x in records {
+            if x.command == Command::CommandRecord {
+                logged.push(x.clone());
+            }
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use crate::appender::{Command, FastLogRecord, LogAppender};
+use std::cell::RefCell;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+
+/// send every formatted record as one udp datagram, lost datagrams are not resent
+/// for example:
+/// ```rust,no_run
+/// use fast_log::Config;
+/// use fast_log::plugin::udp::UdpAppender;
+/// fast_log::init(Config::new().add_appender(UdpAppender::new("127.0.0.1:8125").max_datagram(1400))).unwrap();
+/// ```
+pub struct UdpAppender {
+    addr: String,
+    max_datagram: Option<usize>,
+    socket: RefCell<Option<UdpSocket>>,
+}
+
+impl UdpAppender {
+    pub fn new(addr: &str) -> Self {
+        Self {
+            addr: addr.to_string(),
+            max_datagram: None,
+            socket: RefCell::new(None),
+        }
+    }
+
+    /// truncate datagrams longer than `size` bytes (at a char boundary). default no truncate
+    pub fn max_datagram(mut self, size: usize) -> Self {
+        self.max_datagram = Some(size);
+        self
+    }
+
+    fn connect(&self) -> std::io::Result<UdpSocket> {
+        let addr = self.addr.to_socket_addrs()?.next().ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::AddrNotAvailable,
+                format!("[fast_log] no address of {}", self.addr),
+            )
+        })?;
+        let socket = match addr {
+            SocketAddr::V4(_) => UdpSocket::bind("0.0.0.0:0")?,
+            SocketAddr::V6(_) => UdpSocket::bind("[::]:0")?,
+        };
+        socket.connect(addr)?;
+        Ok(socket)
+    }
+
+    fn send(&self, msg: &str) -> std::io::Result<()> {
+        let mut socket = self.socket.borrow_mut();
+        if socket.is_none() {
+            *socket = Some(self.connect()?);
+        }
+        let data = truncate(msg, self.max_datagram);
+        if let Err(e) = socket.as_ref().unwrap().send(data.as_bytes()) {
+            //resolve and bind again next time
+            *socket = None;
+            return Err(e);
+        }
+        Ok(())
+    }
+}
+
+impl LogAppender for UdpAppender {
+    fn do_logs(&self, records: &[FastLogRecord]) {
+        for x in records {
+            match x.command {
+                Command::CommandRecord => {
+                    let _ = self.send(&x.formated);
+                }
+                Command::CommandExit => {}
+                Command::CommandAddAppender(_) | Command::CommandRemoveAppender(_) => {}
+                Command::CommandRotate => {}
+                Command::CommandFlush(_) => {}
+            }
+        }
+    }
+}
+
+fn truncate(msg: &str, max: Option<usize>) -> &str {
+    match max {
+        Some(max) if msg.len() > max => {
+            let mut end = max;
+            while !msg.is_char_boundary(end) {
+                end -= 1;
+            }
+            &msg[..end]
+        }
+        _ => msg,
+    }
+}
@@ -0,0 +1,211 @@
+use crate::appender::{Command, FastLogRecord, LogAppender};
+use crate::error::LogError;
+use crate::formats::escape_json;
+use crate::plugin::batch::{flush_wait, spawn_batch, BatchMsg, RateLimiter};
+use crate::plugin::http::{Http, HttpOptions, InFlight};
+use crate::{report_error, Sender};
+use log::Level;
+use once_cell::sync::OnceCell;
+use std::time::Duration;
+
+/// POST an alert to a webhook url for every record at or above a level.
+///
+/// the body is rendered from a template, the placeholders are
+/// `{level}` `{target}` `{args}` `{formated}` `{module_path}` `{file}` `{line}` `{time}`
+/// and the values are json escaped.
+/// for example:
+/// ```rust,no_run
+/// use fast_log::Config;
+/// use fast_log::plugin::webhook::WebhookAppender;
+/// fast_log::init(Config::new().console().add_appender(
+///     WebhookAppender::slack("https://hooks.slack.com/services/xxx").max_per_minute(10),
+/// ))
+/// .unwrap();
+/// ```
+pub struct WebhookAppender {
+    url: String,
+    template: String,
+    content_type: String,
+    headers: Vec<(String, String)>,
+    level: Level,
+    max_per_minute: usize,
+    options: HttpOptions,
+    in_flight: InFlight,
+    sender: OnceCell<Sender<BatchMsg<String>>>,
+}
+
+impl WebhookAppender {
+    /// generic json body `{"level":..,"target":..,"message":..,"time":..}`
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            template:
+                r#"{"level":"{level}","target":"{target}","message":"{args}","time":"{time}"}"#
+                    .to_string(),
+            content_type: "application/json".to_string(),
+            headers: vec![],
+            level: Level::Error,
+            max_per_minute: 30,
+            options: HttpOptions::default(),
+            in_flight: InFlight::default(),
+            sender: OnceCell::new(),
+        }
+    }
+
+    /// slack incoming webhook
+    pub fn slack(url: &str) -> Self {
+        Self::new(url).template(r#"{"text":"[{level}] {target}: {args}"}"#)
+    }
+
+    /// microsoft teams incoming webhook
+    pub fn teams(url: &str) -> Self {
+        Self::new(url).template(r#"{"text":"[{level}] {target}: {args}"}"#)
+    }
+
+    /// discord webhook
+    pub fn discord(url: &str) -> Self {
+        Self::new(url).template(r#"{"content":"[{level}] {target}: {args}"}"#)
+    }
+
+    /// set body template
+    pub fn template(mut self, template: &str) -> Self {
+        self.template = template.to_string();
+        self
+    }
+
+    /// set Content-Type, default application/json
+    pub fn content_type(mut self, content_type: &str) -> Self {
+        self.content_type = content_type.to_string();
+        self
+    }
+
+    /// add an http header, for example `Authorization`
+    pub fn header(mut self, key: &str, value: &str) -> Self {
+        self.headers.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// lowest level posted, default Level::Error
+    pub fn level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// post at most `max` alerts every minute, others are dropped. default 30
+    pub fn max_per_minute(mut self, max: usize) -> Self {
+        self.max_per_minute = max;
+        self
+    }
+
+    /// retry failed posts `retries` times with backoff, default 3
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.options.retries = retries;
+        self
+    }
+
+    /// max bytes of alerts waiting to be posted(queued or being retried), default 16MB.
+    /// records logged while it is full are dropped
+    pub fn max_in_flight(mut self, bytes: usize) -> Self {
+        self.options.max_in_flight = bytes.max(1);
+        self
+    }
+
+    /// render the template with a record
+    pub fn render(&self, record: &FastLogRecord) -> String {
+        let mut body = String::with_capacity(self.template.len() + record.args.len());
+        let mut rest = self.template.as_str();
+        while let Some(start) = rest.find('{') {
+            body.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let value = rest
+                .find('}')
+                .and_then(|end| placeholder(&rest[1..end], record).map(|v| (end, v)));
+            match value {
+                Some((end, value)) => {
+                    body.push_str(&escape_json(&value));
+                    rest = &rest[end + 1..];
+                }
+                None => {
+                    body.push('{');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        body.push_str(rest);
+        body
+    }
+
+    fn sender(&self) -> &Sender<BatchMsg<String>> {
+        self.sender.get_or_init(|| {
+            let url = self.url.clone();
+            let mut headers = self.headers.clone();
+            headers.push(("Content-Type".to_string(), self.content_type.clone()));
+            let in_flight = self.in_flight.clone();
+            let http = Http::with_options(&self.options);
+            let mut limiter = RateLimiter::new(self.max_per_minute, Duration::from_secs(60));
+            spawn_batch(100, Duration::from_secs(1), move |bodies: Vec<String>| {
+                for body in bodies {
+                    if limiter.allow() {
+                        let result = match &http {
+                            Ok(http) => http.send_retry("POST", &url, &headers, body.as_bytes()),
+                            Err(e) => Err(LogError::from(format!(
+                                "[fast_log] webhook client fail: {}",
+                                e
+                            ))),
+                        };
+                        if let Err(e) = result {
+                            report_error(&LogError::from(format!(
+                                "[fast_log] webhook send fail: {}",
+                                e
+                            )));
+                        }
+                    }
+                    in_flight.remove(body.len());
+                }
+            })
+        })
+    }
+}
+
+impl LogAppender for WebhookAppender {
+    fn do_logs(&self, records: &[FastLogRecord]) {
+        let sender = self.sender();
+        for x in records {
+            match x.command {
+                Command::CommandRecord => {
+                    if x.level > self.level {
+                        continue;
+                    }
+                    let body = self.render(x);
+                    if !self
+                        .in_flight
+                        .add(body.len(), self.options.max_in_flight, "webhook")
+                    {
+                        continue;
+                    }
+                    let _ = sender.send(BatchMsg::Item(body));
+                }
+                Command::CommandExit => flush_wait(sender),
+                Command::CommandAddAppender(_) | Command::CommandRemoveAppender(_) => {}
+                Command::CommandRotate => {}
+                Command::CommandFlush(ref w) => {
+                    let _ = sender.send(BatchMsg::Flush(w.clone()));
+                }
+            }
+        }
+    }
+}
+
+fn placeholder(name: &str, record: &FastLogRecord) -> Option<String> {
+    Some(match name {
+        "level" => record.level.to_string(),
+        "target" => record.target.to_string(),
+        "args" => record.args.clone(),
+        "formated" => record.formated.trim_end_matches('\n').to_string(),
+        "module_path" => record.module_path.to_string(),
+        "file" => record.file.to_string(),
+        "line" => record.line.unwrap_or_default().to_string(),
+        "time" => fastdate::DateTime::from(record.now).format("YYYY-MM-DDThh:mm:ss.000Z"),
+        _ => return None,
+    })
+}
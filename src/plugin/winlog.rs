@@ -0,0 +1,133 @@
+use crate::appender::{Command, FastLogRecord, LogAppender};
+use crate::error::LogError;
+use log::Level;
+use std::ffi::c_void;
+
+type Handle = *mut c_void;
+
+const EVENTLOG_ERROR_TYPE: u16 = 0x0001;
+const EVENTLOG_WARNING_TYPE: u16 = 0x0002;
+const EVENTLOG_INFORMATION_TYPE: u16 = 0x0004;
+/// max chars of one insertion string
+const MAX_MESSAGE: usize = 31839;
+
+#[link(name = "advapi32")]
+extern "system" {
+    fn RegisterEventSourceW(server_name: *const u16, source_name: *const u16) -> Handle;
+    fn ReportEventW(
+        event_log: Handle,
+        event_type: u16,
+        category: u16,
+        event_id: u32,
+        user_sid: *mut c_void,
+        num_strings: u16,
+        data_size: u32,
+        strings: *const *const u16,
+        raw_data: *mut c_void,
+    ) -> i32;
+    fn DeregisterEventSource(event_log: Handle) -> i32;
+}
+
+/// write records to the windows Application event log.
+/// ERROR is an error event, WARN a warning event, other levels information events.
+///
+/// without a registered message file(`HKLM\SYSTEM\CurrentControlSet\Services\EventLog\Application\<source>`)
+/// the Event Viewer shows a "description can not be found" note before the message
+/// for example:
+/// ```rust,no_run
+/// use fast_log::Config;
+/// use fast_log::plugin::winlog::EventLogAppender;
+/// fast_log::init(Config::new().add_appender(EventLogAppender::new("my_service").unwrap())).unwrap();
+/// ```
+pub struct EventLogAppender {
+    handle: Handle,
+    event_id: u32,
+}
+
+/// the event log handle can be used from any thread
+unsafe impl Send for EventLogAppender {}
+
+impl EventLogAppender {
+    /// register the event source on the local computer
+    pub fn new(source: &str) -> Result<Self, LogError> {
+        let source = wide(source);
+        let handle = unsafe { RegisterEventSourceW(std::ptr::null(), source.as_ptr()) };
+        if handle.is_null() {
+            return Err(LogError::from(format!(
+                "[fast_log] RegisterEventSourceW fail: {}",
+                std::io::Error::last_os_error()
+            )));
+        }
+        Ok(Self {
+            handle,
+            event_id: 0,
+        })
+    }
+
+    /// the event id of all events, default 0
+    pub fn event_id(mut self, id: u32) -> Self {
+        self.event_id = id;
+        self
+    }
+
+    fn report(&self, record: &FastLogRecord) -> Result<(), LogError> {
+        let event_type = match record.level {
+            Level::Error => EVENTLOG_ERROR_TYPE,
+            Level::Warn => EVENTLOG_WARNING_TYPE,
+            _ => EVENTLOG_INFORMATION_TYPE,
+        };
+        let message: String = record
+            .formated
+            .trim_end_matches('\n')
+            .chars()
+            .take(MAX_MESSAGE)
+            .collect();
+        let message = wide(&message);
+        let strings = [message.as_ptr()];
+        let ok = unsafe {
+            ReportEventW(
+                self.handle,
+                event_type,
+                0,
+                self.event_id,
+                std::ptr::null_mut(),
+                1,
+                0,
+                strings.as_ptr(),
+                std::ptr::null_mut(),
+            )
+        };
+        if ok == 0 {
+            return Err(LogError::from(format!(
+                "[fast_log] ReportEventW fail: {}",
+                std::io::Error::last_os_error()
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl LogAppender for EventLogAppender {
+    fn do_logs(&self, records: &[FastLogRecord]) {
+        for x in records {
+            if x.command == Command::CommandRecord {
+                if let Err(e) = self.report(x) {
+                    crate::report_error(&e);
+                }
+            }
+        }
+    }
+}
+
+impl Drop for EventLogAppender {
+    fn drop(&mut self) {
+        unsafe {
+            DeregisterEventSource(self.handle);
+        }
+    }
+}
+
+/// null terminated utf-16
+fn wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(std::iter::once(0)).collect()
+}
@@ -0,0 +1,79 @@
+use crate::appender::{Command, FastLogRecord, LogAppender};
+use crate::error::LogError;
+use crate::plugin::console::ConsoleAppender;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+/// what a file appender does when a write fails(for example disk full or permission revoked).
+/// every failure is reported to `Config::on_error` first
+#[derive(Default)]
+pub enum WritePolicy {
+    /// drop the records, counted by `Metrics::write_errors`. the default
+    #[default]
+    Drop,
+    /// retry `times`, waiting `backoff` doubled after every retry, then drop the records
+    Retry { times: u32, backoff: Duration },
+    /// write the records to another appender, for example `WritePolicy::console()`
+    Fallback(Box<dyn LogAppender>),
+    /// panic the appender thread, for example when losing audit records is worse than stopping
+    Panic,
+}
+
+impl WritePolicy {
+    /// fall back to a ConsoleAppender
+    pub fn console() -> Self {
+        Self::Fallback(Box::new(ConsoleAppender::new()))
+    }
+
+    /// handle the failed write `e` of `records` to `path`, `retry` writes them again.
+    /// return true if a retry wrote them
+    pub fn on_error<W: FnMut() -> std::io::Result<()>>(
+        &self,
+        path: &str,
+        records: &[FastLogRecord],
+        e: std::io::Error,
+        retry: W,
+    ) -> bool {
+        let lost = records
+            .iter()
+            .filter(|v| v.command == Command::CommandRecord)
+            .count();
+        self.on_buffer_error(path, records, lost, e, retry)
+    }
+
+    /// like on_error for the failed flush of a buffer holding `lost` records,
+    /// `records` are their copies and only needed by a Fallback
+    pub(crate) fn on_buffer_error<W: FnMut() -> std::io::Result<()>>(
+        &self,
+        path: &str,
+        records: &[FastLogRecord],
+        lost: usize,
+        e: std::io::Error,
+        mut retry: W,
+    ) -> bool {
+        crate::report_error(&LogError::from(format!(
+            "[fast_log] write {} fail: {}",
+            path, e
+        )));
+        match self {
+            WritePolicy::Drop => {}
+            WritePolicy::Retry { times, backoff } => {
+                let mut delay = *backoff;
+                for _ in 0..*times {
+                    std::thread::sleep(delay);
+                    if retry().is_ok() {
+                        return true;
+                    }
+                    delay *= 2;
+                }
+            }
+            WritePolicy::Fallback(appender) => {
+                appender.do_logs(records);
+                return false;
+            }
+            WritePolicy::Panic => panic!("[fast_log] write {} fail: {}", path, e),
+        }
+        crate::fast_log::with_logger(|l| l.write_errors.fetch_add(lost as u64, Ordering::Relaxed));
+        false
+    }
+}
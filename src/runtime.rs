@@ -1,3 +1,9 @@
+//! the channel and thread primitives used by the logger, selected by a runtime feature.
+//! `runtime_thread`(default) only needs std threads and crossbeam channels
+
+#[cfg(not(feature = "runtime_thread"))]
+compile_error!("fast_log needs a runtime, enable the \"runtime_thread\" feature");
+
 #[cfg(feature = "runtime_thread")]
 pub type Receiver<T> = crossbeam::channel::Receiver<T>;
 #[cfg(feature = "runtime_thread")]
@@ -5,6 +11,8 @@ pub type Sender<T> = crossbeam::channel::Sender<T>;
 #[cfg(feature = "runtime_thread")]
 pub type SendError<T> = crossbeam_channel::SendError<T>;
 #[cfg(feature = "runtime_thread")]
+pub type TrySendError<T> = crossbeam_channel::TrySendError<T>;
+#[cfg(feature = "runtime_thread")]
 pub type RecvError = crossbeam_channel::RecvError;
 #[cfg(feature = "runtime_thread")]
 pub type JoinHandle<T> = std::thread::JoinHandle<T>;
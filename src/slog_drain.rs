@@ -0,0 +1,82 @@
+//! forward `slog` records into fast_log, so legacy slog crates share its appenders. needs the `slog` feature.
+//! the key values of the record and of the slog Logger become key values of the fast_log record.
+//! ```rust,ignore
+//! use slog::{o, Drain};
+//! fast_log::init(fast_log::Config::new().console()).unwrap();
+//! let root = slog::Logger::root(fast_log::slog_drain::SlogDrain::new().fuse(), o!("app" => "web"));
+//! slog::info!(root, "login"; "user" => "bob");
+//! ```
+use crate::fast_log::LOGGER;
+use log::Log;
+use std::fmt::Arguments;
+
+/// a slog Drain sending records to the fast_log logger
+#[derive(Clone, Debug, Default)]
+pub struct SlogDrain {}
+
+impl SlogDrain {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+fn level(level: slog::Level) -> log::Level {
+    match level {
+        slog::Level::Critical | slog::Level::Error => log::Level::Error,
+        slog::Level::Warning => log::Level::Warn,
+        slog::Level::Info => log::Level::Info,
+        slog::Level::Debug => log::Level::Debug,
+        slog::Level::Trace => log::Level::Trace,
+    }
+}
+
+/// collect the key values of a record
+#[derive(Default)]
+struct KeyValues(Vec<(String, String)>);
+
+impl slog::Serializer for KeyValues {
+    fn emit_arguments(&mut self, key: slog::Key, val: &Arguments<'_>) -> slog::Result {
+        self.0.push((key.to_string(), val.to_string()));
+        Ok(())
+    }
+}
+
+impl slog::Drain for SlogDrain {
+    type Ok = ();
+    type Err = slog::Never;
+
+    /// the tag is the target, or the module when the tag is empty.
+    /// the LOGGER applies its level, filters and enrichers
+    fn log(
+        &self,
+        record: &slog::Record<'_>,
+        values: &slog::OwnedKVList,
+    ) -> Result<(), slog::Never> {
+        let target = match record.tag() {
+            "" => record.module(),
+            tag => tag,
+        };
+        let metadata = log::Metadata::builder()
+            .level(level(record.level()))
+            .target(target)
+            .build();
+        if !LOGGER.enabled(&metadata) {
+            return Ok(());
+        }
+        let mut kvs = KeyValues::default();
+        //a failed serializer only loses key values, the message is still logged
+        let _ = slog::KV::serialize(&record.kv(), record, &mut kvs);
+        let _ = slog::KV::serialize(values, record, &mut kvs);
+        LOGGER.log(
+            &log::Record::builder()
+                .metadata(metadata)
+                .args(*record.msg())
+                .module_path(Some(record.module()))
+                .file(Some(record.file()))
+                .line(Some(record.line()))
+                .key_values(&kvs.0)
+                .build(),
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+//! log the elapsed time of a scope or a block, the record has the field `elapsed_ms`.
+//! for example:
+//! ```rust,no_run
+//! use fast_log::Config;
+//! use log::Level;
+//! fast_log::init(Config::new().console().json()).unwrap();
+//! {
+//!     let _t = fast_log::time_scope!("load_config");
+//!     // ... logs `load_config took 1.2ms` when _t is dropped
+//! }
+//! let sum = fast_log::log_elapsed!(Level::Debug, "sum", { (0..100u64).sum::<u64>() });
+//! ```
+use log::{Level, Record};
+use std::time::{Duration, Instant};
+
+/// logs `"<name> took <elapsed>"` when dropped, see `time_scope!`
+#[must_use = "the elapsed time is logged when TimeScope is dropped"]
+pub struct TimeScope {
+    name: &'static str,
+    level: Level,
+    start: Instant,
+    target: &'static str,
+    file: &'static str,
+    line: u32,
+}
+
+impl TimeScope {
+    pub fn new(
+        name: &'static str,
+        level: Level,
+        target: &'static str,
+        file: &'static str,
+        line: u32,
+    ) -> Self {
+        Self {
+            name,
+            level,
+            start: Instant::now(),
+            target,
+            file,
+            line,
+        }
+    }
+
+    /// the time since the scope started
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+impl Drop for TimeScope {
+    fn drop(&mut self) {
+        if self.level > log::max_level() {
+            return;
+        }
+        let elapsed = self.start.elapsed();
+        let kvs = [("elapsed_ms", elapsed.as_secs_f64() * 1000.0)];
+        log::logger().log(
+            &Record::builder()
+                .level(self.level)
+                .target(self.target)
+                .module_path_static(Some(self.target))
+                .file_static(Some(self.file))
+                .line(Some(self.line))
+                .key_values(&kvs)
+                .args(format_args!("{} took {:?}", self.name, elapsed))
+                .build(),
+        );
+    }
+}
+
+/// time the rest of the scope, logged at Info(or the given level) when the returned guard is dropped
+/// ```rust,no_run
+/// let _t = fast_log::time_scope!("load_config");
+/// let _t = fast_log::time_scope!(log::Level::Debug, "query");
+/// ```
+#[macro_export]
+macro_rules! time_scope {
+    ($level:expr, $name:expr) => {
+        $crate::timing::TimeScope::new($name, $level, module_path!(), file!(), line!())
+    };
+    ($name:expr) => {
+        $crate::time_scope!(log::Level::Info, $name)
+    };
+}
+
+/// run the block, log its elapsed time at Info(or the given level) and return its value
+/// ```rust,no_run
+/// let rows = fast_log::log_elapsed!("query", { vec![1, 2, 3] });
+/// let rows = fast_log::log_elapsed!(log::Level::Debug, "query", { vec![1, 2, 3] });
+/// ```
+#[macro_export]
+macro_rules! log_elapsed {
+    ($level:expr, $name:expr, $body:block) => {{
+        let _t = $crate::time_scope!($level, $name);
+        $body
+    }};
+    ($name:expr, $body:block) => {
+        $crate::log_elapsed!(log::Level::Info, $name, $body)
+    };
+}
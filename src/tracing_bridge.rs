@@ -0,0 +1,130 @@
+//! route `tracing` events into fast_log, so one pipeline writes both `log` and `tracing` records.
+//! needs the `tracing` feature. the `message` field becomes the args, other fields become key values.
+//! ```rust,ignore
+//! use tracing_subscriber::layer::SubscriberExt;
+//! fast_log::init(fast_log::Config::new().console()).unwrap();
+//! let subscriber = tracing_subscriber::registry().with(fast_log::tracing_bridge::Layer::new());
+//! tracing::subscriber::set_global_default(subscriber).unwrap();
+//! tracing::info!(user = "bob", "login");
+//! ```
+//! or without tracing-subscriber, `Layer` is a Subscriber too:
+//! ```rust,ignore
+//! tracing::subscriber::set_global_default(fast_log::tracing_bridge::Layer::new()).unwrap();
+//! ```
+use crate::fast_log::LOGGER;
+use log::Log;
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing_core::field::{Field, Visit};
+use tracing_core::span::{Attributes, Id, Record};
+use tracing_core::{Event, Interest, Level, Metadata, Subscriber};
+use tracing_subscriber::layer::Context;
+
+/// send tracing events to the fast_log logger. spans are not kept, only events are logged
+#[derive(Debug, Default)]
+pub struct Layer {
+    next_id: AtomicU64,
+}
+
+impl Layer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+fn level(level: &Level) -> log::Level {
+    match *level {
+        Level::ERROR => log::Level::Error,
+        Level::WARN => log::Level::Warn,
+        Level::INFO => log::Level::Info,
+        Level::DEBUG => log::Level::Debug,
+        Level::TRACE => log::Level::Trace,
+    }
+}
+
+fn log_metadata<'a>(metadata: &'a Metadata<'a>) -> log::Metadata<'a> {
+    log::Metadata::builder()
+        .level(level(metadata.level()))
+        .target(metadata.target())
+        .build()
+}
+
+/// collect the message and fields of an event
+#[derive(Default)]
+struct Fields {
+    message: String,
+    key_values: Vec<(String, String)>,
+}
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            self.key_values
+                .push((field.name().to_string(), value.to_string()));
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            self.key_values
+                .push((field.name().to_string(), format!("{:?}", value)));
+        }
+    }
+}
+
+/// build a log Record of the event, the LOGGER applies its level, filters and enrichers
+fn forward(event: &Event) {
+    let metadata = event.metadata();
+    if !LOGGER.enabled(&log_metadata(metadata)) {
+        return;
+    }
+    let mut fields = Fields::default();
+    event.record(&mut fields);
+    LOGGER.log(
+        &log::Record::builder()
+            .metadata(log_metadata(metadata))
+            .args(format_args!("{}", fields.message))
+            .module_path(metadata.module_path())
+            .file(metadata.file())
+            .line(metadata.line())
+            .key_values(&fields.key_values)
+            .build(),
+    );
+}
+
+impl<S: Subscriber> tracing_subscriber::Layer<S> for Layer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        forward(event);
+    }
+}
+
+impl Subscriber for Layer {
+    fn register_callsite(&self, _metadata: &'static Metadata<'static>) -> Interest {
+        //the level of fast_log can change at runtime, ask `enabled` every time
+        Interest::sometimes()
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.is_event() && LOGGER.enabled(&log_metadata(metadata))
+    }
+
+    fn new_span(&self, _span: &Attributes<'_>) -> Id {
+        Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed) + 1)
+    }
+
+    fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        forward(event);
+    }
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+}
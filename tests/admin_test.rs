@@ -0,0 +1,72 @@
+#[cfg(test)]
+#[cfg(feature = "admin")]
+mod test {
+    use fast_log::plugin::admin::AdminServer;
+    use fast_log::Config;
+    use std::io::{Read, Write};
+    use std::net::{SocketAddr, TcpStream};
+
+    fn request(addr: SocketAddr, method: &str, path: &str, token: &str, body: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nAuthorization: Bearer {}\r\nContent-Length: {}\r\n\r\n{}",
+            method,
+            path,
+            token,
+            body.len(),
+            body
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn test_admin_server() {
+        fast_log::init(Config::new().console().level(log::LevelFilter::Info)).unwrap();
+        let addr = AdminServer::new("127.0.0.1:0")
+            .token("secret")
+            .start()
+            .unwrap();
+        let r = request(addr, "PUT", "/level", "wrong", "debug");
+        assert!(r.starts_with("HTTP/1.1 401"));
+        let r = request(addr, "PUT", "/level", "secret", "debug");
+        assert!(r.starts_with("HTTP/1.1 200"));
+        assert_eq!(fast_log::get_level(), log::LevelFilter::Debug);
+        let r = request(addr, "GET", "/level", "secret", "");
+        assert!(r.ends_with("\r\n\r\ndebug\n"));
+        let r = request(addr, "PUT", "/level", "secret", "loud");
+        assert!(r.starts_with("HTTP/1.1 400"));
+        log::info!("counted");
+        let r = request(addr, "POST", "/flush", "secret", "");
+        assert!(r.starts_with("HTTP/1.1 200"));
+        let r = request(addr, "GET", "/metrics", "secret", "");
+        assert!(r.contains("\nfast_log_received_total 1\n"));
+        let r = request(addr, "DELETE", "/level", "secret", "");
+        assert!(r.starts_with("HTTP/1.1 405"));
+        let r = request(addr, "POST", "/rotate", "secret", "");
+        assert!(r.starts_with("HTTP/1.1 200"));
+    }
+
+    #[test]
+    fn test_admin_limits() {
+        let addr = AdminServer::new("127.0.0.1:0")
+            .token("secret")
+            .start()
+            .unwrap();
+        //an idle client does not block other requests
+        let _idle = TcpStream::connect(addr).unwrap();
+        let r = request(addr, "GET", "/level", "secre", "");
+        assert!(r.starts_with("HTTP/1.1 401"));
+        //8KB of headers without the end, all read by the server before it answers
+        let mut head = "GET /level HTTP/1.1\r\nX-Pad: ".to_string();
+        head.push_str(&"a".repeat(8 * 1024 - head.len()));
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(head.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 431"));
+    }
+}
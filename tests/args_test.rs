@@ -0,0 +1,32 @@
+#[cfg(test)]
+mod test {
+    use fast_log::plugin::test::TestAppender;
+    use fast_log::Config;
+    use std::fmt::{Display, Formatter};
+
+    /// logs while it is formatted
+    struct Noisy;
+
+    impl Display for Noisy {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            log::info!("inner {}", 1);
+            write!(f, "outer")
+        }
+    }
+
+    #[test]
+    fn test_format_args() {
+        let appender = TestAppender::new();
+        fast_log::init(Config::new().add_appender(appender.clone())).unwrap();
+        log::info!("static message");
+        log::info!("{} {}", Noisy, 2);
+        log::info!("{}", "x".repeat(100 * 1024));
+        log::info!("after {}", 3);
+        log::logger().flush();
+        let records = appender.take_records();
+        let args: Vec<&str> = records.iter().map(|r| r.args.as_str()).collect();
+        assert_eq!(args[..3], ["static message", "inner 1", "outer 2"]);
+        assert_eq!(args[3].len(), 100 * 1024);
+        assert_eq!(args[4], "after 3");
+    }
+}
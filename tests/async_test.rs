@@ -0,0 +1,39 @@
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_async_appender() {
+        use fast_log::appender::{AsyncLogAppender, Command, FastLogRecord};
+        use fast_log::Config;
+        use std::sync::{Arc, Mutex};
+
+        struct AsyncCollect(Arc<Mutex<Vec<String>>>);
+
+        #[async_trait::async_trait]
+        impl AsyncLogAppender for AsyncCollect {
+            async fn do_logs(&self, records: &[FastLogRecord]) {
+                tokio::task::yield_now().await;
+                for x in records {
+                    if x.command == Command::CommandRecord {
+                        self.0.lock().unwrap().push(x.args.clone());
+                    }
+                }
+            }
+        }
+
+        let records = Arc::new(Mutex::new(vec![]));
+        let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        fast_log::init(
+            Config::new().add_async_appender(rt.handle().clone(), AsyncCollect(records.clone())),
+        )
+        .unwrap();
+        log::info!("hello");
+        log::info!("async");
+        rt.block_on(async {
+            tokio::task::spawn_blocking(|| fast_log::flush().unwrap().wait())
+                .await
+                .unwrap();
+        });
+        assert_eq!(*records.lock().unwrap(), vec!["hello", "async"]);
+    }
+}
@@ -0,0 +1,25 @@
+#[cfg(test)]
+mod test {
+    use fast_log::bencher::Histogram;
+    use std::time::Duration;
+
+    #[test]
+    fn test_histogram_percentile() {
+        let histogram = Histogram::new();
+        for i in 1..=10000u64 {
+            histogram.record(i * 1000);
+        }
+        histogram.record(u64::MAX);
+        assert_eq!(histogram.count(), 10001);
+        let near = |d: Duration, nanos: u64| {
+            let d = d.as_nanos() as f64;
+            (d - nanos as f64).abs() / nanos as f64 <= 0.04
+        };
+        assert!(near(histogram.percentile(0.5), 5_000_000));
+        assert!(near(histogram.percentile(0.99), 9_900_000));
+        assert!(near(histogram.percentile(0.999), 9_990_000));
+        assert_eq!(histogram.max(), Duration::from_nanos(u64::MAX));
+        assert_eq!(histogram.percentile(1.0), Duration::from_nanos(u64::MAX));
+        assert_eq!(Histogram::new().percentile(0.5), Duration::ZERO);
+    }
+}
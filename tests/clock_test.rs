@@ -0,0 +1,41 @@
+#[cfg(test)]
+mod test {
+    use fast_log::clock::MockClock;
+    use fast_log::Config;
+    use fastdate::DateTime;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    fn local_date(time: SystemTime) -> String {
+        DateTime::from_system_time(time, 0)
+            .set_offset(fastdate::offset_sec())
+            .format("YYYY-MM-DD")
+    }
+
+    #[test]
+    fn test_mock_clock_rotation() {
+        let _ = std::fs::remove_dir_all("target/test_clock/");
+        let start = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let clock = MockClock::new(start);
+        fast_log::init(
+            Config::new()
+                .file_date("target/test_clock/app.log")
+                .clock(clock.clone()),
+        )
+        .unwrap();
+        assert_eq!(fast_log::now(), start);
+        log::info!("first day");
+        clock.advance(Duration::from_secs(86400));
+        log::info!("second day");
+        log::logger().flush();
+        let first =
+            std::fs::read_to_string(format!("target/test_clock/app-{}.log", local_date(start)))
+                .unwrap();
+        let second = std::fs::read_to_string(format!(
+            "target/test_clock/app-{}.log",
+            local_date(start + Duration::from_secs(86400))
+        ))
+        .unwrap();
+        assert!(first.contains("first day") && first.starts_with(&local_date(start)));
+        assert!(second.contains("second day") && !second.contains("first day"));
+    }
+}
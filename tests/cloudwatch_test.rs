@@ -0,0 +1,115 @@
+#[cfg(test)]
+#[cfg(feature = "cloudwatch")]
+mod test {
+    use fast_log::appender::{Command, FastLogRecord, LogAppender};
+    use fast_log::plugin::cloudwatch::CloudWatchAppender;
+    use fast_log::WaitGroup;
+    use log::Level;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::time::Duration;
+
+    fn record(command: Command, formated: &str) -> FastLogRecord {
+        FastLogRecord {
+            formated: formated.to_string(),
+            ..FastLogRecord::new(command, Level::Info, "")
+        }
+    }
+
+    /// accept one http request, reply `status` and `reply`, return (x-amz-target, body)
+    fn serve_one(server: &TcpListener, status: &str, reply: &str) -> (String, String) {
+        let (stream, _) = server.accept().unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+        let mut target = String::new();
+        let mut len = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if line == "\r\n" {
+                break;
+            }
+            let lower = line.to_lowercase();
+            if let Some(v) = lower.strip_prefix("content-length:") {
+                len = v.trim().parse().unwrap();
+            }
+            if let Some(v) = line.strip_prefix("x-amz-target:") {
+                target = v.trim().to_string();
+            }
+            if lower.starts_with("authorization:") {
+                assert!(lower.contains("/us-east-1/logs/aws4_request"));
+            }
+        }
+        let mut body = vec![0u8; len];
+        reader.read_exact(&mut body).unwrap();
+        let mut stream = reader.into_inner();
+        stream
+            .write_all(
+                format!(
+                    "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    reply.len(),
+                    reply
+                )
+                .as_bytes(),
+            )
+            .unwrap();
+        (target, String::from_utf8(body).unwrap())
+    }
+
+    #[test]
+    fn test_cloudwatch_put_log_events() {
+        let server = TcpListener::bind("127.0.0.1:0").unwrap();
+        let appender = CloudWatchAppender::new("group", "stream", "us-east-1")
+            .endpoint(&format!("http://{}", server.local_addr().unwrap()))
+            .credentials("AKID", "SECRET")
+            .backoff(Duration::from_millis(10))
+            .flush_interval(Duration::from_secs(60));
+        let wg = WaitGroup::new();
+        appender.do_logs(&[
+            record(Command::CommandRecord, "hello \"cloudwatch\"\n"),
+            record(Command::CommandFlush(wg.clone()), ""),
+        ]);
+        let (target, body) = serve_one(
+            &server,
+            "400 Bad Request",
+            r#"{"__type":"ResourceAlreadyExistsException","message":"exists"}"#,
+        );
+        assert_eq!(target, "Logs_20140328.CreateLogGroup");
+        assert_eq!(body, r#"{"logGroupName":"group"}"#);
+        let (target, _) = serve_one(&server, "200 OK", "{}");
+        assert_eq!(target, "Logs_20140328.CreateLogStream");
+        let (target, throttled) = serve_one(
+            &server,
+            "400 Bad Request",
+            r#"{"__type":"ThrottlingException","message":"Rate exceeded"}"#,
+        );
+        assert_eq!(target, "Logs_20140328.PutLogEvents");
+        let (_, body) = serve_one(&server, "200 OK", r#"{"nextSequenceToken":"t1"}"#);
+        wg.wait();
+        assert_eq!(throttled, body);
+        assert!(body.starts_with(
+            r#"{"logGroupName":"group","logStreamName":"stream","logEvents":[{"timestamp":"#
+        ));
+        assert!(body.ends_with(r#","message":"hello \"cloudwatch\""}]}"#));
+
+        let wg = WaitGroup::new();
+        appender.do_logs(&[
+            record(Command::CommandRecord, "next\n"),
+            record(Command::CommandFlush(wg.clone()), ""),
+        ]);
+        let (_, body) = serve_one(
+            &server,
+            "400 Bad Request",
+            r#"{"__type":"InvalidSequenceTokenException","expectedSequenceToken":"t2"}"#,
+        );
+        assert!(body.contains(r#""sequenceToken":"t1""#));
+        let (_, body) = serve_one(&server, "200 OK", r#"{"nextSequenceToken":"t3"}"#);
+        wg.wait();
+        assert!(body.contains(r#""sequenceToken":"t2""#));
+    }
+}
@@ -0,0 +1,80 @@
+#[cfg(test)]
+#[cfg(feature = "toml")]
+mod test {
+    use fast_log::Config;
+    use log::LevelFilter;
+
+    #[test]
+    fn test_from_toml() {
+        let _ = std::fs::remove_dir_all("target/test_config_file/");
+        let config = Config::from_toml(
+            r#"
+            # comment
+            level = "debug"
+            format = "logfmt"
+            chan_len = 100_000
+            deny_targets = ["noisy", 'other'] # trailing comment
+
+            [[appender]]
+            type = "console"
+            stream = "by_level"
+
+            [[appender]]
+            type = "file_split"
+            path = "target/test_config_file/split/"
+            size = "1MB"
+            keep = "7d"
+            packer = "log"
+            period = "day"
+
+            [[appender]]
+            type = "file_level"
+            path = "target/test_config_file/level"
+            keep = 3
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.level, LevelFilter::Debug);
+        assert_eq!(config.chan_len, Some(100000));
+        assert_eq!(config.appends.len(), 3);
+        assert!(!config.target_enabled("other::db"));
+        assert!(std::path::Path::new("target/test_config_file/level/error.log").exists());
+
+        let e = Config::from_toml("level = \"info\"\nchan_len = [1,\n").unwrap_err();
+        assert!(e.to_string().contains("line 2"));
+        let e = Config::from_toml("[[appender]]\ntype = \"file\"\npaht = \"a.log\"").unwrap_err();
+        assert!(e.to_string().contains("unknown key paht"));
+        let config = Config::from_toml("deny_targets = [\n  \"a\",\n  \"b\",\n]\n").unwrap();
+        assert!(!config.target_enabled("b::c"));
+        let e = Config::from_toml("chan_len = 1.5").unwrap_err();
+        assert!(e.to_string().contains("chan_len must be"));
+        let _ = std::fs::remove_dir_all("target/test_config_file/");
+    }
+
+    #[test]
+    fn test_deny_args() {
+        let config = Config::from_toml("deny_args = [\"^health check\"]");
+        #[cfg(feature = "regex")]
+        assert_eq!(config.unwrap().filters.len(), 1);
+        #[cfg(not(feature = "regex"))]
+        assert!(config.unwrap_err().to_string().contains("regex feature"));
+    }
+
+    #[test]
+    fn test_init_from_file() {
+        let _ = std::fs::remove_dir_all("target/test_init_from_file/");
+        std::fs::create_dir_all("target/test_init_from_file/").unwrap();
+        std::fs::write(
+            "target/test_init_from_file/log.toml",
+            "level = \"info\"\n[[appender]]\ntype = \"file\"\npath = \"target/test_init_from_file/app.log\"\n",
+        )
+        .unwrap();
+        fast_log::init_from_file("target/test_init_from_file/log.toml").unwrap();
+        log::info!("from toml");
+        log::debug!("dropped by level");
+        log::logger().flush();
+        let data = std::fs::read_to_string("target/test_init_from_file/app.log").unwrap();
+        assert!(data.contains("from toml"));
+        assert!(!data.contains("dropped by level"));
+    }
+}
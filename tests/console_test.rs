@@ -0,0 +1,16 @@
+#[cfg(test)]
+mod test {
+    use fast_log::plugin::console::ConsoleStream;
+    use log::Level;
+
+    #[test]
+    fn test_console_stream_by_level() {
+        let stream = ConsoleStream::ByLevel;
+        assert!(stream.is_stderr(Level::Error));
+        assert!(stream.is_stderr(Level::Warn));
+        assert!(!stream.is_stderr(Level::Info));
+        assert!(!stream.is_stderr(Level::Trace));
+        assert!(ConsoleStream::Stderr.is_stderr(Level::Info));
+        assert!(!ConsoleStream::Stdout.is_stderr(Level::Error));
+    }
+}
@@ -0,0 +1,41 @@
+#[cfg(test)]
+#[cfg(unix)]
+mod test {
+    use fast_log::Config;
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+
+    #[test]
+    fn test_control_socket() {
+        std::fs::create_dir_all("target/test_control/").unwrap();
+        //no appenders, setup fails before the socket and the level
+        let _ = std::fs::remove_file("target/test_control/bad.sock");
+        let bad = Config::new()
+            .level(log::LevelFilter::Trace)
+            .control_socket("target/test_control/bad.sock");
+        assert!(fast_log::init(bad).is_err());
+        assert!(!std::path::Path::new("target/test_control/bad.sock").exists());
+        assert_eq!(fast_log::get_level(), log::LevelFilter::Off);
+        assert_eq!(log::max_level(), log::LevelFilter::Off);
+        fast_log::init(
+            Config::new()
+                .console()
+                .level(log::LevelFilter::Info)
+                .control_socket("target/test_control/log.sock"),
+        )
+        .unwrap();
+        let mut stream = UnixStream::connect("target/test_control/log.sock").unwrap();
+        stream
+            .write_all(b"level=debug\nlevel\nflush\nstats\nlevel loud\nbogus\n")
+            .unwrap();
+        let mut lines = BufReader::new(stream).lines();
+        let mut next = || lines.next().unwrap().unwrap();
+        assert_eq!(next(), "ok");
+        assert_eq!(next(), "debug");
+        assert_eq!(next(), "ok");
+        assert!(next().starts_with("received="));
+        assert_eq!(next(), "error: unknown level loud");
+        assert!(next().starts_with("error: unknown command bogus"));
+        assert_eq!(fast_log::get_level(), log::LevelFilter::Debug);
+    }
+}
@@ -0,0 +1,107 @@
+#[cfg(test)]
+#[cfg(feature = "http")]
+mod test {
+    use fast_log::appender::{Command, FastLogRecord, LogAppender};
+    use fast_log::plugin::datadog::DatadogAppender;
+    use fast_log::WaitGroup;
+    use log::Level;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::time::Duration;
+
+    fn record(command: Command, args: &str) -> FastLogRecord {
+        FastLogRecord {
+            target: "app".into(),
+            module_path: "app".into(),
+            file: "src/main.rs".into(),
+            line: Some(1),
+            ..FastLogRecord::new(command, Level::Info, args)
+        }
+    }
+
+    /// accept one http request, reply `status`, return (lowercase head, body)
+    fn serve_one(server: &TcpListener, status: &str) -> (String, String) {
+        let (stream, _) = server.accept().unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut head = String::new();
+        let mut len = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if line == "\r\n" {
+                break;
+            }
+            if let Some(v) = line.to_lowercase().strip_prefix("content-length:") {
+                len = v.trim().parse().unwrap();
+            }
+            head.push_str(&line);
+        }
+        let mut body = vec![0u8; len];
+        reader.read_exact(&mut body).unwrap();
+        let mut stream = reader.into_inner();
+        stream
+            .write_all(
+                format!(
+                    "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    status
+                )
+                .as_bytes(),
+            )
+            .unwrap();
+        (head.to_lowercase(), String::from_utf8(body).unwrap())
+    }
+
+    #[test]
+    fn test_datadog_max_in_flight() {
+        let server = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        let appender = DatadogAppender::new("key123")
+            .endpoint(&format!("http://{}/api/v2/logs", addr))
+            .max_in_flight(10);
+        let wg = WaitGroup::new();
+        appender.do_logs(&[
+            record(Command::CommandRecord, "dropped"),
+            record(Command::CommandFlush(wg.clone()), ""),
+        ]);
+        wg.wait();
+        server.set_nonblocking(true).unwrap();
+        assert!(server.accept().is_err());
+    }
+
+    #[test]
+    fn test_datadog_intake_retry() {
+        let server = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        let appender = DatadogAppender::new("key123")
+            .endpoint(&format!("http://{}/api/v2/logs", addr))
+            .service("web")
+            .hostname("host-1")
+            .tag("env", "prod")
+            .tag("team", "core")
+            .backoff(Duration::from_millis(10))
+            .flush_interval(Duration::from_secs(60));
+        let mut second = record(Command::CommandRecord, "second");
+        second.key_values = vec![("user".to_string(), "42".to_string())];
+        let wg = WaitGroup::new();
+        appender.do_logs(&[
+            record(Command::CommandRecord, "hello \"dd\""),
+            second,
+            record(Command::CommandFlush(wg.clone()), ""),
+        ]);
+        let (_, first) = serve_one(&server, "429 Too Many Requests");
+        let (head, body) = serve_one(&server, "202 Accepted");
+        wg.wait();
+        assert_eq!(first, body);
+        assert!(head.starts_with("post /api/v2/logs "));
+        assert!(head.contains("dd-api-key: key123\r\n"));
+        assert!(head.contains("content-type: application/json\r\n"));
+        assert!(body.starts_with(
+            r#"[{"message":"hello \"dd\"","status":"info","service":"web","ddsource":"rust","ddtags":"env:prod,team:core","hostname":"host-1","timestamp":"#
+        ));
+        assert!(body.contains(r#"},{"message":"second","#));
+        assert!(body.ends_with(r#","user":"42"}]"#));
+    }
+}
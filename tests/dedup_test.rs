@@ -0,0 +1,57 @@
+#[cfg(test)]
+mod test {
+    use fast_log::appender::{Command, FastLogRecord, LogAppender};
+    use fast_log::plugin::dedup::{DedupAppender, DedupCompare};
+    use fast_log::WaitGroup;
+    use log::Level;
+    use std::sync::{Arc, Mutex};
+
+    fn record(command: Command, args: &str) -> FastLogRecord {
+        FastLogRecord {
+            target: "app".into(),
+            module_path: "app".into(),
+            formated: format!("{}\n", args),
+            ..FastLogRecord::new(command, Level::Info, args)
+        }
+    }
+
+    struct Collect(Arc<Mutex<Vec<String>>>);
+
+    impl LogAppender for Collect {
+        fn do_logs(&self, records: &[FastLogRecord]) {
+            for x in records {
+                if x.command == Command::CommandRecord {
+                    self.0.lock().unwrap().push(x.args.clone());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_dedup() {
+        let lines = Arc::new(Mutex::new(vec![]));
+        let appender = DedupAppender::new(Collect(lines.clone())).compare(DedupCompare::Args);
+        appender.do_logs(&[
+            record(Command::CommandRecord, "a"),
+            record(Command::CommandRecord, "a"),
+            record(Command::CommandRecord, "a"),
+        ]);
+        appender.do_logs(&[
+            record(Command::CommandRecord, "a"),
+            record(Command::CommandRecord, "b"),
+            record(Command::CommandRecord, "b"),
+            record(Command::CommandFlush(WaitGroup::new()), ""),
+            record(Command::CommandRecord, "b"),
+        ]);
+        assert_eq!(
+            *lines.lock().unwrap(),
+            vec![
+                "a",
+                "last message repeated 3 times",
+                "b",
+                "last message repeated 1 times",
+                "b"
+            ]
+        );
+    }
+}
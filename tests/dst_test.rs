@@ -0,0 +1,18 @@
+#[cfg(test)]
+#[cfg(unix)]
+mod test {
+    use fast_log::plugin::file_split::Period;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn test_period_range_dst() {
+        //central europe, +1h in winter and +2h in summer. set before any local time is read
+        std::env::set_var("TZ", "CET-1CEST,M3.5.0,M10.5.0/3");
+        //2024-01-15 12:30 UTC is 13:30 CET
+        let winter = UNIX_EPOCH + Duration::from_secs(1705321800);
+        assert_eq!(Period::Hour.range(winter).0, 1705276800 + 13 * 3600);
+        //2024-07-15 12:30 UTC is 14:30 CEST
+        let summer = UNIX_EPOCH + Duration::from_secs(1721046600);
+        assert_eq!(Period::Hour.range(summer).0, 1721001600 + 14 * 3600);
+    }
+}
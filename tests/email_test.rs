@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod test {
+    use fast_log::appender::{Command, FastLogRecord, LogAppender};
+    use fast_log::plugin::email::EmailAppender;
+    use fast_log::WaitGroup;
+    use log::Level;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    fn record(command: Command, level: Level, formated: &str) -> FastLogRecord {
+        FastLogRecord {
+            formated: formated.to_string(),
+            ..FastLogRecord::new(command, level, "")
+        }
+    }
+
+    /// a smtp server accept every command, collect the client lines of each session
+    fn smtp_server() -> (String, Arc<Mutex<Vec<Vec<String>>>>) {
+        let server = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap().to_string();
+        let sessions = Arc::new(Mutex::new(vec![]));
+        let s = sessions.clone();
+        std::thread::spawn(move || {
+            for stream in server.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut lines = vec![];
+                stream.write_all(b"220 mock\r\n").unwrap();
+                let mut data = false;
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap() == 0 {
+                        break;
+                    }
+                    let line = line.trim_end().to_string();
+                    lines.push(line.clone());
+                    let reply: &[u8] = if data {
+                        if line != "." {
+                            continue;
+                        }
+                        data = false;
+                        b"250 queued\r\n"
+                    } else if line.starts_with("EHLO") {
+                        b"250-mock\r\n250 AUTH PLAIN\r\n"
+                    } else if line.starts_with("AUTH") {
+                        b"235 ok\r\n"
+                    } else if line == "DATA" {
+                        data = true;
+                        b"354 go\r\n"
+                    } else if line == "QUIT" {
+                        stream.write_all(b"221 bye\r\n").unwrap();
+                        break;
+                    } else {
+                        b"250 ok\r\n"
+                    };
+                    stream.write_all(reply).unwrap();
+                }
+                s.lock().unwrap().push(lines);
+            }
+        });
+        (addr, sessions)
+    }
+
+    #[test]
+    fn test_email_digest_rate_limit() {
+        let (addr, sessions) = smtp_server();
+        let appender = EmailAppender::new(&addr, "app@example.com", &["ops@example.com"])
+            .credentials("u", "p")
+            .digest_interval(Duration::from_secs(60))
+            .rate_limit(1, Duration::from_secs(3600));
+        let wg = WaitGroup::new();
+        appender.do_logs(&[
+            record(Command::CommandRecord, Level::Error, "db down\n"),
+            record(Command::CommandRecord, Level::Info, "ignored\n"),
+            record(Command::CommandRecord, Level::Error, ".dot\n"),
+            record(Command::CommandFlush(wg.clone()), Level::Info, ""),
+        ]);
+        wg.wait();
+        let wg = WaitGroup::new();
+        appender.do_logs(&[
+            record(Command::CommandRecord, Level::Error, "limited\n"),
+            record(Command::CommandFlush(wg.clone()), Level::Info, ""),
+        ]);
+        wg.wait();
+        std::thread::sleep(Duration::from_millis(100));
+        let sessions = sessions.lock().unwrap();
+        assert_eq!(sessions.len(), 1);
+        let lines = &sessions[0];
+        assert!(lines.contains(&"AUTH PLAIN AHUAcA==".to_string()));
+        assert!(lines.contains(&"RCPT TO:<ops@example.com>".to_string()));
+        assert!(lines.contains(&"Subject: [fast_log] error logs (2)".to_string()));
+        assert!(lines.contains(&"db down".to_string()));
+        assert!(lines.contains(&"..dot".to_string()));
+        assert!(!lines.iter().any(|v| v == "ignored" || v == "limited"));
+    }
+}
@@ -0,0 +1,25 @@
+#[cfg(test)]
+mod test {
+    use fast_log::plugin::test::TestAppender;
+    use fast_log::Config;
+
+    #[test]
+    fn test_enricher() {
+        let appender = TestAppender::new();
+        fast_log::init(
+            Config::new()
+                .logfmt()
+                .add_appender(appender.clone())
+                .enricher(|r| r.key_values.push(("region".to_string(), "eu".to_string())))
+                .enricher(|r| r.args = format!("[{}] {}", r.key_values.len(), r.args))
+                .sync(true),
+        )
+        .unwrap();
+        log::info!(user = "bob"; "login");
+        let records = appender.take_records();
+        assert_eq!(records[0].args, "[2] login");
+        assert!(records[0]
+            .formated
+            .ends_with(" msg=\"[2] login\" user=bob region=eu\n"));
+    }
+}
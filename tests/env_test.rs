@@ -0,0 +1,27 @@
+#[cfg(test)]
+mod test {
+    use fast_log::Config;
+
+    #[test]
+    fn test_env_overrides() {
+        let _ = std::fs::remove_dir_all("target/test_env/");
+        std::env::set_var("FAST_LOG_LEVEL", "warn");
+        std::env::set_var("FAST_LOG_FORMAT", "json");
+        std::env::set_var("FAST_LOG_FILE", "target/test_env/app.log");
+        std::env::set_var("FAST_LOG_CONSOLE", "false");
+        let config = Config::new().env_overrides(false).apply_env();
+        assert_eq!(config.level, log::LevelFilter::Warn);
+        assert_eq!(config.appends.len(), 1);
+        std::env::set_var("FAST_LOG_CONSOLE", "maybe");
+        assert_eq!(Config::new().apply_env().errors.len(), 1);
+        std::env::set_var("FAST_LOG_CONSOLE", "false");
+
+        fast_log::init(Config::new().level(log::LevelFilter::Trace)).unwrap();
+        log::warn!("from env");
+        log::info!("dropped by level");
+        log::logger().flush();
+        let data = std::fs::read_to_string("target/test_env/app.log").unwrap();
+        assert!(data.contains("\"args\":\"from env\""));
+        assert!(!data.contains("dropped by level"));
+    }
+}
@@ -0,0 +1,52 @@
+#[cfg(test)]
+mod test {
+    use fast_log::consts::LogSize;
+    use fast_log::error::LogError;
+    use fast_log::plugin::file_split::{Packer, RollingType};
+    use fast_log::Config;
+    use std::fs::File;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    struct FailPacker {}
+
+    impl Packer for FailPacker {
+        fn pack_name(&self) -> &'static str {
+            "fail"
+        }
+
+        fn do_pack(&self, _log_file: File, _log_file_path: &str) -> Result<bool, LogError> {
+            Err(LogError::from("disk full"))
+        }
+    }
+
+    #[test]
+    fn test_on_error_packer() {
+        let _ = std::fs::remove_dir_all("target/test_on_error/");
+        let errors = Arc::new(Mutex::new(vec![]));
+        let e = errors.clone();
+        fast_log::init(
+            Config::new()
+                .file_split(
+                    "target/test_on_error/",
+                    LogSize::B(10),
+                    RollingType::All,
+                    FailPacker {},
+                )
+                .on_error(move |err| e.lock().unwrap().push(err.to_string())),
+        )
+        .unwrap();
+        log::info!("first record longer than the temp size");
+        log::info!("second record");
+        log::logger().flush();
+        for _ in 0..50 {
+            if !errors.lock().unwrap().is_empty() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        let errors = errors.lock().unwrap();
+        assert!(errors.iter().any(|v| v.contains("disk full")));
+        let _ = std::fs::remove_dir_all("target/test_on_error/");
+    }
+}
@@ -0,0 +1,35 @@
+#[cfg(test)]
+mod test {
+    use fast_log::appender::{Command, FastLogRecord, LogAppender};
+    use fast_log::consts::LogSize;
+    use fast_log::plugin::file_level::LevelFileAppender;
+    use fast_log::plugin::file_split::RollingType;
+    use fast_log::plugin::packer::LogPacker;
+    use log::Level;
+    use std::fs::remove_dir_all;
+
+    fn record(level: Level, formated: &str) -> FastLogRecord {
+        FastLogRecord {
+            formated: formated.to_string(),
+            ..FastLogRecord::new(Command::CommandRecord, level, "")
+        }
+    }
+
+    #[test]
+    fn test_level_file() {
+        let dir = "target/test_level_file";
+        let _ = remove_dir_all(dir);
+        let appender =
+            LevelFileAppender::split(dir, LogSize::MB(1), RollingType::All, LogPacker {}).unwrap();
+        appender.do_logs(&[
+            record(Level::Error, "boom\n"),
+            record(Level::Info, "hello\n"),
+            record(Level::Error, "boom again\n"),
+        ]);
+        let read = |name: &str| std::fs::read_to_string(format!("{}/{}", dir, name)).unwrap();
+        assert_eq!(read("error.log"), "boom\nboom again\n");
+        assert_eq!(read("info.log"), "hello\n");
+        assert_eq!(read("warn.log"), "");
+        let _ = remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,231 @@
+#[cfg(test)]
+mod test {
+    use fast_log::appender::{Command, FastLogRecord, LogAppender};
+    use fast_log::plugin::file::FileAppender;
+    use fast_log::plugin::file_date::DateFileAppender;
+    use fast_log::plugin::file_split::Period;
+    use fast_log::WaitGroup;
+    use log::Level;
+    use std::time::{Duration, SystemTime};
+
+    fn record(command: Command, formated: &str) -> FastLogRecord {
+        FastLogRecord {
+            formated: formated.to_string(),
+            ..FastLogRecord::new(command, Level::Info, "")
+        }
+    }
+
+    #[test]
+    fn test_file_buffered() {
+        let path = "target/test_file_buffered.log";
+        let _ = std::fs::remove_file(path);
+        let appender = FileAppender::new(path)
+            .unwrap()
+            .flush_interval(Some(Duration::from_secs(3600)));
+        appender.do_logs(&[record(Command::CommandRecord, "a\n")]);
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "");
+        appender.do_logs(&[record(Command::CommandFlush(WaitGroup::new()), "")]);
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "a\n");
+    }
+
+    #[test]
+    fn test_file_write_through() {
+        let path = "target/test_file_write_through.log";
+        let _ = std::fs::remove_file(path);
+        let appender = FileAppender::new(path).unwrap().flush_interval(None);
+        appender.do_logs(&[record(Command::CommandRecord, "a\n")]);
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "a\n");
+    }
+
+    #[test]
+    fn test_file_flush_interval() {
+        let path = "target/test_file_flush_interval.log";
+        let _ = std::fs::remove_file(path);
+        let appender = FileAppender::new(path)
+            .unwrap()
+            .flush_interval(Some(Duration::from_millis(50)));
+        appender.do_logs(&[record(Command::CommandRecord, "a\n")]);
+        std::thread::sleep(Duration::from_millis(300));
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "a\n");
+    }
+
+    #[test]
+    fn test_date_file() {
+        let _ = std::fs::remove_dir_all("target/test_date/");
+        let appender = DateFileAppender::new("target/test_date/app.log").unwrap();
+        let now = SystemTime::now();
+        let mut second = record(Command::CommandRecord, "second\n");
+        second.now = now + Duration::from_secs(24 * 3600);
+        let mut first = record(Command::CommandRecord, "first\n");
+        first.now = now;
+        appender.do_logs(&[first, second]);
+        let (start, end) = Period::Day.range(now);
+        let name = appender.file_name(start);
+        assert_eq!(
+            name,
+            format!("target/test_date/app-{}.log", Period::Day.name(start))
+        );
+        assert_eq!(std::fs::read_to_string(name).unwrap(), "first\n");
+        assert_eq!(
+            std::fs::read_to_string(appender.file_name(end)).unwrap(),
+            "second\n"
+        );
+        let _ = std::fs::remove_dir_all("target/test_date/");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_date_file_link() {
+        let _ = std::fs::remove_dir_all("target/test_date_link/");
+        let appender = DateFileAppender::new("target/test_date_link/app.log")
+            .unwrap()
+            .current_link("current.log");
+        appender.do_logs(&[record(Command::CommandRecord, "a\n")]);
+        let (start, _) = Period::Day.range(SystemTime::now());
+        assert_eq!(
+            std::fs::read_link("target/test_date_link/current.log").unwrap(),
+            std::path::Path::new(&format!("app-{}.log", Period::Day.name(start)))
+        );
+        assert_eq!(
+            std::fs::read_to_string("target/test_date_link/current.log").unwrap(),
+            "a\n"
+        );
+        let _ = std::fs::remove_dir_all("target/test_date_link/");
+    }
+
+    #[test]
+    fn test_file_moved() {
+        let path = "target/test_file_moved.log";
+        let moved = "target/test_file_moved.log.1";
+        let _ = std::fs::remove_file(path);
+        let appender = FileAppender::new(path).unwrap().flush_interval(None);
+        appender.do_logs(&[record(Command::CommandRecord, "a\n")]);
+        std::fs::rename(path, moved).unwrap();
+        appender.do_logs(&[record(Command::CommandRecord, "b\n")]);
+        assert_eq!(std::fs::read_to_string(moved).unwrap(), "a\n");
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "b\n");
+        let _ = std::fs::remove_file(path);
+        let _ = std::fs::remove_file(moved);
+    }
+
+    #[test]
+    fn test_file_lock() {
+        let path = "target/test_file_lock/app.log";
+        let _ = std::fs::remove_dir_all("target/test_file_lock/");
+        let writers: Vec<_> = ["a", "b"]
+            .into_iter()
+            .map(|c| {
+                let appender = FileAppender::new(path).unwrap().lock(true);
+                std::thread::spawn(move || {
+                    let line = format!("{}\n", c.repeat(10000));
+                    for _ in 0..100 {
+                        appender.do_logs(&[record(Command::CommandRecord, &line)]);
+                    }
+                })
+            })
+            .collect();
+        for w in writers {
+            w.join().unwrap();
+        }
+        let data = std::fs::read_to_string(path).unwrap();
+        assert_eq!(data.lines().count(), 200);
+        for line in data.lines() {
+            assert!(line == "a".repeat(10000) || line == "b".repeat(10000));
+        }
+        assert!(std::path::Path::new("target/test_file_lock/.app.log.lock").exists());
+        let _ = std::fs::remove_dir_all("target/test_file_lock/");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_file_mode() {
+        use fast_log::plugin::file_mode::FileMode;
+        use std::os::unix::fs::PermissionsExt;
+        let _ = std::fs::remove_dir_all("target/test_file_mode/");
+        let path = "target/test_file_mode/a/app.log";
+        let appender = FileAppender::with_mode(path, FileMode::new(0o600).dir(0o700))
+            .unwrap()
+            .flush_interval(None);
+        let mode = |p: &str| std::fs::metadata(p).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(path), 0o600);
+        assert_eq!(mode("target/test_file_mode/a"), 0o700);
+        assert_eq!(mode("target/test_file_mode"), 0o700);
+        assert_ne!(mode("target"), 0o700);
+        std::fs::remove_file(path).unwrap();
+        appender.do_logs(&[record(Command::CommandRecord, "a\n")]);
+        assert_eq!(mode(path), 0o600);
+        let _ = std::fs::remove_dir_all("target/test_file_mode/");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_write_policy() {
+        use fast_log::plugin::test::TestAppender;
+        use fast_log::plugin::write_policy::WritePolicy;
+        let fallback = TestAppender::new();
+        let appender = FileAppender::new("/dev/full")
+            .unwrap()
+            .flush_interval(None)
+            .write_policy(WritePolicy::Fallback(Box::new(fallback.clone())));
+        appender.do_logs(&[record(Command::CommandRecord, "to fallback\n")]);
+        fallback.assert_logged(Level::Info, "to fallback");
+        let before = fast_log::LOGGER.metrics().write_errors;
+        let appender = FileAppender::new("/dev/full")
+            .unwrap()
+            .flush_interval(None)
+            .write_policy(WritePolicy::Retry {
+                times: 2,
+                backoff: Duration::from_millis(1),
+            });
+        appender.do_logs(&[record(Command::CommandRecord, "lost\n")]);
+        assert_eq!(fast_log::LOGGER.metrics().write_errors, before + 1);
+        //the interval flusher goes through the policy too
+        let fallback = TestAppender::new();
+        let appender = FileAppender::new("/dev/full")
+            .unwrap()
+            .flush_interval(Some(Duration::from_millis(10)))
+            .write_policy(WritePolicy::Fallback(Box::new(fallback.clone())));
+        appender.do_logs(&[record(Command::CommandRecord, "buffered\n")]);
+        assert!(fallback.is_empty());
+        std::thread::sleep(Duration::from_millis(200));
+        fallback.assert_logged(Level::Info, "buffered");
+    }
+
+    #[test]
+    fn test_audit_file() {
+        use fast_log::plugin::file_audit::AuditFileAppender;
+        let _ = std::fs::remove_dir_all("target/test_audit/");
+        let appender = AuditFileAppender::new("target/test_audit/audit.log").unwrap();
+        appender.do_logs(&[
+            record(Command::CommandRecord, "one\n"),
+            record(Command::CommandRotate, ""),
+            record(Command::CommandRecord, "two\n"),
+        ]);
+        let data = std::fs::read_to_string("target/test_audit/audit.log").unwrap();
+        assert_eq!(data, "one\ntwo\n");
+        std::fs::rename("target/test_audit/audit.log", "target/test_audit/old.log").unwrap();
+        appender.do_logs(&[record(Command::CommandRecord, "three\n")]);
+        let data = std::fs::read_to_string("target/test_audit/audit.log").unwrap();
+        assert_eq!(data, "three\n");
+        let _ = std::fs::remove_dir_all("target/test_audit/");
+    }
+
+    #[test]
+    fn test_audit_file_sync() {
+        use fast_log::plugin::file_audit::AuditFileAppender;
+        use fast_log::{Config, FastLogger};
+        let _ = std::fs::remove_dir_all("target/test_audit_sync/");
+        let logger = FastLogger::new(
+            Config::new().sync(true).console().route(
+                "audit",
+                AuditFileAppender::new("target/test_audit_sync/audit.log").unwrap(),
+            ),
+        )
+        .unwrap();
+        log::info!(logger: logger, target: "audit", "transfer id=42");
+        //written and synced when log! returns, no flush
+        let data = std::fs::read_to_string("target/test_audit_sync/audit.log").unwrap();
+        assert!(data.ends_with("transfer id=42\n"));
+        let _ = std::fs::remove_dir_all("target/test_audit_sync/");
+    }
+}
@@ -3,7 +3,11 @@ mod test {
     use log::LevelFilter;
     use fast_log::{Config, FastLogFormat};
     use fast_log::appender::{Command, FastLogRecord, LogAppender};
-    use fast_log::filter::ModuleFilter;
+    use fast_log::filter::{
+        Filter, FilterExt, FilterHandle, LevelAndTargetFilter, ModuleFilter, ModuleLevelFilter,
+        NoFilter, OrFilter, Sample, SamplingFilter,
+    };
+    use log::Level;
 
     #[test]
     fn test_send_pack() {
@@ -27,4 +31,121 @@ mod test {
         log::info!("aaa");
         log::logger().flush();
     }
+
+    fn enabled(filter: &dyn Filter, target: &str, level: Level) -> bool {
+        filter.accept(&log::Record::builder().target(target).level(level).build())
+    }
+
+    #[test]
+    fn test_module_level_filter() {
+        let f = ModuleLevelFilter::parse("hyper=warn, my_app::db=trace,info");
+        assert!(enabled(&f, "hyper", Level::Warn));
+        assert!(!enabled(&f, "hyper::client", Level::Info));
+        assert!(enabled(&f, "my_app::db::pool", Level::Trace));
+        assert!(!enabled(&f, "my_app", Level::Debug));
+        assert!(enabled(&f, "my_app", Level::Info));
+        //not a sub module of hyper
+        assert!(enabled(&f, "hyperx", Level::Info));
+        assert_eq!(f.max_level(), LevelFilter::Trace);
+    }
+
+    #[test]
+    fn test_module_level_filter_no_default() {
+        let f = ModuleLevelFilter::parse("my_app");
+        assert!(enabled(&f, "my_app", Level::Trace));
+        assert!(!enabled(&f, "other", Level::Error));
+    }
+
+    #[test]
+    fn test_sampling_filter() {
+        let f = SamplingFilter::new(Sample::Every(10))
+            .target("hot")
+            .level(LevelFilter::Debug);
+        let kept = (0..100).filter(|_| enabled(&f, "hot::loop", Level::Trace)).count();
+        assert_eq!(kept, 10);
+        assert!((0..10).all(|_| enabled(&f, "hot", Level::Info)));
+        assert!((0..10).all(|_| enabled(&f, "cold", Level::Trace)));
+        let f = SamplingFilter::new(Sample::Probability(0.0));
+        assert!(!enabled(&f, "hot", Level::Warn));
+        assert!(enabled(&f, "hot", Level::Error));
+        let f = SamplingFilter::new(Sample::Probability(0.5));
+        let kept = (0..1000).filter(|_| enabled(&f, "hot", Level::Info)).count();
+        assert!(kept > 350 && kept < 650);
+    }
+
+    #[test]
+    fn test_filter_combinators() {
+        let noisy = ModuleLevelFilter::parse("noisy=trace");
+        let f = ModuleLevelFilter::parse("info").and(noisy.not());
+        assert!(enabled(&f, "app", Level::Info));
+        assert!(!enabled(&f, "app", Level::Debug));
+        assert!(!enabled(&f, "noisy::io", Level::Error));
+        let f = OrFilter::new()
+            .add_filter(ModuleLevelFilter::parse("db=trace"))
+            .add_filter(ModuleLevelFilter::parse("warn"));
+        assert!(enabled(&f, "db", Level::Trace));
+        assert!(enabled(&f, "app", Level::Warn));
+        assert!(!enabled(&f, "app", Level::Info));
+        assert!(!OrFilter::new().accept(&log::Record::builder().build()));
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn test_regex_filter() {
+        use fast_log::filter::RegexFilter;
+        let f = RegexFilter::new().args(r"^retry \d+").unwrap();
+        let log = |f: &RegexFilter, args: std::fmt::Arguments| {
+            f.accept(&log::Record::builder().target("app").args(args).build())
+        };
+        assert!(!log(&f, format_args!("retry {}", 3)));
+        assert!(log(&f, format_args!("ok")));
+        let f = RegexFilter::new().keep().target("^db(::|$)").unwrap();
+        assert!(enabled(&f, "db::pool", Level::Info));
+        assert!(!enabled(&f, "dbx", Level::Info));
+        assert!(RegexFilter::new().args("(").is_err());
+    }
+
+    #[test]
+    fn test_filter_handle() {
+        let handle = FilterHandle::default();
+        let filter = handle.clone();
+        assert!(enabled(&filter, "db", Level::Debug));
+        handle.set(ModuleLevelFilter::parse("db=warn"));
+        assert!(!enabled(&filter, "db", Level::Debug));
+        assert!(enabled(&filter, "db", Level::Warn));
+    }
+
+    #[test]
+    fn test_level_and_target_filter() {
+        let f = LevelAndTargetFilter::new(LevelFilter::Info).target("app");
+        assert!(enabled(&f, "app::http", Level::Info));
+        assert!(!enabled(&f, "app", Level::Debug));
+        assert!(!enabled(&f, "application", Level::Error));
+        let f = LevelAndTargetFilter::new(LevelFilter::Warn);
+        assert!(enabled(&f, "any", Level::Warn));
+        assert!(enabled(&NoFilter, "any", Level::Trace));
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_legacy_do_log_filter() {
+        struct Legacy;
+        impl Filter for Legacy {
+            fn do_log(&self, record: &log::Record) -> bool {
+                record.target() != "noisy"
+            }
+        }
+        assert!(enabled(&Legacy, "app", Level::Info));
+        assert!(!enabled(&Legacy, "noisy", Level::Info));
+    }
+
+    #[test]
+    fn test_target_lists() {
+        let cfg = Config::new().deny_target("cogo::io::sys::select");
+        assert!(!cfg.target_enabled("cogo::io::sys::select"));
+        assert!(cfg.target_enabled("cogo::io"));
+        let cfg = cfg.allow_target("app").allow_target("db");
+        assert!(cfg.target_enabled("app::http"));
+        assert!(!cfg.target_enabled("hyper"));
+    }
 }
@@ -0,0 +1,107 @@
+#[cfg(test)]
+mod test {
+    use fast_log::appender::{Command, FastLogRecord, LogAppender};
+    use fast_log::plugin::fluentd::FluentdAppender;
+    use log::Level;
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::time::Duration;
+
+    fn record(target: &str, args: &str) -> FastLogRecord {
+        FastLogRecord {
+            target: target.into(),
+            module_path: target.into(),
+            file: "src/main.rs".into(),
+            line: Some(1),
+            key_values: vec![("user".to_string(), "bob".to_string())],
+            ..FastLogRecord::new(Command::CommandRecord, Level::Info, args)
+        }
+    }
+
+    /// read one forward message with a chunk option, return (message, chunk)
+    fn read_message(stream: &mut TcpStream) -> (Vec<u8>, String) {
+        let marker = b"\xa5chunk\xb8";
+        let mut data = vec![];
+        let mut buf = [0u8; 1024];
+        loop {
+            let n = stream.read(&mut buf).unwrap();
+            assert!(n > 0);
+            data.extend_from_slice(&buf[..n]);
+            if let Some(i) = data.windows(marker.len()).position(|w| w == marker) {
+                let start = i + marker.len();
+                if data.len() >= start + 24 {
+                    let chunk = String::from_utf8(data[start..start + 24].to_vec()).unwrap();
+                    return (data, chunk);
+                }
+            }
+        }
+    }
+
+    fn ack(stream: &mut TcpStream, chunk: &str) {
+        let mut out = vec![0x81, 0xa3, b'a', b'c', b'k', 0xb8];
+        out.extend_from_slice(chunk.as_bytes());
+        stream.write_all(&out).unwrap();
+    }
+
+    fn contains(data: &[u8], part: &[u8]) -> bool {
+        data.windows(part.len()).any(|w| w == part)
+    }
+
+    #[test]
+    fn test_fluentd_ack() {
+        let server = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        let handle = std::thread::spawn(move || {
+            let (mut stream, _) = server.accept().unwrap();
+            let mut messages = vec![];
+            for _ in 0..2 {
+                let (data, chunk) = read_message(&mut stream);
+                ack(&mut stream, &chunk);
+                messages.push(data);
+            }
+            messages
+        });
+        let appender = FluentdAppender::new(&addr.to_string(), "k8s")
+            .target_tag(true)
+            .field("pod", "web-0")
+            .require_ack(true);
+        appender.do_logs(&[
+            record("app::db", "query"),
+            record("web", "request"),
+            record("app::db", "commit"),
+        ]);
+        let messages = handle.join().unwrap();
+        //[tag, [[time, record], [time, record]], {"size":2,"chunk":..}]
+        assert!(messages[0].starts_with(b"\x93\xaak8s.app.db\x92\x92\xd7\x00"));
+        assert!(contains(&messages[0], b"\xa7message\xa5query"));
+        assert!(contains(&messages[0], b"\xa7message\xa6commit"));
+        assert!(contains(&messages[0], b"\xa4user\xa3bob"));
+        assert!(contains(&messages[0], b"\xa3pod\xa5web-0"));
+        assert!(contains(&messages[0], b"\x82\xa4size\x02\xa5chunk"));
+        assert!(messages[1].starts_with(b"\x93\xa7k8s.web\x91"));
+        assert!(contains(&messages[1], b"\xa7message\xa7request"));
+        assert_eq!(appender.dropped(), 0);
+    }
+
+    #[test]
+    fn test_fluentd_resend_without_ack() {
+        let server = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        let handle = std::thread::spawn(move || {
+            let (mut stream, _) = server.accept().unwrap();
+            let (_, first) = read_message(&mut stream);
+            drop(stream);
+            let (mut stream, _) = server.accept().unwrap();
+            let (_, second) = read_message(&mut stream);
+            ack(&mut stream, &second);
+            (first, second)
+        });
+        let appender = FluentdAppender::new(&addr.to_string(), "app")
+            .require_ack(true)
+            .ack_timeout(Duration::from_secs(5));
+        appender.do_logs(&[record("app", "once")]);
+        appender.do_logs(&[]);
+        let (first, second) = handle.join().unwrap();
+        assert_eq!(first, second);
+    }
+}
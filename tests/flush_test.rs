@@ -0,0 +1,31 @@
+#[cfg(test)]
+mod test {
+    use fast_log::appender::{Command, FastLogRecord, LogAppender};
+    use fast_log::Config;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    struct SlowAppender(Arc<Mutex<Vec<String>>>);
+
+    impl LogAppender for SlowAppender {
+        fn do_logs(&self, records: &[FastLogRecord]) {
+            for x in records {
+                if x.command == Command::CommandRecord {
+                    std::thread::sleep(Duration::from_millis(200));
+                    self.0.lock().unwrap().push(x.args.clone());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_flush_sync() {
+        let records = Arc::new(Mutex::new(vec![]));
+        fast_log::init(Config::new().add_appender(SlowAppender(records.clone()))).unwrap();
+        log::info!("first");
+        fast_log::flush_sync(Duration::from_secs(5)).unwrap();
+        assert_eq!(*records.lock().unwrap(), vec!["first"]);
+        log::info!("second");
+        assert!(fast_log::flush_sync(Duration::from_millis(10)).is_err());
+    }
+}
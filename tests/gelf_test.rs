@@ -0,0 +1,96 @@
+#[cfg(test)]
+mod test {
+    use fast_log::appender::{Command, FastLogRecord, LogAppender};
+    use fast_log::plugin::gelf::{GelfAppender, GelfTransport};
+    use log::Level;
+    use std::io::Read;
+    use std::net::{TcpListener, UdpSocket};
+    use std::time::{Duration, SystemTime};
+
+    fn record(args: &str) -> FastLogRecord {
+        FastLogRecord {
+            target: "app".into(),
+            module_path: "app::db".into(),
+            file: "src/db.rs".into(),
+            line: Some(7),
+            now: SystemTime::UNIX_EPOCH + Duration::from_millis(1500),
+            ..FastLogRecord::new(Command::CommandRecord, Level::Warn, args)
+        }
+    }
+
+    #[test]
+    fn test_gelf_message() {
+        let appender = GelfAppender::new(GelfTransport::Udp("127.0.0.1:12201".to_string()))
+            .host("h1")
+            .field("env", "prod");
+        assert_eq!(
+            appender.message(&record("first\nsecond")),
+            r#"{"version":"1.1","host":"h1","short_message":"first","full_message":"first\nsecond","timestamp":1.500,"level":4,"_target":"app","_module_path":"app::db","_file":"src/db.rs","_line":7,"_env":"prod"}"#
+        );
+    }
+
+    #[test]
+    fn test_gelf_udp_chunked() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        server
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let addr = server.local_addr().unwrap().to_string();
+        let appender = GelfAppender::new(GelfTransport::Udp(addr)).chunk_size(100);
+        let args = "x".repeat(300);
+        let expect = appender.message(&record(&args));
+        appender.do_logs(&[record(&args)]);
+        let mut buf = [0u8; 200];
+        let mut data = vec![];
+        let mut seq = 0;
+        loop {
+            let n = server.recv(&mut buf).unwrap();
+            assert!(n <= 100);
+            assert_eq!(&buf[..2], &[0x1e, 0x0f]);
+            assert_eq!(buf[10], seq);
+            let count = buf[11];
+            data.extend_from_slice(&buf[12..n]);
+            seq += 1;
+            if seq == count {
+                break;
+            }
+        }
+        assert!(seq > 1);
+        assert_eq!(String::from_utf8(data).unwrap(), expect);
+    }
+
+    #[test]
+    fn test_gelf_tcp_fallback() {
+        let udp = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let tcp = TcpListener::bind("127.0.0.1:0").unwrap();
+        let appender = GelfAppender::new(GelfTransport::Udp(udp.local_addr().unwrap().to_string()))
+            .chunk_size(20)
+            .tcp_fallback(&tcp.local_addr().unwrap().to_string());
+        let args = "y".repeat(2000);
+        appender.do_logs(&[record(&args)]);
+        let (mut stream, _) = tcp.accept().unwrap();
+        let expect = appender.message(&record(&args));
+        let mut data = vec![0u8; expect.len() + 1];
+        stream.read_exact(&mut data).unwrap();
+        assert_eq!(data.pop(), Some(0));
+        assert_eq!(String::from_utf8(data).unwrap(), expect);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gelf_compress() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        server
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let addr = server.local_addr().unwrap().to_string();
+        let appender = GelfAppender::new(GelfTransport::Udp(addr)).compress(true);
+        appender.do_logs(&[record("zipped")]);
+        let mut buf = [0u8; 2000];
+        let n = server.recv(&mut buf).unwrap();
+        let mut decoder = flate2::read::ZlibDecoder::new(&buf[..n]);
+        let mut msg = String::new();
+        decoder.read_to_string(&mut msg).unwrap();
+        assert_eq!(msg, appender.message(&record("zipped")));
+    }
+}
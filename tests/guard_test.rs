@@ -0,0 +1,35 @@
+#[cfg(test)]
+mod test {
+    use fast_log::appender::{Command, FastLogRecord, LogAppender};
+    use fast_log::Config;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    struct SlowAppender {
+        records: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl LogAppender for SlowAppender {
+        fn do_logs(&self, records: &[FastLogRecord]) {
+            std::thread::sleep(Duration::from_millis(100));
+            for x in records {
+                if x.command == Command::CommandRecord {
+                    self.records.lock().unwrap().push(x.args.clone());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_guard_flush_on_drop() {
+        let records = Arc::new(Mutex::new(vec![]));
+        let guard = fast_log::init_guard(Config::new().add_appender(SlowAppender {
+            records: records.clone(),
+        }))
+        .unwrap()
+        .timeout(Duration::from_secs(5));
+        log::info!("last words");
+        drop(guard);
+        assert_eq!(*records.lock().unwrap(), vec!["last words".to_string()]);
+    }
+}
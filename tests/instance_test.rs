@@ -0,0 +1,95 @@
+#[cfg(test)]
+mod test {
+    use fast_log::plugin::test::TestAppender;
+    use fast_log::{Config, FastLogger};
+    use log::{Level, LevelFilter};
+
+    #[test]
+    fn test_fast_logger() {
+        let first = TestAppender::new();
+        let second = TestAppender::new();
+        let a = FastLogger::new(Config::new().add_appender(first.clone())).unwrap();
+        let b = FastLogger::new(
+            Config::new()
+                .add_appender(second.clone())
+                .level(LevelFilter::Warn)
+                .sync(true),
+        )
+        .unwrap();
+        log::info!(logger: a, "to a");
+        log::info!(logger: b, "dropped by level");
+        log::warn!(logger: b, "to b");
+        //the global logger is not set
+        log::error!("nowhere");
+        a.flush().unwrap().wait();
+        first.assert_logged(Level::Info, "to a");
+        assert_eq!(first.len(), 1);
+        second.assert_logged(Level::Warn, "to b");
+        assert_eq!(second.len(), 1);
+        assert_eq!(a.metrics().received, 1);
+    }
+
+    #[test]
+    fn test_bad_appender_lenient() {
+        let _ = std::fs::create_dir_all("target/test_lenient/");
+        //a file blocks the dir
+        std::fs::write("target/test_lenient/file", "").unwrap();
+        let bad = "target/test_lenient/file/app.log";
+        assert!(FastLogger::new(Config::new().file(bad)).is_err());
+        let errors = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let count = errors.clone();
+        let logger = FastLogger::new(Config::new().file(bad).lenient(true).on_error(move |_| {
+            count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        }));
+        assert!(logger.is_ok());
+        assert_eq!(errors.load(std::sync::atomic::Ordering::SeqCst), 1);
+        let _ = std::fs::remove_dir_all("target/test_lenient/");
+    }
+
+    #[test]
+    fn test_fast_logger_own_config() {
+        use fast_log::appender::{FastLogRecord, LogAppender};
+        use fast_log::error::LogError;
+        use fast_log::TimeFormat;
+        use std::sync::{Arc, Mutex};
+        struct Fail(&'static str);
+        impl LogAppender for Fail {
+            fn do_logs(&self, records: &[FastLogRecord]) {
+                if records.iter().any(|r| !r.args.is_empty()) {
+                    fast_log::report_error(&LogError::from(self.0));
+                }
+            }
+        }
+        let errors = Arc::new(Mutex::new(vec![]));
+        let config = |name: &'static str, appender: &TestAppender, time_format: TimeFormat| {
+            let errors = errors.clone();
+            Config::new()
+                .pattern("%d %m%n")
+                .time_format(time_format)
+                .add_appender(appender.clone())
+                .add_appender(Fail(name))
+                .on_error(move |e| errors.lock().unwrap().push((name, e.to_string())))
+        };
+        let first = TestAppender::new();
+        let second = TestAppender::new();
+        let a = FastLogger::new(config("a", &first, TimeFormat::epoch_millis())).unwrap();
+        let b = FastLogger::new(config("b", &second, TimeFormat::rfc3339()).sync(true)).unwrap();
+        log::info!(logger: a, "to a");
+        log::info!(logger: b, "to b");
+        a.flush().unwrap().wait();
+        let formated = first.take_records()[0].formated.clone();
+        let (millis, rest) = formated.split_once(' ').unwrap();
+        assert!(millis.parse::<u128>().is_ok());
+        assert_eq!(rest, "to a\n");
+        let formated = second.take_records()[0].formated.clone();
+        let (time, rest) = formated.split_once(' ').unwrap();
+        assert!(time.contains('T'));
+        assert_eq!(rest, "to b\n");
+        //each hook only gets the errors of its own appenders
+        let mut errors = errors.lock().unwrap().clone();
+        errors.sort();
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].0 == "a" && errors[0].1.ends_with(": a"));
+        assert!(errors[1].0 == "b" && errors[1].1.ends_with(": b"));
+    }
+}
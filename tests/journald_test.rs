@@ -0,0 +1,39 @@
+#[cfg(all(test, unix))]
+mod test {
+    use fast_log::appender::{Command, FastLogRecord, LogAppender};
+    use fast_log::plugin::journald::JournaldAppender;
+    use log::Level;
+    use std::os::unix::net::UnixDatagram;
+    use std::time::Duration;
+
+    #[test]
+    fn test_journald_entry() {
+        let _ = std::fs::remove_dir_all("target/test_journald/");
+        std::fs::create_dir_all("target/test_journald/").unwrap();
+        let server = UnixDatagram::bind("target/test_journald/socket").unwrap();
+        server
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let appender = JournaldAppender::new("my_app")
+            .unwrap()
+            .socket("target/test_journald/socket");
+        appender.do_logs(&[FastLogRecord {
+            target: "app::db".into(),
+            module_path: "app::db".into(),
+            file: "src/db.rs".into(),
+            line: Some(7),
+            key_values: vec![("user-id".to_string(), "7".to_string())],
+            ..FastLogRecord::new(Command::CommandRecord, Level::Warn, "slow\nquery")
+        }]);
+        let mut buf = [0u8; 1024];
+        let n = server.recv(&mut buf).unwrap();
+        let mut expect = b"MESSAGE\n".to_vec();
+        expect.extend_from_slice(&10u64.to_le_bytes());
+        expect.extend_from_slice(b"slow\nquery\n");
+        expect.extend_from_slice(
+            b"PRIORITY=4\nSYSLOG_IDENTIFIER=my_app\nTARGET=app::db\nCODE_MODULE=app::db\nCODE_FILE=src/db.rs\nCODE_LINE=7\nUSER_ID=7\n",
+        );
+        assert_eq!(&buf[..n], &expect[..]);
+        let _ = std::fs::remove_dir_all("target/test_journald/");
+    }
+}
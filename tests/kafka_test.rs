@@ -0,0 +1,113 @@
+#[cfg(test)]
+mod test {
+    use fast_log::appender::{Command, FastLogRecord, LogAppender};
+    use fast_log::error::LogError;
+    use fast_log::plugin::kafka::{KafkaAppender, KafkaKey, KafkaMessage, KafkaProducer};
+    use fast_log::WaitGroup;
+    use log::Level;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct MockProducer {
+        sent: Arc<Mutex<Vec<(String, KafkaMessage)>>>,
+        flushed: Arc<Mutex<usize>>,
+        fail_flush: bool,
+    }
+
+    impl KafkaProducer for MockProducer {
+        fn send(&self, topic: &str, message: &KafkaMessage) -> Result<(), LogError> {
+            if message.payload.contains("fail") {
+                return Err(LogError::from("broker down"));
+            }
+            self.sent
+                .lock()
+                .unwrap()
+                .push((topic.to_string(), message.clone()));
+            Ok(())
+        }
+
+        fn flush(&self) -> Result<(), LogError> {
+            *self.flushed.lock().unwrap() += 1;
+            if self.fail_flush {
+                return Err(LogError::from("flush timeout"));
+            }
+            Ok(())
+        }
+    }
+
+    fn record(level: Level, formated: &str) -> FastLogRecord {
+        FastLogRecord {
+            target: "app::db".into(),
+            formated: formated.to_string(),
+            ..FastLogRecord::new(Command::CommandRecord, level, "")
+        }
+    }
+
+    #[test]
+    fn test_kafka_appender() {
+        let producer = MockProducer::default();
+        let failed = Arc::new(Mutex::new(vec![]));
+        let f = failed.clone();
+        let appender = KafkaAppender::new("logs", producer.clone())
+            .key(KafkaKey::Level)
+            .batch_size(2)
+            .on_error(move |m, _| f.lock().unwrap().push(m.payload.clone()));
+        appender.do_logs(&[
+            record(Level::Warn, "a"),
+            record(Level::Info, "fail"),
+            record(Level::Error, "b"),
+        ]);
+        let sent = producer.sent.lock().unwrap().clone();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[0].0, "logs");
+        assert_eq!(sent[0].1.key.as_deref(), Some("WARN"));
+        assert_eq!(sent[1].1.payload, "b");
+        assert_eq!(*failed.lock().unwrap(), vec!["fail".to_string()]);
+        assert_eq!(*producer.flushed.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_kafka_flush_fail() {
+        let producer = MockProducer {
+            fail_flush: true,
+            ..Default::default()
+        };
+        let failed = Arc::new(Mutex::new(vec![]));
+        let f = failed.clone();
+        let appender = KafkaAppender::new("logs", producer.clone()).on_error(move |m, e| {
+            assert!(e.to_string().ends_with("flush timeout"));
+            f.lock().unwrap().push(m.payload.clone())
+        });
+        let mut flush = record(Level::Info, "");
+        flush.command = Command::CommandFlush(WaitGroup::new());
+        appender.do_logs(&[record(Level::Warn, "a"), record(Level::Info, "b"), flush]);
+        assert_eq!(
+            *failed.lock().unwrap(),
+            vec!["a".to_string(), "b".to_string()]
+        );
+        let mut flush = record(Level::Info, "");
+        flush.command = Command::CommandFlush(WaitGroup::new());
+        appender.do_logs(&[flush]);
+        assert_eq!(failed.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "kafka")]
+    fn test_rdkafka_delivery_fail() {
+        use fast_log::plugin::kafka::{ClientConfig, RdKafkaProducer};
+        let mut config = ClientConfig::new();
+        config
+            .set("bootstrap.servers", "127.0.0.1:1")
+            .set("message.timeout.ms", "200");
+        let producer = RdKafkaProducer::from_config(&config).unwrap();
+        let failed = Arc::new(Mutex::new(vec![]));
+        let f = failed.clone();
+        let appender = KafkaAppender::new("logs", producer)
+            .on_error(move |m, _| f.lock().unwrap().push(m.payload.clone()));
+        appender.do_logs(&[record(Level::Info, "lost")]);
+        let mut exit = record(Level::Info, "");
+        exit.command = Command::CommandExit;
+        appender.do_logs(&[exit]);
+        assert_eq!(*failed.lock().unwrap(), vec!["lost".to_string()]);
+    }
+}
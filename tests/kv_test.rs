@@ -0,0 +1,59 @@
+#[cfg(test)]
+mod test {
+    use fast_log::appender::{Command, FastLogRecord, LogAppender};
+    use fast_log::Config;
+    use std::sync::{Arc, Mutex};
+
+    type KeyValues = Vec<(String, String)>;
+
+    struct Collect(Arc<Mutex<Vec<KeyValues>>>);
+
+    impl LogAppender for Collect {
+        fn do_logs(&self, records: &[FastLogRecord]) {
+            for x in records {
+                if x.command == Command::CommandRecord {
+                    self.0.lock().unwrap().push(x.key_values.clone());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_key_values() {
+        let records = Arc::new(Mutex::new(vec![]));
+        fast_log::init(Config::new().add_appender(Collect(records.clone()))).unwrap();
+        log::info!(user = "bob", id = 7; "login");
+        log::info!("no kv");
+        fast_log::context::insert("request_id", "r1");
+        fast_log::context::insert("user", "alice");
+        log::info!(user = "bob"; "in request");
+        fast_log::context::clear();
+        fast_log::flush().unwrap().wait();
+        assert_eq!(
+            *records.lock().unwrap(),
+            vec![
+                vec![
+                    ("user".to_string(), "bob".to_string()),
+                    ("id".to_string(), "7".to_string())
+                ],
+                vec![],
+                vec![
+                    ("user".to_string(), "bob".to_string()),
+                    ("request_id".to_string(), "r1".to_string())
+                ]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_context() {
+        fast_log::context::insert("a", 1);
+        fast_log::context::insert("a", 2);
+        assert_eq!(fast_log::context::get("a"), Some("2".to_string()));
+        std::thread::spawn(|| assert_eq!(fast_log::context::get("a"), None))
+            .join()
+            .unwrap();
+        assert_eq!(fast_log::context::remove("a"), Some("2".to_string()));
+        assert_eq!(fast_log::context::get("a"), None);
+    }
+}
@@ -0,0 +1,39 @@
+#[cfg(test)]
+mod test {
+    use fast_log::appender::{Command, FastLogRecord, LogAppender};
+    use fast_log::Config;
+    use log::{Level, LevelFilter};
+    use std::sync::{Arc, Mutex};
+
+    struct LevelAppender {
+        levels: Arc<Mutex<Vec<Level>>>,
+    }
+
+    impl LogAppender for LevelAppender {
+        fn do_logs(&self, records: &[FastLogRecord]) {
+            for x in records {
+                if x.command == Command::CommandRecord {
+                    self.levels.lock().unwrap().push(x.level);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_set_level() {
+        let levels = Arc::new(Mutex::new(vec![]));
+        fast_log::init(Config::new().add_appender(LevelAppender {
+            levels: levels.clone(),
+        }))
+        .unwrap();
+        fast_log::set_level(LevelFilter::Warn);
+        assert_eq!(fast_log::get_level(), LevelFilter::Warn);
+        log::info!("dropped");
+        log::warn!("kept");
+        fast_log::set_level(LevelFilter::Debug);
+        log::debug!("kept");
+        log::trace!("dropped");
+        log::logger().flush();
+        assert_eq!(*levels.lock().unwrap(), vec![Level::Warn, Level::Debug]);
+    }
+}
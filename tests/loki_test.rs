@@ -0,0 +1,109 @@
+#[cfg(test)]
+#[cfg(feature = "http")]
+mod test {
+    use fast_log::appender::{Command, FastLogRecord, LogAppender};
+    use fast_log::plugin::loki::LokiAppender;
+    use fast_log::WaitGroup;
+    use log::Level;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::time::Duration;
+
+    fn record(command: Command, level: Level, formated: &str) -> FastLogRecord {
+        FastLogRecord {
+            formated: formated.to_string(),
+            ..FastLogRecord::new(command, level, "")
+        }
+    }
+
+    /// accept one http request, reply `status`, return (request line, body)
+    fn serve_one(server: &TcpListener, status: &str) -> (String, String) {
+        let (stream, _) = server.accept().unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+        let mut len = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if line == "\r\n" {
+                break;
+            }
+            if let Some(v) = line.to_lowercase().strip_prefix("content-length:") {
+                len = v.trim().parse().unwrap();
+            }
+        }
+        let mut body = vec![0u8; len];
+        reader.read_exact(&mut body).unwrap();
+        let mut stream = reader.into_inner();
+        stream
+            .write_all(
+                format!(
+                    "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    status
+                )
+                .as_bytes(),
+            )
+            .unwrap();
+        (request_line, String::from_utf8(body).unwrap())
+    }
+
+    #[test]
+    fn test_loki_push_retry() {
+        let server = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        let appender = LokiAppender::new(&format!("http://{}", addr))
+            .label("app", "demo")
+            .backoff(Duration::from_millis(10))
+            .flush_interval(Duration::from_secs(60));
+        let wg = WaitGroup::new();
+        appender.do_logs(&[
+            record(Command::CommandRecord, Level::Info, "hello \"loki\"\n"),
+            record(Command::CommandRecord, Level::Error, "boom\n"),
+            record(Command::CommandFlush(wg.clone()), Level::Info, ""),
+        ]);
+        let (_, first) = serve_one(&server, "503 Service Unavailable");
+        let (request_line, body) = serve_one(&server, "204 No Content");
+        wg.wait();
+        assert_eq!(first, body);
+        assert!(request_line.starts_with("POST /loki/api/v1/push "));
+        assert!(body.contains(r#"{"stream":{"app":"demo","level":"error"},"values":[[""#));
+        assert!(body.contains(r#"","boom"]]}"#));
+        assert!(body.contains(r#"","hello \"loki\""]]}"#));
+    }
+
+    #[test]
+    fn test_loki_exit_flush() {
+        let server = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        let appender =
+            LokiAppender::new(&format!("http://{}", addr)).flush_interval(Duration::from_secs(60));
+        let handle = std::thread::spawn(move || {
+            appender.do_logs(&[
+                record(Command::CommandRecord, Level::Info, "last words\n"),
+                record(Command::CommandExit, Level::Info, ""),
+            ]);
+        });
+        let (_, body) = serve_one(&server, "204 No Content");
+        handle.join().unwrap();
+        assert!(body.contains(r#"","last words"]]}"#));
+    }
+
+    #[test]
+    fn test_loki_max_in_flight() {
+        let server = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        let appender = LokiAppender::new(&format!("http://{}", addr)).max_in_flight(5);
+        let wg = WaitGroup::new();
+        appender.do_logs(&[
+            record(Command::CommandRecord, Level::Info, "dropped\n"),
+            record(Command::CommandFlush(wg.clone()), Level::Info, ""),
+        ]);
+        wg.wait();
+        server.set_nonblocking(true).unwrap();
+        assert!(server.accept().is_err());
+    }
+}
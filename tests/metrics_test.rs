@@ -0,0 +1,46 @@
+#[cfg(test)]
+mod test {
+    use fast_log::appender::{FastLogRecord, LogAppender};
+    use fast_log::consts::LogSize;
+    use fast_log::plugin::file_split::RollingType;
+    use fast_log::plugin::packer::LogPacker;
+    use fast_log::Config;
+
+    struct Nop;
+
+    impl LogAppender for Nop {
+        fn do_logs(&self, _records: &[FastLogRecord]) {}
+    }
+
+    #[test]
+    fn test_metrics() {
+        let _ = std::fs::remove_dir_all("target/test_metrics/");
+        fast_log::init(
+            Config::new()
+                .add_appender(Nop)
+                .file_split(
+                    "target/test_metrics/",
+                    LogSize::B(10),
+                    RollingType::All,
+                    LogPacker {},
+                )
+                .route("audit", Nop),
+        )
+        .unwrap();
+        fast_log::print("0123456789\n".to_string()).unwrap();
+        fast_log::print("abc\n".to_string()).unwrap();
+        log::info!(target: "audit", "x");
+        fast_log::flush().unwrap().wait();
+        let metrics = fast_log::metrics();
+        assert_eq!(metrics.received, 3);
+        assert_eq!(metrics.dropped, 0);
+        assert_eq!(metrics.seq, 3);
+        assert_eq!(metrics.queue_len, 0);
+        assert!(metrics.rotations >= 1);
+        assert_eq!(metrics.appender_bytes[0], ("0".to_string(), 15));
+        assert_eq!(metrics.appender_bytes[1], ("1".to_string(), 15));
+        assert_eq!(metrics.appender_bytes[2].0, "route:audit");
+        assert!(metrics.appender_bytes[2].1 > 0);
+        let _ = std::fs::remove_dir_all("target/test_metrics/");
+    }
+}
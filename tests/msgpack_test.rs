@@ -0,0 +1,73 @@
+#[cfg(test)]
+mod test {
+    use fast_log::appender::{Command, FastLogRecord, LogAppender};
+    use fast_log::plugin::msgpack::{decode, MsgPackFileAppender, MsgPackReader};
+    use log::Level;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn record(args: &str) -> FastLogRecord {
+        FastLogRecord {
+            target: "app::db".into(),
+            module_path: "app::db".into(),
+            file: "src/db.rs".into(),
+            line: Some(7),
+            now: UNIX_EPOCH + Duration::from_nanos(1_700_000_000_123_456_789),
+            key_values: vec![("user".to_string(), "bob".to_string())],
+            pid: 42,
+            thread_id: 3,
+            thread_name: "worker".into(),
+            hostname: "host".into(),
+            trace_id: Some("4bf92f35".to_string()),
+            seq: 300,
+            ..FastLogRecord::new(Command::CommandRecord, Level::Warn, args)
+        }
+    }
+
+    #[test]
+    fn test_msgpack_file() {
+        let _ = std::fs::remove_dir_all("target/test_msgpack/");
+        let appender = MsgPackFileAppender::new("target/test_msgpack/trace.mpk").unwrap();
+        let long = "x".repeat(70_000);
+        appender.do_logs(&[record("hello"), record(&long)]);
+        let records: Vec<FastLogRecord> = MsgPackReader::open("target/test_msgpack/trace.mpk")
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(records.len(), 2);
+        let r = &records[0];
+        let want = record("hello");
+        assert_eq!(
+            (r.level, r.args.as_str(), r.line, r.seq),
+            (Level::Warn, "hello", Some(7), 300)
+        );
+        assert_eq!(
+            (r.target.as_ref(), r.file.as_ref()),
+            ("app::db", "src/db.rs")
+        );
+        assert_eq!(
+            (r.pid, r.thread_id, r.thread_name.as_ref()),
+            (42, 3, "worker")
+        );
+        assert_eq!(
+            (r.now, &r.key_values, &r.trace_id),
+            (want.now, &want.key_values, &want.trace_id)
+        );
+        assert_eq!(r.span_id, None);
+        assert_eq!(records[1].args, long);
+
+        //unknown keys are skipped: {"x":[1,"a"],"msg":"hi"}
+        let r = decode(&[
+            0x82, 0xa1, b'x', 0x92, 1, 0xa1, b'a', 0xa3, b'm', b's', b'g', 0xa2, b'h', b'i',
+        ])
+        .unwrap();
+        assert_eq!(r.args, "hi");
+
+        //a frame cut by a crash
+        let data = std::fs::read("target/test_msgpack/trace.mpk").unwrap();
+        let cut = &data[..data.len() - 10];
+        let mut reader = MsgPackReader::new(cut);
+        assert!(reader.next().unwrap().is_ok());
+        assert!(reader.next().unwrap().is_err());
+        assert!(reader.next().is_none());
+    }
+}
@@ -0,0 +1,123 @@
+#[cfg(test)]
+#[cfg(feature = "http")]
+mod test {
+    use fast_log::appender::{Command, FastLogRecord, LogAppender};
+    use fast_log::plugin::ndjson::HttpAppender;
+    use fast_log::WaitGroup;
+    use log::Level;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::time::Duration;
+
+    fn record(command: Command, args: &str) -> FastLogRecord {
+        FastLogRecord {
+            target: "app".into(),
+            module_path: "app".into(),
+            file: "src/main.rs".into(),
+            line: Some(1),
+            ..FastLogRecord::new(command, Level::Info, args)
+        }
+    }
+
+    /// accept one http request, reply `status`, return (request line and headers, body)
+    fn serve_one(server: &TcpListener, status: &str) -> (String, Vec<u8>) {
+        let (stream, _) = server.accept().unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut head = String::new();
+        let mut len = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if line == "\r\n" {
+                break;
+            }
+            if let Some(v) = line.to_lowercase().strip_prefix("content-length:") {
+                len = v.trim().parse().unwrap();
+            }
+            head.push_str(&line);
+        }
+        let mut body = vec![0u8; len];
+        reader.read_exact(&mut body).unwrap();
+        let mut stream = reader.into_inner();
+        stream
+            .write_all(
+                format!(
+                    "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    status
+                )
+                .as_bytes(),
+            )
+            .unwrap();
+        (head.to_lowercase(), body)
+    }
+
+    #[test]
+    fn test_http_ndjson_retry() {
+        let server = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        let appender = HttpAppender::new(&format!("http://{}/logs", addr))
+            .bearer_auth("secret")
+            .backoff(Duration::from_millis(10))
+            .flush_interval(Duration::from_secs(60));
+        let wg = WaitGroup::new();
+        appender.do_logs(&[
+            record(Command::CommandRecord, "hello \"http\""),
+            record(Command::CommandRecord, "second"),
+            record(Command::CommandFlush(wg.clone()), ""),
+        ]);
+        let (_, first) = serve_one(&server, "503 Service Unavailable");
+        let (head, body) = serve_one(&server, "200 OK");
+        wg.wait();
+        assert_eq!(first, body);
+        assert!(head.starts_with("post /logs "));
+        assert!(head.contains("authorization: bearer secret\r\n"));
+        assert!(head.contains("content-type: application/x-ndjson\r\n"));
+        let body = String::from_utf8(body).unwrap();
+        let lines: Vec<&str> = body.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with(r#"{"args":"hello \"http\"","#));
+        assert!(lines[1].starts_with(r#"{"args":"second","#) && body.ends_with("}\n"));
+    }
+
+    #[test]
+    fn test_http_max_in_flight() {
+        let server = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        let appender = HttpAppender::new(&format!("http://{}/logs", addr)).max_in_flight(10);
+        let wg = WaitGroup::new();
+        appender.do_logs(&[
+            record(Command::CommandRecord, "dropped"),
+            record(Command::CommandFlush(wg.clone()), ""),
+        ]);
+        wg.wait();
+        server.set_nonblocking(true).unwrap();
+        assert!(server.accept().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "gzip")]
+    fn test_http_gzip() {
+        let server = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+        let appender = HttpAppender::new(&format!("http://{}/logs", addr))
+            .basic_auth("user", "pass")
+            .gzip(true);
+        let wg = WaitGroup::new();
+        appender.do_logs(&[
+            record(Command::CommandRecord, "zipped"),
+            record(Command::CommandFlush(wg.clone()), ""),
+        ]);
+        let (head, body) = serve_one(&server, "200 OK");
+        wg.wait();
+        assert!(head.contains("content-encoding: gzip\r\n"));
+        assert!(head.contains("authorization: basic dxnlcjpwyxnz\r\n"));
+        let mut text = String::new();
+        flate2::read::GzDecoder::new(&body[..])
+            .read_to_string(&mut text)
+            .unwrap();
+        assert!(text.starts_with(r#"{"args":"zipped","#));
+    }
+}
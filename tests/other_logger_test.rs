@@ -0,0 +1,29 @@
+#[cfg(test)]
+#[cfg(unix)]
+mod test {
+    use fast_log::Config;
+
+    struct OtherLogger;
+
+    impl log::Log for OtherLogger {
+        fn enabled(&self, _metadata: &log::Metadata) -> bool {
+            false
+        }
+        fn log(&self, _record: &log::Record) {}
+        fn flush(&self) {}
+    }
+
+    #[test]
+    fn test_init_other_logger() {
+        log::set_logger(&OtherLogger).unwrap();
+        std::fs::create_dir_all("target/test_other_logger/").unwrap();
+        let _ = std::fs::remove_file("target/test_other_logger/log.sock");
+        let config = Config::new()
+            .console()
+            .level(log::LevelFilter::Trace)
+            .control_socket("target/test_other_logger/log.sock");
+        assert!(fast_log::init(config).is_err());
+        assert!(!std::path::Path::new("target/test_other_logger/log.sock").exists());
+        assert_eq!(log::max_level(), log::LevelFilter::Off);
+    }
+}
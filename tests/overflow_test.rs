@@ -0,0 +1,31 @@
+#[cfg(test)]
+mod test {
+    use fast_log::appender::{FastLogRecord, LogAppender};
+    use fast_log::config::OverflowPolicy;
+    use fast_log::{Config, LOGGER};
+    use std::time::Duration;
+
+    struct SlowAppender {}
+
+    impl LogAppender for SlowAppender {
+        fn do_logs(&self, _records: &[FastLogRecord]) {
+            std::thread::sleep(Duration::from_millis(50));
+        }
+    }
+
+    #[test]
+    fn test_drop_newest() {
+        fast_log::init(
+            Config::new()
+                .chan_len(Some(1))
+                .overflow(OverflowPolicy::DropNewest)
+                .add_appender(SlowAppender {}),
+        )
+        .unwrap();
+        for _ in 0..1000 {
+            log::info!("Commencing yak shaving");
+        }
+        assert!(LOGGER.dropped() > 0);
+        log::logger().flush();
+    }
+}
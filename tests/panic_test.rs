@@ -0,0 +1,43 @@
+#[cfg(test)]
+mod test {
+    use fast_log::appender::{Command, FastLogRecord, LogAppender};
+    use fast_log::Config;
+    use log::Level;
+    use std::sync::{Arc, Mutex};
+
+    struct Collect(Arc<Mutex<Vec<(Level, String, String)>>>);
+
+    impl LogAppender for Collect {
+        fn do_logs(&self, records: &[FastLogRecord]) {
+            for x in records {
+                if x.command == Command::CommandRecord {
+                    self.0
+                        .lock()
+                        .unwrap()
+                        .push((x.level, x.target.to_string(), x.args.clone()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_panic_hook() {
+        let records = Arc::new(Mutex::new(vec![]));
+        fast_log::init(Config::new().add_appender(Collect(records.clone()))).unwrap();
+        fast_log::install_panic_hook();
+        let result = std::thread::Builder::new()
+            .name("worker".to_string())
+            .spawn(|| panic!("boom {}", 1))
+            .unwrap()
+            .join();
+        assert!(result.is_err());
+        //the hook has flushed before the thread unwinds
+        let records = records.lock().unwrap();
+        assert_eq!(records.len(), 1);
+        let (level, target, args) = &records[0];
+        assert_eq!(*level, Level::Error);
+        assert_eq!(target, "panic");
+        assert!(args.starts_with("thread 'worker' panicked at tests/panic_test.rs:"));
+        assert!(args.contains(": boom 1"));
+    }
+}
@@ -0,0 +1,37 @@
+#[cfg(test)]
+mod test {
+    use fast_log::plugin::test::TestAppender;
+    use fast_log::Config;
+
+    #[test]
+    fn test_record_process_fields() {
+        let appender = TestAppender::new();
+        fast_log::init(Config::new().add_appender(appender.clone()).sync(true)).unwrap();
+        log::info!("main");
+        std::thread::Builder::new()
+            .name("worker".to_string())
+            .spawn(|| log::info!("worker"))
+            .unwrap()
+            .join()
+            .unwrap();
+        std::thread::spawn(|| log::info!("unnamed")).join().unwrap();
+        let records = appender.take_records();
+        assert_eq!(records.len(), 3);
+        assert!(records.iter().all(|x| x.pid == std::process::id()));
+        assert_eq!(&*records[1].thread_name, "worker");
+        assert_eq!(&*records[2].thread_name, "");
+        assert_ne!(records[0].thread_id, records[1].thread_id);
+        assert_ne!(records[1].thread_id, records[2].thread_id);
+        assert_eq!(records[0].hostname, records[1].hostname);
+        #[cfg(unix)]
+        assert!(!records[0].hostname.is_empty());
+    }
+
+    #[test]
+    fn test_intern() {
+        let a = fast_log::intern("app::db");
+        assert!(std::sync::Arc::ptr_eq(&a, &fast_log::intern("app::db")));
+        assert!(!std::sync::Arc::ptr_eq(&a, &fast_log::intern("app::web")));
+        assert_eq!(&*a, "app::db");
+    }
+}
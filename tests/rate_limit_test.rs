@@ -0,0 +1,52 @@
+#[cfg(test)]
+mod test {
+    use fast_log::appender::{Command, FastLogRecord, LogAppender};
+    use fast_log::filter::{RateLimitFilter, RateLimitKey};
+    use fast_log::Config;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    struct Collect(Arc<Mutex<Vec<String>>>);
+
+    impl LogAppender for Collect {
+        fn do_logs(&self, records: &[FastLogRecord]) {
+            for x in records {
+                if x.command == Command::CommandRecord {
+                    self.0.lock().unwrap().push(x.args.clone());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let records = Arc::new(Mutex::new(vec![]));
+        fast_log::init(
+            Config::new()
+                .add_appender(Collect(records.clone()))
+                .add_filter(RateLimitFilter::new(
+                    2,
+                    Duration::from_millis(200),
+                    RateLimitKey::Message,
+                )),
+        )
+        .unwrap();
+        for _ in 0..5 {
+            log::error!("db down");
+        }
+        log::error!("other");
+        std::thread::sleep(Duration::from_millis(300));
+        log::error!("db down");
+        fast_log::flush().unwrap().wait();
+        assert_eq!(
+            *records.lock().unwrap(),
+            vec![
+                "db down",
+                "db down",
+                "other",
+                "suppressed 3 similar messages",
+                "db down"
+            ]
+        );
+    }
+}
@@ -0,0 +1,70 @@
+#[cfg(test)]
+#[cfg(feature = "toml")]
+mod test {
+    use std::time::Duration;
+
+    #[test]
+    fn test_init_from_file_watch() {
+        let _ = std::fs::remove_dir_all("target/test_reload/");
+        std::fs::create_dir_all("target/test_reload/").unwrap();
+        let a = "[[appender]]\ntype = \"file\"\npath = \"target/test_reload/a.log\"\n";
+        let b = "[[appender]]\ntype = \"file\"\npath = \"target/test_reload/b.log\"\n";
+        let c = "[[appender]]\ntype = \"file\"\npath = \"target/test_reload/c.log\"\n";
+        //a bad appender fails init before the logger is installed
+        std::fs::write("target/test_reload/dir", "").unwrap();
+        std::fs::write(
+            "target/test_reload/log.toml",
+            "[[appender]]\ntype = \"file\"\npath = \"target/test_reload/dir/x.log\"\n",
+        )
+        .unwrap();
+        assert!(fast_log::init_from_file_watch(
+            "target/test_reload/log.toml",
+            Duration::from_millis(20)
+        )
+        .is_err());
+        //the add commands of a small channel are sent after the dispatcher runs
+        std::fs::write(
+            "target/test_reload/log.toml",
+            format!("level = \"info\"\nchan_len = 1\n{}{}", a, c),
+        )
+        .unwrap();
+        fast_log::init_from_file_watch("target/test_reload/log.toml", Duration::from_millis(20))
+            .unwrap();
+        log::info!("first");
+        log::logger().flush();
+        std::thread::sleep(Duration::from_millis(50));
+        std::fs::write(
+            "target/test_reload/log.toml",
+            format!(
+                "level = \"warn\"\nchan_len = 1\ndeny_args = [\"^secret\"]\n{}{}{}",
+                a, b, c
+            ),
+        )
+        .unwrap();
+        let mut reloaded = false;
+        for _ in 0..250 {
+            log::warn!("second");
+            log::logger().flush();
+            let data = std::fs::read_to_string("target/test_reload/b.log").unwrap_or_default();
+            if data.contains("second") {
+                reloaded = true;
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert!(reloaded);
+        assert_eq!(log::max_level(), log::LevelFilter::Warn);
+        log::info!("dropped by level");
+        log::warn!("secret dropped by filter");
+        log::logger().flush();
+        let a_data = std::fs::read_to_string("target/test_reload/a.log").unwrap();
+        let b_data = std::fs::read_to_string("target/test_reload/b.log").unwrap();
+        assert!(a_data.contains("first") && a_data.contains("second"));
+        assert!(std::fs::read_to_string("target/test_reload/c.log")
+            .unwrap()
+            .contains("first"));
+        assert!(!b_data.contains("first"));
+        assert!(!a_data.contains("dropped"));
+        assert!(!b_data.contains("dropped"));
+    }
+}
@@ -0,0 +1,31 @@
+#[cfg(test)]
+mod test {
+    use fast_log::appender::{Command, FastLogRecord, LogAppender};
+    use fast_log::plugin::ring_buffer::RingBufferAppender;
+    use log::Level;
+
+    fn record(formated: &str) -> FastLogRecord {
+        FastLogRecord {
+            formated: formated.to_string(),
+            ..FastLogRecord::new(Command::CommandRecord, Level::Info, "")
+        }
+    }
+
+    #[test]
+    fn test_ring_buffer() {
+        let ring = RingBufferAppender::new(2);
+        let shared = ring.clone();
+        ring.do_logs(&[record("a\n"), record("b\n")]);
+        ring.do_logs(&[record("c\n")]);
+        let mut out = vec![];
+        shared.dump(&mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "b\nc\n");
+        let _ = std::fs::remove_dir_all("target/test_ring/");
+        shared.dump_to_file("target/test_ring/dump.log").unwrap();
+        assert_eq!(
+            std::fs::read_to_string("target/test_ring/dump.log").unwrap(),
+            "b\nc\n"
+        );
+        let _ = std::fs::remove_dir_all("target/test_ring/");
+    }
+}
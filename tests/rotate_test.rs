@@ -0,0 +1,56 @@
+#[cfg(test)]
+mod test {
+    use fast_log::consts::LogSize;
+    use fast_log::plugin::file_split::RollingType;
+    use fast_log::plugin::packer::LogPacker;
+    use fast_log::Config;
+    use std::fs::remove_dir_all;
+    use std::time::Duration;
+
+    fn packs(dir: &str) -> usize {
+        std::fs::read_dir(dir)
+            .unwrap()
+            .filter(|v| v.as_ref().unwrap().file_name() != "temp.log")
+            .count()
+    }
+
+    #[test]
+    fn test_rotate() {
+        let dir = "target/test_rotate/";
+        let _ = remove_dir_all(dir);
+        fast_log::init(Config::new().file_split(
+            dir,
+            LogSize::MB(1),
+            RollingType::All,
+            LogPacker {},
+        ))
+        .unwrap();
+        log::info!("first");
+        fast_log::rotate().unwrap();
+        fast_log::flush_sync(Duration::from_secs(5)).unwrap();
+        assert_eq!(packs(dir), 1);
+        //nothing to roll
+        fast_log::rotate().unwrap();
+        fast_log::flush_sync(Duration::from_secs(5)).unwrap();
+        assert_eq!(packs(dir), 1);
+        #[cfg(unix)]
+        {
+            fast_log::rotate_on_sighup().unwrap();
+            //packs are named by the time
+            std::thread::sleep(Duration::from_secs(1));
+            log::info!("second");
+            fast_log::flush_sync(Duration::from_secs(5)).unwrap();
+            std::process::Command::new("kill")
+                .args(["-HUP", &std::process::id().to_string()])
+                .status()
+                .unwrap();
+            let mut n = 0;
+            while packs(dir) != 2 && n < 50 {
+                std::thread::sleep(Duration::from_millis(100));
+                n += 1;
+            }
+            assert_eq!(packs(dir), 2);
+        }
+        let _ = remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,37 @@
+#[cfg(test)]
+mod test {
+    use fast_log::appender::{Command, FastLogRecord, LogAppender};
+    use fast_log::Config;
+    use std::sync::{Arc, Mutex};
+
+    struct Collect(Arc<Mutex<Vec<String>>>);
+
+    impl LogAppender for Collect {
+        fn do_logs(&self, records: &[FastLogRecord]) {
+            for x in records {
+                if x.command == Command::CommandRecord {
+                    self.0.lock().unwrap().push(x.args.clone());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_route() {
+        let main = Arc::new(Mutex::new(vec![]));
+        let audit = Arc::new(Mutex::new(vec![]));
+        fast_log::init(
+            Config::new()
+                .add_appender(Collect(main.clone()))
+                .route("audit::*", Collect(audit.clone())),
+        )
+        .unwrap();
+        log::info!("app");
+        log::info!(target: "audit", "audit");
+        log::info!(target: "audit::login", "login");
+        log::info!(target: "auditor", "auditor");
+        fast_log::flush().unwrap().wait();
+        assert_eq!(*main.lock().unwrap(), vec!["app", "auditor"]);
+        assert_eq!(*audit.lock().unwrap(), vec!["audit", "login"]);
+    }
+}
@@ -0,0 +1,36 @@
+#[cfg(test)]
+mod test {
+    use fast_log::appender::{Command, FastLogRecord, LogAppender};
+    use fast_log::Config;
+    use std::sync::{Arc, Mutex};
+
+    struct Collect(Arc<Mutex<Vec<String>>>);
+
+    impl LogAppender for Collect {
+        fn do_logs(&self, records: &[FastLogRecord]) {
+            for x in records {
+                if x.command == Command::CommandRecord {
+                    self.0.lock().unwrap().push(x.args.clone());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_add_remove_appender() {
+        let all = Arc::new(Mutex::new(vec![]));
+        let debug = Arc::new(Mutex::new(vec![]));
+        let logger = fast_log::init(Config::new().add_appender(Collect(all.clone()))).unwrap();
+        log::info!("before");
+        logger
+            .add_appender("debug", Box::new(Collect(debug.clone())))
+            .unwrap();
+        log::info!("during");
+        fast_log::flush().unwrap().wait();
+        logger.remove_appender("debug").unwrap();
+        log::info!("after");
+        fast_log::flush().unwrap().wait();
+        assert_eq!(*all.lock().unwrap(), vec!["before", "during", "after"]);
+        assert_eq!(*debug.lock().unwrap(), vec!["during"]);
+    }
+}
@@ -0,0 +1,75 @@
+#[cfg(test)]
+#[cfg(feature = "s3")]
+mod test {
+    use fast_log::plugin::file_split::Packer;
+    use fast_log::plugin::packer::LogPacker;
+    use fast_log::plugin::s3::S3Packer;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::path::Path;
+    use std::time::Duration;
+
+    /// accept one http request, reply `status`, return (request line, headers, body)
+    fn serve_one(server: &TcpListener, status: &str) -> (String, Vec<String>, String) {
+        let (stream, _) = server.accept().unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+        let mut headers = vec![];
+        let mut len = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if line == "\r\n" {
+                break;
+            }
+            if let Some(v) = line.to_lowercase().strip_prefix("content-length:") {
+                len = v.trim().parse().unwrap();
+            }
+            headers.push(line.trim().to_lowercase());
+        }
+        let mut body = vec![0u8; len];
+        reader.read_exact(&mut body).unwrap();
+        let mut stream = reader.into_inner();
+        stream
+            .write_all(
+                format!(
+                    "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    status
+                )
+                .as_bytes(),
+            )
+            .unwrap();
+        (request_line, headers, String::from_utf8(body).unwrap())
+    }
+
+    #[test]
+    fn test_s3_upload_retry() {
+        let _ = std::fs::remove_dir_all("target/test_s3/");
+        std::fs::create_dir_all("target/test_s3/").unwrap();
+        std::fs::write("target/test_s3/temp1.log", "hello").unwrap();
+        let server = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}", server.local_addr().unwrap());
+        let packer = S3Packer::new(LogPacker {}, "logs", "us-east-1")
+            .endpoint(&endpoint)
+            .credentials("AKID", "SECRET")
+            .prefix("app/")
+            .delete_local(true);
+        let handle = std::thread::spawn(move || {
+            packer.pack(Path::new("target/test_s3/temp1.log")).unwrap();
+        });
+        let (_, _, _) = serve_one(&server, "503 Service Unavailable");
+        let (request_line, headers, body) = serve_one(&server, "200 OK");
+        handle.join().unwrap();
+        assert_eq!(request_line, "PUT /logs/app/temp1.log HTTP/1.1\r\n");
+        assert_eq!(body, "hello");
+        assert!(headers
+            .iter()
+            .any(|v| v.starts_with("authorization: aws4-hmac-sha256 credential=akid/")));
+        assert!(!Path::new("target/test_s3/temp1.log").exists());
+        let _ = std::fs::remove_dir_all("target/test_s3/");
+    }
+}
@@ -0,0 +1,87 @@
+#[cfg(test)]
+#[cfg(feature = "http")]
+mod test {
+    use fast_log::appender::{Command, FastLogRecord, LogAppender};
+    use fast_log::plugin::sentry::{Dsn, SentryAppender};
+    use fast_log::WaitGroup;
+    use log::Level;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::time::Duration;
+
+    fn record(command: Command, level: Level, args: &str) -> FastLogRecord {
+        FastLogRecord {
+            target: "app".into(),
+            module_path: "app::db".into(),
+            file: "src/db.rs".into(),
+            line: Some(9),
+            ..FastLogRecord::new(command, level, args)
+        }
+    }
+
+    /// accept one http request, reply 200, return (head, body)
+    fn serve_one(server: &TcpListener) -> (String, String) {
+        let (stream, _) = server.accept().unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut head = String::new();
+        let mut len = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if line == "\r\n" {
+                break;
+            }
+            if let Some(v) = line.to_lowercase().strip_prefix("content-length:") {
+                len = v.trim().parse().unwrap();
+            }
+            head.push_str(&line);
+        }
+        let mut body = vec![0u8; len];
+        reader.read_exact(&mut body).unwrap();
+        let mut stream = reader.into_inner();
+        stream
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        (head, String::from_utf8(body).unwrap())
+    }
+
+    #[test]
+    fn test_dsn_parse() {
+        let dsn = Dsn::parse("https://abc@sentry.example.com/prefix/42").unwrap();
+        assert_eq!(dsn.public_key, "abc");
+        assert_eq!(
+            dsn.envelope_url(),
+            "https://sentry.example.com/prefix/api/42/envelope/"
+        );
+        assert!(Dsn::parse("https://sentry.example.com/42").is_err());
+    }
+
+    #[test]
+    fn test_sentry_error_only() {
+        let server = TcpListener::bind("127.0.0.1:0").unwrap();
+        let dsn = format!("http://key1@{}/7", server.local_addr().unwrap());
+        let appender = SentryAppender::new(&dsn).unwrap().environment("test");
+        let wg = WaitGroup::new();
+        appender.do_logs(&[
+            record(Command::CommandRecord, Level::Warn, "ignored"),
+            record(Command::CommandRecord, Level::Error, "db down"),
+            record(Command::CommandFlush(wg.clone()), Level::Info, ""),
+        ]);
+        let (head, body) = serve_one(&server);
+        wg.wait();
+        assert!(head.starts_with("POST /api/7/envelope/ "));
+        assert!(head.contains("sentry_key=key1"));
+        let lines: Vec<&str> = body.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].starts_with(r#"{"type":"event","length":"#));
+        assert!(lines[2].contains(r#""level":"error""#));
+        assert!(lines[2].contains(r#""environment":"test""#));
+        assert!(lines[2].contains(
+            r#""frames":[{"filename":"src/db.rs","module":"app::db","lineno":9,"in_app":true}]"#
+        ));
+        assert!(!body.contains("ignored"));
+    }
+}
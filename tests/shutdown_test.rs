@@ -0,0 +1,40 @@
+#[cfg(test)]
+mod test {
+    use fast_log::appender::{Command, FastLogRecord, LogAppender};
+    use fast_log::Config;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    struct SlowAppender {
+        records: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl LogAppender for SlowAppender {
+        fn do_logs(&self, records: &[FastLogRecord]) {
+            for x in records {
+                if x.command == Command::CommandRecord {
+                    std::thread::sleep(Duration::from_millis(100));
+                    self.records.lock().unwrap().push(x.args.clone());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_shutdown() {
+        let records = Arc::new(Mutex::new(vec![]));
+        fast_log::init(Config::new().add_appender(SlowAppender {
+            records: records.clone(),
+        }))
+        .unwrap();
+        for i in 0..3 {
+            log::info!("{}", i);
+        }
+        fast_log::shutdown(Duration::from_secs(5)).unwrap();
+        assert_eq!(*records.lock().unwrap(), vec!["0", "1", "2"]);
+        //the logger has exited
+        log::info!("after");
+        assert!(fast_log::shutdown(Duration::from_millis(100)).is_err());
+        assert_eq!(records.lock().unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,38 @@
+#[cfg(test)]
+#[cfg(feature = "slog")]
+mod test {
+    use fast_log::plugin::test::TestAppender;
+    use fast_log::slog_drain::SlogDrain;
+    use fast_log::Config;
+    use log::{Level, LevelFilter};
+    use slog::{o, Drain};
+
+    #[test]
+    fn test_slog_drain() {
+        let appender = TestAppender::new();
+        fast_log::init(
+            Config::new()
+                .add_appender(appender.clone())
+                .level(LevelFilter::Info)
+                .sync(true),
+        )
+        .unwrap();
+        let root = slog::Logger::root(SlogDrain::new().fuse(), o!("app" => "web"));
+        slog::info!(root, "login {}", "ok"; "user" => "bob");
+        slog::debug!(root, "too verbose");
+        slog::crit!(root, #"db", "down");
+        let records = appender.take_records();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].level, Level::Info);
+        assert_eq!(records[0].args, "login ok");
+        assert_eq!(
+            records[0].key_values,
+            vec![
+                ("user".to_string(), "bob".to_string()),
+                ("app".to_string(), "web".to_string())
+            ]
+        );
+        assert_eq!(records[1].level, Level::Error);
+        assert_eq!(records[1].target.as_ref(), "db");
+    }
+}
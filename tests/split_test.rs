@@ -3,8 +3,11 @@ mod test {
     use fast_log::appender::{Command, FastLogRecord, LogAppender};
     use fast_log::consts::LogSize;
     use fast_log::plugin::file_name::FileName;
-    use fast_log::plugin::file_split::{FileSplitAppender, Keep, Packer, RawFile, RollingType};
+    use fast_log::plugin::file_split::{
+        FileSplitAppender, Keep, KeepType, Packed, Packer, Period, RawFile, RollingType,
+    };
     use fast_log::plugin::packer::LogPacker;
+    use fast_log::WaitGroup;
     use log::Level;
     use std::fs::remove_dir_all;
     use std::thread::sleep;
@@ -20,17 +23,7 @@ mod test {
             Box::new(LogPacker {}),
         )
         .unwrap();
-        appender.do_logs(&[FastLogRecord {
-            command: Command::CommandRecord,
-            level: Level::Error,
-            target: "".to_string(),
-            args: "".to_string(),
-            module_path: "".to_string(),
-            file: "".to_string(),
-            line: None,
-            now: SystemTime::now(),
-            formated: "".to_string(),
-        }]);
+        appender.do_logs(&[FastLogRecord::new(Command::CommandRecord, Level::Error, "")]);
         appender.send_pack();
         sleep(Duration::from_secs(1));
         let rolling_num = RollingType::KeepNum(0).do_keep("target/test/", "temp.log");
@@ -38,6 +31,120 @@ mod test {
         let _ = remove_dir_all("target/test/");
     }
 
+    #[test]
+    fn test_send_pack_moves_temp_file() {
+        let _ = remove_dir_all("target/test_move/");
+        let appender = FileSplitAppender::<RawFile>::new(
+            "target/test_move/",
+            LogSize::MB(1),
+            RollingType::All,
+            Box::new(LogPacker {}),
+        )
+        .unwrap();
+        let record = |formated: &str| FastLogRecord {
+            formated: formated.to_string(),
+            ..FastLogRecord::new(Command::CommandRecord, Level::Error, "")
+        };
+        appender.do_logs(&[record("a\n")]);
+        appender.send_pack();
+        appender.do_logs(&[record("b\n")]);
+        let mut packs = vec![];
+        for entry in std::fs::read_dir("target/test_move/").unwrap() {
+            let path = entry.unwrap().path();
+            if !path.ends_with("temp.log") {
+                packs.push(std::fs::read_to_string(path).unwrap());
+            }
+        }
+        assert_eq!(packs, vec!["a\n".to_string()]);
+        assert_eq!(
+            std::fs::read_to_string("target/test_move/temp.log").unwrap(),
+            "b\n"
+        );
+        let _ = remove_dir_all("target/test_move/");
+    }
+
+    #[test]
+    fn test_temp_file_deleted() {
+        let _ = remove_dir_all("target/test_deleted/");
+        let appender = FileSplitAppender::<RawFile>::new(
+            "target/test_deleted/",
+            LogSize::MB(1),
+            RollingType::All,
+            Box::new(LogPacker {}),
+        )
+        .unwrap();
+        let record = |formated: &str| FastLogRecord {
+            formated: formated.to_string(),
+            ..FastLogRecord::new(Command::CommandRecord, Level::Info, "")
+        };
+        appender.do_logs(&[record("a\n")]);
+        std::fs::remove_file("target/test_deleted/temp.log").unwrap();
+        appender.do_logs(&[record("b\n")]);
+        assert_eq!(
+            std::fs::read_to_string("target/test_deleted/temp.log").unwrap(),
+            "b\n"
+        );
+        let _ = remove_dir_all("target/test_deleted/");
+    }
+
+    #[test]
+    fn test_nested_dir_and_temp_fallback() {
+        let _ = remove_dir_all("target/test_nested/");
+        FileSplitAppender::<RawFile>::new(
+            "target/test_nested/app/2024/",
+            LogSize::MB(1),
+            RollingType::All,
+            Box::new(LogPacker {}),
+        )
+        .unwrap();
+        assert!(std::path::Path::new("target/test_nested/app/2024/temp.log").exists());
+        //a file blocks the dir
+        std::fs::write("target/test_nested/file", "").unwrap();
+        assert!(FileSplitAppender::<RawFile>::new(
+            "target/test_nested/file/logs/",
+            LogSize::MB(1),
+            RollingType::All,
+            Box::new(LogPacker {}),
+        )
+        .is_err());
+        let temp = std::env::temp_dir().join("fast_log/target/test_nested/file/logs/");
+        let _ = remove_dir_all(&temp);
+        let _config = fast_log::Config::new().temp_dir_fallback(true).file_split(
+            "target/test_nested/file/logs/",
+            LogSize::MB(1),
+            RollingType::All,
+            LogPacker {},
+        );
+        assert!(temp.join("temp.log").exists());
+        let _ = remove_dir_all(&temp);
+        let _ = remove_dir_all("target/test_nested/");
+    }
+
+    #[test]
+    fn test_current_link() {
+        let _ = remove_dir_all("target/test_link/");
+        let appender = FileSplitAppender::<RawFile>::new(
+            "target/test_link/",
+            LogSize::MB(1),
+            RollingType::All,
+            Box::new(LogPacker {}),
+        )
+        .unwrap()
+        .current_link("current.log");
+        let record = |formated: &str| FastLogRecord {
+            formated: formated.to_string(),
+            ..FastLogRecord::new(Command::CommandRecord, Level::Info, "")
+        };
+        appender.do_logs(&[record("a\n")]);
+        appender.send_pack();
+        appender.do_logs(&[record("b\n")]);
+        assert_eq!(
+            std::fs::read_to_string("target/test_link/current.log").unwrap(),
+            "b\n"
+        );
+        let _ = remove_dir_all("target/test_link/");
+    }
+
     #[test]
     fn test_log_name_create() {
         let p = LogPacker {};
@@ -80,4 +187,289 @@ mod test {
         let p = "C:\\logs\\aa.log".extract_file_name();
         assert_eq!(p, "aa.log");
     }
+
+    #[test]
+    fn test_split_period() {
+        let _ = remove_dir_all("target/test_period/");
+        let appender = FileSplitAppender::<RawFile>::new(
+            "target/test_period/",
+            LogSize::MB(1),
+            RollingType::All,
+            Box::new(LogPacker {}),
+        )
+        .unwrap()
+        .period(Period::Day);
+        let now = SystemTime::now();
+        let record = |now: SystemTime, formated: &str| FastLogRecord {
+            now,
+            formated: formated.to_string(),
+            ..FastLogRecord::new(Command::CommandRecord, Level::Info, "")
+        };
+        appender.do_logs(&[
+            record(now, "first\n"),
+            record(now + Duration::from_secs(24 * 3600), "second\n"),
+        ]);
+        let (start, _) = Period::Day.range(now);
+        let name = format!("target/test_period/temp{}.log", Period::Day.name(start));
+        assert_eq!(std::fs::read_to_string(name).unwrap(), "first\n");
+        assert_eq!(
+            std::fs::read_to_string("target/test_period/temp.log").unwrap(),
+            "second\n"
+        );
+        let _ = remove_dir_all("target/test_period/");
+    }
+
+    #[test]
+    fn test_name_template() {
+        let _ = remove_dir_all("target/test_template/");
+        let appender = FileSplitAppender::<RawFile>::new(
+            "target/test_template/app.log",
+            LogSize::MB(1),
+            RollingType::All,
+            Box::new(LogPacker {}),
+        )
+        .unwrap()
+        .name_template("{dir}/{name}-{date}-{index}.log");
+        appender.send_pack();
+        appender.send_pack();
+        let date = fastdate::DateTime::now().format("YYYY-MM-DD");
+        for index in ["001", "002"] {
+            let name = format!("target/test_template/app-{}-{}.log", date, index);
+            assert!(std::path::Path::new(&name).exists(), "{}", name);
+        }
+        let _ = remove_dir_all("target/test_template/");
+    }
+
+    #[test]
+    fn test_period_week() {
+        let (start, end) = Period::Week.range(SystemTime::now());
+        assert_eq!(end - start, 7 * 24 * 3600);
+        //monday
+        assert_eq!(fastdate::DateTime::from_timestamp(start).week_day(), 1);
+    }
+
+    #[test]
+    fn test_keep_num_after_flush() {
+        let _ = remove_dir_all("target/test_keep/");
+        let appender = FileSplitAppender::<RawFile>::new(
+            "target/test_keep/",
+            LogSize::MB(1),
+            RollingType::KeepNum(1),
+            Box::new(LogPacker {}),
+        )
+        .unwrap();
+        let wg = WaitGroup::new();
+        appender.do_logs(&[FastLogRecord::new(
+            Command::CommandFlush(wg.clone()),
+            Level::Info,
+            "",
+        )]);
+        wg.wait();
+        appender.send_pack();
+        sleep(Duration::from_millis(10));
+        appender.send_pack();
+        sleep(Duration::from_secs(1));
+        let packs = std::fs::read_dir("target/test_keep/")
+            .unwrap()
+            .filter(|v| v.as_ref().unwrap().file_name() != "temp.log")
+            .count();
+        assert_eq!(packs, 1);
+        let _ = remove_dir_all("target/test_keep/");
+    }
+
+    #[test]
+    fn test_keep_size() {
+        let _ = remove_dir_all("target/test_keep_size/");
+        std::fs::create_dir_all("target/test_keep_size/").unwrap();
+        for name in ["temp.log", "temp1.log", "temp2.log", "temp3.log"] {
+            std::fs::write(format!("target/test_keep_size/{}", name), "abc").unwrap();
+        }
+        let removed =
+            KeepType::KeepSize(LogSize::B(7)).do_keep("target/test_keep_size/", "temp.log");
+        assert_eq!(removed, 1);
+        assert!(!std::path::Path::new("target/test_keep_size/temp1.log").exists());
+        assert!(std::path::Path::new("target/test_keep_size/temp.log").exists());
+        let _ = remove_dir_all("target/test_keep_size/");
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip_pack_name() {
+        use fast_log::plugin::packer::GZipPacker;
+        use std::fs::File;
+        let _ = remove_dir_all("target/test_gzip/");
+        std::fs::create_dir_all("target/test_gzip/").unwrap();
+        std::fs::write("target/test_gzip/temp1.log", "hello").unwrap();
+        let file = File::open("target/test_gzip/temp1.log").unwrap();
+        let remove = GZipPacker {}
+            .do_pack(file, "target/test_gzip/temp1.log")
+            .unwrap();
+        assert!(remove);
+        let data = std::fs::read("target/test_gzip/temp1.log.gz").unwrap();
+        assert_eq!(&data[0..2], &[0x1f, 0x8b]);
+        let _ = remove_dir_all("target/test_gzip/");
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_tar_gz_pack() {
+        use fast_log::plugin::packer::TarGzPacker;
+        use std::io::Read;
+        use std::path::Path;
+        let _ = remove_dir_all("target/test_tar_gz/");
+        std::fs::create_dir_all("target/test_tar_gz/").unwrap();
+        let packer = TarGzPacker::new("logs");
+        let mut archive = Packed::Unknown;
+        for (name, data) in [("temp1.log", "hello"), ("temp2.log", "world\n")] {
+            let path = format!("target/test_tar_gz/{}", name);
+            std::fs::write(&path, data).unwrap();
+            archive = packer.pack(Path::new(&path)).unwrap();
+        }
+        let date = fastdate::DateTime::now().format("YYYY-MM-DD");
+        let archive_path = format!("target/test_tar_gz/logs-{}.tar.gz", date);
+        assert_eq!(archive, Packed::Archive(archive_path.clone().into()));
+        let archive = archive_path;
+        let mut tar = vec![];
+        flate2::read::MultiGzDecoder::new(std::fs::File::open(&archive).unwrap())
+            .read_to_end(&mut tar)
+            .unwrap();
+        let mut entries = vec![];
+        let mut at = 0;
+        while at < tar.len() {
+            let header = &tar[at..at + 512];
+            let name = String::from_utf8_lossy(&header[..100])
+                .trim_end_matches('\0')
+                .to_string();
+            let size =
+                usize::from_str_radix(String::from_utf8_lossy(&header[124..135]).as_ref(), 8)
+                    .unwrap();
+            let data = String::from_utf8_lossy(&tar[at + 512..at + 512 + size]).to_string();
+            entries.push((name, data));
+            at += 512 + (size + 511) / 512 * 512;
+        }
+        assert_eq!(
+            entries,
+            vec![
+                ("temp1.log".to_string(), "hello".to_string()),
+                ("temp2.log".to_string(), "world\n".to_string())
+            ]
+        );
+        let _ = remove_dir_all("target/test_tar_gz/");
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_zstd_pack() {
+        use fast_log::plugin::packer::ZstdPacker;
+        use std::fs::File;
+        let _ = remove_dir_all("target/test_zstd/");
+        std::fs::create_dir_all("target/test_zstd/").unwrap();
+        std::fs::write("target/test_zstd/temp1.log", "hello").unwrap();
+        let file = File::open("target/test_zstd/temp1.log").unwrap();
+        let remove = ZstdPacker::new(19)
+            .do_pack(file, "target/test_zstd/temp1.log")
+            .unwrap();
+        assert!(remove);
+        let data = std::fs::read("target/test_zstd/temp1.log.zst").unwrap();
+        assert_eq!(&data[0..4], &[0x28, 0xb5, 0x2f, 0xfd]);
+        let _ = remove_dir_all("target/test_zstd/");
+    }
+
+    #[cfg(feature = "integrity")]
+    #[test]
+    fn test_integrity() {
+        use fast_log::plugin::integrity::Integrity;
+        let _ = remove_dir_all("target/test_integrity/");
+        let appender = FileSplitAppender::<RawFile>::new(
+            "target/test_integrity/",
+            LogSize::MB(1),
+            RollingType::All,
+            Box::new(LogPacker {}),
+        )
+        .unwrap()
+        .name_template("{dir}/{name}{index}.log")
+        .integrity(Integrity::new("target/test_integrity/MANIFEST", b"key"));
+        let record = |command: Command, formated: &str| FastLogRecord {
+            formated: formated.to_string(),
+            ..FastLogRecord::new(command, Level::Info, "")
+        };
+        for formated in ["a\n", "b\n"] {
+            appender.do_logs(&[record(Command::CommandRecord, formated)]);
+            appender.send_pack();
+        }
+        let wg = WaitGroup::new();
+        appender.do_logs(&[record(Command::CommandFlush(wg.clone()), "")]);
+        wg.wait();
+        let sidecar = std::fs::read_to_string("target/test_integrity/temp001.log.sha256").unwrap();
+        assert!(sidecar.ends_with("  temp001.log\n"));
+        let integrity = Integrity::new("target/test_integrity/MANIFEST", b"key");
+        assert_eq!(integrity.verify().unwrap(), 2);
+        assert!(Integrity::new("target/test_integrity/MANIFEST", b"other")
+            .verify()
+            .is_err());
+        std::fs::write("target/test_integrity/temp002.log", "c\n").unwrap();
+        assert!(integrity.verify().is_err());
+        let _ = remove_dir_all("target/test_integrity/");
+    }
+
+    #[cfg(feature = "encrypt")]
+    #[test]
+    fn test_encrypted_pack() {
+        use fast_log::plugin::encrypt::EncryptedPacker;
+        use std::path::Path;
+        let _ = remove_dir_all("target/test_encrypt/");
+        std::fs::create_dir_all("target/test_encrypt/").unwrap();
+        std::fs::write("target/test_encrypt/key", [7u8; 32]).unwrap();
+        std::fs::write("target/test_encrypt/temp1.log", "hello").unwrap();
+        let packer = EncryptedPacker::key_file(LogPacker {}, "target/test_encrypt/key").unwrap();
+        let packed = packer
+            .pack(Path::new("target/test_encrypt/temp1.log"))
+            .unwrap();
+        let archive = Path::new("target/test_encrypt/temp1.log.enc");
+        assert_eq!(packed, Packed::Archive(archive.to_path_buf()));
+        let data = std::fs::read(&archive).unwrap();
+        assert!(!data.windows(5).any(|v| v == b"hello"));
+        assert_eq!(packer.decrypt(&archive).unwrap(), b"hello");
+        let other = EncryptedPacker::password(LogPacker {}, "other");
+        assert!(other.decrypt(&archive).is_err());
+        let mut tampered = data.clone();
+        tampered[40] ^= 1;
+        std::fs::write(&archive, tampered).unwrap();
+        assert!(packer.decrypt(&archive).is_err());
+        let _ = remove_dir_all("target/test_encrypt/");
+    }
+
+    #[test]
+    fn test_packer_file_shim() {
+        use fast_log::error::LogError;
+        use std::fs::File;
+        use std::path::Path;
+        //an old style packer only implements do_pack
+        struct CopyPacker {}
+        impl Packer for CopyPacker {
+            fn pack_name(&self) -> &'static str {
+                "bak"
+            }
+            fn do_pack(&self, mut log_file: File, log_file_path: &str) -> Result<bool, LogError> {
+                let mut out = File::create(log_file_path.replace("temp1.log", "temp1.bak"))?;
+                std::io::copy(&mut log_file, &mut out)?;
+                Ok(true)
+            }
+        }
+        let _ = remove_dir_all("target/test_shim.logs/");
+        std::fs::create_dir_all("target/test_shim.logs/").unwrap();
+        std::fs::write("target/test_shim.logs/temp1.log", "hello").unwrap();
+        //the archive of do_pack is not known, nothing guesses it from the path
+        let packed = CopyPacker {}
+            .pack(Path::new("target/test_shim.logs/temp1.log"))
+            .unwrap();
+        assert_eq!(packed, Packed::Unknown);
+        let data = std::fs::read_to_string("target/test_shim.logs/temp1.bak").unwrap();
+        assert_eq!(data, "hello");
+        let keep = LogPacker {}
+            .pack(Path::new("target/test_shim.logs/temp1.log"))
+            .unwrap();
+        assert_eq!(keep, Packed::Kept);
+        let _ = remove_dir_all("target/test_shim.logs/");
+    }
 }
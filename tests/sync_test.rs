@@ -0,0 +1,93 @@
+#[cfg(test)]
+mod test {
+    use fast_log::appender::{Command, FastLogRecord, LogAppender};
+    use fast_log::Config;
+    use std::sync::{Arc, Mutex};
+
+    struct Collect(Arc<Mutex<Vec<(String, std::thread::ThreadId)>>>);
+
+    impl LogAppender for Collect {
+        fn do_logs(&self, records: &[FastLogRecord]) {
+            for x in records {
+                if x.command == Command::CommandRecord {
+                    self.0
+                        .lock()
+                        .unwrap()
+                        .push((x.formated.clone(), std::thread::current().id()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_sync() {
+        let records = Arc::new(Mutex::new(vec![]));
+        let logger = fast_log::init(
+            Config::new()
+                .pattern("%l %m%n")
+                .add_appender(Collect(records.clone()))
+                .sync(true),
+        )
+        .unwrap();
+        log::info!("hello");
+        //written before log! returns, on the same thread
+        assert_eq!(
+            *records.lock().unwrap(),
+            vec![("INFO hello\n".to_string(), std::thread::current().id())]
+        );
+        let added = Arc::new(Mutex::new(vec![]));
+        logger
+            .add_appender("added", Box::new(Collect(added.clone())))
+            .unwrap();
+        log::warn!("both");
+        logger.remove_appender("added").unwrap();
+        log::warn!("first only");
+        fast_log::flush().unwrap().wait();
+        assert_eq!(records.lock().unwrap().len(), 3);
+        assert_eq!(added.lock().unwrap().len(), 1);
+        fast_log::exit().unwrap();
+        log::info!("after exit");
+        assert_eq!(records.lock().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_sync_nested_log() {
+        use fast_log::FastLogger;
+        //an appender logging again on the logging thread
+        struct Nested(Arc<Mutex<Vec<String>>>);
+        impl LogAppender for Nested {
+            fn do_logs(&self, records: &[FastLogRecord]) {
+                for x in records {
+                    if x.command == Command::CommandRecord {
+                        self.0.lock().unwrap().push(x.args.clone());
+                        if x.args == "outer" {
+                            fast_log::report_error(&"nested error".into());
+                        }
+                    }
+                }
+            }
+        }
+        let records = Arc::new(Mutex::new(vec![]));
+        let logger = Arc::new(Mutex::new(None::<Arc<FastLogger>>));
+        let on_error = logger.clone();
+        let created = Arc::new(
+            FastLogger::new(
+                Config::new()
+                    .add_appender(Nested(records.clone()))
+                    .on_error(move |e| {
+                        if let Some(logger) = on_error.lock().unwrap().as_ref() {
+                            log::warn!(logger: logger, "{}", e);
+                            logger.flush().unwrap().wait();
+                        }
+                    })
+                    .sync(true),
+            )
+            .unwrap(),
+        );
+        *logger.lock().unwrap() = Some(created.clone());
+        log::info!(logger: created, "outer");
+        assert_eq!(records.lock().unwrap()[0], "outer");
+        assert!(records.lock().unwrap()[1].ends_with("nested error"));
+        logger.lock().unwrap().take();
+    }
+}
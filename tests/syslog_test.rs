@@ -0,0 +1,75 @@
+#[cfg(test)]
+mod test {
+    use fast_log::appender::{Command, FastLogRecord, LogAppender};
+    use fast_log::plugin::syslog::{Facility, SyslogAppender, SyslogTransport};
+    use log::Level;
+    use std::io::Read;
+    use std::net::{TcpListener, UdpSocket};
+
+    fn record(level: Level, args: &str) -> FastLogRecord {
+        FastLogRecord::new(Command::CommandRecord, level, args)
+    }
+
+    #[test]
+    fn test_syslog_message() {
+        let appender =
+            SyslogAppender::new("my app", SyslogTransport::Udp("127.0.0.1:514".to_string()))
+                .facility(Facility::Local0)
+                .hostname("host1");
+        let msg = appender.message(&record(Level::Warn, "disk low"));
+        assert!(msg.starts_with("<132>1 "));
+        assert!(msg.ends_with(&format!(" host1 myapp {} - - disk low", std::process::id())));
+    }
+
+    #[test]
+    fn test_syslog_udp() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap().to_string();
+        let appender = SyslogAppender::new("app", SyslogTransport::Udp(addr));
+        appender.do_logs(&[record(Level::Error, "boom")]);
+        let mut buf = [0u8; 1024];
+        let n = server.recv(&mut buf).unwrap();
+        let msg = String::from_utf8_lossy(&buf[..n]).to_string();
+        assert!(msg.starts_with("<11>1 "));
+        assert!(msg.ends_with(" - - boom"));
+    }
+
+    #[test]
+    fn test_syslog_tcp() {
+        let server = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap().to_string();
+        let appender = SyslogAppender::new("app", SyslogTransport::Tcp(addr));
+        appender.do_logs(&[record(Level::Info, "hello")]);
+        drop(appender);
+        let (mut stream, _) = server.accept().unwrap();
+        let mut data = String::new();
+        stream.read_to_string(&mut data).unwrap();
+        let (len, msg) = data.split_once(' ').unwrap();
+        assert_eq!(len.parse::<usize>().unwrap(), msg.len());
+        assert!(msg.starts_with("<14>1 "));
+    }
+
+    #[test]
+    fn test_syslog_send_fail() {
+        use fast_log::{Config, FastLogger};
+        use std::sync::{Arc, Mutex};
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .to_string();
+        let errors = Arc::new(Mutex::new(vec![]));
+        let hook = errors.clone();
+        let logger = FastLogger::new(
+            Config::new()
+                .sync(true)
+                .add_appender(SyslogAppender::new("app", SyslogTransport::Tcp(addr)))
+                .on_error(move |e| hook.lock().unwrap().push(e.to_string())),
+        )
+        .unwrap();
+        log::info!(logger: logger, "lost");
+        let errors = errors.lock().unwrap();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("[fast_log] syslog send fail"));
+    }
+}
@@ -0,0 +1,112 @@
+#[cfg(test)]
+mod test {
+    use fast_log::appender::{Command, FastLogRecord, LogAppender};
+    use fast_log::plugin::tcp::TcpAppender;
+    use fast_log::WaitGroup;
+    use log::Level;
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn record(formated: &str) -> FastLogRecord {
+        FastLogRecord {
+            formated: formated.to_string(),
+            ..FastLogRecord::new(Command::CommandRecord, Level::Info, "")
+        }
+    }
+
+    fn read_until(server: &TcpListener, len: usize) -> String {
+        let (mut stream, _) = server.accept().unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let mut data = vec![0u8; len];
+        stream.read_exact(&mut data).unwrap();
+        String::from_utf8(data).unwrap()
+    }
+
+    #[test]
+    fn test_tcp_send() {
+        let server = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap().to_string();
+        let appender = TcpAppender::new(&addr);
+        appender.do_logs(&[record("a\n"), record("b\n")]);
+        assert_eq!(read_until(&server, 4), "a\nb\n");
+    }
+
+    #[test]
+    fn test_tcp_buffer_while_disconnected() {
+        let addr = {
+            let server = TcpListener::bind("127.0.0.1:0").unwrap();
+            server.local_addr().unwrap().to_string()
+        };
+        let appender = TcpAppender::new(&addr).buffer_len(2);
+        appender.do_logs(&[record("1\n"), record("2\n"), record("3\n")]);
+        assert_eq!(appender.dropped(), 1);
+
+        let server = TcpListener::bind(&addr).unwrap();
+        std::thread::sleep(Duration::from_millis(150));
+        appender.do_logs(&[record("4\n")]);
+        assert_eq!(read_until(&server, 4), "3\n4\n");
+    }
+
+    #[test]
+    fn test_tcp_retry_on_flush() {
+        let addr = {
+            let server = TcpListener::bind("127.0.0.1:0").unwrap();
+            server.local_addr().unwrap().to_string()
+        };
+        let appender = TcpAppender::new(&addr).max_backoff(Duration::from_secs(60));
+        appender.do_logs(&[record("1\n")]);
+        let server = TcpListener::bind(&addr).unwrap();
+        let mut flush = record("");
+        flush.command = Command::CommandFlush(WaitGroup::new());
+        appender.do_logs(&[flush]);
+        assert_eq!(read_until(&server, 2), "1\n");
+    }
+
+    /// writes 3 bytes, then fails
+    struct ShortWriter {
+        stream: TcpStream,
+        written: bool,
+    }
+
+    impl Write for ShortWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            if self.written {
+                return Err(std::io::ErrorKind::BrokenPipe.into());
+            }
+            self.written = true;
+            self.stream.write(&buf[..buf.len().min(3)])
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            self.stream.flush()
+        }
+    }
+
+    #[test]
+    fn test_tcp_partial_write() {
+        let server = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap().to_string();
+        let short = Arc::new(AtomicBool::new(true));
+        let appender = TcpAppender::new(&addr).connector(Box::new(move |stream| {
+            if short.swap(false, Ordering::SeqCst) {
+                Ok(Box::new(ShortWriter {
+                    stream,
+                    written: false,
+                }))
+            } else {
+                Ok(Box::new(stream))
+            }
+        }));
+        appender.do_logs(&[record("aa\n"), record("bb\n")]);
+        assert_eq!(read_until(&server, 3), "aa\n");
+        let mut flush = record("");
+        flush.command = Command::CommandFlush(WaitGroup::new());
+        appender.do_logs(&[flush]);
+        assert_eq!(read_until(&server, 3), "bb\n");
+    }
+}
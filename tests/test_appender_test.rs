@@ -0,0 +1,40 @@
+#[cfg(test)]
+mod test {
+    use fast_log::appender::{Command, FastLogRecord, LogAppender};
+    use fast_log::plugin::test::TestAppender;
+    use log::Level;
+
+    fn record(level: Level, args: &str) -> FastLogRecord {
+        FastLogRecord {
+            target: "app".into(),
+            module_path: "app".into(),
+            file: "src/main.rs".into(),
+            line: Some(1),
+            formated: format!("{} {}\n", level, args),
+            ..FastLogRecord::new(Command::CommandRecord, level, args)
+        }
+    }
+
+    #[test]
+    fn test_test_appender() {
+        let appender = TestAppender::new();
+        let shared = appender.clone();
+        let mut exit = record(Level::Info, "");
+        exit.command = Command::CommandExit;
+        appender.do_logs(&[record(Level::Warn, "disk full"), exit]);
+        assert_eq!(shared.len(), 1);
+        shared.assert_logged(Level::Warn, "disk");
+        assert!(!shared.logged(Level::Error, "disk"));
+        let records = appender.take_records();
+        assert_eq!(records[0].args, "disk full");
+        assert!(shared.is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "no ERROR record contains \"boom\"")]
+    fn test_assert_logged_panic() {
+        let appender = TestAppender::new();
+        appender.do_logs(&[record(Level::Info, "boom")]);
+        appender.assert_logged(Level::Error, "boom");
+    }
+}
@@ -0,0 +1,23 @@
+#[cfg(test)]
+mod test {
+    use fast_log::plugin::test::TestAppender;
+    use fast_log::{Config, TimeFormat};
+
+    #[test]
+    fn test_config_time_format() {
+        let appender = TestAppender::new();
+        fast_log::init(
+            Config::new()
+                .pattern("%d [%l] %m%n")
+                .time_format(TimeFormat::epoch_millis())
+                .add_appender(appender.clone())
+                .sync(true),
+        )
+        .unwrap();
+        log::info!("hello");
+        let records = appender.take_records();
+        let (millis, rest) = records[0].formated.split_once(' ').unwrap();
+        assert!(millis.parse::<u128>().unwrap() > 1_600_000_000_000);
+        assert_eq!(rest, "[INFO] hello\n");
+    }
+}
@@ -0,0 +1,35 @@
+#[cfg(test)]
+mod test {
+    use fast_log::plugin::test::TestAppender;
+    use fast_log::Config;
+    use log::{Level, LevelFilter};
+
+    #[test]
+    fn test_time_scope() {
+        let appender = TestAppender::new();
+        fast_log::init(
+            Config::new()
+                .add_appender(appender.clone())
+                .level(LevelFilter::Info),
+        )
+        .unwrap();
+        {
+            let _t = fast_log::time_scope!("load_config");
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        let sum = fast_log::log_elapsed!(Level::Debug, "sum", { 1 + 2 });
+        assert_eq!(sum, 3);
+        let sum = fast_log::log_elapsed!("sum", { 3 + 4 });
+        assert_eq!(sum, 7);
+        log::logger().flush();
+        let records = appender.take_records();
+        assert_eq!(records.len(), 2);
+        assert!(records[0].args.starts_with("load_config took "));
+        assert_eq!(records[0].level, Level::Info);
+        assert_eq!(records[0].target.as_ref(), module_path!());
+        assert_eq!(records[0].key_values[0].0, "elapsed_ms");
+        let ms: f64 = records[0].key_values[0].1.parse().unwrap();
+        assert!(ms >= 5.0);
+        assert!(records[1].args.starts_with("sum took "));
+    }
+}
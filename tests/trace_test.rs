@@ -0,0 +1,33 @@
+#[cfg(test)]
+mod test {
+    use fast_log::plugin::test::TestAppender;
+    use fast_log::Config;
+    use std::cell::RefCell;
+
+    thread_local! {
+        static SPAN: RefCell<Option<(String, String)>> = const { RefCell::new(None) };
+    }
+
+    #[test]
+    fn test_trace_context() {
+        let appender = TestAppender::new();
+        fast_log::init(
+            Config::new()
+                .json()
+                .add_appender(appender.clone())
+                .trace_context(|| SPAN.with(|s| s.borrow().clone()))
+                .sync(true),
+        )
+        .unwrap();
+        log::info!("outside");
+        SPAN.with(|s| *s.borrow_mut() = Some(("4bf92f35".to_string(), "00f067aa".to_string())));
+        log::info!("inside");
+        let records = appender.take_records();
+        assert_eq!(records[0].trace_id, None);
+        assert!(!records[0].formated.contains("trace_id"));
+        assert_eq!(records[1].span_id.as_deref(), Some("00f067aa"));
+        assert!(records[1]
+            .formated
+            .ends_with(",\"trace_id\":\"4bf92f35\",\"span_id\":\"00f067aa\"}\n"));
+    }
+}
@@ -0,0 +1,38 @@
+#[cfg(test)]
+#[cfg(feature = "tracing")]
+mod test {
+    use fast_log::plugin::test::TestAppender;
+    use fast_log::tracing_bridge::Layer;
+    use fast_log::Config;
+    use log::{Level, LevelFilter};
+
+    #[test]
+    fn test_tracing_events() {
+        let appender = TestAppender::new();
+        fast_log::init(
+            Config::new()
+                .add_appender(appender.clone())
+                .level(LevelFilter::Info)
+                .sync(true),
+        )
+        .unwrap();
+        tracing::subscriber::with_default(Layer::new(), || {
+            tracing::info!(user = "bob", id = 7, "login {}", "ok");
+            tracing::debug!("too verbose");
+            tracing::warn!(target: "app::db", "slow query");
+        });
+        let records = appender.take_records();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].level, Level::Info);
+        assert_eq!(records[0].args, "login ok");
+        assert_eq!(
+            records[0].key_values,
+            vec![
+                ("user".to_string(), "bob".to_string()),
+                ("id".to_string(), "7".to_string())
+            ]
+        );
+        assert_eq!(records[1].level, Level::Warn);
+        assert_eq!(records[1].target.as_ref(), "app::db");
+    }
+}
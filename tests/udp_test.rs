@@ -0,0 +1,31 @@
+#[cfg(test)]
+mod test {
+    use fast_log::appender::{Command, FastLogRecord, LogAppender};
+    use fast_log::plugin::udp::UdpAppender;
+    use log::Level;
+    use std::net::UdpSocket;
+    use std::time::Duration;
+
+    fn record(formated: &str) -> FastLogRecord {
+        FastLogRecord {
+            formated: formated.to_string(),
+            ..FastLogRecord::new(Command::CommandRecord, Level::Info, "")
+        }
+    }
+
+    #[test]
+    fn test_udp_truncate() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        server
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let addr = server.local_addr().unwrap().to_string();
+        let appender = UdpAppender::new(&addr).max_datagram(4);
+        appender.do_logs(&[record("ab\n"), record("abc你好")]);
+        let mut buf = [0u8; 64];
+        let n = server.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"ab\n");
+        let n = server.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"abc");
+    }
+}
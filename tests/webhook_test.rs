@@ -0,0 +1,78 @@
+#[cfg(test)]
+#[cfg(feature = "http")]
+mod test {
+    use fast_log::appender::{Command, FastLogRecord, LogAppender};
+    use fast_log::plugin::webhook::WebhookAppender;
+    use fast_log::WaitGroup;
+    use log::Level;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::time::Duration;
+
+    fn record(command: Command, level: Level, args: &str) -> FastLogRecord {
+        FastLogRecord {
+            target: "app".into(),
+            line: Some(3),
+            ..FastLogRecord::new(command, level, args)
+        }
+    }
+
+    /// accept one http request, reply 200, return the body
+    fn serve_one(server: &TcpListener) -> String {
+        let (stream, _) = server.accept().unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut len = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            if line == "\r\n" {
+                break;
+            }
+            if let Some(v) = line.to_lowercase().strip_prefix("content-length:") {
+                len = v.trim().parse().unwrap();
+            }
+        }
+        let mut body = vec![0u8; len];
+        reader.read_exact(&mut body).unwrap();
+        let mut stream = reader.into_inner();
+        stream
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+            .unwrap();
+        String::from_utf8(body).unwrap()
+    }
+
+    #[test]
+    fn test_webhook_render() {
+        let appender = WebhookAppender::new("http://127.0.0.1:1")
+            .template(r#"{"t":"{target}:{line} {args} {unknown}"}"#);
+        assert_eq!(
+            appender.render(&record(
+                Command::CommandRecord,
+                Level::Error,
+                "say \"{args}\""
+            )),
+            r#"{"t":"app:3 say \"{args}\" {unknown}"}"#
+        );
+    }
+
+    #[test]
+    fn test_webhook_rate_cap() {
+        let server = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", server.local_addr().unwrap());
+        let appender = WebhookAppender::slack(&url).max_per_minute(1);
+        let wg = WaitGroup::new();
+        appender.do_logs(&[
+            record(Command::CommandRecord, Level::Warn, "below level"),
+            record(Command::CommandRecord, Level::Error, "first"),
+            record(Command::CommandRecord, Level::Error, "capped"),
+            record(Command::CommandFlush(wg.clone()), Level::Info, ""),
+        ]);
+        assert_eq!(serve_one(&server), r#"{"text":"[ERROR] app: first"}"#);
+        wg.wait();
+        server.set_nonblocking(true).unwrap();
+        assert!(server.accept().is_err());
+    }
+}
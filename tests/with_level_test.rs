@@ -0,0 +1,48 @@
+#[cfg(test)]
+mod test {
+    use fast_log::plugin::test::TestAppender;
+    use fast_log::Config;
+    use log::{Level, LevelFilter};
+
+    #[test]
+    fn test_with_level() {
+        let appender = TestAppender::new();
+        fast_log::init(
+            Config::new()
+                .add_appender(appender.clone())
+                .level(LevelFilter::Info),
+        )
+        .unwrap();
+        log::debug!("before");
+        assert_eq!(log::max_level(), LevelFilter::Info);
+        let out = fast_log::with_level(Level::Trace, || {
+            assert_eq!(log::max_level(), LevelFilter::Trace);
+            log::trace!("inside");
+            std::thread::spawn(|| log::debug!("other thread"))
+                .join()
+                .unwrap();
+            fast_log::with_level(Level::Debug, || log::trace!("nested"));
+            1
+        });
+        assert_eq!(out, 1);
+        //the macros skip records above the logger level again
+        assert_eq!(log::max_level(), LevelFilter::Info);
+        log::debug!("after");
+        log::info!("info");
+        log::logger().flush();
+        let records = appender.take_records();
+        let args: Vec<&str> = records.iter().map(|r| r.args.as_str()).collect();
+        assert_eq!(args, ["inside", "nested", "info"]);
+        assert_eq!(fast_log::get_level(), LevelFilter::Info);
+
+        //a FastLogger holds its level until it is dropped
+        let logger =
+            fast_log::FastLogger::new(Config::new().console().level(LevelFilter::Debug)).unwrap();
+        assert_eq!(log::max_level(), LevelFilter::Debug);
+        logger.set_level(LevelFilter::Error);
+        assert_eq!(log::max_level(), LevelFilter::Info);
+        logger.set_level(LevelFilter::Trace);
+        drop(logger);
+        assert_eq!(log::max_level(), LevelFilter::Info);
+    }
+}
@@ -0,0 +1,52 @@
+#[cfg(test)]
+mod test {
+    use fast_log::appender::{Command, FastLogRecord, LogAppender};
+    use fast_log::config::Delivery;
+    use fast_log::Config;
+    use std::sync::{Arc, Mutex};
+
+    struct Collect(Arc<Mutex<Vec<(String, String)>>>);
+
+    impl LogAppender for Collect {
+        fn do_logs(&self, records: &[FastLogRecord]) {
+            for x in records {
+                if x.command == Command::CommandRecord {
+                    self.0
+                        .lock()
+                        .unwrap()
+                        .push((x.target.to_string(), x.args.clone()));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_appender_workers() {
+        let ordered = Arc::new(Mutex::new(vec![]));
+        let unordered = Arc::new(Mutex::new(vec![]));
+        fast_log::init(
+            Config::new()
+                .add_appender_workers(Collect(ordered.clone()), 4, Delivery::Ordered)
+                .add_appender_workers(Collect(unordered.clone()), 4, Delivery::Unordered),
+        )
+        .unwrap();
+        for i in 0..100 {
+            for target in ["a", "b", "c"] {
+                log::info!(target: target, "{}", i);
+            }
+        }
+        fast_log::flush().unwrap().wait();
+        let ordered = ordered.lock().unwrap();
+        assert_eq!(ordered.len(), 300);
+        for target in ["a", "b", "c"] {
+            let args: Vec<String> = ordered
+                .iter()
+                .filter(|(t, _)| t == target)
+                .map(|(_, a)| a.clone())
+                .collect();
+            let expect: Vec<String> = (0..100).map(|i| i.to_string()).collect();
+            assert_eq!(args, expect);
+        }
+        assert_eq!(unordered.lock().unwrap().len(), 300);
+    }
+}